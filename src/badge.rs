@@ -0,0 +1,189 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, OnceLock, Weak},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{Error, Result},
+    lock,
+    message::Message,
+};
+
+pub(crate) type OnBadgeStoreError = Arc<dyn Fn(&Error) + Send + Sync>;
+
+const VERSION: u32 = 1;
+
+/// What a badge store file holds.
+#[derive(Serialize, Deserialize)]
+struct Stored<B> {
+    version: u32,
+    badges: B,
+}
+
+/// The last badge sent to each device key, shared by every client in the
+/// process that names the same store file.
+struct Counter {
+    counts: Mutex<BTreeMap<String, u64>>,
+    path: Option<PathBuf>,
+}
+
+/// Numbers the badges of a client's messages; see
+/// [`ClientBuilder::auto_badge`](crate::client::ClientBuilder::auto_badge).
+#[derive(Clone)]
+pub(crate) struct Badges {
+    counter: Arc<Counter>,
+    on_error: Option<OnBadgeStoreError>,
+}
+
+impl Badges {
+    pub(crate) fn in_memory() -> Self {
+        Badges {
+            counter: Arc::new(Counter {
+                counts: Mutex::default(),
+                path: None,
+            }),
+            on_error: None,
+        }
+    }
+
+    /// Loads `path`, or joins a client in this process that already did. A file
+    /// that does not parse is reported to `on_error` and replaced on the next send.
+    pub(crate) fn open(path: &Path, on_error: Option<OnBadgeStoreError>) -> Result<Self> {
+        static OPEN: OnceLock<Mutex<HashMap<PathBuf, Weak<Counter>>>> = OnceLock::new();
+
+        let path = canonical(path).map_err(|source| Error::BadgeStore {
+            path: path.to_owned(),
+            source,
+        })?;
+        let mut open = lock(OPEN.get_or_init(Mutex::default));
+
+        if let Some(counter) = open.get(&path).and_then(Weak::upgrade) {
+            return Ok(Badges { counter, on_error });
+        }
+
+        let counts = match load(&path) {
+            Ok(counts) => counts,
+            Err(error @ Error::InvalidBadgeStore { .. }) => {
+                if let Some(on_error) = &on_error {
+                    on_error(&error);
+                }
+                BTreeMap::new()
+            }
+            Err(error) => return Err(error),
+        };
+
+        open.retain(|_, counter| counter.strong_count() > 0);
+
+        let counter = Arc::new(Counter {
+            counts: Mutex::new(counts),
+            path: Some(path.clone()),
+        });
+        open.insert(path, Arc::downgrade(&counter));
+
+        Ok(Badges { counter, on_error })
+    }
+
+    pub(crate) fn path(&self) -> Option<&Path> {
+        self.counter.path.as_deref()
+    }
+
+    pub(crate) fn get(&self, device_key: &str) -> Option<u64> {
+        lock(&self.counter.counts).get(device_key).copied()
+    }
+
+    /// Gives a message without a badge one more than the highest its keys were
+    /// sent, and records the badge it goes out with for each key. The store is
+    /// written before the send, so a crash never hands out a number twice.
+    pub(crate) fn assign(&self, message: &mut Message) {
+        if message.ciphertext.is_some() || message.device_keys.is_empty() {
+            return;
+        }
+
+        let mut counts = lock(&self.counter.counts);
+        let badge = message.badge.unwrap_or_else(|| {
+            message
+                .device_keys
+                .iter()
+                .filter_map(|key| counts.get(key))
+                .max()
+                .map_or(1, |badge| badge.saturating_add(1))
+        });
+
+        message.badge = Some(badge);
+        for key in &message.device_keys {
+            counts.insert(key.clone(), badge);
+        }
+
+        if let Some(path) = &self.counter.path
+            && let Err(error) = save(path, &counts)
+            && let Some(on_error) = &self.on_error
+        {
+            on_error(&error);
+        }
+    }
+}
+
+/// `path` with its directory resolved, so two spellings of one file share a counter.
+fn canonical(path: &Path) -> io::Result<PathBuf> {
+    let name = path.file_name().ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidInput, "badge store must name a file")
+    })?;
+    let directory = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+
+    Ok(fs::canonicalize(directory)?.join(name))
+}
+
+fn load(path: &Path) -> Result<BTreeMap<String, u64>> {
+    let contents = match fs::read(path) {
+        Ok(contents) => contents,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+        Err(source) => {
+            return Err(Error::BadgeStore {
+                path: path.to_owned(),
+                source,
+            });
+        }
+    };
+    let invalid = |reason: String| Error::InvalidBadgeStore {
+        path: path.to_owned(),
+        reason,
+    };
+
+    match serde_json::from_slice::<Stored<BTreeMap<String, u64>>>(&contents) {
+        Ok(stored) if stored.version == VERSION => Ok(stored.badges),
+        Ok(stored) => Err(invalid(format!("unsupported version {}", stored.version))),
+        Err(error) => Err(invalid(error.to_string())),
+    }
+}
+
+/// Writes a temporary file next to `path` and renames it over, so a reader
+/// never sees half a file.
+fn save(path: &Path, badges: &BTreeMap<String, u64>) -> Result<()> {
+    let stored = Stored {
+        version: VERSION,
+        badges,
+    };
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(".tmp");
+    let temporary = PathBuf::from(temporary);
+
+    let written = (|| {
+        let mut file = fs::File::create(&temporary)?;
+        file.write_all(&serde_json::to_vec(&stored)?)?;
+        file.sync_all()?;
+        fs::rename(&temporary, path)
+    })();
+
+    written.map_err(|source| Error::BadgeStore {
+        path: path.to_owned(),
+        source,
+    })
+}
@@ -0,0 +1,109 @@
+use std::{sync::Mutex, time::Duration};
+
+use crate::{
+    client::BarkResponse,
+    clock::Clock,
+    error::{Error, Result},
+    lock,
+    message::Message,
+    retry,
+    time::Instant,
+};
+
+/// How [`Client::send_all_with`](crate::client::Client::send_all_with) spreads a batch out.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SendAllOptions {
+    /// Requests in flight at once; `None` uses the client's batch concurrency.
+    pub concurrency: Option<usize>,
+    /// The least time between two request starts, across all workers.
+    pub min_interval: Duration,
+    /// Moves each interval by a random amount up to this, either way.
+    pub jitter: Duration,
+    /// Starts no more sends once one fails in a way resending would not fix;
+    /// the rest end up in [`BatchReport::remaining`].
+    pub stop_on_fatal: bool,
+}
+
+/// What [`Client::send_all_with`](crate::client::Client::send_all_with) did with
+/// each message, in input order.
+#[derive(Debug)]
+pub struct BatchReport {
+    pub items: Vec<BatchItem>,
+}
+
+#[derive(Debug)]
+pub struct BatchItem {
+    /// The message as passed in, before client defaults.
+    pub message: Message,
+    /// `None` if the batch stopped before sending it.
+    pub result: Option<Result<BarkResponse>>,
+}
+
+impl BatchReport {
+    pub fn all_ok(&self) -> bool {
+        self.items
+            .iter()
+            .all(|item| matches!(item.result, Some(Ok(_))))
+    }
+
+    /// The messages never sent plus those that failed transiently, to pass back
+    /// to `send_all`. To resume after a restart, store each as a
+    /// [`StoredRecord`](crate::record::StoredRecord): a message's own JSON is
+    /// only what goes on the wire and drops its send options.
+    pub fn remaining(&self) -> Vec<Message> {
+        self.items
+            .iter()
+            .filter(|item| match &item.result {
+                None => true,
+                Some(Ok(_)) => false,
+                Some(Err(error)) => retry::is_resumable(error),
+            })
+            .map(|item| item.message.clone())
+            .collect()
+    }
+}
+
+/// A failure that stops a batch under [`SendAllOptions::stop_on_fatal`]; a hook
+/// skipping one message is not.
+pub(crate) fn is_fatal(error: &Error) -> bool {
+    !retry::is_resumable(error) && !matches!(error, Error::Skipped)
+}
+
+/// Hands out start times `min_interval ± jitter` apart to every worker of a batch.
+/// The client's rate limiter still applies after a worker's slot comes up.
+pub(crate) struct Pacer {
+    interval: Duration,
+    jitter: Duration,
+    next_start: Mutex<Option<Instant>>,
+}
+
+impl Pacer {
+    pub(crate) fn new(options: &SendAllOptions) -> Self {
+        Pacer {
+            interval: options.min_interval,
+            jitter: options.jitter.min(options.min_interval),
+            next_start: Mutex::new(None),
+        }
+    }
+
+    pub(crate) async fn wait(&self, clock: &dyn Clock) {
+        if self.interval.is_zero() {
+            return;
+        }
+
+        let start = {
+            let mut next_start = lock(&self.next_start);
+            let now = clock.instant();
+            let start = next_start.map_or(now, |next_start| next_start.max(now));
+            *next_start = Some(start + self.gap());
+            start
+        };
+
+        clock.sleep_until(start).await;
+    }
+
+    fn gap(&self) -> Duration {
+        let offset = self.jitter.mul_f64(2.0 * retry::random_fraction());
+        (self.interval + offset).saturating_sub(self.jitter)
+    }
+}
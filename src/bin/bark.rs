@@ -0,0 +1,185 @@
+use std::{
+    env, fs,
+    io::{self, Read},
+    path::PathBuf,
+    process::ExitCode,
+};
+
+use bark::{
+    client::Client,
+    message::{BodyKind, Level},
+};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use serde::Deserialize;
+
+#[derive(Parser)]
+#[command(name = "bark", version, about = "Send Bark push notifications")]
+struct Cli {
+    #[arg(long, env = "BARK_CONFIG", global = true)]
+    config: Option<PathBuf>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    Send(SendArgs),
+}
+
+#[derive(Args)]
+struct SendArgs {
+    #[arg(long, env = "BARK_SERVER")]
+    server: Option<String>,
+    #[arg(long = "device-key", env = "BARK_DEVICE_KEY", value_delimiter = ',')]
+    device_keys: Vec<String>,
+    #[arg(long)]
+    title: Option<String>,
+    #[arg(long)]
+    subtitle: Option<String>,
+    #[arg(long, help = "Message body, or `-` to read it from stdin")]
+    body: Option<String>,
+    #[arg(long)]
+    markdown: bool,
+    #[arg(long, value_enum, env = "BARK_LEVEL")]
+    level: Option<LevelArg>,
+    #[arg(long, env = "BARK_GROUP")]
+    group: Option<String>,
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum LevelArg {
+    Critical,
+    Active,
+    #[value(name = "timeSensitive", alias = "time-sensitive")]
+    TimeSensitive,
+    Passive,
+}
+
+impl From<LevelArg> for Level {
+    fn from(level: LevelArg) -> Self {
+        match level {
+            LevelArg::Critical => Level::Critical,
+            LevelArg::Active => Level::Active,
+            LevelArg::TimeSensitive => Level::TimeSensitive,
+            LevelArg::Passive => Level::Passive,
+        }
+    }
+}
+
+#[derive(Default, Deserialize)]
+struct Config {
+    server: Option<String>,
+    #[serde(default)]
+    device_keys: Vec<String>,
+    group: Option<String>,
+}
+
+impl Config {
+    fn load(path: Option<PathBuf>) -> Result<Self, String> {
+        let Some(path) = path.or_else(Self::default_path) else {
+            return Ok(Config::default());
+        };
+
+        let contents = fs::read_to_string(&path)
+            .map_err(|err| format!("failed to read {}: {err}", path.display()))?;
+
+        toml::from_str(&contents).map_err(|err| format!("invalid {}: {err}", path.display()))
+    }
+
+    fn default_path() -> Option<PathBuf> {
+        let dir = env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+
+        Some(dir.join("bark").join("config.toml")).filter(|path| path.is_file())
+    }
+}
+
+async fn send(args: SendArgs, config: Config) -> Result<(), String> {
+    let server = args
+        .server
+        .or(config.server)
+        .ok_or("missing server: pass --server or set BARK_SERVER")?;
+
+    let device_keys = if args.device_keys.is_empty() {
+        config.device_keys
+    } else {
+        args.device_keys
+    };
+
+    let body = match args.body.as_deref() {
+        Some("-") => {
+            let mut body = String::new();
+            io::stdin()
+                .read_to_string(&mut body)
+                .map_err(|err| format!("failed to read stdin: {err}"))?;
+            Some(body)
+        }
+        _ => args.body,
+    };
+
+    let client = Client::builder()
+        .base_url(server)
+        .device_keys(device_keys)
+        .build()
+        .map_err(|err| err.to_string())?;
+
+    let mut message = client.message();
+
+    if let Some(title) = args.title {
+        message = message.title(title);
+    }
+
+    if let Some(subtitle) = args.subtitle {
+        message = message.subtitle(subtitle);
+    }
+
+    if let Some(body) = body {
+        message = message.body(body);
+    }
+
+    if args.markdown {
+        message = message.body_kind(BodyKind::Markdown);
+    }
+
+    if let Some(level) = args.level {
+        message = message.level(level.into());
+    }
+
+    if let Some(group) = args.group.or(config.group) {
+        message = message.group(group);
+    }
+
+    let response = message.send().await.map_err(|err| err.to_string())?;
+
+    if args.json {
+        let json = serde_json::to_string(&response).map_err(|err| err.to_string())?;
+        println!("{json}");
+    } else {
+        println!("{}", response.message);
+    }
+
+    Ok(())
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let result = match Config::load(cli.config) {
+        Ok(config) => match cli.command {
+            Command::Send(args) => send(args, config).await,
+        },
+        Err(err) => Err(err),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,265 @@
+//! A synchronous wrapper over the async [`Client`](crate::client::Client), not a
+//! separate client on `reqwest::blocking`: it runs the async client on a private
+//! current-thread tokio runtime, so retries, hooks, rate limits and every other
+//! client feature behave the same in both. That still pulls in tokio and rules
+//! out calling it from inside a runtime; see [`Client`]. For a client with no
+//! async runtime at all, use `bark::sync` behind the `ureq` feature.
+
+use std::{future::Future, sync::Arc, time::Duration};
+
+use tokio::runtime::{Builder, Handle, Runtime};
+
+use crate::{
+    batch::{BatchItem, BatchReport, SendAllOptions},
+    client::{
+        self, BarkResponse, ChunkReport, ClientBuilder, ClientMessageBuilder, FanoutReport,
+        SendOutcome, ServerInfo,
+    },
+    config::EffectiveConfig,
+    error::{Error, Result},
+    message::{Level, Message, MessageBuilder, ToMessage},
+    register::{RegisterRequest, RegisterResponse},
+    stats::ClientStats,
+};
+
+/// A synchronous client that drives the async one on a private runtime.
+///
+/// Sending where a tokio runtime is current, `spawn_blocking` threads included,
+/// fails with [`Error::InsideRuntime`] instead of panicking; use the async
+/// client there. Dropping the last clone there panics, as dropping any runtime does.
+#[derive(Debug, Clone)]
+pub struct Client {
+    inner: client::Client,
+    runtime: Arc<Runtime>,
+}
+
+impl Client {
+    #[cfg(feature = "reqwest")]
+    pub fn new(base_url: impl Into<String>) -> Result<Self> {
+        Client::from_async(client::Client::new(base_url))
+    }
+
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::default()
+    }
+
+    #[cfg(feature = "reqwest")]
+    pub fn from_env() -> Result<Self> {
+        Client::from_async(client::Client::from_env()?)
+    }
+
+    #[cfg(feature = "reqwest")]
+    pub fn from_env_prefixed(prefix: &str) -> Result<Self> {
+        Client::from_async(client::Client::from_env_prefixed(prefix)?)
+    }
+
+    pub(crate) fn from_async(inner: client::Client) -> Result<Self> {
+        let runtime = Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(Error::Runtime)?;
+
+        Ok(Client {
+            inner,
+            runtime: Arc::new(runtime),
+        })
+    }
+
+    /// Runs `future` on the private runtime, unless a tokio runtime is current
+    /// here and `block_on` would panic.
+    fn block_on<F: Future>(&self, future: F) -> Result<F::Output> {
+        if inside_runtime() {
+            return Err(Error::InsideRuntime);
+        }
+
+        Ok(self.runtime.block_on(future))
+    }
+
+    #[must_use]
+    pub fn with_device_key(mut self, device_key: impl Into<String>) -> Self {
+        self.inner = self.inner.with_device_key(device_key);
+        self
+    }
+
+    #[must_use]
+    pub fn with_device_keys<I, S>(mut self, device_keys: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.inner = self.inner.with_device_keys(device_keys);
+        self
+    }
+
+    #[must_use]
+    pub fn with_group(mut self, group: impl Into<String>) -> Self {
+        self.inner = self.inner.with_group(group);
+        self
+    }
+
+    #[must_use]
+    pub fn with_icon(mut self, icon: impl Into<String>) -> Self {
+        self.inner = self.inner.with_icon(icon);
+        self
+    }
+
+    #[must_use]
+    pub fn with_sound(mut self, sound: impl Into<String>) -> Self {
+        self.inner = self.inner.with_sound(sound);
+        self
+    }
+
+    #[must_use]
+    pub fn with_level(mut self, level: Level) -> Self {
+        self.inner = self.inner.with_level(level);
+        self
+    }
+
+    pub fn message(&self) -> ClientMessageBuilder<'_, Client> {
+        ClientMessageBuilder::new(self, MessageBuilder::default())
+    }
+
+    pub fn replacing(&self, id: impl Into<String>) -> ClientMessageBuilder<'_, Client> {
+        ClientMessageBuilder::new(self, MessageBuilder::default().id(id))
+    }
+
+    pub fn update_notification<F>(&self, id: impl Into<String>, update: F) -> Result<BarkResponse>
+    where
+        F: FnOnce(MessageBuilder) -> MessageBuilder,
+    {
+        self.block_on(self.inner.update_notification(id, update))?
+    }
+
+    pub fn send(&self, message: &Message) -> Result<BarkResponse> {
+        self.block_on(self.inner.send(message))?
+    }
+
+    pub fn notify(
+        &self,
+        title: impl Into<String>,
+        body: impl Into<String>,
+    ) -> Result<BarkResponse> {
+        self.block_on(self.inner.notify(title, body))?
+    }
+
+    pub fn notify_markdown(
+        &self,
+        title: impl Into<String>,
+        body: impl Into<String>,
+    ) -> Result<BarkResponse> {
+        self.block_on(self.inner.notify_markdown(title, body))?
+    }
+
+    pub fn send_with_timeout(&self, message: &Message, timeout: Duration) -> Result<BarkResponse> {
+        self.block_on(self.inner.send_with_timeout(message, timeout))?
+    }
+
+    pub fn send_detailed(&self, message: &Message) -> Result<SendOutcome> {
+        self.block_on(self.inner.send_detailed(message))?
+    }
+
+    pub fn send_all<I>(&self, messages: I) -> Vec<Result<BarkResponse>>
+    where
+        I: IntoIterator<Item = Message>,
+    {
+        if inside_runtime() {
+            return messages
+                .into_iter()
+                .map(|_| Err(Error::InsideRuntime))
+                .collect();
+        }
+
+        self.runtime.block_on(self.inner.send_all(messages))
+    }
+
+    pub fn send_all_with<I>(&self, messages: I, options: SendAllOptions) -> BatchReport
+    where
+        I: IntoIterator<Item = Message>,
+    {
+        // Nothing was sent, so everything stays in `remaining`.
+        if inside_runtime() {
+            let items = messages
+                .into_iter()
+                .map(|message| BatchItem {
+                    message,
+                    result: None,
+                })
+                .collect();
+            return BatchReport { items };
+        }
+
+        self.runtime
+            .block_on(self.inner.send_all_with(messages, options))
+    }
+
+    pub fn send_fanout(&self, message: &Message) -> Result<FanoutReport> {
+        self.block_on(self.inner.send_fanout(message))?
+    }
+
+    pub fn send_chunked(&self, message: &Message, chunk_size: usize) -> Result<ChunkReport> {
+        self.block_on(self.inner.send_chunked(message, chunk_size))?
+    }
+
+    pub fn send_to<I, S>(&self, device_keys: I, message: &Message) -> Result<BarkResponse>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.block_on(self.inner.send_to(device_keys, message))?
+    }
+
+    pub fn send_item<T: ToMessage + ?Sized>(&self, item: &T) -> Result<BarkResponse> {
+        self.block_on(self.inner.send_item(item))?
+    }
+
+    pub fn send_into(&self, builder: impl Into<MessageBuilder>) -> Result<BarkResponse> {
+        self.block_on(self.inner.send_into(builder))?
+    }
+
+    pub fn register_device(&self, request: &RegisterRequest) -> Result<RegisterResponse> {
+        self.block_on(self.inner.register_device(request))?
+    }
+
+    pub fn healthz(&self) -> Result<()> {
+        self.block_on(self.inner.healthz())?
+    }
+
+    pub fn ping(&self) -> Result<BarkResponse> {
+        self.block_on(self.inner.ping())?
+    }
+
+    pub fn server_info(&self) -> Result<ServerInfo> {
+        self.block_on(self.inner.server_info())?
+    }
+
+    pub fn stats(&self) -> ClientStats {
+        self.inner.stats()
+    }
+
+    pub fn reset_stats(&self) {
+        self.inner.reset_stats();
+    }
+
+    pub fn effective_config(&self) -> EffectiveConfig {
+        self.inner.effective_config()
+    }
+}
+
+fn inside_runtime() -> bool {
+    Handle::try_current().is_ok()
+}
+
+impl AsRef<client::Client> for Client {
+    fn as_ref(&self) -> &client::Client {
+        &self.inner
+    }
+}
+
+impl ClientMessageBuilder<'_, Client> {
+    pub fn send(self) -> Result<BarkResponse> {
+        let (client, builder) = self.into_parts();
+        let mut message = builder.build()?;
+
+        client.block_on(client.inner.dispatch(&mut message))?
+    }
+}
@@ -1,48 +1,1679 @@
-use std::collections::HashSet;
+#[cfg(all(feature = "reqwest", not(target_arch = "wasm32")))]
+use std::net::SocketAddr;
+#[cfg(feature = "tower")]
+use std::task::{Context, Poll};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt,
+    path::PathBuf,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+
+use futures_util::{StreamExt, stream};
+#[cfg(feature = "gzip")]
+use http::header::CONTENT_ENCODING;
+use http::{
+    Method, StatusCode,
+    header::{HeaderMap, HeaderName, HeaderValue},
+};
+#[cfg(all(feature = "reqwest", not(target_arch = "wasm32")))]
+use reqwest::dns::Resolve;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "tower")]
+use tower::Service;
+#[cfg(all(feature = "tower", not(target_arch = "wasm32")))]
+use tower::{BoxError, Layer};
+use url::Url;
+
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+use crate::blocking;
+#[cfg(feature = "gzip")]
+use crate::compress::RequestCompression;
+#[cfg(feature = "test-util")]
+use crate::test_util::Captured;
+#[cfg(feature = "reqwest")]
+use crate::transport::ReqwestTransport;
+#[cfg(feature = "tracing")]
+use crate::transport::wire_log;
+#[cfg(all(feature = "tower", not(target_arch = "wasm32")))]
+use crate::transport::{TransportService, service};
+use crate::{
+    badge::{Badges, OnBadgeStoreError},
+    batch::{self, BatchItem, BatchReport, Pacer, SendAllOptions},
+    clock::{self, Clock, SystemClock},
+    config::{EffectiveConfig, TransportConfig},
+    device_key::{self, DeviceKey},
+    error::{Error, Result},
+    group::GroupHandle,
+    hook::{
+        self, AfterSend, BeforeSend, FailureKind, HookControl, MessageSummary, OnRetry,
+        RetryAttempt, SendReport,
+    },
+    id::{IdGenerator, UuidV4},
+    lock,
+    message::{Level, Message, MessageBuilder, MessageDefaults, ToMessage},
+    pipeline::{self, Envelope},
+    queue::{QueueOptions, SendQueue},
+    rate_limit::{DeviceRateLimiter, RateLimit, RateLimiter},
+    redact,
+    register::{RegisterData, RegisterRequest, RegisterResponse},
+    repeat::{RepeatHandle, RepeatOptions},
+    retry::{self, RetryPolicy},
+    split::{self, SplitOptions},
+    stats::{ClientStats, StatsRecorder},
+    time::Instant,
+    transport::{PreparedRequest, RawResponse, Transport},
+    upload::Uploader,
+    validate,
+};
+
+pub use crate::{message::ClientMessageBuilder, pipeline::BarkResponse};
+
+/// What `/info` reports; every field is optional across server versions.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ServerInfo {
+    pub version: Option<String>,
+    pub build: Option<String>,
+    pub arch: Option<String>,
+    pub commit: Option<String>,
+    pub devices: Option<u64>,
+}
+
+#[derive(Debug)]
+pub struct SendOutcome {
+    pub response: BarkResponse,
+    pub attempts: u32,
+    pub elapsed: Duration,
+    pub request_id: Option<String>,
+    /// The id the push went out with, generated or not.
+    pub id: Option<String>,
+    /// The base URL that answered, which differs from the primary after a failover.
+    pub server: Option<String>,
+}
+
+/// Per-device-key results of [`Client::send_fanout`].
+#[derive(Debug)]
+pub struct FanoutReport {
+    pub results: BTreeMap<String, Result<BarkResponse>>,
+}
+
+impl FanoutReport {
+    pub fn all_ok(&self) -> bool {
+        self.results.values().all(Result::is_ok)
+    }
+
+    pub fn failed_keys(&self) -> Vec<&str> {
+        self.results
+            .iter()
+            .filter(|(_, result)| result.is_err())
+            .map(|(key, _)| key.as_str())
+            .collect()
+    }
+}
+
+/// Per-chunk results of [`Client::send_chunked`], in key order.
+#[derive(Debug)]
+pub struct ChunkReport {
+    pub chunks: Vec<ChunkResult>,
+}
+
+#[derive(Debug)]
+pub struct ChunkResult {
+    pub device_keys: Vec<String>,
+    pub result: Result<BarkResponse>,
+}
+
+impl ChunkReport {
+    pub fn all_ok(&self) -> bool {
+        self.chunks.iter().all(|chunk| chunk.result.is_ok())
+    }
+
+    pub fn failed_keys(&self) -> Vec<&str> {
+        self.chunks
+            .iter()
+            .filter(|chunk| chunk.result.is_err())
+            .flat_map(|chunk| chunk.device_keys.iter().map(String::as_str))
+            .collect()
+    }
+}
+
+/// How pushes are encoded on the wire.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiStyle {
+    /// JSON `POST /push`, one request for all device keys.
+    #[default]
+    Json,
+    /// Legacy `GET /:device_key/:title/:body`, one request per device key.
+    Path,
+}
+
+/// How the client's default device keys combine with a message's own.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyMergeStrategy {
+    /// Defaults are used only when the message names no keys.
+    #[default]
+    FallbackOnly,
+    /// Defaults are always added to the message's keys.
+    Union,
+    /// Defaults are never used.
+    Ignore,
+}
+
+#[cfg(feature = "reqwest")]
+fn env_var(name: &str) -> Result<Option<String>> {
+    match std::env::var(name) {
+        Ok(value) => Ok(Some(value)),
+        Err(std::env::VarError::NotPresent) => Ok(None),
+        Err(error) => Err(Error::InvalidEnvVar {
+            name: name.to_owned(),
+            reason: error.to_string(),
+        }),
+    }
+}
+
+#[derive(Default)]
+struct Delivery {
+    attempts: u32,
+    request_id: Option<String>,
+    server: Option<String>,
+    /// Path-style keys already pushed, which retries and failover skip.
+    delivered_keys: HashSet<String>,
+}
+
+/// What is left of a send's [`RetryPolicy::total_deadline`], if it has one.
+struct Budget {
+    total: Option<Duration>,
+    started: Instant,
+}
+
+impl Budget {
+    fn deadline(&self) -> Option<Instant> {
+        self.total.map(|total| self.started + total)
+    }
+
+    fn remaining(&self, now: Instant) -> Option<Duration> {
+        self.deadline()
+            .map(|deadline| deadline.saturating_duration_since(now))
+    }
+
+    fn is_spent(&self, now: Instant) -> bool {
+        self.remaining(now)
+            .is_some_and(|remaining| remaining.is_zero())
+    }
+
+    /// A timeout naming the deadline, counting attempts like any other final failure.
+    fn exceeded(&self, now: Instant, attempts: u32) -> Error {
+        let timeout = Error::Timeout {
+            elapsed: now.saturating_duration_since(self.started),
+            deadline: self.total,
+        };
+
+        if attempts > 1 {
+            Error::RetriesExhausted {
+                attempts,
+                source: Box::new(timeout),
+            }
+        } else {
+            timeout
+        }
+    }
+}
+
+#[derive(Clone)]
+struct RequestIdConfig {
+    header: HeaderName,
+    generator: Arc<dyn IdGenerator>,
+}
+
+/// Cheap to clone: clones share configuration and stats, and `with_*` calls on one
+/// clone copy what they change, so the others never see them.
+#[derive(Clone)]
+pub struct Client {
+    transport: Arc<dyn Transport>,
+    transport_config: Arc<TransportConfig>,
+    base_url: Arc<str>,
+    fallback_urls: Arc<[String]>,
+    default_device_keys: Arc<HashSet<String>>,
+    key_merge_strategy: KeyMergeStrategy,
+    level_icons: Arc<HashMap<Level, String>>,
+    defaults: Arc<MessageDefaults>,
+    before_send: Arc<[BeforeSend]>,
+    after_send: Arc<[AfterSend]>,
+    on_retry: Arc<[OnRetry]>,
+    stats: Arc<StatsRecorder>,
+    request_id: Option<RequestIdConfig>,
+    id_generator: Arc<dyn IdGenerator>,
+    clock: Arc<dyn Clock>,
+    uploader: Option<Arc<dyn Uploader>>,
+    api_style: ApiStyle,
+    retry: Option<RetryPolicy>,
+    level_retries: Arc<HashMap<Level, RetryPolicy>>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    device_rate_limiter: Option<Arc<DeviceRateLimiter>>,
+    batch_concurrency: usize,
+    health_check_timeout: Duration,
+    payload_limit: usize,
+    auto_ids: bool,
+    updates: Option<Arc<Mutex<HashMap<String, MessageBuilder>>>>,
+    badges: Option<Badges>,
+    #[cfg(feature = "gzip")]
+    compression: Option<RequestCompression>,
+}
+
+/// Shows configuration only, with device keys shortened and URL passwords hidden.
+impl fmt::Debug for Client {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut device_keys: Vec<String> = self
+            .default_device_keys
+            .iter()
+            .map(|key| redact::device_key(key))
+            .collect();
+        device_keys.sort();
+
+        f.debug_struct("Client")
+            .field("base_url", &redact::url(&self.base_url))
+            .field(
+                "fallback_urls",
+                &self
+                    .fallback_urls
+                    .iter()
+                    .map(|url| redact::url(url))
+                    .collect::<Vec<_>>(),
+            )
+            .field("default_device_keys", &device_keys)
+            .field("key_merge_strategy", &self.key_merge_strategy)
+            .field("level_icons", &self.level_icons)
+            .field("defaults", &self.defaults)
+            .field("api_style", &self.api_style)
+            .field("retry", &self.retry)
+            .field("level_retries", &self.level_retries)
+            .field(
+                "rate_limit",
+                &self.rate_limiter.as_ref().map(|limiter| limiter.limit),
+            )
+            .field(
+                "rate_limit_per_device",
+                &self
+                    .device_rate_limiter
+                    .as_ref()
+                    .and_then(|limiter| limiter.default),
+            )
+            .field("batch_concurrency", &self.batch_concurrency)
+            .field("health_check_timeout", &self.health_check_timeout)
+            .field("payload_limit", &self.payload_limit)
+            .field("auto_ids", &self.auto_ids)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Client {
+    pub const DEFAULT_BATCH_CONCURRENCY: usize = 8;
+    pub const DEFAULT_HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+    pub const DEFAULT_PAYLOAD_LIMIT: usize = pipeline::DEFAULT_PAYLOAD_LIMIT;
+
+    /// Normalizes `base_url` like [`Client::try_new`]; a URL that does not parse
+    /// is kept as given, minus trailing slashes, and fails at send time.
+    #[cfg(feature = "reqwest")]
+    pub fn new(base_url: impl Into<String>) -> Self {
+        let transport = Arc::new(ReqwestTransport::new(reqwest::Client::default()));
+
+        Client::with_transport(base_url.into(), transport)
+    }
+
+    fn with_transport(base_url: String, transport: Arc<dyn Transport>) -> Self {
+        let base_url = validate::base_url("base_url", &base_url)
+            .unwrap_or_else(|_| base_url.trim_end_matches('/').to_owned());
+
+        Client {
+            transport,
+            transport_config: Arc::default(),
+            base_url: base_url.into(),
+            fallback_urls: Arc::new([]),
+            default_device_keys: Arc::default(),
+            key_merge_strategy: KeyMergeStrategy::FallbackOnly,
+            level_icons: Arc::default(),
+            defaults: Arc::default(),
+            before_send: Arc::new([]),
+            after_send: Arc::new([]),
+            on_retry: Arc::new([]),
+            stats: Arc::default(),
+            request_id: None,
+            id_generator: Arc::new(UuidV4),
+            clock: Arc::new(SystemClock),
+            uploader: None,
+            api_style: ApiStyle::Json,
+            retry: None,
+            level_retries: Arc::default(),
+            rate_limiter: None,
+            device_rate_limiter: None,
+            batch_concurrency: Client::DEFAULT_BATCH_CONCURRENCY,
+            health_check_timeout: Client::DEFAULT_HEALTH_CHECK_TIMEOUT,
+            payload_limit: Client::DEFAULT_PAYLOAD_LIMIT,
+            auto_ids: false,
+            updates: None,
+            badges: None,
+            #[cfg(feature = "gzip")]
+            compression: None,
+        }
+    }
+
+    /// A client that records requests in memory instead of sending them.
+    #[cfg(feature = "test-util")]
+    pub fn capturing() -> (Client, Captured) {
+        Client::builder()
+            .base_url("http://bark.test")
+            .build_capturing()
+            .expect("a static base URL is valid")
+    }
+
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::default()
+    }
+
+    /// Validates `base_url` as http(s), assuming https without a scheme, and
+    /// strips trailing slashes so subpath mounts like `https://host/bark/` work.
+    #[cfg(feature = "reqwest")]
+    pub fn try_new(base_url: impl Into<String>) -> Result<Client> {
+        Client::builder().base_url(base_url).build()
+    }
+
+    /// Reads `BARK_BASE_URL` and the optional comma-separated `BARK_DEVICE_KEY(S)`.
+    #[cfg(feature = "reqwest")]
+    pub fn from_env() -> Result<Client> {
+        Client::from_env_prefixed("BARK_")
+    }
+
+    /// Like [`Client::from_env`], with `prefix` in place of `BARK_`.
+    #[cfg(feature = "reqwest")]
+    pub fn from_env_prefixed(prefix: &str) -> Result<Client> {
+        let base_url_var = format!("{prefix}BASE_URL");
+        let base_url = env_var(&base_url_var)?.ok_or(Error::MissingEnvVar(base_url_var.clone()))?;
+        let mut builder = Client::builder().base_url(base_url);
+
+        for name in ["DEVICE_KEY", "DEVICE_KEYS"] {
+            let name = format!("{prefix}{name}");
+
+            if let Some(keys) = env_var(&name)? {
+                let keys = keys
+                    .split(',')
+                    .filter(|key| !key.trim().is_empty())
+                    .map(DeviceKey::new)
+                    .collect::<Result<Vec<_>>>()
+                    .map_err(|error| Error::InvalidEnvVar {
+                        name,
+                        reason: error.to_string(),
+                    })?;
+                builder = builder.device_keys(keys);
+            }
+        }
+
+        builder.build().map_err(|error| Error::InvalidEnvVar {
+            name: base_url_var,
+            reason: error.to_string(),
+        })
+    }
+
+    #[must_use]
+    pub fn with_device_key(mut self, device_key: impl Into<String>) -> Self {
+        Arc::make_mut(&mut self.default_device_keys).insert(device_key.into());
+        self
+    }
+
+    #[must_use]
+    pub fn with_device_keys<I, S>(mut self, device_keys: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Arc::make_mut(&mut self.default_device_keys)
+            .extend(device_keys.into_iter().map(Into::into));
+        self
+    }
+
+    /// Like the default device keys, these only fill fields the message leaves unset.
+    #[must_use]
+    pub fn with_group(mut self, group: impl Into<String>) -> Self {
+        Arc::make_mut(&mut self.defaults).group = Some(group.into());
+        self
+    }
+
+    #[must_use]
+    pub fn with_icon(mut self, icon: impl Into<String>) -> Self {
+        Arc::make_mut(&mut self.defaults).icon = Some(icon.into());
+        self
+    }
+
+    #[must_use]
+    pub fn with_sound(mut self, sound: impl Into<String>) -> Self {
+        Arc::make_mut(&mut self.defaults).sound = Some(sound.into());
+        self
+    }
+
+    #[must_use]
+    pub fn with_level(mut self, level: Level) -> Self {
+        Arc::make_mut(&mut self.defaults).level = Some(level);
+        self
+    }
+
+    pub fn message(&self) -> ClientMessageBuilder<'_> {
+        self.message_with(MessageBuilder::default())
+    }
+
+    /// Starts a message that replaces the notification already delivered under `id`.
+    pub fn replacing(&self, id: impl Into<String>) -> ClientMessageBuilder<'_> {
+        self.message_with(MessageBuilder::default().id(id))
+    }
+
+    /// Re-sends under `id`, replacing the delivered notification, e.g. to step a
+    /// progress bar. `update` starts from the last content sent for `id` when
+    /// [`ClientBuilder::remember_updates`] is on, and from scratch otherwise.
+    pub async fn update_notification<F>(
+        &self,
+        id: impl Into<String>,
+        update: F,
+    ) -> Result<BarkResponse>
+    where
+        F: FnOnce(MessageBuilder) -> MessageBuilder,
+    {
+        let id = id.into();
+        let previous = self
+            .updates
+            .as_ref()
+            .and_then(|updates| lock(updates).get(&id).cloned())
+            .unwrap_or_default();
+        let builder = update(previous).id(id.clone());
+        let mut message = builder.clone().build()?;
+        let response = self.dispatch(&mut message).await?;
+
+        if let Some(updates) = &self.updates {
+            if message.delete.0 {
+                lock(updates).remove(&id);
+            } else {
+                lock(updates).insert(id, builder);
+            }
+        }
+
+        Ok(response)
+    }
+
+    pub(crate) fn message_with(&self, builder: MessageBuilder) -> ClientMessageBuilder<'_> {
+        ClientMessageBuilder::new(self, builder)
+    }
+
+    pub fn group(&self, group: impl Into<String>) -> GroupHandle {
+        GroupHandle::new(self.clone(), group.into())
+    }
+
+    /// The settings this client actually resolved to, with keys and tokens redacted.
+    pub fn effective_config(&self) -> EffectiveConfig {
+        let Client {
+            transport: _,
+            transport_config,
+            base_url,
+            fallback_urls,
+            default_device_keys,
+            key_merge_strategy,
+            level_icons,
+            defaults,
+            before_send,
+            after_send,
+            on_retry,
+            stats: _,
+            request_id,
+            id_generator: _,
+            clock: _,
+            uploader,
+            api_style,
+            retry,
+            level_retries,
+            rate_limiter,
+            device_rate_limiter,
+            batch_concurrency,
+            health_check_timeout,
+            payload_limit,
+            auto_ids,
+            updates,
+            badges,
+            #[cfg(feature = "gzip")]
+            compression,
+        } = self;
+
+        let mut default_device_keys: Vec<String> = default_device_keys
+            .iter()
+            .map(|key| redact::device_key(key))
+            .collect();
+        default_device_keys.sort();
+
+        EffectiveConfig {
+            base_url: redact::url(base_url),
+            fallback_urls: fallback_urls.iter().map(|url| redact::url(url)).collect(),
+            default_device_keys,
+            key_merge_strategy: *key_merge_strategy,
+            level_icons: level_icons
+                .iter()
+                .map(|(level, icon)| (level.as_str().to_owned(), icon.clone()))
+                .collect(),
+            message_defaults: MessageDefaults::clone(defaults),
+            before_send_hooks: before_send.len(),
+            after_send_hooks: after_send.len(),
+            on_retry_hooks: on_retry.len(),
+            request_id_header: request_id
+                .as_ref()
+                .map(|config| config.header.as_str().to_owned()),
+            uploader: uploader.is_some(),
+            api_style: *api_style,
+            retry: *retry,
+            level_retries: level_retries
+                .iter()
+                .map(|(level, policy)| (level.as_str().to_owned(), *policy))
+                .collect(),
+            rate_limit: rate_limiter.as_ref().map(|limiter| limiter.limit),
+            rate_limit_per_device: device_rate_limiter
+                .as_ref()
+                .and_then(|limiter| limiter.default),
+            device_rate_limits: device_rate_limiter
+                .iter()
+                .flat_map(|limiter| &limiter.limits)
+                .map(|(key, limit)| (redact::device_key(key), *limit))
+                .collect(),
+            batch_concurrency: *batch_concurrency,
+            health_check_timeout: *health_check_timeout,
+            payload_limit: *payload_limit,
+            auto_ids: *auto_ids,
+            remember_updates: updates.is_some(),
+            auto_badge: badges.is_some(),
+            badge_store: badges
+                .as_ref()
+                .and_then(Badges::path)
+                .map(|path| path.display().to_string()),
+            #[cfg(feature = "gzip")]
+            compress_threshold: compression.as_ref().map(RequestCompression::threshold),
+            #[cfg(not(feature = "gzip"))]
+            compress_threshold: None,
+            transport: TransportConfig::clone(transport_config),
+            features: EffectiveConfig::features(),
+        }
+    }
+
+    /// Counters are atomics and latencies a bounded window, so keeping them
+    /// costs a few relaxed increments and one short lock per send.
+    pub fn stats(&self) -> ClientStats {
+        self.stats.snapshot()
+    }
+
+    /// The last badge [`ClientBuilder::auto_badge`] sent to `device_key`.
+    pub fn badge_count(&self, device_key: &str) -> Option<u64> {
+        self.badges.as_ref()?.get(device_key)
+    }
+
+    pub fn reset_stats(&self) {
+        self.stats.reset();
+    }
+
+    /// Sends a copy of `message`; client defaults and hooks never touch the caller's value.
+    pub async fn send(&self, message: &Message) -> Result<BarkResponse> {
+        self.dispatch(&mut message.clone()).await
+    }
+
+    /// Sends `title` and `body` to the default device keys with the client's defaults.
+    pub async fn notify(
+        &self,
+        title: impl Into<String>,
+        body: impl Into<String>,
+    ) -> Result<BarkResponse> {
+        self.dispatch(&mut Message::titled(title, body)).await
+    }
+
+    pub async fn notify_markdown(
+        &self,
+        title: impl Into<String>,
+        body: impl Into<String>,
+    ) -> Result<BarkResponse> {
+        let mut message = Message {
+            title: Some(title.into()),
+            ..Message::markdown(body)
+        };
+
+        self.dispatch(&mut message).await
+    }
+
+    #[deprecated(note = "use `send`, which leaves the message unchanged")]
+    pub async fn send_mut(&self, message: &mut Message) -> Result<BarkResponse> {
+        self.dispatch(message).await
+    }
+
+    /// Like [`send`](Self::send), with each attempt capped at `timeout`.
+    pub async fn send_with_timeout(
+        &self,
+        message: &Message,
+        timeout: Duration,
+    ) -> Result<BarkResponse> {
+        if timeout.is_zero() {
+            return Err(Error::ZeroTimeout("timeout"));
+        }
+
+        let mut message = message.clone();
+        message.timeout = Some(timeout);
+
+        self.dispatch(&mut message).await
+    }
+
+    pub async fn send_detailed(&self, message: &Message) -> Result<SendOutcome> {
+        self.dispatch_detailed(&mut message.clone()).await
+    }
+
+    pub(crate) async fn dispatch(&self, message: &mut Message) -> Result<BarkResponse> {
+        self.dispatch_detailed(message)
+            .await
+            .map(|outcome| outcome.response)
+    }
+
+    pub(crate) async fn dispatch_detailed(&self, message: &mut Message) -> Result<SendOutcome> {
+        let started = self.clock.instant();
+        let mut delivery = Delivery::default();
+        let outcome = self.deliver(message, &mut delivery).await;
+        let elapsed = self.clock.instant().saturating_duration_since(started);
+
+        self.stats.record(&outcome, delivery.attempts, elapsed);
+
+        if !self.after_send.is_empty() {
+            let report = SendReport {
+                summary: MessageSummary::from(&*message),
+                attempts: delivery.attempts,
+                elapsed,
+                request_id: delivery.request_id.as_deref(),
+                server: delivery.server.as_deref(),
+                outcome: &outcome,
+            };
+
+            hook::run_after_send(&self.after_send, &report);
+        }
+
+        outcome.map(|response| SendOutcome {
+            request_id: response.request_id.clone(),
+            response,
+            attempts: delivery.attempts,
+            elapsed,
+            id: message.id.clone(),
+            server: delivery.server,
+        })
+    }
+
+    pub async fn send_to<I, S>(&self, device_keys: I, message: &Message) -> Result<BarkResponse>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut message = message.clone();
+        message.device_keys = device_keys.into_iter().map(Into::into).collect();
+        message.keys_resolved = true;
+
+        if message.device_keys.is_empty() {
+            return Err(Error::MissingDeviceKey);
+        }
+
+        self.dispatch(&mut message).await
+    }
+
+    pub async fn send_item<T: ToMessage + ?Sized>(&self, item: &T) -> Result<BarkResponse> {
+        let mut message = item.to_message().build()?;
+        self.dispatch(&mut message).await
+    }
+
+    pub async fn send_into(&self, builder: impl Into<MessageBuilder>) -> Result<BarkResponse> {
+        let mut message = builder.into().build()?;
+        self.dispatch(&mut message).await
+    }
+
+    pub async fn send_split(
+        &self,
+        message: &Message,
+        options: SplitOptions,
+    ) -> Result<Vec<SendOutcome>> {
+        let Some(body) = &message.body else {
+            return Ok(vec![self.send_detailed(message).await?]);
+        };
+
+        let parts = split::split_body(&body.content, body.kind.is_markdown(), options);
+
+        if parts.len() == 1 {
+            return Ok(vec![self.send_detailed(message).await?]);
+        }
+
+        let total = parts.len();
+        let id = message.id.clone().unwrap_or_else(|| self.generate_id());
+        let mut responses = Vec::with_capacity(total);
+
+        for (index, content) in parts.into_iter().enumerate() {
+            let number = index + 1;
+            let mut part = message.clone();
+            let suffix = format!("({number}/{total})");
+
+            part.title = Some(match &message.title {
+                Some(title) => format!("{title} {suffix}"),
+                None => suffix,
+            });
+            part.id = Some(format!("{id}-{number}"));
+
+            if let Some(body) = &mut part.body {
+                body.content = content;
+            }
+
+            responses.push(self.dispatch_detailed(&mut part).await?);
+        }
+
+        Ok(responses)
+    }
+
+    /// Sends every message concurrently; results keep the input order.
+    pub async fn send_all<I>(&self, messages: I) -> Vec<Result<BarkResponse>>
+    where
+        I: IntoIterator<Item = Message>,
+    {
+        // Without `stop_on_fatal` every message is sent, so no result is dropped.
+        self.send_all_with(messages, SendAllOptions::default())
+            .await
+            .items
+            .into_iter()
+            .filter_map(|item| item.result)
+            .collect()
+    }
+
+    /// Like [`send_all`](Self::send_all), with request starts spaced out by `options`
+    /// and a report of what is left to resend.
+    pub async fn send_all_with<I>(&self, messages: I, options: SendAllOptions) -> BatchReport
+    where
+        I: IntoIterator<Item = Message>,
+    {
+        let pacer = &Pacer::new(&options);
+        let stopped = &AtomicBool::new(false);
+
+        let items = stream::iter(messages)
+            .map(|message| async move {
+                if !stopped.load(Ordering::Relaxed) {
+                    pacer.wait(&*self.clock).await;
+                }
+
+                // Checked again: the batch may have stopped while this one was paced.
+                if stopped.load(Ordering::Relaxed) {
+                    return BatchItem {
+                        message,
+                        result: None,
+                    };
+                }
+
+                let result = self.dispatch(&mut message.clone()).await;
+
+                if options.stop_on_fatal && result.as_ref().is_err_and(batch::is_fatal) {
+                    stopped.store(true, Ordering::Relaxed);
+                }
+
+                BatchItem {
+                    message,
+                    result: Some(result),
+                }
+            })
+            .buffered(options.concurrency.unwrap_or(self.batch_concurrency).max(1))
+            .collect()
+            .await;
+
+        BatchReport { items }
+    }
+
+    /// Sends one request per device key so a revoked key only fails its own push.
+    pub async fn send_fanout(&self, message: &Message) -> Result<FanoutReport> {
+        let keys = self.recipients(message)?;
+
+        let results = stream::iter(keys)
+            .map(|key| async move {
+                let mut single = message.clone();
+                single.device_keys = HashSet::from([key.clone()]);
+                single.keys_resolved = true;
+                (key, self.dispatch(&mut single).await)
+            })
+            .buffer_unordered(self.batch_concurrency)
+            .collect()
+            .await;
+
+        Ok(FanoutReport { results })
+    }
+
+    /// Splits the device keys into batches of at most `chunk_size`, one request each.
+    pub async fn send_chunked(&self, message: &Message, chunk_size: usize) -> Result<ChunkReport> {
+        let mut keys: Vec<String> = self.recipients(message)?.into_iter().collect();
+        keys.sort();
+
+        let batches: Vec<Vec<String>> = keys
+            .chunks(chunk_size.max(1))
+            .map(<[String]>::to_vec)
+            .collect();
+
+        let chunks = stream::iter(batches)
+            .map(|device_keys| async move {
+                let mut batch = message.clone();
+                batch.device_keys = device_keys.iter().cloned().collect();
+                batch.keys_resolved = true;
+                let result = self.dispatch(&mut batch).await;
+
+                ChunkResult {
+                    device_keys,
+                    result,
+                }
+            })
+            .buffered(self.batch_concurrency)
+            .collect()
+            .await;
+
+        Ok(ChunkReport { chunks })
+    }
+
+    fn recipients(&self, message: &Message) -> Result<HashSet<String>> {
+        let mut keys = message.device_keys.clone();
+
+        if !message.no_client_defaults {
+            self.merge_keys(&mut keys);
+        }
+
+        if keys.is_empty() {
+            return Err(Error::MissingDeviceKey);
+        }
+
+        device_key::normalize(keys)
+    }
+
+    fn merge_keys(&self, keys: &mut HashSet<String>) {
+        let merge = match self.key_merge_strategy {
+            KeyMergeStrategy::FallbackOnly => keys.is_empty(),
+            KeyMergeStrategy::Union => true,
+            KeyMergeStrategy::Ignore => false,
+        };
+
+        if merge {
+            keys.extend(self.default_device_keys.iter().cloned());
+        }
+    }
+
+    pub async fn register_device(&self, request: &RegisterRequest) -> Result<RegisterResponse> {
+        if request.device_token.trim().is_empty() {
+            return Err(Error::EmptyDeviceToken);
+        }
+
+        let mut url = self.endpoint("register")?;
+        let request = match self.api_style {
+            ApiStyle::Json => self.json_request(url.clone(), None, serde_json::to_vec(request)?)?,
+            ApiStyle::Path => {
+                url.query_pairs_mut()
+                    .append_pair("devicetoken", &request.device_token)
+                    .extend_pairs(request.key.as_ref().map(|key| ("key", key)));
+                self.request(Method::GET, url.clone(), None)?
+            }
+        };
+
+        let envelope = self.parse_envelope(url, self.transport.execute(request).await?)?;
+        let data = envelope.data.unwrap_or_default();
+        let data: RegisterData =
+            serde_json::from_value(data.clone()).map_err(|source| Error::InvalidResponse {
+                status: envelope.status,
+                body: redact::response_body(data.to_string().as_bytes()),
+                source,
+            })?;
+
+        Ok(RegisterResponse {
+            response: envelope.response,
+            device_key: data.device_key,
+            device_token: data.device_token,
+        })
+    }
+
+    /// Checks `/healthz`, which answers a plain `ok`.
+    pub async fn healthz(&self) -> Result<()> {
+        let response = self.health_check("healthz").await?;
+        let body = String::from_utf8_lossy(&response.body);
+
+        if body.trim() != "ok" {
+            return Err(Error::Unhealthy {
+                status: Some(response.status),
+                body: redact::response_body(&response.body),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Checks `/ping`, which answers the usual envelope.
+    pub async fn ping(&self) -> Result<BarkResponse> {
+        let url = self.endpoint("ping")?;
+        let response = self.health_check("ping").await?;
+
+        self.parse(url, response)
+    }
+
+    pub async fn server_info(&self) -> Result<ServerInfo> {
+        let url = self.endpoint("info")?;
+        let request = self.request(Method::GET, url.clone(), None)?;
+        let response = self.transport.execute(request).await?;
+
+        match response.status {
+            StatusCode::NOT_FOUND => Err(Error::EndpointDisabled { endpoint: "info" }),
+            status if !status.is_success() => Err(Error::HttpStatus {
+                status,
+                url,
+                body: redact::response_body(&response.body),
+            }),
+            status => {
+                serde_json::from_slice(&response.body).map_err(|source| Error::InvalidResponse {
+                    status,
+                    body: redact::response_body(&response.body),
+                    source,
+                })
+            }
+        }
+    }
+
+    async fn health_check(&self, path: &str) -> Result<RawResponse> {
+        let request = self.request(Method::GET, self.endpoint(path)?, None)?;
+        let deadline = self.clock.instant() + self.health_check_timeout;
+        let response = clock::timeout_at(&*self.clock, deadline, self.transport.execute(request))
+            .await
+            .ok_or_else(|| Error::Unhealthy {
+                status: None,
+                body: format!("no response within {:?}", self.health_check_timeout),
+            })??;
+
+        if !response.status.is_success() {
+            return Err(Error::Unhealthy {
+                status: Some(response.status),
+                body: redact::response_body(&response.body),
+            });
+        }
+
+        Ok(response)
+    }
+
+    /// The `curl` command [`send`](Self::send) would amount to, after client defaults.
+    pub fn dry_run_curl(&self, message: &Message) -> String {
+        let mut message = message.clone();
+
+        if !message.no_client_defaults {
+            self.apply_defaults(&mut message);
+        }
+
+        message.to_curl(&self.base_url)
+    }
+
+    pub fn send_repeated(&self, message: &Message, options: RepeatOptions) -> RepeatHandle {
+        RepeatHandle::new(self.clone(), message.clone(), options)
+    }
+
+    /// A queue that sends through this client once [`SendQueue::run`] is awaited.
+    pub fn queue(&self, options: QueueOptions) -> SendQueue {
+        SendQueue::new(self.clone(), options)
+    }
+
+    pub(crate) fn clock(&self) -> &dyn Clock {
+        &*self.clock
+    }
+
+    pub(crate) fn defaults(&self) -> &MessageDefaults {
+        &self.defaults
+    }
+
+    pub(crate) fn generate_id(&self) -> String {
+        self.id_generator.generate()
+    }
+
+    async fn deliver(
+        &self,
+        message: &mut Message,
+        delivery: &mut Delivery,
+    ) -> Result<BarkResponse> {
+        self.prepare(message)?;
+        self.upload_image(message).await?;
+        pipeline::seal(message, &*self.clock, self.payload_limit)?;
+
+        delivery.request_id = self
+            .request_id
+            .as_ref()
+            .map(|config| config.generator.generate());
+        let request_id = delivery.request_id.as_deref();
+        let policy = self.retry_policy(message);
+        let max_attempts = policy.map_or(1, |policy| policy.max_attempts.max(1));
+
+        let budget = Budget {
+            total: policy.and_then(|policy| policy.total_deadline),
+            started: self.clock.instant(),
+        };
+        let result = loop {
+            match self.acquire(message, budget.deadline()).await {
+                Ok(true) => {}
+                Ok(false) => {
+                    let now = self.clock.instant();
+                    break Err(budget.exceeded(now, delivery.attempts));
+                }
+                Err(error) => break Err(error),
+            }
+
+            let started = self.clock.instant();
+            let timeout = match budget.remaining(started) {
+                None => message.timeout,
+                Some(remaining) if remaining.is_zero() => {
+                    break Err(budget.exceeded(started, delivery.attempts));
+                }
+                Some(remaining) => Some(
+                    message
+                        .timeout
+                        .or(self.transport_config.timeout)
+                        .map_or(remaining, |timeout| timeout.min(remaining)),
+                ),
+            };
+
+            delivery.attempts += 1;
+
+            let posted = self.post(
+                message,
+                timeout,
+                request_id,
+                &mut delivery.server,
+                &mut delivery.delivered_keys,
+            );
+            // Custom transports may ignore the request timeout; this bounds them too.
+            let posted = match budget.remaining(started) {
+                None => posted.await,
+                Some(remaining) => clock::timeout_at(&*self.clock, started + remaining, posted)
+                    .await
+                    .unwrap_or(Err(Error::Timeout {
+                        elapsed: remaining,
+                        deadline: budget.total,
+                    })),
+            };
+
+            match posted {
+                Err(error) if delivery.attempts < max_attempts && retry::is_transient(&error) => {
+                    if let Some(policy) = &policy {
+                        let delay = match error {
+                            Error::RateLimited {
+                                retry_after: Some(retry_after),
+                            } => retry_after,
+                            _ => policy.backoff(delivery.attempts),
+                        };
+                        let now = self.clock.instant();
+
+                        if let Some(remaining) = budget.remaining(now)
+                            && delay + now.saturating_duration_since(started) >= remaining
+                        {
+                            break Err(budget.exceeded(now, delivery.attempts));
+                        }
+
+                        if !self.on_retry.is_empty() {
+                            let attempt = RetryAttempt {
+                                attempt: delivery.attempts,
+                                max_attempts,
+                                error: &error,
+                                failure: FailureKind::of(&error),
+                                delay,
+                                summary: &MessageSummary::from(&*message),
+                            };
+                            hook::run_on_retry(&self.on_retry, &attempt);
+                        }
+
+                        self.clock.sleep_until(now + delay).await;
+                    }
+                }
+                Err(_) if budget.is_spent(self.clock.instant()) => {
+                    let now = self.clock.instant();
+                    break Err(budget.exceeded(now, delivery.attempts));
+                }
+                Err(error) if delivery.attempts > 1 => {
+                    break Err(Error::RetriesExhausted {
+                        attempts: delivery.attempts,
+                        source: Box::new(error),
+                    });
+                }
+                result => break result,
+            }
+        };
+
+        match result {
+            Ok(mut response) => {
+                response.request_id = request_id.map(str::to_owned);
+                Ok(response)
+            }
+            Err(source) => match request_id {
+                Some(request_id) => Err(Error::WithRequestId {
+                    request_id: request_id.to_owned(),
+                    source: Box::new(source),
+                }),
+                None => Err(source),
+            },
+        }
+    }
+
+    /// Waits on the client and per-device limiters; `Ok(false)` if that would pass `deadline`.
+    async fn acquire(&self, message: &Message, deadline: Option<Instant>) -> Result<bool> {
+        if let Some(limiter) = &self.rate_limiter
+            && !limiter.acquire(&*self.clock, deadline).await?
+        {
+            return Ok(false);
+        }
+
+        match &self.device_rate_limiter {
+            Some(limiter) => {
+                limiter
+                    .acquire(&message.device_keys, &*self.clock, deadline)
+                    .await
+            }
+            None => Ok(true),
+        }
+    }
+
+    /// The message's own policy, else its level's, else the client's.
+    fn retry_policy(&self, message: &Message) -> Option<RetryPolicy> {
+        message.retry.or_else(|| {
+            message
+                .level
+                .as_ref()
+                .and_then(|level| self.level_retries.get(level))
+                .copied()
+                .or(self.retry)
+        })
+    }
+
+    fn prepare(&self, message: &mut Message) -> Result<()> {
+        if !message.no_client_defaults {
+            self.apply_defaults(message);
+        }
+
+        pipeline::require_keys(message)?;
+
+        if self.auto_ids && message.id.is_none() {
+            message.id = Some(self.generate_id());
+        }
+
+        hook::run_before_send(&self.before_send, message)?;
+
+        if let Some(badges) = &self.badges {
+            badges.assign(message);
+        }
+
+        Ok(())
+    }
+
+    async fn upload_image(&self, message: &mut Message) -> Result<()> {
+        let Some(image) = &message.image_bytes else {
+            return Ok(());
+        };
+
+        let uploader = self.uploader.as_ref().ok_or(Error::MissingUploader)?;
+        let url = uploader.upload(&image.bytes, &image.content_type).await?;
+
+        message.image = Some(url.into());
+        message.image_bytes = None;
+
+        Ok(())
+    }
+
+    fn apply_defaults(&self, message: &mut Message) {
+        if !message.keys_resolved {
+            self.merge_keys(&mut message.device_keys);
+        }
+
+        self.defaults.apply(message);
+
+        if message.icon.is_none()
+            && let Some(icon) = message
+                .level
+                .as_ref()
+                .and_then(|level| self.level_icons.get(level))
+        {
+            message.icon = Some(icon.clone());
+        }
+    }
+
+    pub(crate) fn endpoint(&self, path: &str) -> Result<Url> {
+        let url = format!("{}/{path}", self.base_url);
+
+        validate::http_url("base_url", &url)
+    }
+
+    fn json_request(
+        &self,
+        url: Url,
+        request_id: Option<&str>,
+        body: Vec<u8>,
+    ) -> Result<PreparedRequest> {
+        let mut request = pipeline::json_request(url, body);
+        self.tag(&mut request, request_id)?;
+
+        Ok(request)
+    }
+
+    fn request(
+        &self,
+        method: Method,
+        url: Url,
+        request_id: Option<&str>,
+    ) -> Result<PreparedRequest> {
+        let mut request = PreparedRequest {
+            method,
+            url,
+            headers: HeaderMap::new(),
+            body: Vec::new(),
+            timeout: None,
+            redacted_url: None,
+        };
+        self.tag(&mut request, request_id)?;
+
+        Ok(request)
+    }
+
+    /// Adds the request-id header, if the client sends one.
+    fn tag(&self, request: &mut PreparedRequest, request_id: Option<&str>) -> Result<()> {
+        if let (Some(config), Some(request_id)) = (&self.request_id, request_id) {
+            let value = HeaderValue::from_str(request_id)
+                .map_err(|_| Error::InvalidHeaderValue(request_id.to_owned()))?;
+            request.headers.insert(config.header.clone(), value);
+        }
+
+        Ok(())
+    }
+
+    /// Tries the primary, then each fallback while servers are unreachable or answer 5xx.
+    async fn post(
+        &self,
+        message: &Message,
+        timeout: Option<Duration>,
+        request_id: Option<&str>,
+        server: &mut Option<String>,
+        delivered_keys: &mut HashSet<String>,
+    ) -> Result<BarkResponse> {
+        let mut index: usize = 0;
+
+        loop {
+            let base_url = match index.checked_sub(1) {
+                None => &*self.base_url,
+                Some(fallback) => self.fallback_urls[fallback].as_str(),
+            };
+            *server = Some(redact::url(base_url));
+
+            match self
+                .post_to(base_url, message, timeout, request_id, delivered_keys)
+                .await
+            {
+                Err(error) if index < self.fallback_urls.len() && retry::is_unavailable(&error) => {
+                    index += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    async fn post_to(
+        &self,
+        base_url: &str,
+        message: &Message,
+        timeout: Option<Duration>,
+        request_id: Option<&str>,
+        delivered_keys: &mut HashSet<String>,
+    ) -> Result<BarkResponse> {
+        if self.api_style == ApiStyle::Path {
+            return self
+                .get_each(base_url, message, timeout, request_id, delivered_keys)
+                .await;
+        }
+
+        let url = pipeline::push_url(base_url)?;
+        let body = serde_json::to_vec(message)?;
+
+        #[cfg(feature = "gzip")]
+        if let Some(compression) = &self.compression
+            && compression.applies(body.len())
+        {
+            let mut request =
+                self.json_request(url.clone(), request_id, compression.encode(&body))?;
+            request
+                .headers
+                .insert(CONTENT_ENCODING, HeaderValue::from_static("gzip"));
+            request.timeout = timeout;
+
+            let response = self.transport.execute(request).await?;
+
+            if !refuses_gzip(&response) {
+                return self.parse(url, response);
+            }
+
+            compression.reject();
+        }
+
+        let mut request = self.json_request(url.clone(), request_id, body)?;
+        request.timeout = timeout;
+        let response = self.transport.execute(request).await?;
+
+        self.parse(url, response)
+    }
 
-use serde::Deserialize;
+    /// One GET per key not in `delivered_keys`, adding those that succeed; the last
+    /// response is returned. A lone failing key fails with its own error, and a
+    /// partial failure with [`Error::DeviceKeysFailed`].
+    async fn get_each(
+        &self,
+        base_url: &str,
+        message: &Message,
+        timeout: Option<Duration>,
+        request_id: Option<&str>,
+        delivered_keys: &mut HashSet<String>,
+    ) -> Result<BarkResponse> {
+        let mut keys: Vec<&String> = message
+            .device_keys
+            .iter()
+            .filter(|key| !delivered_keys.contains(*key))
+            .collect();
+        keys.sort();
 
-use crate::{
-    error::{Error, Result},
-    message::{BodyKind, Level, Message, MessageBuilder},
-};
+        let mut last = None;
+        let mut failed = Vec::new();
+
+        for device_key in keys {
+            match self
+                .get_one(base_url, message, device_key, timeout, request_id)
+                .await
+            {
+                Ok(response) => {
+                    delivered_keys.insert(device_key.clone());
+                    last = Some(response);
+                }
+                Err(error) => failed.push((device_key.clone(), error)),
+            }
+        }
+
+        if failed.is_empty() {
+            return last.ok_or(Error::MissingDeviceKey);
+        }
+
+        if failed.len() == 1 && delivered_keys.is_empty() {
+            return Err(failed.remove(0).1);
+        }
+
+        let mut delivered: Vec<String> = delivered_keys.iter().cloned().collect();
+        delivered.sort();
+
+        Err(Error::DeviceKeysFailed { delivered, failed })
+    }
+
+    /// Errors and logs see the URL with the key shortened.
+    async fn get_one(
+        &self,
+        base_url: &str,
+        message: &Message,
+        device_key: &str,
+        timeout: Option<Duration>,
+        request_id: Option<&str>,
+    ) -> Result<BarkResponse> {
+        let url = message.to_url(base_url, device_key)?.url;
+        let redacted_url = message
+            .to_url(base_url, &redact::device_key(device_key))?
+            .url;
+        let mut request = self.request(Method::GET, url, request_id)?;
+        request.timeout = timeout;
+        request.redacted_url = Some(redacted_url.clone());
+
+        let redact = |error| redact::key_in_error(error, device_key, &redacted_url);
+        let response = self.transport.execute(request).await.map_err(redact)?;
+
+        self.parse(redacted_url.clone(), response).map_err(redact)
+    }
+
+    fn parse(&self, url: Url, response: RawResponse) -> Result<BarkResponse> {
+        pipeline::parse_response(url, response, self.clock.now())
+    }
 
-#[derive(Debug, Deserialize)]
-pub struct BarkResponse {
-    pub code: i64,
-    pub message: String,
-    pub timestamp: i64,
+    fn parse_envelope(&self, url: Url, response: RawResponse) -> Result<Envelope> {
+        pipeline::parse_envelope(url, response, self.clock.now())
+    }
 }
 
-pub struct Client {
-    reqwest: reqwest::Client,
-    base_url: String,
-    default_device_keys: HashSet<String>,
+/// A 415, or a 400 that is not a bark error about the push itself: proxies and
+/// servers without gzip support answer those, and the push is resent uncompressed.
+#[cfg(feature = "gzip")]
+fn refuses_gzip(response: &RawResponse) -> bool {
+    match response.status {
+        StatusCode::UNSUPPORTED_MEDIA_TYPE => true,
+        StatusCode::BAD_REQUEST => serde_json::from_slice::<pipeline::RawEnvelope>(&response.body)
+            .ok()
+            .is_none_or(|envelope| {
+                let message = envelope.response.message.to_ascii_lowercase();
+                message.contains("gzip") || message.contains("encoding")
+            }),
+        _ => false,
+    }
 }
 
-impl Client {
-    pub fn new(base_url: impl Into<String>) -> Self {
-        let mut base_url = base_url.into();
+#[cfg(all(feature = "reqwest", not(target_arch = "wasm32")))]
+#[derive(Debug, Clone, Copy)]
+enum HttpVersion {
+    Http1Only,
+    Http2PriorKnowledge,
+}
+
+/// Settings for the native reqwest client that browser fetch has no equivalent for.
+#[cfg(all(feature = "reqwest", not(target_arch = "wasm32")))]
+#[derive(Default)]
+struct ConnectOptions {
+    resolve: Vec<(String, SocketAddr)>,
+    dns_resolver: Option<Arc<dyn Resolve>>,
+    http_version: Option<HttpVersion>,
+    http2_keep_alive_interval: Option<Duration>,
+    http2_keep_alive_timeout: Option<Duration>,
+    tcp_keepalive: Option<Duration>,
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    no_redirects: bool,
+    proxy: Option<String>,
+    proxy_auth: Option<(String, String)>,
+    no_proxy: bool,
+}
 
-        if base_url.ends_with('/') {
-            base_url.pop();
+#[cfg(all(feature = "reqwest", not(target_arch = "wasm32")))]
+impl ConnectOptions {
+    fn validate(&self) -> Result<()> {
+        let timeouts = [
+            ("timeout", self.timeout),
+            ("connect_timeout", self.connect_timeout),
+        ];
+
+        for (field, value) in timeouts {
+            if value.is_some_and(|value| value.is_zero()) {
+                return Err(Error::ZeroTimeout(field));
+            }
         }
 
-        Client {
-            reqwest: reqwest::Client::default(),
-            base_url,
-            default_device_keys: HashSet::default(),
+        if let Some(proxy) = &self.proxy {
+            validate::proxy_url(proxy)?;
+        }
+
+        Ok(())
+    }
+
+    fn describe(&self, config: &mut TransportConfig) {
+        config.http_version = self.http_version.map(|version| match version {
+            HttpVersion::Http1Only => "http1_only",
+            HttpVersion::Http2PriorKnowledge => "http2_prior_knowledge",
+        });
+        config.http2_keep_alive_interval = self.http2_keep_alive_interval;
+        config.http2_keep_alive_timeout = self.http2_keep_alive_timeout;
+        config.tcp_keepalive = self.tcp_keepalive;
+        config.timeout = self.timeout;
+        config.connect_timeout = self.connect_timeout;
+        config.no_redirects = self.no_redirects;
+        config.proxy = self.proxy.as_deref().map(redact::url);
+        config.proxy_auth = self.proxy_auth.is_some();
+        config.no_proxy = self.no_proxy;
+        config.resolve_overrides = self.resolve.iter().map(|(host, _)| host.clone()).collect();
+        config.custom_dns_resolver = self.dns_resolver.is_some();
+    }
+
+    fn apply(self, mut http: reqwest::ClientBuilder) -> Result<reqwest::ClientBuilder> {
+        let ConnectOptions {
+            resolve,
+            dns_resolver,
+            http_version,
+            http2_keep_alive_interval,
+            http2_keep_alive_timeout,
+            tcp_keepalive,
+            timeout,
+            connect_timeout,
+            no_redirects,
+            proxy,
+            proxy_auth,
+            no_proxy,
+        } = self;
+
+        for (host, addr) in &resolve {
+            http = http.resolve(host, *addr);
+        }
+
+        if let Some(resolver) = dns_resolver {
+            http = http.dns_resolver(resolver);
+        }
+
+        match http_version {
+            Some(HttpVersion::Http1Only) => http = http.http1_only(),
+            Some(HttpVersion::Http2PriorKnowledge) => http = http.http2_prior_knowledge(),
+            None => {}
+        }
+
+        if let Some(interval) = http2_keep_alive_interval {
+            http = http.http2_keep_alive_interval(interval);
+        }
+
+        if let Some(timeout) = http2_keep_alive_timeout {
+            http = http.http2_keep_alive_timeout(timeout);
+        }
+
+        if let Some(keepalive) = tcp_keepalive {
+            http = http.tcp_keepalive(keepalive);
         }
+
+        if let Some(timeout) = timeout {
+            http = http.timeout(timeout);
+        }
+
+        if let Some(timeout) = connect_timeout {
+            http = http.connect_timeout(timeout);
+        }
+
+        if no_redirects {
+            http = http.redirect(reqwest::redirect::Policy::none());
+        }
+
+        if no_proxy {
+            http = http.no_proxy();
+        }
+
+        if let Some(proxy) = &proxy {
+            let mut proxy = reqwest::Proxy::all(proxy.as_str())?;
+
+            if let Some((username, password)) = &proxy_auth {
+                proxy = proxy.basic_auth(username, password);
+            }
+
+            http = http.proxy(proxy);
+        }
+
+        Ok(http)
+    }
+}
+
+#[derive(Default)]
+pub struct ClientBuilder {
+    base_url: Option<String>,
+    fallback_urls: Vec<String>,
+    transport: Option<Arc<dyn Transport>>,
+    #[cfg(feature = "reqwest")]
+    http_client: Option<reqwest::Client>,
+    default_device_keys: HashSet<String>,
+    key_merge_strategy: KeyMergeStrategy,
+    level_icons: HashMap<Level, String>,
+    before_send: Vec<BeforeSend>,
+    after_send: Vec<AfterSend>,
+    on_retry: Vec<OnRetry>,
+    #[cfg(all(feature = "reqwest", not(target_arch = "wasm32")))]
+    connect: ConnectOptions,
+    #[cfg(feature = "reqwest")]
+    user_agent: Option<String>,
+    request_id_header: Option<String>,
+    request_id_generator: Option<Arc<dyn IdGenerator>>,
+    id_generator: Option<Arc<dyn IdGenerator>>,
+    clock: Option<Arc<dyn Clock>>,
+    uploader: Option<Arc<dyn Uploader>>,
+    api_style: ApiStyle,
+    retry: Option<RetryPolicy>,
+    level_retries: HashMap<Level, RetryPolicy>,
+    rate_limit: Option<RateLimit>,
+    device_rate_limit: Option<RateLimit>,
+    device_rate_limits: HashMap<String, RateLimit>,
+    device_rate_limit_capacity: Option<usize>,
+    batch_concurrency: Option<usize>,
+    health_check_timeout: Option<Duration>,
+    payload_limit: Option<usize>,
+    auto_ids: bool,
+    remember_updates: bool,
+    auto_badge: bool,
+    badge_store: Option<PathBuf>,
+    on_badge_store_error: Option<OnBadgeStoreError>,
+    #[cfg(feature = "tracing")]
+    wire_logging: Option<usize>,
+    #[cfg(feature = "gzip")]
+    compress_threshold: Option<usize>,
+    #[cfg(all(feature = "tower", not(target_arch = "wasm32")))]
+    layers: Vec<service::LayerFn>,
+}
+
+impl ClientBuilder {
+    #[must_use]
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = Some(base_url.into());
+        self
     }
 
+    /// Adds a server to try, in order, when the ones before it are unreachable or
+    /// answer 5xx. 4xx and bark error codes never fail over.
     #[must_use]
-    pub fn with_device_key(mut self, device_key: impl Into<String>) -> Self {
+    pub fn fallback_url(mut self, base_url: impl Into<String>) -> Self {
+        self.fallback_urls.push(base_url.into());
+        self
+    }
+
+    /// Sends through `transport` instead of the built-in reqwest client; the
+    /// HTTP connection options are then ignored.
+    #[must_use]
+    pub fn transport(mut self, transport: Arc<dyn Transport>) -> Self {
+        self.transport = Some(transport);
+        self
+    }
+
+    /// Reuses an existing client; the HTTP options on this builder are then ignored.
+    #[cfg(feature = "reqwest")]
+    #[must_use]
+    pub fn http_client(mut self, http: reqwest::Client) -> Self {
+        self.http_client = Some(http);
+        self
+    }
+
+    #[must_use]
+    pub fn device_key(mut self, device_key: impl Into<String>) -> Self {
         self.default_device_keys.insert(device_key.into());
         self
     }
 
     #[must_use]
-    pub fn with_device_keys<I, S>(mut self, device_keys: I) -> Self
+    pub fn device_keys<I, S>(mut self, device_keys: I) -> Self
     where
         I: IntoIterator<Item = S>,
         S: Into<String>,
@@ -52,182 +1683,636 @@ impl Client {
         self
     }
 
-    pub fn message(&self) -> ClientMessageBuilder<'_> {
-        ClientMessageBuilder {
-            client: self,
-            builder: MessageBuilder::default(),
-        }
+    #[must_use]
+    pub fn key_merge_strategy(mut self, strategy: KeyMergeStrategy) -> Self {
+        self.key_merge_strategy = strategy;
+        self
     }
 
-    pub async fn send(&self, message: &mut Message) -> Result<BarkResponse> {
-        if message.device_keys.is_empty() {
-            message
-                .device_keys
-                .extend(self.default_device_keys.iter().cloned());
-        }
+    #[must_use]
+    pub fn icon_for_level(mut self, level: Level, icon: impl Into<String>) -> Self {
+        self.level_icons.insert(level, icon.into());
+        self
+    }
 
-        if message.device_keys.is_empty() {
-            return Err(Error::MissingDeviceKey);
-        }
+    #[must_use]
+    pub fn before_send<F, R>(mut self, hook: F) -> Self
+    where
+        F: Fn(&mut Message) -> R + Send + Sync + 'static,
+        R: Into<HookControl>,
+    {
+        self.before_send
+            .push(Arc::new(move |message: &mut Message| hook(message).into()));
+        self
+    }
 
-        let response = self
-            .reqwest
-            .post(format!("{}/push", self.base_url))
-            .json(message)
-            .send()
-            .await?
-            .error_for_status()?
-            .json::<BarkResponse>()
-            .await?;
+    /// Runs inline once per logical send, success or failure, after any retries,
+    /// so it must return quickly; hand slow work off to a task or channel.
+    /// A panic in it is caught and does not change the send's result.
+    #[must_use]
+    pub fn after_send<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&SendReport<'_>) + Send + Sync + 'static,
+    {
+        self.after_send.push(Arc::new(hook));
+        self
+    }
 
-        Ok(response)
+    /// Observes every send's outcome, for metrics: latency, attempts, key count, and
+    /// the Bark code or [`FailureKind`]. Runs with the
+    /// [`after_send`](Self::after_send) hooks, in the order they were added.
+    #[must_use]
+    pub fn on_result<F>(self, observer: F) -> Self
+    where
+        F: Fn(&SendReport<'_>) + Send + Sync + 'static,
+    {
+        self.after_send(observer)
     }
-}
 
-pub struct ClientMessageBuilder<'a> {
-    client: &'a Client,
-    builder: MessageBuilder,
-}
+    /// Runs inline before each retry's wait, for a log line or metric per retry;
+    /// it cannot cancel the retry, and a panic in it is caught.
+    #[must_use]
+    pub fn on_retry<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&RetryAttempt<'_>) + Send + Sync + 'static,
+    {
+        self.on_retry.push(Arc::new(hook));
+        self
+    }
 
-impl ClientMessageBuilder<'_> {
+    #[cfg(feature = "reqwest")]
     #[must_use]
-    pub fn title(mut self, title: impl Into<String>) -> Self {
-        self.builder = self.builder.title(title);
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
         self
     }
 
+    /// Wraps the HTTP transport in a tower layer. Layers added first end up
+    /// outermost, matching `tower::ServiceBuilder`.
+    #[cfg(all(feature = "tower", not(target_arch = "wasm32")))]
     #[must_use]
-    pub fn subtitle(mut self, subtitle: impl Into<String>) -> Self {
-        self.builder = self.builder.subtitle(subtitle);
+    pub fn layer<L>(mut self, layer: L) -> Self
+    where
+        L: Layer<TransportService> + Send + Sync + 'static,
+        L::Service:
+            Service<PreparedRequest, Response = RawResponse> + Clone + Send + Sync + 'static,
+        <L::Service as Service<PreparedRequest>>::Error: Into<BoxError>,
+        <L::Service as Service<PreparedRequest>>::Future: Send + 'static,
+    {
+        self.layers
+            .push(Box::new(move |inner| service::boxed(layer.layer(inner))));
         self
     }
 
+    /// Sends a fresh id (from the id generator unless `request_id_generator` is
+    /// set) in this header on every push and reports it on the response or wraps
+    /// errors with it.
     #[must_use]
-    pub fn body(mut self, body: impl Into<String>) -> Self {
-        self.builder = self.builder.body(body);
+    pub fn request_id_header(mut self, name: impl Into<String>) -> Self {
+        self.request_id_header = Some(name.into());
         self
     }
 
     #[must_use]
-    pub fn body_kind(mut self, body_kind: BodyKind) -> Self {
-        self.builder = self.builder.body_kind(body_kind);
+    pub fn request_id_generator<G>(mut self, generator: G) -> Self
+    where
+        G: IdGenerator + 'static,
+    {
+        self.request_id_generator = Some(Arc::new(generator));
         self
     }
 
+    /// Mints message ids for split parts and `with_generated_id`; UUIDv4 by default.
     #[must_use]
-    pub fn device_key(mut self, device_key: impl Into<String>) -> Self {
-        self.builder = self.builder.device_key(device_key);
+    pub fn id_generator(mut self, generator: Arc<dyn IdGenerator>) -> Self {
+        self.id_generator = Some(generator);
         self
     }
 
+    /// Bodies larger than `threshold_bytes` are sent gzip-compressed. A 415, or
+    /// a 400 that is not a bark error envelope (unless it names the encoding),
+    /// is retried once uncompressed and compression stays off for this client
+    /// (and its clones) from then on.
+    #[cfg(feature = "gzip")]
     #[must_use]
-    pub fn device_keys<I, S>(mut self, device_keys: I) -> Self
-    where
-        I: IntoIterator<Item = S>,
-        S: Into<String>,
-    {
-        self.builder = self.builder.device_keys(device_keys);
+    pub fn compress_requests(mut self, threshold_bytes: usize) -> Self {
+        self.compress_threshold = Some(threshold_bytes);
+        self
+    }
+
+    /// Logs every request and response at TRACE level on the `bark::wire` target,
+    /// with device keys and credentials redacted.
+    #[cfg(feature = "tracing")]
+    #[must_use]
+    pub fn debug_wire_logging(mut self, enabled: bool) -> Self {
+        self.wire_logging =
+            enabled.then_some(self.wire_logging.unwrap_or(wire_log::DEFAULT_BODY_LIMIT));
+        self
+    }
+
+    /// Truncates logged bodies to `max_len` bytes, enabling wire logging.
+    #[cfg(feature = "tracing")]
+    #[must_use]
+    pub fn wire_log_body_limit(mut self, max_len: usize) -> Self {
+        self.wire_logging = Some(max_len);
+        self
+    }
+
+    /// Time source for latency, timestamps, and anything that waits; the system clock by default.
+    #[must_use]
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    #[must_use]
+    pub fn uploader(mut self, uploader: Arc<dyn Uploader>) -> Self {
+        self.uploader = Some(uploader);
+        self
+    }
+
+    #[must_use]
+    pub fn api_style(mut self, api_style: ApiStyle) -> Self {
+        self.api_style = api_style;
+        self
+    }
+
+    /// Retries connect errors, timeouts, 429 and 5xx responses; bark error codes are final.
+    #[must_use]
+    pub fn retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = Some(policy);
+        self
+    }
+
+    /// Replaces [`retry`](Self::retry) for messages at `level`, including a level
+    /// set by the client defaults; pass `max_attempts: 1` to never retry them.
+    #[must_use]
+    pub fn retry_for_level(mut self, level: Level, policy: RetryPolicy) -> Self {
+        self.level_retries.insert(level, policy);
+        self
+    }
+
+    /// Throttles every request, retries included, across all clones of the client.
+    #[must_use]
+    pub fn rate_limit(mut self, limit: RateLimit) -> Self {
+        self.rate_limit = Some(limit);
+        self
+    }
+
+    /// Gives every device key its own bucket, on top of [`rate_limit`](Self::rate_limit);
+    /// a push to several keys takes a slot from each and waits for the fullest.
+    #[must_use]
+    pub fn rate_limit_per_device(mut self, limit: RateLimit) -> Self {
+        self.device_rate_limit = Some(limit);
+        self
+    }
+
+    /// Overrides [`rate_limit_per_device`](Self::rate_limit_per_device) for one key;
+    /// without a per-device default, other keys only count against the global limit.
+    #[must_use]
+    pub fn rate_limit_for_device(
+        mut self,
+        device_key: impl Into<String>,
+        limit: RateLimit,
+    ) -> Self {
+        self.device_rate_limits.insert(device_key.into(), limit);
+        self
+    }
+
+    /// How many per-device buckets to keep before the least recently used idle
+    /// ones are dropped; 1024 by default.
+    #[must_use]
+    pub fn device_rate_limit_capacity(mut self, capacity: usize) -> Self {
+        self.device_rate_limit_capacity = Some(capacity);
+        self
+    }
+
+    /// How many requests the batch sends (`send_all`, `send_fanout`, `send_chunked`) keep in flight.
+    #[must_use]
+    pub fn batch_concurrency(mut self, limit: usize) -> Self {
+        self.batch_concurrency = Some(limit);
+        self
+    }
+
+    /// Bounds [`Client::healthz`] and [`Client::ping`], independently of [`timeout`](Self::timeout).
+    #[must_use]
+    pub fn health_check_timeout(mut self, timeout: Duration) -> Self {
+        self.health_check_timeout = Some(timeout);
+        self
+    }
+
+    /// Caps [`Message::estimated_size`] at send time; servers relaying to APNs drop larger pushes.
+    #[must_use]
+    pub fn payload_limit(mut self, limit: usize) -> Self {
+        self.payload_limit = Some(limit);
+        self
+    }
+
+    /// Gives messages sent without an `id` one from the id generator, so they
+    /// can be updated or deleted later; see [`SendOutcome::id`].
+    #[must_use]
+    pub fn auto_ids(mut self, enabled: bool) -> Self {
+        self.auto_ids = enabled;
+        self
+    }
+
+    /// Keeps the last content sent through [`Client::update_notification`] per id,
+    /// shared by all clones, so updates can start from it. Entries live until a delete.
+    #[must_use]
+    pub fn remember_updates(mut self, enabled: bool) -> Self {
+        self.remember_updates = enabled;
         self
     }
 
+    /// Numbers the badge of each message sent without one: one more than the
+    /// highest badge its device keys were sent, counted per key and shared by all
+    /// clones. Messages with a badge set it as their keys' count; ciphertext is left alone.
     #[must_use]
-    pub fn level(mut self, level: Level) -> Self {
-        self.builder = self.builder.level(level);
+    pub fn auto_badge(mut self, enabled: bool) -> Self {
+        self.auto_badge = enabled;
         self
     }
 
+    /// Turns on [`auto_badge`](Self::auto_badge) and keeps the counts in a JSON file
+    /// at `path`, loaded at build and rewritten (temp file, then rename) as each badge
+    /// is assigned, so restarts carry on counting. Clients in this process naming the
+    /// same file share one count; only one process may write a file at a time.
     #[must_use]
-    pub fn volume(mut self, volume: u8) -> Self {
-        self.builder = self.builder.volume(volume);
+    pub fn badge_store(mut self, path: impl Into<PathBuf>) -> Self {
+        self.badge_store = Some(path.into());
         self
     }
 
+    /// Hears about a badge store that did not parse, and so was started fresh, or
+    /// could not be written; the send goes ahead either way.
     #[must_use]
-    pub fn badge(mut self, badge: u64) -> Self {
-        self.builder = self.builder.badge(badge);
+    pub fn on_badge_store_error<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&Error) + Send + Sync + 'static,
+    {
+        self.on_badge_store_error = Some(Arc::new(callback));
         self
     }
 
+    #[cfg(feature = "test-util")]
+    pub fn build_capturing(self) -> Result<(Client, Captured)> {
+        let captured = Captured::default();
+        let client = self.transport(captured.transport()).build()?;
+
+        Ok((client, captured))
+    }
+
+    #[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+    pub fn build_blocking(self) -> Result<blocking::Client> {
+        blocking::Client::from_async(self.build()?)
+    }
+
+    pub fn build(self) -> Result<Client> {
+        let ClientBuilder {
+            base_url,
+            fallback_urls,
+            transport,
+            #[cfg(feature = "reqwest")]
+            http_client,
+            default_device_keys,
+            key_merge_strategy,
+            level_icons,
+            before_send,
+            after_send,
+            on_retry,
+            #[cfg(all(feature = "reqwest", not(target_arch = "wasm32")))]
+            connect,
+            #[cfg(feature = "reqwest")]
+            user_agent,
+            request_id_header,
+            request_id_generator,
+            id_generator,
+            clock,
+            uploader,
+            api_style,
+            retry,
+            level_retries,
+            rate_limit,
+            device_rate_limit,
+            device_rate_limits,
+            device_rate_limit_capacity,
+            batch_concurrency,
+            health_check_timeout,
+            payload_limit,
+            auto_ids,
+            remember_updates,
+            auto_badge,
+            badge_store,
+            on_badge_store_error,
+            #[cfg(feature = "tracing")]
+            wire_logging,
+            #[cfg(feature = "gzip")]
+            compress_threshold,
+            #[cfg(all(feature = "tower", not(target_arch = "wasm32")))]
+            layers,
+        } = self;
+
+        let base_url = base_url
+            .filter(|base_url| !base_url.trim().is_empty())
+            .ok_or(Error::MissingBaseUrl)?;
+        let base_url = validate::base_url("base_url", &base_url)?;
+        let fallback_urls = fallback_urls
+            .iter()
+            .map(|url| validate::base_url("fallback_url", url))
+            .collect::<Result<Vec<_>>>()?;
+        let default_device_keys = device_key::normalize(default_device_keys)?;
+        let device_rate_limits = device_rate_limits
+            .into_iter()
+            .map(|(key, limit)| Ok((DeviceKey::new(key)?.into(), limit)))
+            .collect::<Result<HashMap<String, RateLimit>>>()?;
+
+        if health_check_timeout.is_some_and(|timeout| timeout.is_zero()) {
+            return Err(Error::ZeroTimeout("health_check_timeout"));
+        }
+
+        #[cfg(all(feature = "reqwest", not(target_arch = "wasm32")))]
+        connect.validate()?;
+
+        #[cfg(feature = "reqwest")]
+        let user_agent = user_agent
+            .map(|user_agent| {
+                HeaderValue::from_str(&user_agent)
+                    .map_err(|_| Error::InvalidHeaderValue(user_agent))
+            })
+            .transpose()?;
+
+        for icon in level_icons.values() {
+            validate::http_url("icon", icon)?;
+        }
+
+        let id_generator = id_generator.unwrap_or_else(|| Arc::new(UuidV4));
+        let clock = clock.unwrap_or_else(|| Arc::new(SystemClock));
+
+        let request_id = request_id_header
+            .map(|name| {
+                let header = HeaderName::from_bytes(name.as_bytes())
+                    .map_err(|_| Error::InvalidHeaderName(name.clone()))?;
+                let generator = request_id_generator.unwrap_or_else(|| id_generator.clone());
+
+                Ok::<_, Error>(RequestIdConfig { header, generator })
+            })
+            .transpose()?;
+
+        #[cfg_attr(
+            not(all(feature = "reqwest", not(target_arch = "wasm32"))),
+            allow(unused_mut)
+        )]
+        let mut transport_config = TransportConfig {
+            custom_transport: transport.is_some(),
+            #[cfg(feature = "reqwest")]
+            custom_http_client: http_client.is_some(),
+            #[cfg(feature = "reqwest")]
+            user_agent: user_agent
+                .as_ref()
+                .and_then(|user_agent| user_agent.to_str().ok())
+                .map(str::to_owned),
+            #[cfg(all(feature = "tower", not(target_arch = "wasm32")))]
+            tower_layers: layers.len(),
+            #[cfg(not(all(feature = "tower", not(target_arch = "wasm32"))))]
+            tower_layers: 0,
+            #[cfg(feature = "tracing")]
+            wire_log_body_limit: wire_logging,
+            #[cfg(not(feature = "tracing"))]
+            wire_log_body_limit: None,
+            ..TransportConfig::default()
+        };
+
+        #[cfg(all(feature = "reqwest", not(target_arch = "wasm32")))]
+        connect.describe(&mut transport_config);
+
+        let transport: Arc<dyn Transport> = match transport {
+            Some(transport) => transport,
+            #[cfg(feature = "reqwest")]
+            None => {
+                let http = match http_client {
+                    Some(http) => http,
+                    None => {
+                        let mut http = reqwest::Client::builder();
+
+                        #[cfg(not(target_arch = "wasm32"))]
+                        {
+                            http = connect.apply(http)?;
+                        }
+
+                        if let Some(user_agent) = user_agent {
+                            http = http.user_agent(user_agent);
+                        }
+
+                        http.build()?
+                    }
+                };
+
+                Arc::new(ReqwestTransport::new(http).with_clock(clock.clone()))
+            }
+            #[cfg(not(feature = "reqwest"))]
+            None => return Err(Error::MissingTransport),
+        };
+
+        #[cfg(all(feature = "tower", not(target_arch = "wasm32")))]
+        let transport: Arc<dyn Transport> = if layers.is_empty() {
+            transport
+        } else {
+            let service = layers
+                .into_iter()
+                .rev()
+                .fold(service::transport_service(transport), |service, layer| {
+                    layer(service)
+                });
+
+            Arc::new(service::ServiceTransport(service))
+        };
+
+        #[cfg(feature = "tracing")]
+        let transport: Arc<dyn Transport> = match wire_logging {
+            Some(body_limit) => Arc::new(wire_log::WireLogTransport {
+                inner: transport,
+                body_limit,
+            }),
+            None => transport,
+        };
+
+        let mut client = Client::with_transport(base_url, transport);
+        client.fallback_urls = fallback_urls.into();
+        client.transport_config = Arc::new(transport_config);
+        client.default_device_keys = Arc::new(default_device_keys);
+        client.key_merge_strategy = key_merge_strategy;
+        client.level_icons = Arc::new(level_icons);
+        client.before_send = before_send.into();
+        client.after_send = after_send.into();
+        client.on_retry = on_retry.into();
+        client.request_id = request_id;
+        client.id_generator = id_generator;
+        client.clock = clock;
+        client.uploader = uploader;
+        client.api_style = api_style;
+        client.retry = retry;
+        client.level_retries = Arc::new(level_retries);
+        client.rate_limiter = rate_limit.map(|limit| Arc::new(RateLimiter::new(limit)));
+
+        if device_rate_limit.is_some() || !device_rate_limits.is_empty() {
+            client.device_rate_limiter = Some(Arc::new(DeviceRateLimiter::new(
+                device_rate_limit,
+                device_rate_limits,
+                device_rate_limit_capacity.unwrap_or(DeviceRateLimiter::DEFAULT_CAPACITY),
+            )));
+        }
+
+        if let Some(limit) = batch_concurrency {
+            client.batch_concurrency = limit.max(1);
+        }
+
+        if let Some(timeout) = health_check_timeout {
+            client.health_check_timeout = timeout;
+        }
+
+        if let Some(limit) = payload_limit {
+            client.payload_limit = limit;
+        }
+
+        client.auto_ids = auto_ids;
+        client.updates = remember_updates.then(Arc::default);
+        client.badges = match badge_store {
+            Some(path) => Some(Badges::open(&path, on_badge_store_error)?),
+            None => auto_badge.then(Badges::in_memory),
+        };
+
+        #[cfg(feature = "gzip")]
+        {
+            client.compression = compress_threshold.map(RequestCompression::new);
+        }
+
+        Ok(client)
+    }
+}
+
+#[cfg(all(feature = "reqwest", not(target_arch = "wasm32")))]
+impl ClientBuilder {
     #[must_use]
-    pub fn call(mut self, call: bool) -> Self {
-        self.builder = self.builder.call(call);
+    pub fn resolve(mut self, host: impl Into<String>, addr: SocketAddr) -> Self {
+        self.connect.resolve.push((host.into(), addr));
         self
     }
 
     #[must_use]
-    pub fn auto_copy(mut self, auto_copy: bool) -> Self {
-        self.builder = self.builder.auto_copy(auto_copy);
+    pub fn dns_resolver(mut self, resolver: Arc<dyn Resolve>) -> Self {
+        self.connect.dns_resolver = Some(resolver);
         self
     }
 
     #[must_use]
-    pub fn copy(mut self, copy: impl Into<String>) -> Self {
-        self.builder = self.builder.copy(copy);
+    pub fn http1_only(mut self) -> Self {
+        self.connect.http_version = Some(HttpVersion::Http1Only);
         self
     }
 
     #[must_use]
-    pub fn sound(mut self, sound: impl Into<String>) -> Self {
-        self.builder = self.builder.sound(sound);
+    pub fn http2_prior_knowledge(mut self) -> Self {
+        self.connect.http_version = Some(HttpVersion::Http2PriorKnowledge);
         self
     }
 
     #[must_use]
-    pub fn icon(mut self, icon: impl Into<String>) -> Self {
-        self.builder = self.builder.icon(icon);
+    pub fn http2_keep_alive_interval(mut self, interval: Duration) -> Self {
+        self.connect.http2_keep_alive_interval = Some(interval);
         self
     }
 
     #[must_use]
-    pub fn image(mut self, image: impl Into<String>) -> Self {
-        self.builder = self.builder.image(image);
+    pub fn http2_keep_alive_timeout(mut self, timeout: Duration) -> Self {
+        self.connect.http2_keep_alive_timeout = Some(timeout);
         self
     }
 
     #[must_use]
-    pub fn group(mut self, group: impl Into<String>) -> Self {
-        self.builder = self.builder.group(group);
+    pub fn tcp_keepalive(mut self, keepalive: Duration) -> Self {
+        self.connect.tcp_keepalive = Some(keepalive);
         self
     }
 
     #[must_use]
-    pub fn ciphertext(mut self, ciphertext: impl Into<String>) -> Self {
-        self.builder = self.builder.ciphertext(ciphertext);
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.connect.timeout = Some(timeout);
         self
     }
 
     #[must_use]
-    pub fn is_archive(mut self, is_archive: bool) -> Self {
-        self.builder = self.builder.is_archive(is_archive);
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect.connect_timeout = Some(timeout);
         self
     }
 
     #[must_use]
-    pub fn url(mut self, url: impl Into<String>) -> Self {
-        self.builder = self.builder.url(url);
+    pub fn no_redirects(mut self) -> Self {
+        self.connect.no_redirects = true;
         self
     }
 
+    /// Sends all traffic through `url`: `http://` or `https://`, or `socks5://`
+    /// and `socks5h://` with the `socks` feature.
     #[must_use]
-    pub fn action(mut self, action: impl Into<String>) -> Self {
-        self.builder = self.builder.action(action);
+    pub fn proxy(mut self, url: impl Into<String>) -> Self {
+        self.connect.proxy = Some(url.into());
         self
     }
 
+    /// Basic auth credentials for the [`proxy`](Self::proxy).
     #[must_use]
-    pub fn id(mut self, id: impl Into<String>) -> Self {
-        self.builder = self.builder.id(id);
+    pub fn proxy_auth(mut self, username: impl Into<String>, password: impl Into<String>) -> Self {
+        self.connect.proxy_auth = Some((username.into(), password.into()));
         self
     }
 
+    /// Ignores `HTTP_PROXY` and friends from the environment.
     #[must_use]
-    pub fn delete(mut self, delete: bool) -> Self {
-        self.builder = self.builder.delete(delete);
+    pub fn no_proxy(mut self) -> Self {
+        self.connect.no_proxy = true;
         self
     }
+}
+
+impl<C: AsRef<Client>> ClientMessageBuilder<'_, C> {
+    #[must_use]
+    pub fn with_generated_id(self) -> Self {
+        let (client, builder) = self.into_parts();
+        let id = client.as_ref().generate_id();
+
+        ClientMessageBuilder::new(client, builder.id(id))
+    }
+}
 
+impl ClientMessageBuilder<'_> {
     pub async fn send(self) -> Result<BarkResponse> {
-        let mut message = self.builder.build()?;
-        self.client.send(&mut message).await
+        let (client, builder) = self.into_parts();
+
+        client.dispatch(&mut builder.build()?).await
+    }
+}
+
+impl AsRef<Client> for Client {
+    fn as_ref(&self) -> &Client {
+        self
+    }
+}
+
+/// Sends each message like [`Client::send`], so it can sit under tower
+/// middleware such as `tower::limit::RateLimit`. Always ready.
+#[cfg(feature = "tower")]
+impl Service<Message> for Client {
+    type Response = BarkResponse;
+    type Error = Error;
+    type Future = crate::BoxFuture<'static, Result<BarkResponse>>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, mut message: Message) -> Self::Future {
+        let client = self.clone();
+        Box::pin(async move { client.dispatch(&mut message).await })
     }
 }
@@ -0,0 +1,54 @@
+#[cfg(feature = "async")]
+use std::future::Future;
+
+use crate::{
+    BoxFuture,
+    time::{self, Instant, SystemTime},
+};
+
+pub trait Clock: Send + Sync {
+    fn now(&self) -> SystemTime;
+
+    fn instant(&self) -> Instant;
+
+    fn sleep_until(&self, deadline: Instant) -> BoxFuture<'_, ()>;
+
+    /// Completes once the clock reaches `deadline`, which timeouts race against.
+    /// Unlike [`sleep_until`](Self::sleep_until), waiting here must not move a
+    /// clock that only moves when told to.
+    fn reached(&self, deadline: Instant) -> BoxFuture<'_, ()> {
+        self.sleep_until(deadline)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+
+    fn instant(&self) -> Instant {
+        time::now()
+    }
+
+    fn sleep_until(&self, deadline: Instant) -> BoxFuture<'_, ()> {
+        Box::pin(time::sleep_until(deadline))
+    }
+}
+
+/// Runs `future` until `clock` reaches `deadline`, or gives up with `None`.
+#[cfg(feature = "async")]
+pub(crate) async fn timeout_at<F: Future>(
+    clock: &dyn Clock,
+    deadline: Instant,
+    future: F,
+) -> Option<F::Output> {
+    use futures_util::future::{self, Either};
+
+    match future::select(std::pin::pin!(future), clock.reached(deadline)).await {
+        Either::Left((output, _)) => Some(output),
+        Either::Right(_) => None,
+    }
+}
@@ -0,0 +1,44 @@
+use std::{
+    io::Write,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+use flate2::{Compression, write::GzEncoder};
+
+#[derive(Debug, Clone)]
+pub(crate) struct RequestCompression {
+    threshold: usize,
+    rejected: Arc<AtomicBool>,
+}
+
+impl RequestCompression {
+    pub(crate) fn new(threshold: usize) -> Self {
+        RequestCompression {
+            threshold,
+            rejected: Arc::default(),
+        }
+    }
+
+    pub(crate) fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    pub(crate) fn applies(&self, len: usize) -> bool {
+        len > self.threshold && !self.rejected.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn reject(&self) {
+        self.rejected.store(true, Ordering::Relaxed);
+    }
+
+    pub(crate) fn encode(&self, body: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(body)
+            .expect("writing to a Vec cannot fail");
+        encoder.finish().expect("writing to a Vec cannot fail")
+    }
+}
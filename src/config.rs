@@ -0,0 +1,100 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use serde::Serialize;
+
+use crate::{
+    client::{ApiStyle, KeyMergeStrategy},
+    message::MessageDefaults,
+    rate_limit::RateLimit,
+    retry::RetryPolicy,
+};
+
+const FEATURES: &[&str] = &[
+    #[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+    "blocking",
+    #[cfg(feature = "chrono")]
+    "chrono",
+    #[cfg(feature = "cli")]
+    "cli",
+    #[cfg(feature = "crypto")]
+    "crypto",
+    #[cfg(feature = "gzip")]
+    "gzip",
+    #[cfg(feature = "put-upload")]
+    "put-upload",
+    #[cfg(feature = "reqwest")]
+    "reqwest",
+    #[cfg(feature = "socks")]
+    "socks",
+    #[cfg(feature = "test-util")]
+    "test-util",
+    #[cfg(feature = "tower")]
+    "tower",
+    #[cfg(feature = "tracing")]
+    "tracing",
+    #[cfg(feature = "ulid")]
+    "ulid",
+    #[cfg(feature = "ureq")]
+    "ureq",
+];
+
+/// Connection settings captured when the client was built.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TransportConfig {
+    pub custom_transport: bool,
+    pub custom_http_client: bool,
+    pub http_version: Option<&'static str>,
+    pub http2_keep_alive_interval: Option<Duration>,
+    pub http2_keep_alive_timeout: Option<Duration>,
+    pub tcp_keepalive: Option<Duration>,
+    pub timeout: Option<Duration>,
+    pub connect_timeout: Option<Duration>,
+    pub user_agent: Option<String>,
+    pub no_redirects: bool,
+    pub proxy: Option<String>,
+    pub proxy_auth: bool,
+    pub no_proxy: bool,
+    pub resolve_overrides: Vec<String>,
+    pub custom_dns_resolver: bool,
+    pub tower_layers: usize,
+    pub wire_log_body_limit: Option<usize>,
+}
+
+/// A redacted snapshot of a client's settings, safe to paste into a bug report.
+#[derive(Debug, Clone, Serialize)]
+pub struct EffectiveConfig {
+    pub base_url: String,
+    pub fallback_urls: Vec<String>,
+    pub default_device_keys: Vec<String>,
+    pub key_merge_strategy: KeyMergeStrategy,
+    pub level_icons: BTreeMap<String, String>,
+    pub message_defaults: MessageDefaults,
+    pub before_send_hooks: usize,
+    pub after_send_hooks: usize,
+    pub on_retry_hooks: usize,
+    pub request_id_header: Option<String>,
+    pub uploader: bool,
+    pub api_style: ApiStyle,
+    pub retry: Option<RetryPolicy>,
+    pub level_retries: BTreeMap<String, RetryPolicy>,
+    pub rate_limit: Option<RateLimit>,
+    pub rate_limit_per_device: Option<RateLimit>,
+    /// Per-key overrides, keyed by the shortened device key.
+    pub device_rate_limits: BTreeMap<String, RateLimit>,
+    pub batch_concurrency: usize,
+    pub health_check_timeout: Duration,
+    pub payload_limit: usize,
+    pub auto_ids: bool,
+    pub remember_updates: bool,
+    pub auto_badge: bool,
+    pub badge_store: Option<String>,
+    pub compress_threshold: Option<usize>,
+    pub transport: TransportConfig,
+    pub features: Vec<&'static str>,
+}
+
+impl EffectiveConfig {
+    pub(crate) fn features() -> Vec<&'static str> {
+        FEATURES.to_vec()
+    }
+}
@@ -0,0 +1,126 @@
+use std::fmt;
+
+use aes::{Aes128, Aes192, Aes256};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use cbc::cipher::{BlockCipher, BlockEncryptMut, KeyInit, KeyIvInit, block_padding::Pkcs7};
+
+use crate::{
+    error::{Error, Result},
+    message::Message,
+};
+
+const IV_LEN: usize = 16;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Cipher {
+    Aes128,
+    Aes192,
+    Aes256,
+}
+
+/// Key and IV for Bark's encrypted pushes (AES-CBC with PKCS#7 padding).
+#[derive(Clone, PartialEq, Eq)]
+pub struct EncryptionConfig {
+    cipher: Cipher,
+    key: Vec<u8>,
+    iv: [u8; IV_LEN],
+}
+
+impl EncryptionConfig {
+    pub fn aes128_cbc(key: impl AsRef<[u8]>, iv: impl AsRef<[u8]>) -> Result<Self> {
+        Self::new(Cipher::Aes128, 16, key.as_ref(), iv.as_ref())
+    }
+
+    pub fn aes192_cbc(key: impl AsRef<[u8]>, iv: impl AsRef<[u8]>) -> Result<Self> {
+        Self::new(Cipher::Aes192, 24, key.as_ref(), iv.as_ref())
+    }
+
+    pub fn aes256_cbc(key: impl AsRef<[u8]>, iv: impl AsRef<[u8]>) -> Result<Self> {
+        Self::new(Cipher::Aes256, 32, key.as_ref(), iv.as_ref())
+    }
+
+    fn new(cipher: Cipher, key_len: usize, key: &[u8], iv: &[u8]) -> Result<Self> {
+        if key.len() != key_len {
+            return Err(Error::InvalidKeyLength {
+                expected: key_len,
+                actual: key.len(),
+            });
+        }
+
+        Ok(EncryptionConfig {
+            cipher,
+            key: key.to_vec(),
+            iv: parse_iv(iv)?,
+        })
+    }
+
+    pub fn encrypt(&self, plaintext: &[u8]) -> String {
+        self.encrypt_with(&self.iv, plaintext)
+    }
+
+    fn encrypt_with(&self, iv: &[u8; IV_LEN], plaintext: &[u8]) -> String {
+        let ciphertext = match self.cipher {
+            Cipher::Aes128 => encrypt::<Aes128>(&self.key, iv, plaintext),
+            Cipher::Aes192 => encrypt::<Aes192>(&self.key, iv, plaintext),
+            Cipher::Aes256 => encrypt::<Aes256>(&self.key, iv, plaintext),
+        };
+
+        STANDARD.encode(ciphertext)
+    }
+
+    /// Moves every field except the device keys and `iv` into the encrypted `ciphertext`.
+    /// A message's own `iv` replaces the configured one, so the app decrypts with what it is sent.
+    pub(crate) fn seal(&self, message: &mut Message) -> Result<()> {
+        let cipher_iv = match &message.iv {
+            Some(iv) => parse_iv(iv.as_bytes())?,
+            None => self.iv,
+        };
+        let device_keys = std::mem::take(&mut message.device_keys);
+        let iv = message.iv.take();
+        let plaintext = serde_json::to_vec(&*message)?;
+
+        *message = Message {
+            device_keys,
+            ciphertext: Some(self.encrypt_with(&cipher_iv, &plaintext)),
+            iv,
+            timeout: message.timeout,
+            #[cfg(feature = "async")]
+            retry: message.retry,
+            no_client_defaults: message.no_client_defaults,
+            ..Message::default()
+        };
+
+        Ok(())
+    }
+}
+
+impl fmt::Debug for EncryptionConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cipher = match self.cipher {
+            Cipher::Aes128 => "aes128_cbc",
+            Cipher::Aes192 => "aes192_cbc",
+            Cipher::Aes256 => "aes256_cbc",
+        };
+
+        f.debug_struct("EncryptionConfig")
+            .field("cipher", &cipher)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Bark sends the IV as a 16-character string and uses its UTF-8 bytes.
+pub(crate) fn parse_iv(iv: &[u8]) -> Result<[u8; IV_LEN]> {
+    iv.try_into().map_err(|_| Error::InvalidIvLength {
+        expected: IV_LEN,
+        actual: iv.len(),
+    })
+}
+
+fn encrypt<C>(key: &[u8], iv: &[u8], plaintext: &[u8]) -> Vec<u8>
+where
+    C: BlockCipher + BlockEncryptMut + KeyInit,
+{
+    cbc::Encryptor::<C>::new_from_slices(key, iv)
+        .expect("key and iv lengths are checked on construction")
+        .encrypt_padded_vec_mut::<Pkcs7>(plaintext)
+}
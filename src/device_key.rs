@@ -0,0 +1,98 @@
+use std::{collections::HashSet, fmt, str::FromStr};
+
+use serde::Serialize;
+
+use crate::{
+    error::{Error, Result},
+    redact,
+};
+
+/// Characters that only show up when a URL or query was pasted instead of a key.
+const FORBIDDEN: &[char] = &['/', '?', '#', '&', '=', ':'];
+
+/// A Bark device key, trimmed and checked for obvious paste mistakes.
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+#[serde(transparent)]
+pub struct DeviceKey(String);
+
+impl DeviceKey {
+    /// Trims surrounding whitespace, then rejects empty keys, inner whitespace and URL syntax.
+    pub fn new(key: impl AsRef<str>) -> Result<Self> {
+        let key = key.as_ref().trim();
+
+        let reason = if key.is_empty() {
+            "is empty"
+        } else if key.chars().any(|c| c.is_whitespace() || c.is_control()) {
+            "contains whitespace"
+        } else if key.contains(FORBIDDEN) {
+            "looks like a URL, expected only the key"
+        } else {
+            return Ok(DeviceKey(key.to_owned()));
+        };
+
+        Err(Error::InvalidDeviceKey {
+            key: redact::device_key(key),
+            reason,
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for DeviceKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("DeviceKey")
+            .field(&redact::device_key(&self.0))
+            .finish()
+    }
+}
+
+impl fmt::Display for DeviceKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl AsRef<str> for DeviceKey {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for DeviceKey {
+    type Err = Error;
+
+    fn from_str(key: &str) -> Result<Self> {
+        DeviceKey::new(key)
+    }
+}
+
+impl TryFrom<&str> for DeviceKey {
+    type Error = Error;
+
+    fn try_from(key: &str) -> Result<Self> {
+        DeviceKey::new(key)
+    }
+}
+
+impl TryFrom<String> for DeviceKey {
+    type Error = Error;
+
+    fn try_from(key: String) -> Result<Self> {
+        DeviceKey::new(key)
+    }
+}
+
+impl From<DeviceKey> for String {
+    fn from(key: DeviceKey) -> Self {
+        key.0
+    }
+}
+
+pub(crate) fn normalize(keys: HashSet<String>) -> Result<HashSet<String>> {
+    keys.into_iter()
+        .map(|key| DeviceKey::new(key).map(String::from))
+        .collect()
+}
@@ -1,4 +1,10 @@
+use std::{path::PathBuf, time::Duration};
+
+use http::StatusCode;
 use thiserror::Error;
+use url::Url;
+
+use crate::message::Level;
 
 #[derive(Debug, Error)]
 pub enum Error {
@@ -6,8 +12,170 @@ pub enum Error {
     VolumeOutOfRange { current: u8, max: u8 },
     #[error("Missing device key")]
     MissingDeviceKey,
+    #[error("Invalid device key {key:?}: {reason}")]
+    InvalidDeviceKey { key: String, reason: &'static str },
+    #[error("Device token is empty")]
+    EmptyDeviceToken,
+    #[error("Missing base URL")]
+    MissingBaseUrl,
+    #[error("Missing environment variable {0}")]
+    MissingEnvVar(String),
+    #[error("Invalid environment variable {name}: {reason}")]
+    InvalidEnvVar { name: String, reason: String },
+    #[error("{0} must be greater than zero")]
+    ZeroTimeout(&'static str),
+    #[error(
+        "Unknown sound {given:?}{}",
+        did_you_mean.as_ref().map(|known| format!(", did you mean {known:?}?")).unwrap_or_default()
+    )]
+    UnknownSound {
+        given: String,
+        did_you_mean: Option<String>,
+    },
+    #[error(
+        "Unknown level {given:?}, expected one of: {}",
+        Level::ALL.map(|level| level.to_string()).join(", ")
+    )]
+    UnknownLevel { given: String },
+    #[error("Unknown body kind {given:?}, expected markdown, md, plaintext, text or body")]
+    UnknownBodyKind { given: String },
+    #[error("Invalid timestamp format: {0}")]
+    InvalidTimestampFormat(String),
+    #[error("Stored record version {found} is newer than the supported version {supported}")]
+    UnsupportedRecordVersion { found: u64, supported: u32 },
+    #[error("Invalid stored record: {0}")]
+    InvalidRecord(String),
+    #[error("Cannot store a message with {0} set; reading it back would lose it")]
+    UnstorableMessage(&'static str),
+    #[error("Badge store {} failed: {source}", path.display())]
+    BadgeStore {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Invalid badge store {}, starting fresh: {reason}", path.display())]
+    InvalidBadgeStore { path: PathBuf, reason: String },
+    #[error("Invalid encryption key length: expected {expected} bytes, got {actual}")]
+    InvalidKeyLength { expected: usize, actual: usize },
+    #[error("Invalid encryption IV length: expected {expected} bytes, got {actual}")]
+    InvalidIvLength { expected: usize, actual: usize },
+    #[error("IV is only meaningful together with ciphertext")]
+    IvWithoutCiphertext,
+    #[error("Ciphertext replaces the plaintext fields, but these are set too: {}", fields.join(", "))]
+    CiphertextConflictsWithPlaintext { fields: Vec<&'static str> },
+    #[error("Volume only takes effect on the critical level")]
+    VolumeRequiresCriticalLevel,
+    #[error("Id must not be empty")]
+    EmptyId,
+    #[error("Delete needs an id naming the notification to remove")]
+    DeleteRequiresId,
+    #[error("Failed to start the blocking runtime: {0}")]
+    Runtime(std::io::Error),
+    #[error("The blocking client cannot send from inside a tokio runtime; use the async client")]
+    InsideRuntime,
+    #[error("No transport configured: enable the reqwest feature or set ClientBuilder::transport")]
+    MissingTransport,
+    #[error("Image bytes attached but no uploader configured")]
+    MissingUploader,
+    #[error("Invalid URL for {field} ({value}): {source}")]
+    InvalidUrl {
+        field: &'static str,
+        value: String,
+        source: UrlError,
+    },
+    #[error("Payload too large: got ({size}) bytes, limit ({limit})")]
+    PayloadTooLarge { size: usize, limit: usize },
+    #[error("URL too long: got ({length}), max ({max})")]
+    UrlTooLong { length: usize, max: usize },
+    #[error("Invalid header name: {0}")]
+    InvalidHeaderName(String),
+    #[error("Invalid header value: {0}")]
+    InvalidHeaderValue(String),
+    #[error("Invalid JSON: {0}")]
+    InvalidJson(#[from] serde_json::Error),
+    #[error("Send skipped by hook")]
+    Skipped,
+    #[error("Hook failed: {0}")]
+    HookFailed(String),
+    #[error("Hook panicked: {0}")]
+    HookPanicked(String),
+    #[error("Queue is closed")]
+    QueueClosed,
+    #[error("Client-side rate limit reached; capacity frees up in {retry_after:?}")]
+    RateLimitExceeded { retry_after: Duration },
+    #[error("Gave up after {attempts} attempts: {source}")]
+    RetriesExhausted { attempts: u32, source: Box<Error> },
+    #[error(
+        "{} of {} device keys failed{}",
+        failed.len(),
+        delivered.len() + failed.len(),
+        failed.first().map(|(_, error)| format!(", first with: {error}")).unwrap_or_default()
+    )]
+    DeviceKeysFailed {
+        /// Keys that got the push, on this attempt or an earlier one.
+        delivered: Vec<String>,
+        failed: Vec<(String, Error)>,
+    },
+    #[error("Request {request_id} failed: {source}")]
+    WithRequestId {
+        request_id: String,
+        source: Box<Error>,
+    },
+    #[error("Server rejected the push with code {code}: {message}")]
+    Server {
+        code: i64,
+        message: String,
+        timestamp: i64,
+    },
+    #[error("The server does not expose /{endpoint}")]
+    EndpointDisabled { endpoint: &'static str },
+    #[error(
+        "Server is unhealthy{}: {body:?}",
+        status.map(|status| format!(" (HTTP status {status})")).unwrap_or_default()
+    )]
+    Unhealthy {
+        status: Option<StatusCode>,
+        body: String,
+    },
+    #[error(
+        "Rate limited{}",
+        retry_after.map(|after| format!(", retry after {after:?}")).unwrap_or_default()
+    )]
+    RateLimited { retry_after: Option<Duration> },
+    #[error("HTTP status {status} for url ({url}): {body:?}")]
+    HttpStatus {
+        status: StatusCode,
+        url: Url,
+        body: String,
+    },
+    #[error("Invalid response (HTTP status {status}): {source}; body: {body:?}")]
+    InvalidResponse {
+        status: StatusCode,
+        body: String,
+        source: serde_json::Error,
+    },
+    #[error(
+        "Request timed out after {elapsed:?}{}",
+        deadline.map(|deadline| format!(", past its {deadline:?} total deadline")).unwrap_or_default()
+    )]
+    Timeout {
+        elapsed: Duration,
+        /// The [`RetryPolicy::total_deadline`](crate::retry::RetryPolicy::total_deadline)
+        /// that ran out, when the whole send timed out.
+        deadline: Option<Duration>,
+    },
+    #[error("Transport failed: {0}")]
+    Transport(Box<dyn std::error::Error + Send + Sync>),
+    #[cfg(feature = "reqwest")]
     #[error("Request failed: {0}")]
     RequestError(#[from] reqwest::Error),
 }
 
+#[derive(Debug, Error)]
+pub enum UrlError {
+    #[error(transparent)]
+    Parse(#[from] url::ParseError),
+    #[error("Unsupported scheme ({0})")]
+    UnsupportedScheme(String),
+}
+
 pub type Result<T> = std::result::Result<T, Error>;
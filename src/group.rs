@@ -0,0 +1,90 @@
+use std::sync::Arc;
+
+use crate::{
+    client::{BarkResponse, Client, ClientMessageBuilder},
+    error::Result,
+    message::{Level, Message, MessageBuilder, MessageDefaults, ToMessage},
+};
+
+#[derive(Clone)]
+pub struct GroupHandle {
+    client: Client,
+    defaults: Arc<MessageDefaults>,
+}
+
+impl GroupHandle {
+    pub(crate) fn new(client: Client, group: String) -> Self {
+        GroupHandle {
+            client,
+            defaults: Arc::new(MessageDefaults {
+                group: Some(group),
+                ..MessageDefaults::default()
+            }),
+        }
+    }
+
+    #[must_use]
+    pub fn icon(mut self, icon: impl Into<String>) -> Self {
+        Arc::make_mut(&mut self.defaults).icon = Some(icon.into());
+        self
+    }
+
+    #[must_use]
+    pub fn sound(mut self, sound: impl Into<String>) -> Self {
+        Arc::make_mut(&mut self.defaults).sound = Some(sound.into());
+        self
+    }
+
+    #[must_use]
+    pub fn default_level(mut self, level: Level) -> Self {
+        Arc::make_mut(&mut self.defaults).level = Some(level);
+        self
+    }
+
+    pub fn defaults(&self) -> &MessageDefaults {
+        &self.defaults
+    }
+
+    pub fn message(&self) -> ClientMessageBuilder<'_> {
+        self.client
+            .message_with(self.defaults.apply_to_builder(MessageBuilder::default()))
+    }
+
+    pub async fn send(&self, message: &Message) -> Result<BarkResponse> {
+        self.dispatch(message.clone()).await
+    }
+
+    /// Sends `title` and `body` to the default device keys with the group's defaults.
+    pub async fn notify(
+        &self,
+        title: impl Into<String>,
+        body: impl Into<String>,
+    ) -> Result<BarkResponse> {
+        self.dispatch(Message::titled(title, body)).await
+    }
+
+    pub async fn notify_markdown(
+        &self,
+        title: impl Into<String>,
+        body: impl Into<String>,
+    ) -> Result<BarkResponse> {
+        self.dispatch(Message {
+            title: Some(title.into()),
+            ..Message::markdown(body)
+        })
+        .await
+    }
+
+    pub async fn send_item<T: ToMessage + ?Sized>(&self, item: &T) -> Result<BarkResponse> {
+        self.dispatch(item.to_message().build()?).await
+    }
+
+    pub async fn send_into(&self, builder: impl Into<MessageBuilder>) -> Result<BarkResponse> {
+        self.dispatch(builder.into().build()?).await
+    }
+
+    async fn dispatch(&self, mut message: Message) -> Result<BarkResponse> {
+        self.defaults.apply(&mut message);
+        self.client.dispatch(&mut message).await
+    }
+}
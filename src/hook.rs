@@ -0,0 +1,198 @@
+use std::{
+    any::Any,
+    panic::{self, AssertUnwindSafe},
+    sync::Arc,
+    time::Duration,
+};
+
+use crate::{
+    client::BarkResponse,
+    error::{Error, Result},
+    message::{Level, Message},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HookControl {
+    Continue,
+    Skip,
+    Fail(String),
+}
+
+impl From<()> for HookControl {
+    fn from(_: ()) -> Self {
+        HookControl::Continue
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MessageSummary {
+    pub title: Option<String>,
+    pub group: Option<String>,
+    pub level: Option<Level>,
+    pub id: Option<String>,
+    pub device_keys: usize,
+}
+
+impl From<&Message> for MessageSummary {
+    fn from(message: &Message) -> Self {
+        MessageSummary {
+            title: message.title.clone(),
+            group: message.group.clone(),
+            level: message.level.clone(),
+            id: message.id.clone(),
+            device_keys: message.device_keys.len(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct SendReport<'a> {
+    pub summary: MessageSummary,
+    pub attempts: u32,
+    pub elapsed: Duration,
+    pub request_id: Option<&'a str>,
+    /// The base URL tried last, which differs from the primary after a failover.
+    pub server: Option<&'a str>,
+    pub outcome: &'a Result<BarkResponse>,
+}
+
+impl SendReport<'_> {
+    /// The Bark response code, on success or when the server rejected the push.
+    pub fn code(&self) -> Option<i64> {
+        match self.outcome {
+            Ok(response) => Some(response.code),
+            Err(error) => match innermost(error) {
+                Error::Server { code, .. } => Some(*code),
+                _ => None,
+            },
+        }
+    }
+
+    pub fn failure(&self) -> Option<FailureKind> {
+        self.outcome.as_ref().err().map(FailureKind::of)
+    }
+}
+
+/// A failed attempt about to be retried, as passed to `ClientBuilder::on_retry`.
+#[derive(Debug)]
+pub struct RetryAttempt<'a> {
+    /// The attempt that failed, counting from 1.
+    pub attempt: u32,
+    pub max_attempts: u32,
+    pub error: &'a Error,
+    pub failure: FailureKind,
+    /// The wait before the next attempt, from the backoff or `Retry-After`.
+    pub delay: Duration,
+    pub summary: &'a MessageSummary,
+}
+
+/// Coarse cause of a failed send, small enough for a metrics label.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FailureKind {
+    /// A before-send hook skipped the message.
+    Skipped,
+    /// A hook failed or panicked.
+    Hook,
+    /// Rejected locally before anything was sent.
+    Invalid,
+    /// The server answered with a Bark error code.
+    Server,
+    RateLimited,
+    /// A non-2xx status without a Bark envelope.
+    Http,
+    /// The request never got an HTTP response.
+    Transport,
+    /// The request ran out of time before the response arrived.
+    Timeout,
+    /// The response body was not a Bark envelope.
+    Response,
+}
+
+impl FailureKind {
+    /// Looks through retry and request-id wrappers to the error that ended the send.
+    pub fn of(error: &Error) -> Self {
+        match innermost(error) {
+            Error::Skipped => FailureKind::Skipped,
+            Error::HookFailed(_) | Error::HookPanicked(_) => FailureKind::Hook,
+            Error::Server { .. } | Error::EndpointDisabled { .. } => FailureKind::Server,
+            Error::RateLimited { .. } | Error::RateLimitExceeded { .. } => FailureKind::RateLimited,
+            Error::HttpStatus { .. } | Error::Unhealthy { .. } => FailureKind::Http,
+            Error::Timeout { .. } => FailureKind::Timeout,
+            Error::Transport(_) => FailureKind::Transport,
+            #[cfg(feature = "reqwest")]
+            Error::RequestError(_) => FailureKind::Transport,
+            Error::InvalidResponse { .. } => FailureKind::Response,
+            Error::DeviceKeysFailed { failed, .. } => failed
+                .first()
+                .map_or(FailureKind::Invalid, |(_, error)| FailureKind::of(error)),
+            _ => FailureKind::Invalid,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FailureKind::Skipped => "skipped",
+            FailureKind::Hook => "hook",
+            FailureKind::Invalid => "invalid",
+            FailureKind::Server => "server",
+            FailureKind::RateLimited => "rate_limited",
+            FailureKind::Http => "http",
+            FailureKind::Transport => "transport",
+            FailureKind::Timeout => "timeout",
+            FailureKind::Response => "response",
+        }
+    }
+}
+
+pub(crate) fn innermost(error: &Error) -> &Error {
+    match error {
+        Error::RetriesExhausted { source, .. } | Error::WithRequestId { source, .. } => {
+            innermost(source)
+        }
+        error => error,
+    }
+}
+
+pub(crate) type BeforeSend = Arc<dyn Fn(&mut Message) -> HookControl + Send + Sync>;
+
+pub(crate) type AfterSend = Arc<dyn Fn(&SendReport<'_>) + Send + Sync>;
+
+pub(crate) type OnRetry = Arc<dyn Fn(&RetryAttempt<'_>) + Send + Sync>;
+
+pub(crate) fn run_before_send(hooks: &[BeforeSend], message: &mut Message) -> Result<()> {
+    for hook in hooks {
+        match panic::catch_unwind(AssertUnwindSafe(|| hook(message))) {
+            Ok(HookControl::Continue) => {}
+            Ok(HookControl::Skip) => return Err(Error::Skipped),
+            Ok(HookControl::Fail(reason)) => return Err(Error::HookFailed(reason)),
+            Err(payload) => return Err(Error::HookPanicked(panic_message(payload.as_ref()))),
+        }
+    }
+
+    Ok(())
+}
+
+/// The send has already finished, so a panicking hook cannot change its outcome;
+/// the panic is dropped and the remaining hooks still run.
+pub(crate) fn run_after_send(hooks: &[AfterSend], report: &SendReport<'_>) {
+    for hook in hooks {
+        let _ = panic::catch_unwind(AssertUnwindSafe(|| hook(report)));
+    }
+}
+
+/// A panicking hook cannot stop the retry; the panic is dropped.
+pub(crate) fn run_on_retry(hooks: &[OnRetry], attempt: &RetryAttempt<'_>) {
+    for hook in hooks {
+        let _ = panic::catch_unwind(AssertUnwindSafe(|| hook(attempt)));
+    }
+}
+
+pub(crate) fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        (*message).to_owned()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic payload".to_owned()
+    }
+}
@@ -0,0 +1,31 @@
+use uuid::Uuid;
+
+pub trait IdGenerator: Send + Sync {
+    fn generate(&self) -> String;
+}
+
+impl<F: Fn() -> String + Send + Sync> IdGenerator for F {
+    fn generate(&self) -> String {
+        self()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UuidV4;
+
+impl IdGenerator for UuidV4 {
+    fn generate(&self) -> String {
+        Uuid::new_v4().to_string()
+    }
+}
+
+#[cfg(feature = "ulid")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Ulid;
+
+#[cfg(feature = "ulid")]
+impl IdGenerator for Ulid {
+    fn generate(&self) -> String {
+        ulid::Ulid::generate().to_string()
+    }
+}
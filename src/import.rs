@@ -0,0 +1,266 @@
+use std::mem;
+
+use serde_json::{Map, Value};
+
+use crate::{
+    error::Result,
+    message::{Level, Message, MessageBuilder},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportWarning {
+    Coerced { field: String, value: String },
+    Dropped { field: String, reason: String },
+}
+
+impl Message {
+    pub fn from_json_lenient(json: &str) -> Result<(Message, Vec<ImportWarning>)> {
+        Message::from_object_lenient(serde_json::from_str(json)?)
+    }
+
+    pub(crate) fn from_object_lenient(
+        object: Map<String, Value>,
+    ) -> Result<(Message, Vec<ImportWarning>)> {
+        let mut importer = Importer::default();
+
+        for (field, value) in object {
+            importer.field(field, value);
+        }
+
+        let Importer {
+            mut builder,
+            body,
+            markdown,
+            mut warnings,
+        } = importer;
+
+        match (markdown, body) {
+            (Some(markdown), body) => {
+                if body.is_some() {
+                    warnings.push(ImportWarning::Dropped {
+                        field: "body".to_owned(),
+                        reason: "markdown takes precedence".to_owned(),
+                    });
+                }
+
+                builder = builder.markdown(markdown);
+            }
+            (None, Some(body)) => builder = builder.body(body),
+            (None, None) => {}
+        }
+
+        Ok((builder.build()?, warnings))
+    }
+}
+
+const UNKNOWN_FIELD: &str = "unknown field";
+
+#[derive(Default)]
+struct Importer {
+    builder: MessageBuilder,
+    body: Option<String>,
+    markdown: Option<String>,
+    warnings: Vec<ImportWarning>,
+}
+
+impl Importer {
+    const MAX_VOLUME: u64 = 10;
+
+    fn field(&mut self, field: String, value: Value) {
+        if value.is_null() {
+            return;
+        }
+
+        let builder = mem::take(&mut self.builder);
+
+        self.builder = match field.as_str() {
+            "title" => apply(builder, self.string(&field, value), MessageBuilder::title),
+            "subtitle" => apply(
+                builder,
+                self.string(&field, value),
+                MessageBuilder::subtitle,
+            ),
+            "body" => {
+                self.body = self.string(&field, value);
+                builder
+            }
+            "markdown" => {
+                self.markdown = self.string(&field, value);
+                builder
+            }
+            "device_key" | "device_keys" | "deviceKey" | "deviceKeys" => apply(
+                builder,
+                self.strings(&field, value),
+                MessageBuilder::device_keys,
+            ),
+            "level" => apply(builder, self.level(&field, value), MessageBuilder::level),
+            "volume" => {
+                let volume = self
+                    .number(&field, value)
+                    .filter(|&volume| self.in_range(&field, volume, Self::MAX_VOLUME));
+                apply(
+                    builder,
+                    volume.map(|volume| volume as u8),
+                    MessageBuilder::volume,
+                )
+            }
+            "badge" => apply(builder, self.number(&field, value), MessageBuilder::badge),
+            "call" => apply(builder, self.flag(&field, value), MessageBuilder::call),
+            "autoCopy" | "auto_copy" => {
+                apply(builder, self.flag(&field, value), MessageBuilder::auto_copy)
+            }
+            "copy" => apply(builder, self.string(&field, value), MessageBuilder::copy),
+            "sound" => apply(
+                builder,
+                self.string(&field, value),
+                MessageBuilder::custom_sound,
+            ),
+            "icon" => apply(builder, self.string(&field, value), MessageBuilder::icon),
+            "image" => apply(builder, self.string(&field, value), MessageBuilder::image),
+            "group" => apply(builder, self.string(&field, value), MessageBuilder::group),
+            "ciphertext" => apply(
+                builder,
+                self.string(&field, value),
+                MessageBuilder::ciphertext,
+            ),
+            "iv" => apply(builder, self.string(&field, value), MessageBuilder::iv),
+            "isArchive" | "is_archive" => apply(
+                builder,
+                self.flag(&field, value),
+                MessageBuilder::is_archive,
+            ),
+            "url" => apply(builder, self.string(&field, value), MessageBuilder::url),
+            "action" => apply(builder, self.string(&field, value), MessageBuilder::action),
+            "id" => apply(builder, self.string(&field, value), MessageBuilder::id),
+            "delete" => apply(builder, self.flag(&field, value), MessageBuilder::delete),
+            _ => {
+                self.drop(&field, UNKNOWN_FIELD);
+                builder
+            }
+        };
+    }
+
+    fn string(&mut self, field: &str, value: Value) -> Option<String> {
+        match value {
+            Value::String(value) => Some(value),
+            Value::Number(_) | Value::Bool(_) => {
+                let value = value.to_string();
+                self.coerce(field, &value);
+                Some(value)
+            }
+            _ => {
+                self.drop(field, "expected a string");
+                None
+            }
+        }
+    }
+
+    fn strings(&mut self, field: &str, value: Value) -> Option<Vec<String>> {
+        match value {
+            Value::Array(values) => Some(
+                values
+                    .into_iter()
+                    .filter_map(|value| self.string(field, value))
+                    .collect(),
+            ),
+            value => self.string(field, value).map(|value| vec![value]),
+        }
+    }
+
+    fn number(&mut self, field: &str, value: Value) -> Option<u64> {
+        match value {
+            Value::Number(number) => number.as_u64().or_else(|| {
+                self.drop(field, "expected a non-negative integer");
+                None
+            }),
+            Value::String(value) => match value.trim().parse() {
+                Ok(number) => {
+                    self.coerce(field, &value);
+                    Some(number)
+                }
+                Err(_) => {
+                    self.drop(field, "expected a non-negative integer");
+                    None
+                }
+            },
+            _ => {
+                self.drop(field, "expected a non-negative integer");
+                None
+            }
+        }
+    }
+
+    fn flag(&mut self, field: &str, value: Value) -> Option<bool> {
+        match value {
+            Value::Bool(value) => Some(value),
+            Value::Number(number) => Some(number.as_f64().is_some_and(|number| number != 0.0)),
+            Value::String(value) => match value.trim().to_ascii_lowercase().as_str() {
+                "1" | "true" | "yes" => {
+                    self.coerce(field, &value);
+                    Some(true)
+                }
+                "0" | "false" | "no" | "" => {
+                    self.coerce(field, &value);
+                    Some(false)
+                }
+                _ => {
+                    self.drop(field, "expected a flag");
+                    None
+                }
+            },
+            _ => {
+                self.drop(field, "expected a flag");
+                None
+            }
+        }
+    }
+
+    fn level(&mut self, field: &str, value: Value) -> Option<Level> {
+        let name = self.string(field, value)?;
+        match name.parse::<Level>() {
+            Ok(level) if level.as_str() == name => Some(level),
+            Ok(level) => {
+                self.coerce(field, &name);
+                Some(level)
+            }
+            Err(_) => {
+                self.coerce(field, &name);
+                Some(Level::Custom(name))
+            }
+        }
+    }
+
+    fn in_range(&mut self, field: &str, value: u64, max: u64) -> bool {
+        if value > max {
+            self.drop(field, &format!("out of range: got ({value}), max ({max})"));
+            return false;
+        }
+
+        true
+    }
+
+    fn coerce(&mut self, field: &str, value: &str) {
+        self.warnings.push(ImportWarning::Coerced {
+            field: field.to_owned(),
+            value: value.to_owned(),
+        });
+    }
+
+    fn drop(&mut self, field: &str, reason: &str) {
+        self.warnings.push(ImportWarning::Dropped {
+            field: field.to_owned(),
+            reason: reason.to_owned(),
+        });
+    }
+}
+
+fn apply<T>(
+    builder: MessageBuilder,
+    value: Option<T>,
+    set: impl FnOnce(MessageBuilder, T) -> MessageBuilder,
+) -> MessageBuilder {
+    match value {
+        Some(value) => set(builder, value),
+        None => builder,
+    }
+}
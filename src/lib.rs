@@ -1,3 +1,102 @@
+//! Also builds for `wasm32-unknown-unknown`, where requests go through `fetch`, so
+//! `Client::builder().base_url(url).device_key(key).build()?.notify(title, body).await`
+//! works unchanged in a browser, extension or Worker. The blocking client, tower
+//! layers and the native connection options on `ClientBuilder` (proxies, DNS,
+//! HTTP/2 tuning, client-wide timeouts) are compiled out there; per-send timeouts
+//! still apply. Check it with `cargo check --target wasm32-unknown-unknown`.
+//!
+//! With neither the `async` nor the `ureq` feature there is no client, only the
+//! message types.
+
+// The send-side helpers have no caller when no client is compiled in.
+#![cfg_attr(
+    not(any(feature = "async", all(feature = "ureq", not(target_arch = "wasm32")))),
+    allow(dead_code)
+)]
+
+#[cfg(feature = "async")]
+mod badge;
+#[cfg(feature = "async")]
+pub mod batch;
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+pub mod blocking;
+#[cfg(feature = "async")]
 pub mod client;
+pub mod clock;
+#[cfg(feature = "gzip")]
+mod compress;
+#[cfg(feature = "async")]
+pub mod config;
+#[cfg(feature = "crypto")]
+pub mod crypto;
+pub mod device_key;
 pub mod error;
+#[cfg(feature = "async")]
+pub mod group;
+#[cfg(feature = "async")]
+pub mod hook;
+#[cfg(feature = "async")]
+pub mod id;
+pub mod import;
+pub mod markdown;
 pub mod message;
+#[cfg(any(feature = "async", all(feature = "ureq", not(target_arch = "wasm32"))))]
+mod pipeline;
+#[cfg(feature = "async")]
+pub mod queue;
+#[cfg(feature = "async")]
+pub mod rate_limit;
+pub mod record;
+mod redact;
+#[cfg(feature = "async")]
+pub mod register;
+#[cfg(feature = "async")]
+pub mod repeat;
+#[cfg(feature = "async")]
+pub mod retry;
+#[cfg(feature = "async")]
+pub mod sender;
+pub mod sound;
+pub mod split;
+#[cfg(feature = "async")]
+pub mod stats;
+pub mod structured;
+#[cfg(all(feature = "ureq", not(target_arch = "wasm32")))]
+pub mod sync;
+#[cfg(feature = "test-util")]
+pub mod test_util;
+pub mod time;
+#[cfg(feature = "chrono")]
+pub mod timestamp;
+pub mod transport;
+pub mod truncate;
+pub mod upload;
+mod validate;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub type BoxFuture<'a, T> = std::pin::Pin<Box<dyn std::future::Future<Output = T> + Send + 'a>>;
+
+/// Not `Send` on wasm, where the fetch futures are tied to the JS thread.
+#[cfg(target_arch = "wasm32")]
+pub type BoxFuture<'a, T> = std::pin::Pin<Box<dyn std::future::Future<Output = T> + 'a>>;
+
+/// Locks `mutex`, carrying on with the data if another thread panicked holding it.
+#[cfg(feature = "async")]
+pub(crate) fn lock<T>(mutex: &std::sync::Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+/// `Send` on native targets; everything qualifies on wasm.
+#[cfg(not(target_arch = "wasm32"))]
+pub trait MaybeSend: Send {}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<T: Send + ?Sized> MaybeSend for T {}
+
+#[cfg(target_arch = "wasm32")]
+pub trait MaybeSend {}
+
+#[cfg(target_arch = "wasm32")]
+impl<T: ?Sized> MaybeSend for T {}
@@ -0,0 +1,17 @@
+const SPECIAL: &[char] = &[
+    '\\', '`', '*', '_', '~', '[', ']', '(', ')', '|', '<', '>', '&', '#', '!',
+];
+
+pub fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        if SPECIAL.contains(&c) {
+            escaped.push('\\');
+        }
+
+        escaped.push(c);
+    }
+
+    escaped
+}
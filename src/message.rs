@@ -1,10 +1,27 @@
-use std::collections::HashSet;
+use std::{collections::HashSet, fmt, str::FromStr, time::Duration};
 
-use serde::{Serialize, ser::SerializeMap};
+use serde::{Deserialize, Serialize, de, ser::SerializeMap};
+use url::Url;
 
-use crate::error::{Error, Result};
+#[cfg(feature = "crypto")]
+use crate::crypto::{self, EncryptionConfig};
+#[cfg(feature = "async")]
+use crate::retry::RetryPolicy;
+#[cfg(feature = "chrono")]
+use crate::timestamp::{Timestamp, TimestampPlacement, TimestampZone};
+use crate::{
+    device_key,
+    error::{Error, Result},
+    markdown::escape_markdown,
+    redact,
+    sound::{self, IntoSound},
+    structured::StructuredBody,
+    truncate::TruncateOptions,
+    upload::ImageBytes,
+    validate,
+};
 
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub struct Flag(pub bool);
 
 impl Flag {
@@ -32,14 +49,101 @@ impl Serialize for Flag {
     }
 }
 
-#[derive(Debug, Default)]
+/// Accepts `1`/`0` and booleans; string spellings are for [`Message::from_json_lenient`].
+impl<'de> Deserialize<'de> for Flag {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct FlagVisitor;
+
+        impl de::Visitor<'_> for FlagVisitor {
+            type Value = Flag;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("1, 0 or a boolean")
+            }
+
+            fn visit_bool<E: de::Error>(self, value: bool) -> std::result::Result<Flag, E> {
+                Ok(Flag(value))
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> std::result::Result<Flag, E> {
+                match value {
+                    0 => Ok(Flag(false)),
+                    1 => Ok(Flag(true)),
+                    _ => Err(E::invalid_value(de::Unexpected::Unsigned(value), &self)),
+                }
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> std::result::Result<Flag, E> {
+                match u64::try_from(value) {
+                    Ok(value) => self.visit_u64(value),
+                    Err(_) => Err(E::invalid_value(de::Unexpected::Signed(value), &self)),
+                }
+            }
+        }
+
+        deserializer.deserialize_any(FlagVisitor)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
 pub enum BodyKind {
     #[default]
     Plaintext,
     Markdown,
 }
 
-#[derive(Debug)]
+impl BodyKind {
+    pub fn is_markdown(&self) -> bool {
+        matches!(self, BodyKind::Markdown)
+    }
+
+    pub fn is_plaintext(&self) -> bool {
+        matches!(self, BodyKind::Plaintext)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BodyKind::Plaintext => "plaintext",
+            BodyKind::Markdown => "markdown",
+        }
+    }
+}
+
+impl fmt::Display for BodyKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Accepts `markdown`/`md` and `plaintext`/`text`/`body`, ignoring case.
+impl FromStr for BodyKind {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "markdown" | "md" => Ok(BodyKind::Markdown),
+            "plaintext" | "text" | "body" => Ok(BodyKind::Plaintext),
+            _ => Err(Error::UnknownBodyKind {
+                given: name.to_owned(),
+            }),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for BodyKind {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let name = String::deserialize(deserializer)?;
+        name.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Body {
     pub kind: BodyKind,
     pub content: String,
@@ -61,16 +165,141 @@ impl Serialize for Body {
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub enum Level {
     Critical,
     Active,
     TimeSensitive,
     Passive,
+    /// Sent verbatim, for forked servers that understand extra levels.
+    #[serde(untagged)]
+    Custom(String),
+}
+
+impl Level {
+    /// The levels stock Bark understands, in decreasing urgency.
+    pub const ALL: [Level; 4] = [
+        Level::Critical,
+        Level::TimeSensitive,
+        Level::Active,
+        Level::Passive,
+    ];
+
+    pub fn custom(name: impl Into<String>) -> Self {
+        Level::Custom(name.into())
+    }
+
+    /// Like [`FromStr`], but keeps unknown names verbatim as [`Level::Custom`]
+    /// instead of failing; for input from forked servers or user config.
+    pub fn parse_lenient(name: &str) -> Self {
+        name.parse()
+            .unwrap_or_else(|_| Level::Custom(name.to_owned()))
+    }
+
+    pub fn is_at_least(&self, other: &Level) -> bool {
+        self >= other
+    }
+
+    fn severity(&self) -> u8 {
+        match self {
+            Level::Custom(_) => 0,
+            Level::Passive => 1,
+            Level::Active => 2,
+            Level::TimeSensitive => 3,
+            Level::Critical => 4,
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> &str {
+        match self {
+            Level::Critical => "critical",
+            Level::Active => "active",
+            Level::TimeSensitive => "timeSensitive",
+            Level::Passive => "passive",
+            Level::Custom(name) => name,
+        }
+    }
+}
+
+/// Orders by severity: `Passive < Active < TimeSensitive < Critical`.
+///
+/// Custom levels sort below every built-in level, by name among themselves.
+impl Ord for Level {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        match (self, other) {
+            (Level::Custom(left), Level::Custom(right)) => left.cmp(right),
+            _ => self.severity().cmp(&other.severity()),
+        }
+    }
+}
+
+impl PartialOrd for Level {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Parses the built-in levels, ignoring case, `-`, `_` and spaces.
+impl FromStr for Level {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Self> {
+        let normalized = name.replace(['-', '_', ' '], "").to_ascii_lowercase();
+
+        Level::ALL
+            .into_iter()
+            .find(|level| level.as_str().eq_ignore_ascii_case(&normalized))
+            .ok_or_else(|| Error::UnknownLevel {
+                given: name.to_owned(),
+            })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Action {
+    /// Tapping the notification does nothing.
+    None,
+    #[serde(untagged)]
+    Other(String),
+}
+
+impl Action {
+    pub(crate) fn as_str(&self) -> &str {
+        match self {
+            Action::None => "none",
+            Action::Other(action) => action,
+        }
+    }
+}
+
+impl From<&str> for Action {
+    fn from(action: &str) -> Self {
+        match action {
+            "none" => Action::None,
+            action => Action::Other(action.to_owned()),
+        }
+    }
+}
+
+impl From<String> for Action {
+    fn from(action: String) -> Self {
+        match action.as_str() {
+            "none" => Action::None,
+            _ => Action::Other(action),
+        }
+    }
 }
 
-#[derive(Debug, Serialize)]
+/// Equality compares every field, device keys as a set.
+#[derive(Clone, Default, PartialEq, Eq, Serialize)]
 pub struct Message {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub(crate) title: Option<String>,
@@ -88,7 +317,7 @@ pub struct Message {
     pub(crate) badge: Option<u64>,
     #[serde(skip_serializing_if = "Flag::is_false")]
     pub(crate) call: Flag,
-    #[serde(skip_serializing_if = "Flag::is_false")]
+    #[serde(rename = "autoCopy", skip_serializing_if = "Flag::is_false")]
     pub(crate) auto_copy: Flag,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub(crate) copy: Option<String>,
@@ -102,31 +331,465 @@ pub struct Message {
     pub(crate) group: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub(crate) ciphertext: Option<String>,
-    #[serde(skip_serializing_if = "Flag::is_false")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) iv: Option<String>,
+    #[serde(rename = "isArchive", skip_serializing_if = "Flag::is_false")]
     pub(crate) is_archive: Flag,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub(crate) url: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub(crate) action: Option<String>,
+    pub(crate) action: Option<Action>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub(crate) id: Option<String>,
     #[serde(skip_serializing_if = "Flag::is_false")]
     pub(crate) delete: Flag,
+    #[serde(skip)]
+    pub(crate) image_bytes: Option<ImageBytes>,
+    #[cfg(feature = "chrono")]
+    #[serde(skip)]
+    pub(crate) timestamp: Option<Timestamp>,
+    #[cfg(feature = "crypto")]
+    #[serde(skip)]
+    pub(crate) encryption: Option<EncryptionConfig>,
+    #[serde(skip)]
+    pub(crate) timeout: Option<Duration>,
+    #[cfg(feature = "async")]
+    #[serde(skip)]
+    pub(crate) retry: Option<RetryPolicy>,
+    #[serde(skip)]
+    pub(crate) no_client_defaults: bool,
+    /// Set on the per-key, per-chunk and override copies the client makes; their
+    /// keys are final, so the client's defaults are not merged in again.
+    #[serde(skip)]
+    pub(crate) keys_resolved: bool,
+}
+
+/// The JSON [`Message`] serializes to; unknown fields are ignored.
+#[derive(Deserialize)]
+struct WireMessage {
+    title: Option<String>,
+    subtitle: Option<String>,
+    body: Option<String>,
+    markdown: Option<String>,
+    #[serde(default)]
+    device_keys: HashSet<String>,
+    level: Option<Level>,
+    volume: Option<u8>,
+    badge: Option<u64>,
+    #[serde(default)]
+    call: Flag,
+    #[serde(rename = "autoCopy", default)]
+    auto_copy: Flag,
+    copy: Option<String>,
+    sound: Option<String>,
+    icon: Option<String>,
+    image: Option<String>,
+    group: Option<String>,
+    ciphertext: Option<String>,
+    iv: Option<String>,
+    #[serde(rename = "isArchive", default)]
+    is_archive: Flag,
+    url: Option<String>,
+    action: Option<Action>,
+    id: Option<String>,
+    #[serde(default)]
+    delete: Flag,
+}
+
+/// The strict inverse of `Serialize`; see [`Message::from_json_lenient`] for
+/// payloads from other senders. The result passes the builder's validation.
+impl<'de> Deserialize<'de> for Message {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let wire = WireMessage::deserialize(deserializer)?;
+        let (body, body_kind) = match (wire.body, wire.markdown) {
+            (Some(_), Some(_)) => {
+                return Err(de::Error::custom("body and markdown cannot both be set"));
+            }
+            (None, Some(markdown)) => (Some(markdown), BodyKind::Markdown),
+            (body, None) => (body, BodyKind::Plaintext),
+        };
+
+        MessageBuilder {
+            title: wire.title,
+            subtitle: wire.subtitle,
+            body,
+            body_kind,
+            device_keys: wire.device_keys,
+            level: wire.level,
+            volume: wire.volume,
+            badge: wire.badge,
+            call: wire.call.0,
+            auto_copy: wire.auto_copy.0,
+            copy: wire.copy,
+            sound: wire.sound,
+            // Serialized sounds were checked when built; custom ones must survive.
+            custom_sound: true,
+            icon: wire.icon,
+            image: wire.image,
+            group: wire.group,
+            ciphertext: wire.ciphertext,
+            iv: wire.iv,
+            is_archive: wire.is_archive.0,
+            url: wire.url,
+            action: wire.action,
+            id: wire.id,
+            delete: wire.delete.0,
+            ..MessageBuilder::default()
+        }
+        .build()
+        .map_err(de::Error::custom)
+    }
+}
+
+/// Shortens device keys and hides ciphertext; see [`Message::debug_full`].
+impl fmt::Debug for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.debug(f, true)
+    }
+}
+
+struct FullDebug<'a>(&'a Message);
+
+impl fmt::Debug for FullDebug<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.debug(f, false)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MessageUrl {
+    pub url: Url,
+    pub omitted: Vec<&'static str>,
 }
 
 impl Message {
+    pub const DEFAULT_MAX_URL_LENGTH: usize = 8192;
+
     #[must_use]
     pub fn builder() -> MessageBuilder {
         MessageBuilder::default()
     }
+
+    /// A plaintext push with only a body; nothing here can fail validation.
+    pub fn text(body: impl Into<String>) -> Message {
+        Message::with_body(BodyKind::Plaintext, body.into())
+    }
+
+    pub fn markdown(body: impl Into<String>) -> Message {
+        Message::with_body(BodyKind::Markdown, body.into())
+    }
+
+    pub fn titled(title: impl Into<String>, body: impl Into<String>) -> Message {
+        Message {
+            title: Some(title.into()),
+            ..Message::text(body)
+        }
+    }
+
+    fn with_body(kind: BodyKind, content: String) -> Message {
+        Message {
+            body: Some(Body { kind, content }),
+            ..Message::default()
+        }
+    }
+
+    pub fn to_url(&self, base_url: &str, device_key: &str) -> Result<MessageUrl> {
+        self.to_url_with_max_length(base_url, device_key, Self::DEFAULT_MAX_URL_LENGTH)
+    }
+
+    pub fn to_url_with_max_length(
+        &self,
+        base_url: &str,
+        device_key: &str,
+        max_length: usize,
+    ) -> Result<MessageUrl> {
+        let mut url = validate::http_url("base_url", base_url)?;
+        let mut query = Vec::new();
+        let mut omitted = Vec::new();
+
+        let plaintext_body = self.body.as_ref().filter(|body| body.kind.is_plaintext());
+
+        {
+            let mut segments = url
+                .path_segments_mut()
+                .expect("http URLs always have path segments");
+            segments.pop_if_empty().push(device_key);
+
+            match plaintext_body {
+                Some(body) => {
+                    if let Some(title) = &self.title {
+                        segments.push(title);
+
+                        if let Some(subtitle) = &self.subtitle {
+                            segments.push(subtitle);
+                        }
+                    } else if let Some(subtitle) = &self.subtitle {
+                        query.push(("subtitle", subtitle.clone()));
+                    }
+
+                    segments.push(&body.content);
+                }
+                None => {
+                    if let Some(title) = &self.title {
+                        query.push(("title", title.clone()));
+                    }
+
+                    if let Some(subtitle) = &self.subtitle {
+                        query.push(("subtitle", subtitle.clone()));
+                    }
+                }
+            }
+        }
+
+        if let Some(body) = &self.body
+            && body.kind.is_markdown()
+        {
+            query.push(("markdown", body.content.clone()));
+        }
+
+        query.extend(self.query_pairs());
+
+        if self.ciphertext.is_some() {
+            omitted.push("ciphertext");
+        }
+
+        if self.iv.is_some() {
+            omitted.push("iv");
+        }
+
+        if !query.is_empty() {
+            url.query_pairs_mut().extend_pairs(query);
+        }
+
+        if url.as_str().len() > max_length {
+            return Err(Error::UrlTooLong {
+                length: url.as_str().len(),
+                max: max_length,
+            });
+        }
+
+        Ok(MessageUrl { url, omitted })
+    }
+
+    /// The serialized JSON length without device keys, roughly what reaches APNs.
+    pub fn estimated_size(&self) -> usize {
+        let mut message = self.clone();
+        message.device_keys.clear();
+
+        serde_json::to_vec(&message).map_or(0, |json| json.len())
+    }
+
+    /// `Debug` output with device keys and ciphertext intact, for local troubleshooting.
+    pub fn debug_full(&self) -> impl fmt::Debug + '_ {
+        FullDebug(self)
+    }
+
+    fn debug(&self, f: &mut fmt::Formatter<'_>, redact: bool) -> fmt::Result {
+        let mut device_keys: Vec<String> = self
+            .device_keys
+            .iter()
+            .map(|key| {
+                if redact {
+                    redact::device_key(key)
+                } else {
+                    key.clone()
+                }
+            })
+            .collect();
+        device_keys.sort();
+
+        let ciphertext = self.ciphertext.as_ref().map(|ciphertext| {
+            if redact {
+                format!("<redacted, {} bytes>", ciphertext.len())
+            } else {
+                ciphertext.clone()
+            }
+        });
+
+        let mut debug = f.debug_struct("Message");
+        debug
+            .field("title", &self.title)
+            .field("subtitle", &self.subtitle)
+            .field("body", &self.body)
+            .field("device_keys", &device_keys)
+            .field("level", &self.level)
+            .field("volume", &self.volume)
+            .field("badge", &self.badge)
+            .field("call", &self.call)
+            .field("auto_copy", &self.auto_copy)
+            .field("copy", &self.copy)
+            .field("sound", &self.sound)
+            .field("icon", &self.icon)
+            .field("image", &self.image)
+            .field("group", &self.group)
+            .field("ciphertext", &ciphertext)
+            .field("iv", &self.iv)
+            .field("is_archive", &self.is_archive)
+            .field("url", &self.url)
+            .field("action", &self.action)
+            .field("id", &self.id)
+            .field("delete", &self.delete)
+            .field("image_bytes", &self.image_bytes);
+        #[cfg(feature = "chrono")]
+        debug.field("timestamp", &self.timestamp);
+        #[cfg(feature = "crypto")]
+        debug.field("encryption", &self.encryption);
+
+        debug.field("timeout", &self.timeout);
+        #[cfg(feature = "async")]
+        debug.field("retry", &self.retry);
+
+        debug
+            .field("no_client_defaults", &self.no_client_defaults)
+            .field("keys_resolved", &self.keys_resolved)
+            .finish()
+    }
+
+    /// A `curl` command line replaying the JSON push this message serializes to.
+    pub fn to_curl(&self, base_url: &str) -> String {
+        curl(base_url, &serde_json::to_string(self).unwrap_or_default())
+    }
+
+    /// Like [`to_curl`](Self::to_curl), with `ciphertext` replaced by a placeholder.
+    pub fn to_curl_redacted(&self, base_url: &str) -> String {
+        let mut message = self.clone();
+
+        if message.ciphertext.is_some() {
+            message.ciphertext = Some("<redacted>".to_owned());
+        }
+
+        message.to_curl(base_url)
+    }
+
+    /// Every field except the device keys as `application/x-www-form-urlencoded`.
+    pub fn to_query_string(&self) -> String {
+        let mut query = url::form_urlencoded::Serializer::new(String::new());
+
+        for (name, value) in [("title", &self.title), ("subtitle", &self.subtitle)] {
+            if let Some(value) = value {
+                query.append_pair(name, value);
+            }
+        }
+
+        if let Some(body) = &self.body {
+            let name = match body.kind {
+                BodyKind::Plaintext => "body",
+                BodyKind::Markdown => "markdown",
+            };
+            query.append_pair(name, &body.content);
+        }
+
+        query.extend_pairs(self.query_pairs());
+
+        for (name, value) in [("ciphertext", &self.ciphertext), ("iv", &self.iv)] {
+            if let Some(value) = value {
+                query.append_pair(name, value);
+            }
+        }
+
+        query.finish()
+    }
+
+    pub(crate) fn query_pairs(&self) -> Vec<(&'static str, String)> {
+        let mut pairs = Vec::new();
+
+        if let Some(level) = &self.level {
+            pairs.push(("level", level.as_str().to_owned()));
+        }
+
+        if let Some(volume) = self.volume {
+            pairs.push(("volume", volume.to_string()));
+        }
+
+        if let Some(badge) = self.badge {
+            pairs.push(("badge", badge.to_string()));
+        }
+
+        let flags = [
+            ("call", &self.call),
+            ("autoCopy", &self.auto_copy),
+            ("isArchive", &self.is_archive),
+            ("delete", &self.delete),
+        ];
+
+        for (name, flag) in flags {
+            if flag.0 {
+                pairs.push((name, "1".to_owned()));
+            }
+        }
+
+        let fields = [
+            ("copy", &self.copy),
+            ("sound", &self.sound),
+            ("icon", &self.icon),
+            ("image", &self.image),
+            ("group", &self.group),
+            ("url", &self.url),
+        ];
+
+        for (name, value) in fields {
+            if let Some(value) = value {
+                pairs.push((name, value.clone()));
+            }
+        }
+
+        if let Some(action) = &self.action {
+            pairs.push(("action", action.as_str().to_owned()));
+        }
+
+        if let Some(id) = &self.id {
+            pairs.push(("id", id.clone()));
+        }
+
+        pairs
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MessageDefaults {
+    pub group: Option<String>,
+    pub icon: Option<String>,
+    pub sound: Option<String>,
+    pub level: Option<Level>,
+}
+
+#[cfg(feature = "async")]
+impl MessageDefaults {
+    pub(crate) fn apply(&self, message: &mut Message) {
+        fill(&mut message.group, &self.group);
+        fill(&mut message.icon, &self.icon);
+        fill(&mut message.sound, &self.sound);
+        fill(&mut message.level, &self.level);
+    }
+
+    pub(crate) fn apply_to_builder(&self, mut builder: MessageBuilder) -> MessageBuilder {
+        fill(&mut builder.group, &self.group);
+        fill(&mut builder.icon, &self.icon);
+        fill(&mut builder.sound, &self.sound);
+        fill(&mut builder.level, &self.level);
+        builder
+    }
+}
+
+#[cfg(feature = "async")]
+fn fill<T: Clone>(field: &mut Option<T>, default: &Option<T>) {
+    if field.is_none() {
+        field.clone_from(default);
+    }
 }
 
-#[derive(Default)]
+/// Clone a configured builder to stamp out messages from a template.
+#[derive(Clone, Default)]
 pub struct MessageBuilder {
     title: Option<String>,
     subtitle: Option<String>,
     body: Option<String>,
+    structured_body: Option<StructuredBody>,
     body_kind: BodyKind,
+    markdown_safe: bool,
     device_keys: HashSet<String>,
     level: Option<Level>,
     volume: Option<u8>,
@@ -135,20 +798,72 @@ pub struct MessageBuilder {
     auto_copy: bool,
     copy: Option<String>,
     sound: Option<String>,
+    custom_sound: bool,
     icon: Option<String>,
     image: Option<String>,
     group: Option<String>,
     ciphertext: Option<String>,
+    iv: Option<String>,
     is_archive: bool,
     url: Option<String>,
-    action: Option<String>,
+    action: Option<Action>,
     id: Option<String>,
     delete: bool,
+    image_bytes: Option<ImageBytes>,
+    #[cfg(feature = "chrono")]
+    timestamp: Option<(TimestampPlacement, String)>,
+    #[cfg(feature = "chrono")]
+    timestamp_zone: TimestampZone,
+    #[cfg(feature = "crypto")]
+    encryption: Option<EncryptionConfig>,
+    timeout: Option<Duration>,
+    #[cfg(feature = "async")]
+    retry: Option<RetryPolicy>,
+    no_client_defaults: bool,
+    strict: bool,
+    truncate: Option<TruncateOptions>,
 }
 
 impl MessageBuilder {
     const MAX_VOLUME: u8 = 10;
 
+    /// Fields the server drops once `ciphertext` is present.
+    fn plaintext_fields(&self) -> Vec<&'static str> {
+        let set = [
+            ("title", self.title.is_some()),
+            ("subtitle", self.subtitle.is_some()),
+            (
+                "body",
+                self.body.is_some() || self.structured_body.is_some(),
+            ),
+            ("level", self.level.is_some()),
+            ("volume", self.volume.is_some()),
+            ("badge", self.badge.is_some()),
+            ("call", self.call),
+            ("autoCopy", self.auto_copy),
+            ("copy", self.copy.is_some()),
+            ("sound", self.sound.is_some()),
+            ("icon", self.icon.is_some()),
+            ("image", self.image.is_some() || self.image_bytes.is_some()),
+            ("group", self.group.is_some()),
+            ("isArchive", self.is_archive),
+            ("url", self.url.is_some()),
+            ("action", self.action.is_some()),
+            ("delete", self.delete),
+            #[cfg(feature = "chrono")]
+            ("timestamp", self.timestamp.is_some()),
+        ];
+
+        set.into_iter()
+            .filter(|&(_, set)| set)
+            .map(|(name, _)| name)
+            .collect()
+    }
+
+    pub fn from_item<T: ToMessage + ?Sized>(item: &T) -> Self {
+        item.to_message()
+    }
+
     #[must_use]
     pub fn title(mut self, title: impl Into<String>) -> Self {
         self.title = Some(title.into());
@@ -164,6 +879,26 @@ impl MessageBuilder {
     #[must_use]
     pub fn body(mut self, body: impl Into<String>) -> Self {
         self.body = Some(body.into());
+        self.structured_body = None;
+        self
+    }
+
+    /// Sets the body and marks it markdown; a later [`body`](Self::body) keeps the kind.
+    #[must_use]
+    pub fn markdown(self, markdown: impl Into<String>) -> Self {
+        self.body(markdown).body_kind(BodyKind::Markdown)
+    }
+
+    #[must_use]
+    pub fn plaintext(self, body: impl Into<String>) -> Self {
+        self.body(body).body_kind(BodyKind::Plaintext)
+    }
+
+    /// Renders `body` according to the body kind when the message is built.
+    #[must_use]
+    pub fn structured_body(mut self, body: StructuredBody) -> Self {
+        self.structured_body = Some(body);
+        self.body = None;
         self
     }
 
@@ -173,6 +908,56 @@ impl MessageBuilder {
         self
     }
 
+    /// Marks the body markdown and escapes text added after this through
+    /// [`kv`](Self::kv) and [`append`](Self::append), for values from users;
+    /// text given to [`markdown`](Self::markdown) is kept as written.
+    #[must_use]
+    pub fn markdown_safe(mut self) -> Self {
+        self.markdown_safe = true;
+        self.body_kind(BodyKind::Markdown)
+    }
+
+    /// Appends a `label: value` line to the body, as a bold-labeled list item in markdown.
+    #[must_use]
+    pub fn kv(mut self, label: impl AsRef<str>, value: impl AsRef<str>) -> Self {
+        let (label, value) = (self.escape(label.as_ref()), self.escape(value.as_ref()));
+        let line = if self.body_kind.is_markdown() {
+            format!("- **{label}**: {value}")
+        } else {
+            format!("{label}: {value}")
+        };
+
+        if let Some(body) = &mut self.body
+            && !body.is_empty()
+            && !body.ends_with('\n')
+        {
+            body.push('\n');
+        }
+
+        self.push_body(&line)
+    }
+
+    /// Appends `text` to the body, escaped under [`markdown_safe`](Self::markdown_safe).
+    #[must_use]
+    pub fn append(self, text: impl AsRef<str>) -> Self {
+        let text = self.escape(text.as_ref());
+        self.push_body(&text)
+    }
+
+    fn escape(&self, text: &str) -> String {
+        if self.markdown_safe {
+            escape_markdown(text)
+        } else {
+            text.to_owned()
+        }
+    }
+
+    fn push_body(mut self, text: &str) -> Self {
+        self.body.get_or_insert_default().push_str(text);
+        self.structured_body = None;
+        self
+    }
+
     #[must_use]
     pub fn device_key(mut self, device_key: impl Into<String>) -> Self {
         self.device_keys.insert(device_key.into());
@@ -226,9 +1011,22 @@ impl MessageBuilder {
         self
     }
 
+    /// Names are checked against [`SOUNDS`](crate::sound::SOUNDS) on build, while
+    /// [`Sound::Custom`](crate::sound::Sound::Custom) is sent as-is, like
+    /// [`custom_sound`](Self::custom_sound).
     #[must_use]
-    pub fn sound(mut self, sound: impl Into<String>) -> Self {
+    pub fn sound(mut self, sound: impl IntoSound) -> Self {
+        let (sound, custom) = sound.into_sound();
+        self.sound = Some(sound);
+        self.custom_sound = custom;
+        self
+    }
+
+    /// Sets a sound without checking it against [`SOUNDS`](crate::sound::SOUNDS).
+    #[must_use]
+    pub fn custom_sound(mut self, sound: impl Into<String>) -> Self {
         self.sound = Some(sound.into());
+        self.custom_sound = true;
         self
     }
 
@@ -244,6 +1042,19 @@ impl MessageBuilder {
         self
     }
 
+    #[must_use]
+    pub fn image_bytes(
+        mut self,
+        bytes: impl Into<Vec<u8>>,
+        content_type: impl Into<String>,
+    ) -> Self {
+        self.image_bytes = Some(ImageBytes {
+            bytes: bytes.into().into(),
+            content_type: content_type.into(),
+        });
+        self
+    }
+
     #[must_use]
     pub fn group(mut self, group: impl Into<String>) -> Self {
         self.group = Some(group.into());
@@ -256,12 +1067,19 @@ impl MessageBuilder {
         self
     }
 
+    #[must_use]
+    pub fn iv(mut self, iv: impl Into<String>) -> Self {
+        self.iv = Some(iv.into());
+        self
+    }
+
     #[must_use]
     pub fn is_archive(mut self, is_archive: bool) -> Self {
         self.is_archive = is_archive;
         self
     }
 
+    /// Any absolute URL, so app deep links like `shortcuts://` work; `icon` and `image` must be http(s).
     #[must_use]
     pub fn url(mut self, url: impl Into<String>) -> Self {
         self.url = Some(url.into());
@@ -269,7 +1087,7 @@ impl MessageBuilder {
     }
 
     #[must_use]
-    pub fn action(mut self, action: impl Into<String>) -> Self {
+    pub fn action(mut self, action: impl Into<Action>) -> Self {
         self.action = Some(action.into());
         self
     }
@@ -286,38 +1104,527 @@ impl MessageBuilder {
         self
     }
 
-    pub fn build(self) -> Result<Message> {
-        if let Some(volume) = self.volume.filter(|&volume| volume > Self::MAX_VOLUME) {
-            return Err(Error::VolumeOutOfRange {
-                current: volume,
+    /// Appends the send time, rendered with a strftime-style `format`, when the message is sent.
+    #[cfg(feature = "chrono")]
+    #[must_use]
+    pub fn timestamped(mut self, placement: TimestampPlacement, format: impl Into<String>) -> Self {
+        self.timestamp = Some((placement, format.into()));
+        self
+    }
+
+    #[cfg(feature = "chrono")]
+    #[must_use]
+    pub fn timestamp_zone(mut self, zone: TimestampZone) -> Self {
+        self.timestamp_zone = zone;
+        self
+    }
+
+    /// Encrypts all fields but the device keys into `ciphertext` when the message is sent.
+    #[cfg(feature = "crypto")]
+    #[must_use]
+    pub fn encrypt(mut self, config: &EncryptionConfig) -> Self {
+        self.encryption = Some(config.clone());
+        self
+    }
+
+    /// Caps each attempt, connect through response body, overriding the client's timeout.
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Retries this message with `policy`, overriding its level's and the client's policy.
+    #[cfg(feature = "async")]
+    #[must_use]
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry = Some(policy);
+        self
+    }
+
+    #[must_use]
+    pub fn no_client_defaults(mut self) -> Self {
+        self.no_client_defaults = true;
+        self
+    }
+
+    /// Shortens an oversized title or body instead of leaving it to the payload limit.
+    #[must_use]
+    pub fn truncate(mut self, options: TruncateOptions) -> Self {
+        self.truncate = Some(options);
+        self
+    }
+
+    /// Also rejects fields the server would silently ignore, such as `volume` off the critical level.
+    #[must_use]
+    pub fn strict(mut self) -> Self {
+        self.strict = true;
+        self
+    }
+
+    pub fn build(self) -> Result<Message> {
+        if self.ciphertext.is_some() {
+            let fields = self.plaintext_fields();
+
+            if !fields.is_empty() {
+                return Err(Error::CiphertextConflictsWithPlaintext { fields });
+            }
+        }
+
+        let sound = match self.sound {
+            Some(sound) if !self.custom_sound => Some(sound::normalize(&sound)?),
+            sound => sound,
+        };
+
+        #[cfg(feature = "chrono")]
+        let timestamp = self
+            .timestamp
+            .map(|(placement, format)| {
+                let timestamp = Timestamp {
+                    placement,
+                    format,
+                    zone: self.timestamp_zone,
+                };
+
+                timestamp.validate().map(|()| timestamp)
+            })
+            .transpose()?;
+
+        #[cfg(feature = "crypto")]
+        let encrypted = self.ciphertext.is_some() || self.encryption.is_some();
+        #[cfg(not(feature = "crypto"))]
+        let encrypted = self.ciphertext.is_some();
+
+        if self.iv.is_some() && !encrypted {
+            return Err(Error::IvWithoutCiphertext);
+        }
+
+        #[cfg(feature = "crypto")]
+        if self.encryption.is_some()
+            && let Some(iv) = &self.iv
+        {
+            crypto::parse_iv(iv.as_bytes())?;
+        }
+
+        for (field, value) in [("icon", &self.icon), ("image", &self.image)] {
+            if let Some(value) = value {
+                validate::http_url(field, value)?;
+            }
+        }
+
+        if let Some(url) = &self.url {
+            validate::url("url", url)?;
+        }
+
+        if self.id.as_deref().is_some_and(|id| id.trim().is_empty()) {
+            return Err(Error::EmptyId);
+        }
+
+        if self.delete && self.id.is_none() {
+            return Err(Error::DeleteRequiresId);
+        }
+
+        if self.timeout.is_some_and(|timeout| timeout.is_zero()) {
+            return Err(Error::ZeroTimeout("timeout"));
+        }
+
+        if let Some(volume) = self.volume.filter(|&volume| volume > Self::MAX_VOLUME) {
+            return Err(Error::VolumeOutOfRange {
+                current: volume,
                 max: Self::MAX_VOLUME,
             });
         }
 
+        if self.strict && self.volume.is_some() && self.level != Some(Level::Critical) {
+            return Err(Error::VolumeRequiresCriticalLevel);
+        }
+
+        let mut title = self.title;
+        let mut body = self
+            .structured_body
+            .map(|body| body.render(&self.body_kind))
+            .or(self.body);
+
+        if let Some(options) = &self.truncate {
+            if let Some(title) = &mut title {
+                options.title(title);
+            }
+
+            if let Some(body) = &mut body {
+                options.body(body, self.body_kind.is_markdown());
+            }
+        }
+
         Ok(Message {
-            title: self.title,
+            title,
             subtitle: self.subtitle,
-            body: self.body.map(|content| Body {
+            body: body.map(|content| Body {
                 kind: self.body_kind,
                 content,
             }),
-            device_keys: self.device_keys,
+            device_keys: device_key::normalize(self.device_keys)?,
             level: self.level,
             volume: self.volume,
             badge: self.badge,
             call: self.call.into(),
             auto_copy: self.auto_copy.into(),
             copy: self.copy,
-            sound: self.sound,
+            sound,
             icon: self.icon,
             image: self.image,
             group: self.group,
             ciphertext: self.ciphertext,
+            iv: self.iv,
             is_archive: self.is_archive.into(),
             url: self.url,
             action: self.action,
             id: self.id,
             delete: self.delete.into(),
+            image_bytes: self.image_bytes,
+            #[cfg(feature = "chrono")]
+            timestamp,
+            #[cfg(feature = "crypto")]
+            encryption: self.encryption,
+            timeout: self.timeout,
+            #[cfg(feature = "async")]
+            retry: self.retry,
+            no_client_defaults: self.no_client_defaults,
+            keys_resolved: false,
         })
     }
 }
+
+impl From<&str> for MessageBuilder {
+    fn from(body: &str) -> Self {
+        MessageBuilder::default().body(body)
+    }
+}
+
+impl From<String> for MessageBuilder {
+    fn from(body: String) -> Self {
+        MessageBuilder::default().body(body)
+    }
+}
+
+impl<T: Into<String>, B: Into<String>> From<(T, B)> for MessageBuilder {
+    fn from((title, body): (T, B)) -> Self {
+        MessageBuilder::default().title(title).body(body)
+    }
+}
+
+/// Describes how a value renders as a notification. There is deliberately no
+/// blanket impl over `Display`, so domain types can implement this freely.
+pub trait ToMessage {
+    fn to_message(&self) -> MessageBuilder;
+}
+
+impl<T: ToMessage + ?Sized> ToMessage for &T {
+    fn to_message(&self) -> MessageBuilder {
+        (**self).to_message()
+    }
+}
+
+impl ToMessage for str {
+    fn to_message(&self) -> MessageBuilder {
+        MessageBuilder::default().body(self)
+    }
+}
+
+impl ToMessage for String {
+    fn to_message(&self) -> MessageBuilder {
+        self.as_str().to_message()
+    }
+}
+
+impl ToMessage for fmt::Arguments<'_> {
+    fn to_message(&self) -> MessageBuilder {
+        MessageBuilder::default().body(self.to_string())
+    }
+}
+
+impl<T: fmt::Display, U: fmt::Display> ToMessage for (T, U) {
+    fn to_message(&self) -> MessageBuilder {
+        MessageBuilder::default()
+            .title(self.0.to_string())
+            .body(self.1.to_string())
+    }
+}
+
+fn curl(base_url: &str, json: &str) -> String {
+    let url = format!("{}/push", base_url.trim_end_matches('/'));
+
+    format!(
+        "curl -X POST {} -H 'Content-Type: application/json' -d {}",
+        shell_quote(&url),
+        shell_quote(json)
+    )
+}
+
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// A [`MessageBuilder`] bound to the client that sends it.
+#[cfg(feature = "async")]
+pub struct ClientMessageBuilder<'a, C: ?Sized = crate::client::Client> {
+    client: &'a C,
+    builder: MessageBuilder,
+}
+
+/// A [`MessageBuilder`] bound to the client that sends it.
+#[cfg(all(not(feature = "async"), feature = "ureq", not(target_arch = "wasm32")))]
+pub struct ClientMessageBuilder<'a, C: ?Sized> {
+    client: &'a C,
+    builder: MessageBuilder,
+}
+
+#[cfg(any(feature = "async", all(feature = "ureq", not(target_arch = "wasm32"))))]
+impl<'a, C: ?Sized> ClientMessageBuilder<'a, C> {
+    pub(crate) fn new(client: &'a C, builder: MessageBuilder) -> Self {
+        ClientMessageBuilder { client, builder }
+    }
+
+    #[must_use]
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.builder = self.builder.title(title);
+        self
+    }
+
+    #[must_use]
+    pub fn subtitle(mut self, subtitle: impl Into<String>) -> Self {
+        self.builder = self.builder.subtitle(subtitle);
+        self
+    }
+
+    #[must_use]
+    pub fn body(mut self, body: impl Into<String>) -> Self {
+        self.builder = self.builder.body(body);
+        self
+    }
+
+    #[must_use]
+    pub fn markdown(mut self, markdown: impl Into<String>) -> Self {
+        self.builder = self.builder.markdown(markdown);
+        self
+    }
+
+    #[must_use]
+    pub fn plaintext(mut self, body: impl Into<String>) -> Self {
+        self.builder = self.builder.plaintext(body);
+        self
+    }
+
+    #[must_use]
+    pub fn structured_body(mut self, body: StructuredBody) -> Self {
+        self.builder = self.builder.structured_body(body);
+        self
+    }
+
+    #[must_use]
+    pub fn body_kind(mut self, body_kind: BodyKind) -> Self {
+        self.builder = self.builder.body_kind(body_kind);
+        self
+    }
+
+    #[must_use]
+    pub fn markdown_safe(mut self) -> Self {
+        self.builder = self.builder.markdown_safe();
+        self
+    }
+
+    #[must_use]
+    pub fn kv(mut self, label: impl AsRef<str>, value: impl AsRef<str>) -> Self {
+        self.builder = self.builder.kv(label, value);
+        self
+    }
+
+    #[must_use]
+    pub fn append(mut self, text: impl AsRef<str>) -> Self {
+        self.builder = self.builder.append(text);
+        self
+    }
+
+    #[must_use]
+    pub fn device_key(mut self, device_key: impl Into<String>) -> Self {
+        self.builder = self.builder.device_key(device_key);
+        self
+    }
+
+    #[must_use]
+    pub fn device_keys<I, S>(mut self, device_keys: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.builder = self.builder.device_keys(device_keys);
+        self
+    }
+
+    #[must_use]
+    pub fn level(mut self, level: Level) -> Self {
+        self.builder = self.builder.level(level);
+        self
+    }
+
+    #[must_use]
+    pub fn volume(mut self, volume: u8) -> Self {
+        self.builder = self.builder.volume(volume);
+        self
+    }
+
+    #[must_use]
+    pub fn badge(mut self, badge: u64) -> Self {
+        self.builder = self.builder.badge(badge);
+        self
+    }
+
+    #[must_use]
+    pub fn call(mut self, call: bool) -> Self {
+        self.builder = self.builder.call(call);
+        self
+    }
+
+    #[must_use]
+    pub fn auto_copy(mut self, auto_copy: bool) -> Self {
+        self.builder = self.builder.auto_copy(auto_copy);
+        self
+    }
+
+    #[must_use]
+    pub fn copy(mut self, copy: impl Into<String>) -> Self {
+        self.builder = self.builder.copy(copy);
+        self
+    }
+
+    #[must_use]
+    pub fn sound(mut self, sound: impl IntoSound) -> Self {
+        self.builder = self.builder.sound(sound);
+        self
+    }
+
+    #[must_use]
+    pub fn custom_sound(mut self, sound: impl Into<String>) -> Self {
+        self.builder = self.builder.custom_sound(sound);
+        self
+    }
+
+    #[must_use]
+    pub fn icon(mut self, icon: impl Into<String>) -> Self {
+        self.builder = self.builder.icon(icon);
+        self
+    }
+
+    #[must_use]
+    pub fn image(mut self, image: impl Into<String>) -> Self {
+        self.builder = self.builder.image(image);
+        self
+    }
+
+    #[must_use]
+    pub fn image_bytes(
+        mut self,
+        bytes: impl Into<Vec<u8>>,
+        content_type: impl Into<String>,
+    ) -> Self {
+        self.builder = self.builder.image_bytes(bytes, content_type);
+        self
+    }
+
+    #[must_use]
+    pub fn group(mut self, group: impl Into<String>) -> Self {
+        self.builder = self.builder.group(group);
+        self
+    }
+
+    #[must_use]
+    pub fn ciphertext(mut self, ciphertext: impl Into<String>) -> Self {
+        self.builder = self.builder.ciphertext(ciphertext);
+        self
+    }
+
+    #[must_use]
+    pub fn iv(mut self, iv: impl Into<String>) -> Self {
+        self.builder = self.builder.iv(iv);
+        self
+    }
+
+    #[must_use]
+    pub fn is_archive(mut self, is_archive: bool) -> Self {
+        self.builder = self.builder.is_archive(is_archive);
+        self
+    }
+
+    #[must_use]
+    pub fn url(mut self, url: impl Into<String>) -> Self {
+        self.builder = self.builder.url(url);
+        self
+    }
+
+    #[must_use]
+    pub fn action(mut self, action: impl Into<Action>) -> Self {
+        self.builder = self.builder.action(action);
+        self
+    }
+
+    #[must_use]
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.builder = self.builder.id(id);
+        self
+    }
+
+    #[must_use]
+    pub fn delete(mut self, delete: bool) -> Self {
+        self.builder = self.builder.delete(delete);
+        self
+    }
+
+    #[cfg(feature = "chrono")]
+    #[must_use]
+    pub fn timestamped(mut self, placement: TimestampPlacement, format: impl Into<String>) -> Self {
+        self.builder = self.builder.timestamped(placement, format);
+        self
+    }
+
+    #[cfg(feature = "chrono")]
+    #[must_use]
+    pub fn timestamp_zone(mut self, zone: TimestampZone) -> Self {
+        self.builder = self.builder.timestamp_zone(zone);
+        self
+    }
+
+    #[cfg(feature = "crypto")]
+    #[must_use]
+    pub fn encrypt(mut self, config: &EncryptionConfig) -> Self {
+        self.builder = self.builder.encrypt(config);
+        self
+    }
+
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.builder = self.builder.timeout(timeout);
+        self
+    }
+
+    #[cfg(feature = "async")]
+    #[must_use]
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.builder = self.builder.retry_policy(policy);
+        self
+    }
+
+    #[must_use]
+    pub fn no_client_defaults(mut self) -> Self {
+        self.builder = self.builder.no_client_defaults();
+        self
+    }
+
+    #[must_use]
+    pub fn strict(mut self) -> Self {
+        self.builder = self.builder.strict();
+        self
+    }
+
+    pub(crate) fn into_parts(self) -> (&'a C, MessageBuilder) {
+        (self.client, self.builder)
+    }
+}
@@ -0,0 +1,192 @@
+//! The steps the async and sync clients share between a message and its
+//! response: key checks, timestamps, encryption, the size limit, the JSON
+//! request, and mapping the response to a result.
+
+use std::time::Duration;
+
+use http::{
+    Method, StatusCode,
+    header::{CONTENT_TYPE, HeaderMap, HeaderValue, RETRY_AFTER},
+};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{
+    clock::Clock,
+    device_key,
+    error::{Error, Result},
+    message::Message,
+    redact,
+    time::SystemTime,
+    transport::{PreparedRequest, RawResponse},
+    validate,
+};
+
+pub(crate) const DEFAULT_PAYLOAD_LIMIT: usize = 4096;
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct BarkResponse {
+    pub code: i64,
+    pub message: String,
+    pub timestamp: i64,
+    #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
+
+impl BarkResponse {
+    pub fn is_success(&self) -> bool {
+        self.code == 200
+    }
+}
+
+#[cfg_attr(not(feature = "async"), allow(dead_code))]
+pub(crate) struct Envelope {
+    pub(crate) response: BarkResponse,
+    pub(crate) data: Option<serde_json::Value>,
+    pub(crate) status: StatusCode,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct RawEnvelope {
+    #[serde(flatten)]
+    pub(crate) response: BarkResponse,
+    #[serde(default)]
+    data: Option<serde_json::Value>,
+}
+
+/// Fails without device keys, else normalizes them; after client defaults are merged.
+pub(crate) fn require_keys(message: &mut Message) -> Result<()> {
+    if message.device_keys.is_empty() {
+        return Err(Error::MissingDeviceKey);
+    }
+
+    message.device_keys = device_key::normalize(std::mem::take(&mut message.device_keys))?;
+
+    Ok(())
+}
+
+/// Renders the timestamp and encrypts, then checks the result against `payload_limit`.
+#[cfg_attr(not(feature = "chrono"), allow(unused_variables))]
+pub(crate) fn seal(message: &mut Message, clock: &dyn Clock, payload_limit: usize) -> Result<()> {
+    #[cfg(feature = "chrono")]
+    if let Some(timestamp) = message.timestamp.take() {
+        timestamp.apply(message, clock.now());
+    }
+
+    #[cfg(feature = "crypto")]
+    if let Some(encryption) = message.encryption.take() {
+        encryption.seal(message)?;
+    }
+
+    let size = message.estimated_size();
+
+    if size > payload_limit {
+        return Err(Error::PayloadTooLarge {
+            size,
+            limit: payload_limit,
+        });
+    }
+
+    Ok(())
+}
+
+pub(crate) fn push_url(base_url: &str) -> Result<Url> {
+    validate::http_url("base_url", &format!("{base_url}/push"))
+}
+
+pub(crate) fn json_request(url: Url, body: Vec<u8>) -> PreparedRequest {
+    let mut headers = HeaderMap::new();
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+
+    PreparedRequest {
+        method: Method::POST,
+        url,
+        headers,
+        body,
+        timeout: None,
+        redacted_url: None,
+    }
+}
+
+/// Maps 429, bark error codes and non-2xx statuses to errors; `now` anchors `Retry-After` dates.
+pub(crate) fn parse_response(
+    url: Url,
+    response: RawResponse,
+    now: SystemTime,
+) -> Result<BarkResponse> {
+    Ok(parse_envelope(url, response, now)?.response)
+}
+
+pub(crate) fn parse_envelope(url: Url, response: RawResponse, now: SystemTime) -> Result<Envelope> {
+    if response.status == StatusCode::TOO_MANY_REQUESTS {
+        return Err(Error::RateLimited {
+            retry_after: retry_after(&response.headers, now),
+        });
+    }
+
+    if response.status.is_client_error()
+        && let Ok(envelope) = serde_json::from_slice::<RawEnvelope>(&response.body)
+    {
+        return Err(Error::Server {
+            code: envelope.response.code,
+            message: envelope.response.message,
+            timestamp: envelope.response.timestamp,
+        });
+    }
+
+    if !response.status.is_success() {
+        return Err(Error::HttpStatus {
+            status: response.status,
+            url,
+            body: redact::response_body(&response.body),
+        });
+    }
+
+    let envelope: RawEnvelope =
+        serde_json::from_slice(&response.body).map_err(|source| Error::InvalidResponse {
+            status: response.status,
+            body: redact::response_body(&response.body),
+            source,
+        })?;
+
+    if !envelope.response.is_success() {
+        return Err(Error::Server {
+            code: envelope.response.code,
+            message: envelope.response.message,
+            timestamp: envelope.response.timestamp,
+        });
+    }
+
+    Ok(Envelope {
+        response: envelope.response,
+        data: envelope.data,
+        status: response.status,
+    })
+}
+
+/// Parses `Retry-After` as delta-seconds or, with the `async` feature, an
+/// HTTP-date relative to `now`.
+#[cfg_attr(not(feature = "async"), allow(unused_variables))]
+pub(crate) fn retry_after(headers: &HeaderMap, now: SystemTime) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+
+    if let Ok(seconds) = value.parse() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    #[cfg(feature = "async")]
+    {
+        // httpdate yields a std `SystemTime`, which differs from ours on wasm.
+        let at = httpdate::parse_http_date(value)
+            .ok()?
+            .duration_since(std::time::UNIX_EPOCH)
+            .ok()?;
+        let now = now
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or(Duration::ZERO);
+        Some(at.saturating_sub(now))
+    }
+
+    #[cfg(not(feature = "async"))]
+    None
+}
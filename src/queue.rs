@@ -0,0 +1,205 @@
+use std::{
+    collections::VecDeque,
+    pin::pin,
+    sync::{Arc, Mutex},
+};
+
+use tokio::sync::Notify;
+
+use crate::{
+    client::{Client, SendOutcome},
+    error::{Error, Result},
+    lock,
+    message::{Body, BodyKind, Level, Message},
+};
+
+/// How a [`SendQueue`] treats messages that pile up while it cannot send.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueueOptions {
+    /// Replaces a waiting message with a newer one from the same group, so only
+    /// the latest update goes out. Messages without a group are never replaced.
+    pub coalesce_by_group: bool,
+    /// Lets critical messages be replaced too; by default each one is delivered.
+    pub coalesce_critical: bool,
+    /// Appends "…and N earlier updates" to the body of a message that replaced others.
+    pub count_superseded: bool,
+}
+
+/// Messages waiting to be sent one at a time; nothing is delivered until
+/// [`run`](Self::run) is awaited. Clones share the same queue.
+#[derive(Clone)]
+pub struct SendQueue {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    client: Client,
+    options: QueueOptions,
+    state: Mutex<State>,
+    notify: Notify,
+}
+
+#[derive(Default)]
+struct State {
+    waiting: VecDeque<Waiting>,
+    superseded: u64,
+    closed: bool,
+}
+
+struct Waiting {
+    message: Message,
+    /// The group it replaces others by, if it takes part in coalescing.
+    key: Option<String>,
+    superseded: u64,
+}
+
+impl SendQueue {
+    pub(crate) fn new(client: Client, options: QueueOptions) -> Self {
+        SendQueue {
+            inner: Arc::new(Inner {
+                client,
+                options,
+                state: Mutex::default(),
+                notify: Notify::new(),
+            }),
+        }
+    }
+
+    /// Adds `message` behind those already waiting, replacing an older one from
+    /// its group when coalescing.
+    pub fn push(&self, message: Message) -> Result<()> {
+        let mut state = lock(&self.inner.state);
+
+        if state.closed {
+            return Err(Error::QueueClosed);
+        }
+
+        let key = self.key(&message);
+        let mut superseded = 0;
+
+        if key.is_some() {
+            let replaced = state.waiting.iter().position(|waiting| waiting.key == key);
+
+            if let Some(older) = replaced.and_then(|index| state.waiting.remove(index)) {
+                superseded = older.superseded + 1;
+                state.superseded += 1;
+            }
+        }
+
+        state.waiting.push_back(Waiting {
+            message,
+            key,
+            superseded,
+        });
+        drop(state);
+
+        self.inner.notify.notify_one();
+        Ok(())
+    }
+
+    /// Messages waiting, not counting one being sent.
+    pub fn len(&self) -> usize {
+        lock(&self.inner.state).waiting.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// How many waiting messages were dropped for a newer one from their group.
+    pub fn superseded(&self) -> u64 {
+        lock(&self.inner.state).superseded
+    }
+
+    /// Refuses further pushes; [`run`](Self::run) returns once the rest are sent.
+    pub fn close(&self) {
+        lock(&self.inner.state).closed = true;
+        self.inner.notify.notify_waiters();
+    }
+
+    /// Sends waiting messages in order, waiting for more until the queue is
+    /// closed and empty. Outcomes go to the client's hooks and observers.
+    pub async fn run(&self) {
+        loop {
+            // Registered before looking, so a push or close in between still wakes us.
+            let mut notified = pin!(self.inner.notify.notified());
+            notified.as_mut().enable();
+
+            let next = {
+                let mut state = lock(&self.inner.state);
+
+                match state.waiting.pop_front() {
+                    Some(waiting) => Some(waiting),
+                    None if state.closed => return,
+                    None => None,
+                }
+            };
+
+            match next {
+                Some(waiting) => {
+                    let _ = self.send(waiting).await;
+                }
+                None => notified.await,
+            }
+        }
+    }
+
+    async fn send(&self, waiting: Waiting) -> Result<SendOutcome> {
+        let Waiting {
+            mut message,
+            superseded,
+            ..
+        } = waiting;
+
+        if self.inner.options.count_superseded && superseded > 0 {
+            append_count(&mut message, superseded);
+        }
+
+        self.inner.client.dispatch_detailed(&mut message).await
+    }
+
+    /// The group `message` goes out with, unless it is exempt from coalescing.
+    fn key(&self, message: &Message) -> Option<String> {
+        let options = &self.inner.options;
+        let defaults = (!message.no_client_defaults).then(|| self.inner.client.defaults());
+        let group = message
+            .group
+            .as_ref()
+            .or_else(|| defaults.and_then(|defaults| defaults.group.as_ref()))?;
+        let level = message
+            .level
+            .as_ref()
+            .or_else(|| defaults.and_then(|defaults| defaults.level.as_ref()));
+
+        let eligible = options.coalesce_by_group
+            && (options.coalesce_critical || level != Some(&Level::Critical));
+
+        eligible.then(|| group.clone())
+    }
+}
+
+/// Appends the superseded count to the body, leaving ciphertext alone since
+/// its body is sealed.
+fn append_count(message: &mut Message, superseded: u64) {
+    if message.ciphertext.is_some() {
+        return;
+    }
+
+    let noun = if superseded == 1 { "update" } else { "updates" };
+    let count = format!("…and {superseded} earlier {noun}");
+
+    match &mut message.body {
+        Some(body) => {
+            if !body.content.is_empty() {
+                body.content.push('\n');
+            }
+            body.content.push_str(&count);
+        }
+        None => {
+            message.body = Some(Body {
+                kind: BodyKind::Plaintext,
+                content: count,
+            })
+        }
+    }
+}
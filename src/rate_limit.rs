@@ -0,0 +1,272 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Mutex,
+    time::Duration,
+};
+
+use serde::Serialize;
+
+use crate::{
+    clock::Clock,
+    error::{Error, Result},
+    lock,
+    time::Instant,
+};
+
+/// At most `requests` pushes per `per`, refilled evenly across the window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct RateLimit {
+    pub requests: u32,
+    pub per: Duration,
+    pub mode: RateLimitMode,
+}
+
+impl RateLimit {
+    pub fn new(requests: u32, per: Duration) -> Self {
+        RateLimit {
+            requests,
+            per,
+            mode: RateLimitMode::Wait,
+        }
+    }
+
+    #[must_use]
+    pub fn fail_fast(mut self) -> Self {
+        self.mode = RateLimitMode::FailFast;
+        self
+    }
+}
+
+/// What a send does when the limit is reached.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RateLimitMode {
+    /// Waits until capacity frees up.
+    #[default]
+    Wait,
+    /// Fails with [`Error::RateLimitExceeded`].
+    FailFast,
+}
+
+/// Generic cell rate algorithm: a bucket is its next free slot, spaced `interval` apart.
+#[derive(Debug, Clone, Copy)]
+struct Gcra {
+    interval: Duration,
+    burst: Duration,
+}
+
+impl Gcra {
+    fn new(limit: RateLimit) -> Self {
+        let interval = limit.per / limit.requests.max(1);
+
+        Gcra {
+            interval,
+            burst: limit.per.saturating_sub(interval),
+        }
+    }
+
+    /// The bucket's next free slot after taking one at `now`, or how long until one frees up.
+    fn take(
+        &self,
+        next_free: Option<Instant>,
+        now: Instant,
+    ) -> std::result::Result<Instant, Duration> {
+        let at = next_free.map_or(now, |next_free| next_free.max(now));
+        let ahead = at - now;
+
+        if ahead > self.burst {
+            return Err(ahead - self.burst);
+        }
+
+        Ok(at + self.interval)
+    }
+}
+
+/// A GCRA token bucket, shared by every clone of a client.
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    pub(crate) limit: RateLimit,
+    gcra: Gcra,
+    next_free: Mutex<Option<Instant>>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(limit: RateLimit) -> Self {
+        RateLimiter {
+            limit,
+            gcra: Gcra::new(limit),
+            next_free: Mutex::new(None),
+        }
+    }
+
+    /// Takes a slot, waiting if need be; `Ok(false)` if the wait would run past `deadline`.
+    pub(crate) async fn acquire(
+        &self,
+        clock: &dyn Clock,
+        deadline: Option<Instant>,
+    ) -> Result<bool> {
+        loop {
+            let wait = match self.try_acquire(clock.instant()) {
+                Ok(()) => return Ok(true),
+                Err(wait) => wait,
+            };
+
+            if self.limit.mode == RateLimitMode::FailFast {
+                return Err(Error::RateLimitExceeded { retry_after: wait });
+            }
+
+            let until = clock.instant() + wait;
+
+            if deadline.is_some_and(|deadline| until > deadline) {
+                return Ok(false);
+            }
+
+            clock.sleep_until(until).await;
+        }
+    }
+
+    /// Takes a slot, or returns how long until one frees up.
+    fn try_acquire(&self, now: Instant) -> std::result::Result<(), Duration> {
+        let mut next_free = lock(&self.next_free);
+        *next_free = Some(self.gcra.take(*next_free, now)?);
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct DeviceBucket {
+    next_free: Option<Instant>,
+    last_used: u64,
+}
+
+impl DeviceBucket {
+    /// A bucket that has refilled completely; dropping it forgives nothing.
+    fn is_idle(&self, now: Instant) -> bool {
+        self.next_free.is_none_or(|next_free| next_free <= now)
+    }
+}
+
+#[derive(Debug, Default)]
+struct DeviceBuckets {
+    buckets: HashMap<String, DeviceBucket>,
+    uses: u64,
+}
+
+/// One bucket per device key, shared by every clone of a client. At most
+/// `capacity` buckets are kept: the least recently used idle one goes first,
+/// else the least recently used one.
+#[derive(Debug)]
+pub(crate) struct DeviceRateLimiter {
+    pub(crate) default: Option<RateLimit>,
+    pub(crate) limits: HashMap<String, RateLimit>,
+    pub(crate) capacity: usize,
+    buckets: Mutex<DeviceBuckets>,
+}
+
+impl DeviceRateLimiter {
+    pub(crate) const DEFAULT_CAPACITY: usize = 1024;
+
+    pub(crate) fn new(
+        default: Option<RateLimit>,
+        limits: HashMap<String, RateLimit>,
+        capacity: usize,
+    ) -> Self {
+        DeviceRateLimiter {
+            default,
+            limits,
+            capacity: capacity.max(1),
+            buckets: Mutex::default(),
+        }
+    }
+
+    /// Takes a slot from every key's bucket at once, waiting for the slowest;
+    /// fails fast if any full bucket's limit says so.
+    pub(crate) async fn acquire(
+        &self,
+        keys: &HashSet<String>,
+        clock: &dyn Clock,
+        deadline: Option<Instant>,
+    ) -> Result<bool> {
+        loop {
+            let (wait, fail_fast) = match self.try_acquire(keys, clock.instant()) {
+                Ok(()) => return Ok(true),
+                Err(full) => full,
+            };
+
+            if fail_fast {
+                return Err(Error::RateLimitExceeded { retry_after: wait });
+            }
+
+            let until = clock.instant() + wait;
+
+            if deadline.is_some_and(|deadline| until > deadline) {
+                return Ok(false);
+            }
+
+            clock.sleep_until(until).await;
+        }
+    }
+
+    fn try_acquire(
+        &self,
+        keys: &HashSet<String>,
+        now: Instant,
+    ) -> std::result::Result<(), (Duration, bool)> {
+        let mut state = lock(&self.buckets);
+        let mut taken = Vec::with_capacity(keys.len());
+        let mut full: Option<(Duration, bool)> = None;
+
+        for key in keys {
+            let Some(limit) = self.limits.get(key).copied().or(self.default) else {
+                continue;
+            };
+            let next_free = state.buckets.get(key).and_then(|bucket| bucket.next_free);
+
+            match Gcra::new(limit).take(next_free, now) {
+                Ok(next_free) => taken.push((key, next_free)),
+                Err(wait) => {
+                    let (longest, fail_fast) = full.unwrap_or_default();
+                    full = Some((
+                        longest.max(wait),
+                        fail_fast || limit.mode == RateLimitMode::FailFast,
+                    ));
+                }
+            }
+        }
+
+        if let Some(full) = full {
+            return Err(full);
+        }
+
+        for (key, next_free) in taken {
+            state.uses += 1;
+            let last_used = state.uses;
+
+            state
+                .buckets
+                .entry(key.clone())
+                .and_modify(|bucket| {
+                    bucket.next_free = Some(next_free);
+                    bucket.last_used = last_used;
+                })
+                .or_insert_with(|| DeviceBucket {
+                    next_free: Some(next_free),
+                    last_used,
+                });
+        }
+
+        while state.buckets.len() > self.capacity {
+            let evict = state
+                .buckets
+                .iter()
+                .min_by_key(|(_, bucket)| (!bucket.is_idle(now), bucket.last_used))
+                .map(|(key, _)| key.clone());
+
+            if let Some(key) = evict {
+                state.buckets.remove(&key);
+            }
+        }
+
+        Ok(())
+    }
+}
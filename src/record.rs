@@ -0,0 +1,216 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+#[cfg(feature = "async")]
+use crate::retry::RetryPolicy;
+
+use crate::{
+    clock::{Clock, SystemClock},
+    error::{Error, Result},
+    message::Message,
+    time::SystemTime,
+};
+
+pub const CURRENT_VERSION: u32 = 2;
+
+/// A message as persisted to disk, wrapped in a versioned envelope. Unlike the
+/// message's own JSON, this keeps its send options, so a stored batch resumes
+/// the way it was first sent.
+#[derive(Debug, Clone)]
+pub struct StoredRecord {
+    pub created_at: Option<SystemTime>,
+    pub attempts: u32,
+    pub message: Message,
+}
+
+#[derive(Serialize)]
+struct Envelope<'a> {
+    version: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    created_at: Option<u64>,
+    attempts: u32,
+    message: &'a Message,
+    #[serde(skip_serializing_if = "Options::is_empty")]
+    options: Options,
+}
+
+/// The parts of a [`Message`] that shape sending but never go on the wire.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+struct Options {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timeout_ms: Option<u64>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    no_client_defaults: bool,
+    #[cfg(feature = "async")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    retry: Option<StoredRetry>,
+}
+
+#[cfg(feature = "async")]
+#[derive(Serialize, Deserialize)]
+struct StoredRetry {
+    max_attempts: u32,
+    initial_backoff_ms: u64,
+    max_backoff_ms: u64,
+    jitter: bool,
+    total_deadline_ms: Option<u64>,
+}
+
+impl Options {
+    /// Fails on state a record cannot carry: a key to encrypt with is a secret,
+    /// and image bytes and timestamp formats do not round-trip.
+    fn of(message: &Message) -> Result<Self> {
+        #[cfg(feature = "crypto")]
+        if message.encryption.is_some() {
+            return Err(Error::UnstorableMessage("encryption"));
+        }
+        #[cfg(feature = "chrono")]
+        if message.timestamp.is_some() {
+            return Err(Error::UnstorableMessage("timestamp"));
+        }
+        if message.image_bytes.is_some() {
+            return Err(Error::UnstorableMessage("image_bytes"));
+        }
+
+        Ok(Options {
+            timeout_ms: message.timeout.map(millis),
+            no_client_defaults: message.no_client_defaults,
+            #[cfg(feature = "async")]
+            retry: message.retry.map(|policy| StoredRetry {
+                max_attempts: policy.max_attempts,
+                initial_backoff_ms: millis(policy.initial_backoff),
+                max_backoff_ms: millis(policy.max_backoff),
+                jitter: policy.jitter,
+                total_deadline_ms: policy.total_deadline.map(millis),
+            }),
+        })
+    }
+
+    fn is_empty(&self) -> bool {
+        #[cfg(feature = "async")]
+        if self.retry.is_some() {
+            return false;
+        }
+
+        self.timeout_ms.is_none() && !self.no_client_defaults
+    }
+
+    fn apply(self, message: &mut Message) {
+        message.timeout = self.timeout_ms.map(Duration::from_millis);
+        message.no_client_defaults = self.no_client_defaults;
+        #[cfg(feature = "async")]
+        {
+            message.retry = self.retry.map(|retry| RetryPolicy {
+                max_attempts: retry.max_attempts,
+                initial_backoff: Duration::from_millis(retry.initial_backoff_ms),
+                max_backoff: Duration::from_millis(retry.max_backoff_ms),
+                jitter: retry.jitter,
+                total_deadline: retry.total_deadline_ms.map(Duration::from_millis),
+            });
+        }
+    }
+}
+
+fn millis(duration: Duration) -> u64 {
+    u64::try_from(duration.as_millis()).unwrap_or(u64::MAX)
+}
+
+impl StoredRecord {
+    pub fn new(message: Message) -> Self {
+        StoredRecord::with_clock(message, &SystemClock)
+    }
+
+    /// Stamps `created_at` from `clock` rather than the system clock.
+    pub fn with_clock(message: Message, clock: &dyn Clock) -> Self {
+        StoredRecord {
+            created_at: Some(clock.now()),
+            attempts: 0,
+            message,
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        let created_at = self.created_at.map(|created_at| {
+            created_at
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs()
+        });
+
+        Ok(serde_json::to_string(&Envelope {
+            version: CURRENT_VERSION,
+            created_at,
+            attempts: self.attempts,
+            message: &self.message,
+            options: Options::of(&self.message)?,
+        })?)
+    }
+}
+
+/// Reads a record written by any earlier version of the crate.
+///
+/// Version 0 is a bare message with no envelope; version 1 has no send options.
+pub fn migrate(record_json: &str) -> Result<StoredRecord> {
+    let mut object: Map<String, Value> = serde_json::from_str(record_json)?;
+
+    let Some(version) = object.remove("version") else {
+        return Ok(StoredRecord {
+            created_at: None,
+            attempts: 0,
+            message: lenient(object)?,
+        });
+    };
+
+    match version.as_u64() {
+        Some(1 | 2) => {}
+        Some(found) => {
+            return Err(Error::UnsupportedRecordVersion {
+                found,
+                supported: CURRENT_VERSION,
+            });
+        }
+        None => return Err(Error::InvalidRecord(format!("version {version}"))),
+    }
+
+    let created_at = match object.remove("created_at") {
+        None | Some(Value::Null) => None,
+        Some(value) => {
+            let secs = value
+                .as_u64()
+                .ok_or_else(|| Error::InvalidRecord(format!("created_at {value}")))?;
+            Some(SystemTime::UNIX_EPOCH + Duration::from_secs(secs))
+        }
+    };
+
+    let attempts = match object.remove("attempts") {
+        None | Some(Value::Null) => 0,
+        Some(value) => value
+            .as_u64()
+            .and_then(|attempts| u32::try_from(attempts).ok())
+            .ok_or_else(|| Error::InvalidRecord(format!("attempts {value}")))?,
+    };
+
+    let mut message = match object.remove("message") {
+        Some(Value::Object(message)) => lenient(message)?,
+        _ => return Err(Error::InvalidRecord("missing message".to_owned())),
+    };
+
+    if let Some(options) = object.remove("options") {
+        serde_json::from_value::<Options>(options)
+            .map_err(|error| Error::InvalidRecord(format!("options: {error}")))?
+            .apply(&mut message);
+    }
+
+    Ok(StoredRecord {
+        created_at,
+        attempts,
+        message,
+    })
+}
+
+fn lenient(object: Map<String, Value>) -> Result<Message> {
+    Message::from_object_lenient(object).map(|(message, _)| message)
+}
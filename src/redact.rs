@@ -0,0 +1,117 @@
+#[cfg(all(feature = "async", feature = "tracing"))]
+use http::header::{AUTHORIZATION, COOKIE, HeaderMap, PROXY_AUTHORIZATION, SET_COOKIE};
+#[cfg(all(feature = "async", feature = "tracing"))]
+use serde_json::Value;
+#[cfg(feature = "async")]
+use url::Url;
+
+#[cfg(feature = "async")]
+use crate::error::Error;
+
+#[cfg(all(feature = "async", feature = "tracing"))]
+const REDACTED: &str = "<redacted>";
+
+/// Hides `device_key` where a path-style push's error would repeat it.
+#[cfg(feature = "async")]
+#[cfg_attr(not(feature = "reqwest"), allow(unused_variables))]
+pub(crate) fn key_in_error(error: Error, device_key: &str, redacted_url: &Url) -> Error {
+    match error {
+        Error::Server {
+            code,
+            message,
+            timestamp,
+        } => Error::Server {
+            code,
+            message: message.replace(device_key, &self::device_key(device_key)),
+            timestamp,
+        },
+        #[cfg(feature = "reqwest")]
+        Error::RequestError(error) => Error::RequestError(error.with_url(redacted_url.clone())),
+        error => error,
+    }
+}
+
+pub(crate) fn device_key(key: &str) -> String {
+    match key.char_indices().nth(4) {
+        Some((end, _)) => format!("{}…", &key[..end]),
+        None => "…".to_owned(),
+    }
+}
+
+#[cfg(all(feature = "async", feature = "tracing"))]
+pub(crate) fn headers(headers: &HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = if [AUTHORIZATION, PROXY_AUTHORIZATION, COOKIE, SET_COOKIE].contains(name) {
+                REDACTED.to_owned()
+            } else {
+                String::from_utf8_lossy(value.as_bytes()).into_owned()
+            };
+
+            (name.as_str().to_owned(), value)
+        })
+        .collect()
+}
+
+#[cfg(all(feature = "async", feature = "tracing"))]
+pub(crate) fn json_body(body: &[u8]) -> String {
+    let Ok(mut value) = serde_json::from_slice::<Value>(body) else {
+        return String::from_utf8_lossy(body).into_owned();
+    };
+
+    if let Some(object) = value.as_object_mut() {
+        for (field, value) in object.iter_mut() {
+            if field == "device_key" || field == "device_keys" {
+                redact_keys(value);
+            }
+        }
+    }
+
+    value.to_string()
+}
+
+#[cfg(all(feature = "async", feature = "tracing"))]
+fn redact_keys(value: &mut Value) {
+    match value {
+        Value::String(key) => *key = device_key(key),
+        Value::Array(keys) => keys.iter_mut().for_each(redact_keys),
+        _ => {}
+    }
+}
+
+const RESPONSE_BODY_LIMIT: usize = 512;
+
+/// The start of a response body, for error messages.
+pub(crate) fn response_body(body: &[u8]) -> String {
+    let mut text = String::from_utf8_lossy(body).into_owned();
+    truncate(&mut text, RESPONSE_BODY_LIMIT);
+    text
+}
+
+pub(crate) fn truncate(text: &mut String, max_len: usize) {
+    if text.len() <= max_len {
+        return;
+    }
+
+    let end = (0..=max_len)
+        .rev()
+        .find(|&index| text.is_char_boundary(index))
+        .unwrap_or(0);
+
+    let omitted = text.len() - end;
+    text.truncate(end);
+    text.push_str(&format!("… ({omitted} bytes truncated)"));
+}
+
+pub(crate) fn url(url: &str) -> String {
+    let Ok(mut parsed) = url::Url::parse(url) else {
+        return url.to_owned();
+    };
+
+    if parsed.password().is_none() || parsed.set_password(Some("redacted")).is_err() {
+        return url.to_owned();
+    }
+
+    parsed.to_string()
+}
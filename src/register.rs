@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+
+use crate::client::BarkResponse;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RegisterRequest {
+    pub(crate) device_token: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) key: Option<String>,
+}
+
+impl RegisterRequest {
+    pub fn new(device_token: impl Into<String>) -> Self {
+        RegisterRequest {
+            device_token: device_token.into(),
+            key: None,
+        }
+    }
+
+    /// Re-registers an existing device key instead of asking for a new one.
+    #[must_use]
+    pub fn key(mut self, key: impl Into<String>) -> Self {
+        self.key = Some(key.into());
+        self
+    }
+}
+
+#[derive(Debug)]
+pub struct RegisterResponse {
+    pub response: BarkResponse,
+    pub device_key: String,
+    pub device_token: Option<String>,
+}
+
+/// The `data` object; servers send `key`, `device_key` or both.
+#[derive(Deserialize)]
+#[serde(try_from = "RawRegisterData")]
+pub(crate) struct RegisterData {
+    pub(crate) device_key: String,
+    pub(crate) device_token: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RawRegisterData {
+    key: Option<String>,
+    device_key: Option<String>,
+    device_token: Option<String>,
+}
+
+impl TryFrom<RawRegisterData> for RegisterData {
+    type Error = &'static str;
+
+    fn try_from(raw: RawRegisterData) -> Result<Self, Self::Error> {
+        Ok(RegisterData {
+            device_key: raw.device_key.or(raw.key).ok_or("missing field `key`")?,
+            device_token: raw.device_token,
+        })
+    }
+}
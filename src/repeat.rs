@@ -0,0 +1,149 @@
+use std::{
+    future::Future,
+    pin::pin,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    task::Poll,
+    time::Duration,
+};
+
+use tokio::sync::Notify;
+
+use crate::{
+    client::{Client, SendOutcome},
+    error::Result,
+    message::Message,
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StopCondition {
+    #[default]
+    Never,
+    OnError,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RepeatIds {
+    /// Every repetition replaces the previous notification in place.
+    #[default]
+    Same,
+    /// Every repetition gets a fresh id and stacks as a new banner.
+    Fresh,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RepeatOptions {
+    pub times: u32,
+    pub interval: Duration,
+    pub stop_on: StopCondition,
+    pub ids: RepeatIds,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RepeatCancel {
+    cancelled: Arc<AtomicBool>,
+    notify: Arc<Notify>,
+}
+
+impl RepeatCancel {
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+        self.notify.notify_one();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    async fn sleep_or_cancel(&self, sleep: impl Future<Output = ()>) {
+        let mut sleep = pin!(sleep);
+        let mut notified = pin!(self.notify.notified());
+
+        std::future::poll_fn(|cx| {
+            if self.is_cancelled()
+                || notified.as_mut().poll(cx).is_ready()
+                || sleep.as_mut().poll(cx).is_ready()
+            {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        })
+        .await;
+    }
+}
+
+/// A pending repeated send; nothing is delivered until [`run`](Self::run) is awaited.
+pub struct RepeatHandle {
+    client: Client,
+    message: Message,
+    options: RepeatOptions,
+    cancel: RepeatCancel,
+}
+
+impl RepeatHandle {
+    pub(crate) fn new(client: Client, message: Message, options: RepeatOptions) -> Self {
+        RepeatHandle {
+            client,
+            message,
+            options,
+            cancel: RepeatCancel::default(),
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancel.cancel();
+    }
+
+    /// A cloneable token that can cancel the run from elsewhere.
+    pub fn canceller(&self) -> RepeatCancel {
+        self.cancel.clone()
+    }
+
+    /// Delivers each repetition and returns their outcomes in order.
+    pub async fn run(self) -> Vec<Result<SendOutcome>> {
+        let RepeatHandle {
+            client,
+            mut message,
+            options,
+            cancel,
+        } = self;
+
+        if options.ids == RepeatIds::Same && message.id.is_none() {
+            message.id = Some(client.generate_id());
+        }
+
+        let mut outcomes = Vec::new();
+
+        for repetition in 0..options.times {
+            if repetition > 0 {
+                let deadline = client.clock().instant() + options.interval;
+                cancel
+                    .sleep_or_cancel(client.clock().sleep_until(deadline))
+                    .await;
+            }
+
+            if cancel.is_cancelled() {
+                break;
+            }
+
+            let mut message = message.clone();
+
+            if options.ids == RepeatIds::Fresh {
+                message.id = Some(client.generate_id());
+            }
+
+            let outcome = client.dispatch_detailed(&mut message).await;
+            let failed = outcome.is_err();
+            outcomes.push(outcome);
+
+            if failed && options.stop_on == StopCondition::OnError {
+                break;
+            }
+        }
+
+        outcomes
+    }
+}
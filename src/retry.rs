@@ -0,0 +1,98 @@
+use std::time::Duration;
+
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::{error::Error, hook};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct RetryPolicy {
+    /// Total attempts, including the first one.
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    /// Waits a random duration between half and all of each backoff.
+    pub jitter: bool,
+    /// Caps the whole send, rate-limiter waits and backoffs included. Each
+    /// attempt's timeout shrinks to what is left, and a retry that would not
+    /// start and run as long as the last attempt before the deadline is skipped,
+    /// failing the send with an [`Error::Timeout`] that names the deadline.
+    pub total_deadline: Option<Duration>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(5),
+            jitter: true,
+            total_deadline: None,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// The delay before retry number `retry`, counting from 1.
+    pub(crate) fn backoff(&self, retry: u32) -> Duration {
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1));
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff);
+
+        if !self.jitter {
+            return backoff;
+        }
+
+        backoff.mul_f64(0.5 + random_fraction() / 2.0)
+    }
+}
+
+/// Uniform in `[0, 1)`, from the v4 UUID generator's randomness.
+pub(crate) fn random_fraction() -> f64 {
+    (Uuid::new_v4().as_u128() >> 75) as f64 / (1u64 << 53) as f64
+}
+
+pub(crate) fn is_transient(error: &Error) -> bool {
+    match error {
+        #[cfg(feature = "reqwest")]
+        Error::RequestError(error) => is_unreachable(error) || error.is_timeout(),
+        Error::Transport(_) | Error::Timeout { .. } => true,
+        Error::HttpStatus { status, .. } => status.is_server_error(),
+        Error::RateLimited { .. } => true,
+        Error::DeviceKeysFailed { failed, .. } => {
+            failed.iter().any(|(_, error)| is_transient(error))
+        }
+        _ => false,
+    }
+}
+
+/// Fetch reports a refused or dropped connection as a plain request error.
+#[cfg(feature = "reqwest")]
+fn is_unreachable(error: &reqwest::Error) -> bool {
+    #[cfg(not(target_arch = "wasm32"))]
+    return error.is_connect();
+
+    #[cfg(target_arch = "wasm32")]
+    return error.is_request();
+}
+
+/// Failures a later resend could get past: transient ones, and running out
+/// of client-side rate limit capacity.
+pub(crate) fn is_resumable(error: &Error) -> bool {
+    let error = hook::innermost(error);
+
+    is_transient(error) || matches!(error, Error::RateLimitExceeded { .. })
+}
+
+/// Errors another server might not share: no response at all, or a 5xx.
+pub(crate) fn is_unavailable(error: &Error) -> bool {
+    match error {
+        Error::RateLimited { .. } => false,
+        Error::DeviceKeysFailed { failed, .. } => {
+            failed.iter().any(|(_, error)| is_unavailable(error))
+        }
+        error => is_transient(error),
+    }
+}
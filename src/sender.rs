@@ -0,0 +1,82 @@
+use std::{future::Future, sync::Arc};
+
+use crate::{
+    BoxFuture, MaybeSend,
+    client::{BarkResponse, Client, ClientMessageBuilder},
+    error::Result,
+    group::GroupHandle,
+    message::{Message, MessageBuilder, ToMessage},
+};
+
+/// Anything that can deliver a message, for code that should not depend on [`Client`].
+pub trait BarkSender: Send + Sync {
+    fn send<'a>(&'a self, message: &'a Message) -> BoxFuture<'a, Result<BarkResponse>>;
+}
+
+impl BarkSender for Client {
+    fn send<'a>(&'a self, message: &'a Message) -> BoxFuture<'a, Result<BarkResponse>> {
+        Box::pin(Client::send(self, message))
+    }
+}
+
+impl BarkSender for GroupHandle {
+    fn send<'a>(&'a self, message: &'a Message) -> BoxFuture<'a, Result<BarkResponse>> {
+        Box::pin(GroupHandle::send(self, message))
+    }
+}
+
+impl<T: BarkSender + ?Sized> BarkSender for &T {
+    fn send<'a>(&'a self, message: &'a Message) -> BoxFuture<'a, Result<BarkResponse>> {
+        (**self).send(message)
+    }
+}
+
+impl<T: BarkSender + ?Sized> BarkSender for Arc<T> {
+    fn send<'a>(&'a self, message: &'a Message) -> BoxFuture<'a, Result<BarkResponse>> {
+        (**self).send(message)
+    }
+}
+
+pub trait BarkSenderExt: BarkSender {
+    /// A builder whose `send` goes through this sender, so code holding only
+    /// the trait can build messages the way it would on a [`Client`].
+    fn message(&self) -> ClientMessageBuilder<'_, dyn BarkSender + '_>
+    where
+        Self: Sized,
+    {
+        ClientMessageBuilder::new(self, MessageBuilder::default())
+    }
+
+    fn notify(
+        &self,
+        title: impl Into<String>,
+        body: impl Into<String>,
+    ) -> impl Future<Output = Result<BarkResponse>> + MaybeSend {
+        self.send_into(MessageBuilder::from((title, body)))
+    }
+
+    fn send_into(
+        &self,
+        builder: impl Into<MessageBuilder>,
+    ) -> impl Future<Output = Result<BarkResponse>> + MaybeSend {
+        let message = builder.into().build();
+        async move { self.send(&message?).await }
+    }
+
+    fn send_item<T: ToMessage + ?Sized>(
+        &self,
+        item: &T,
+    ) -> impl Future<Output = Result<BarkResponse>> + MaybeSend {
+        self.send_into(item.to_message())
+    }
+}
+
+impl<T: BarkSender + ?Sized> BarkSenderExt for T {}
+
+impl<'a> ClientMessageBuilder<'a, dyn BarkSender + 'a> {
+    pub async fn send(self) -> Result<BarkResponse> {
+        let (sender, builder) = self.into_parts();
+
+        sender.send(&builder.build()?).await
+    }
+}
@@ -0,0 +1,185 @@
+use std::fmt;
+
+use serde::{Serialize, Serializer};
+
+use crate::error::{Error, Result};
+
+/// Declares [`SOUNDS`], [`Sound`] and its name mapping from one table.
+macro_rules! sounds {
+    ($($variant:ident => $name:literal,)*) => {
+        pub const SOUNDS: &[&str] = &[$($name),*];
+
+        /// Ringtones bundled with the Bark app, in the same order as [`SOUNDS`].
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub enum Sound {
+            $($variant,)*
+            /// Any other name, passed to the server as-is.
+            Custom(String),
+        }
+
+        impl Sound {
+            const BUILT_IN: &[Sound] = &[$(Sound::$variant),*];
+
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $(Sound::$variant => $name,)*
+                    Sound::Custom(name) => name,
+                }
+            }
+        }
+    };
+}
+
+sounds! {
+    Alarm => "alarm",
+    Anticipate => "anticipate",
+    Bell => "bell",
+    Birdsong => "birdsong",
+    Bloom => "bloom",
+    Calypso => "calypso",
+    Chime => "chime",
+    Choo => "choo",
+    Descent => "descent",
+    Electronic => "electronic",
+    Fanfare => "fanfare",
+    Glass => "glass",
+    GoToSleep => "gotosleep",
+    HealthNotification => "healthnotification",
+    Horn => "horn",
+    Ladder => "ladder",
+    MailSent => "mailsent",
+    Minuet => "minuet",
+    MultiwayInvitation => "multiwayinvitation",
+    NewMail => "newmail",
+    NewsFlash => "newsflash",
+    Noir => "noir",
+    PaymentSuccess => "paymentsuccess",
+    Shake => "shake",
+    SherwoodForest => "sherwoodforest",
+    Silence => "silence",
+    Spell => "spell",
+    Suspense => "suspense",
+    Telegraph => "telegraph",
+    Tiptoes => "tiptoes",
+    Typewriters => "typewriters",
+    Update => "update",
+}
+
+impl Sound {
+    fn built_in(name: &str) -> Option<Sound> {
+        let name = normalize(name).ok()?;
+
+        Sound::BUILT_IN
+            .iter()
+            .find(|sound| sound.as_str() == name)
+            .cloned()
+    }
+}
+
+impl fmt::Display for Sound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for Sound {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl From<Sound> for String {
+    fn from(sound: Sound) -> Self {
+        match sound {
+            Sound::Custom(name) => name,
+            sound => sound.as_str().to_owned(),
+        }
+    }
+}
+
+/// What [`MessageBuilder::sound`](crate::message::MessageBuilder::sound) takes: a
+/// [`Sound`], or a name that must match one of [`SOUNDS`].
+pub trait IntoSound {
+    /// The name to send, and whether it is sent unchecked, as [`Sound::Custom`] is.
+    fn into_sound(self) -> (String, bool);
+}
+
+impl IntoSound for Sound {
+    fn into_sound(self) -> (String, bool) {
+        let custom = matches!(self, Sound::Custom(_));
+        (self.into(), custom)
+    }
+}
+
+impl IntoSound for &str {
+    fn into_sound(self) -> (String, bool) {
+        (self.to_owned(), false)
+    }
+}
+
+impl IntoSound for String {
+    fn into_sound(self) -> (String, bool) {
+        (self, false)
+    }
+}
+
+impl IntoSound for &String {
+    fn into_sound(self) -> (String, bool) {
+        (self.clone(), false)
+    }
+}
+
+impl From<&str> for Sound {
+    fn from(name: &str) -> Self {
+        Sound::built_in(name).unwrap_or_else(|| Sound::Custom(name.to_owned()))
+    }
+}
+
+impl From<String> for Sound {
+    fn from(name: String) -> Self {
+        Sound::built_in(&name).unwrap_or(Sound::Custom(name))
+    }
+}
+
+pub(crate) fn normalize(sound: &str) -> Result<String> {
+    let lower = sound.trim().to_ascii_lowercase();
+    let name = lower.strip_suffix(".caf").unwrap_or(&lower);
+
+    if SOUNDS.contains(&name) {
+        return Ok(name.to_owned());
+    }
+
+    let did_you_mean = SOUNDS
+        .iter()
+        .map(|known| (edit_distance(name, known), *known))
+        .filter(|&(distance, _)| distance <= 3)
+        .min_by_key(|&(distance, _)| distance)
+        .map(|(_, known)| known.to_owned());
+
+    Err(Error::UnknownSound {
+        given: sound.to_owned(),
+        did_you_mean,
+    })
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+
+        for (j, &cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if ca == cb {
+                diagonal
+            } else {
+                1 + diagonal.min(above).min(row[j])
+            };
+            diagonal = above;
+        }
+    }
+
+    row[b.len()]
+}
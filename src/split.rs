@@ -0,0 +1,167 @@
+pub(crate) const FENCE: &str = "```";
+#[cfg(feature = "async")]
+const TRUNCATED: &str = "\n… (truncated)";
+
+#[derive(Debug, Clone, Copy)]
+pub struct SplitOptions {
+    pub max_bytes: usize,
+    pub max_parts: usize,
+}
+
+impl Default for SplitOptions {
+    fn default() -> Self {
+        SplitOptions {
+            max_bytes: 2048,
+            max_parts: 5,
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+pub(crate) fn split_body(content: &str, markdown: bool, options: SplitOptions) -> Vec<String> {
+    let max_parts = options.max_parts.max(1);
+    let parts = split_lines(content, markdown, options.max_bytes);
+
+    if parts.len() <= max_parts {
+        return parts;
+    }
+
+    let budget = options.max_bytes.saturating_sub(TRUNCATED.len());
+    let mut parts = split_lines(content, markdown, budget);
+    parts.truncate(max_parts);
+
+    if let Some(last) = parts.last_mut() {
+        last.push_str(TRUNCATED);
+    }
+
+    parts
+}
+
+#[cfg(feature = "async")]
+fn split_lines(content: &str, markdown: bool, max_bytes: usize) -> Vec<String> {
+    let mut splitter = Splitter {
+        parts: Vec::new(),
+        current: String::new(),
+        fence: None,
+        max_bytes,
+    };
+
+    for line in content.split_inclusive('\n') {
+        splitter.line(line, markdown);
+    }
+
+    splitter.finish()
+}
+
+#[cfg(feature = "async")]
+struct Splitter {
+    parts: Vec<String>,
+    current: String,
+    fence: Option<String>,
+    max_bytes: usize,
+}
+
+#[cfg(feature = "async")]
+impl Splitter {
+    fn line(&mut self, line: &str, markdown: bool) {
+        let toggles = markdown && line.trim_start().starts_with(FENCE);
+        let fence_after = match (&self.fence, toggles) {
+            (Some(_), true) => None,
+            (None, true) => Some(line.trim_end().to_owned()),
+            (fence, false) => fence.clone(),
+        };
+
+        let mut rest = line;
+
+        while !rest.is_empty() {
+            let closing = fence_after.as_ref().map_or(0, |_| FENCE.len() + 1);
+            let available = self.max_bytes.saturating_sub(self.current.len() + closing);
+
+            if rest.len() <= available {
+                self.current.push_str(rest);
+                break;
+            }
+
+            if self.has_content() {
+                self.flush();
+                continue;
+            }
+
+            let cut = floor_char_boundary(rest, available.max(1));
+            let cut = if cut == 0 {
+                rest.chars().next().map_or(rest.len(), char::len_utf8)
+            } else {
+                cut
+            };
+
+            self.current.push_str(&rest[..cut]);
+            rest = &rest[cut..];
+
+            if !rest.is_empty() {
+                self.flush();
+            }
+        }
+
+        self.fence = fence_after;
+    }
+
+    fn has_content(&self) -> bool {
+        match &self.fence {
+            Some(fence) => self.current.len() > fence.len() + 1,
+            None => !self.current.is_empty(),
+        }
+    }
+
+    fn flush(&mut self) {
+        let mut part = std::mem::take(&mut self.current);
+
+        if let Some(fence) = &self.fence {
+            let opening = format!("{fence}\n");
+
+            if let Some(before) = part.strip_suffix(&opening) {
+                part.truncate(before.len());
+            } else {
+                close_fence(&mut part);
+            }
+
+            self.current = opening;
+        }
+
+        if !part.is_empty() {
+            self.parts.push(part);
+        }
+    }
+
+    fn finish(mut self) -> Vec<String> {
+        if self.has_content() || self.parts.is_empty() {
+            let mut part = self.current;
+
+            if self.fence.is_some() {
+                close_fence(&mut part);
+            }
+
+            self.parts.push(part);
+        }
+
+        self.parts
+    }
+}
+
+pub(crate) fn close_fence(part: &mut String) {
+    if !part.ends_with('\n') {
+        part.push('\n');
+    }
+
+    part.push_str(FENCE);
+}
+
+pub(crate) fn floor_char_boundary(text: &str, index: usize) -> usize {
+    if index >= text.len() {
+        return text.len();
+    }
+
+    (0..=index)
+        .rev()
+        .find(|&index| text.is_char_boundary(index))
+        .unwrap_or(0)
+}
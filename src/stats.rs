@@ -0,0 +1,101 @@
+use std::{
+    collections::VecDeque,
+    sync::{
+        Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use serde::Serialize;
+
+use crate::{
+    client::BarkResponse,
+    error::{Error, Result},
+    lock,
+};
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ClientStats {
+    pub sent: u64,
+    pub failed: u64,
+    pub filtered: u64,
+    /// Always `None`: the client does not deduplicate pushes.
+    pub deduplicated: Option<u64>,
+    /// Always `None`: the client runs sends on the caller's task; a
+    /// [`SendQueue`](crate::queue::SendQueue) reports its own depth.
+    pub queue_depth: Option<u64>,
+    /// Always `None`: the client has no circuit breaker.
+    pub circuit_breaker: Option<String>,
+    pub last_error: Option<String>,
+    pub latency_p50: Option<Duration>,
+    pub latency_p95: Option<Duration>,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct StatsRecorder {
+    sent: AtomicU64,
+    failed: AtomicU64,
+    filtered: AtomicU64,
+    last_error: Mutex<Option<String>>,
+    latencies: Mutex<VecDeque<Duration>>,
+}
+
+impl StatsRecorder {
+    const LATENCY_WINDOW: usize = 256;
+
+    pub(crate) fn record(&self, outcome: &Result<BarkResponse>, attempts: u32, elapsed: Duration) {
+        match outcome {
+            Ok(_) => {
+                self.sent.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(Error::Skipped) => {
+                self.filtered.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(err) => {
+                self.failed.fetch_add(1, Ordering::Relaxed);
+                *lock(&self.last_error) = Some(err.to_string());
+            }
+        }
+
+        if attempts > 0 {
+            let mut latencies = lock(&self.latencies);
+
+            if latencies.len() == Self::LATENCY_WINDOW {
+                latencies.pop_front();
+            }
+
+            latencies.push_back(elapsed);
+        }
+    }
+
+    pub(crate) fn snapshot(&self) -> ClientStats {
+        let mut latencies: Vec<Duration> = lock(&self.latencies).iter().copied().collect();
+        latencies.sort_unstable();
+
+        ClientStats {
+            sent: self.sent.load(Ordering::Relaxed),
+            failed: self.failed.load(Ordering::Relaxed),
+            filtered: self.filtered.load(Ordering::Relaxed),
+            deduplicated: None,
+            queue_depth: None,
+            circuit_breaker: None,
+            last_error: lock(&self.last_error).clone(),
+            latency_p50: percentile(&latencies, 50),
+            latency_p95: percentile(&latencies, 95),
+        }
+    }
+
+    pub(crate) fn reset(&self) {
+        self.sent.store(0, Ordering::Relaxed);
+        self.failed.store(0, Ordering::Relaxed);
+        self.filtered.store(0, Ordering::Relaxed);
+        *lock(&self.last_error) = None;
+        lock(&self.latencies).clear();
+    }
+}
+
+fn percentile(sorted: &[Duration], percentile: usize) -> Option<Duration> {
+    let last = sorted.len().checked_sub(1)?;
+    sorted.get(last * percentile / 100).copied()
+}
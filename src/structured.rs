@@ -0,0 +1,157 @@
+use crate::{markdown::escape_markdown, message::BodyKind};
+
+#[derive(Debug, Clone)]
+enum Block {
+    Field { label: String, value: String },
+    Text(String),
+    Code { lang: String, text: String },
+}
+
+#[derive(Debug, Clone, Default)]
+struct Section {
+    title: Option<String>,
+    blocks: Vec<Block>,
+}
+
+/// Sections of labeled fields, text, and code rendered to the message's body kind.
+#[derive(Debug, Clone, Default)]
+pub struct StructuredBody {
+    sections: Vec<Section>,
+    max_len: Option<usize>,
+}
+
+impl StructuredBody {
+    pub fn new() -> Self {
+        StructuredBody::default()
+    }
+
+    #[must_use]
+    pub fn section(mut self, title: impl Into<String>) -> Self {
+        self.sections.push(Section {
+            title: Some(title.into()),
+            blocks: Vec::new(),
+        });
+        self
+    }
+
+    #[must_use]
+    pub fn field(self, label: impl Into<String>, value: impl Into<String>) -> Self {
+        self.block(Block::Field {
+            label: label.into(),
+            value: value.into(),
+        })
+    }
+
+    #[must_use]
+    pub fn text(self, paragraph: impl Into<String>) -> Self {
+        self.block(Block::Text(paragraph.into()))
+    }
+
+    #[must_use]
+    pub fn code(self, lang: impl Into<String>, text: impl Into<String>) -> Self {
+        self.block(Block::Code {
+            lang: lang.into(),
+            text: text.into(),
+        })
+    }
+
+    /// Drops whole trailing sections once the rendered body would exceed `max_len` bytes.
+    #[must_use]
+    pub fn max_len(mut self, max_len: usize) -> Self {
+        self.max_len = Some(max_len);
+        self
+    }
+
+    fn block(mut self, block: Block) -> Self {
+        match self.sections.last_mut() {
+            Some(section) => section.blocks.push(block),
+            None => self.sections.push(Section {
+                title: None,
+                blocks: vec![block],
+            }),
+        }
+        self
+    }
+
+    pub fn render(&self, kind: &BodyKind) -> String {
+        let markdown = kind.is_markdown();
+        let rendered: Vec<String> = self
+            .sections
+            .iter()
+            .map(|section| render_section(section, markdown))
+            .collect();
+
+        let Some(max_len) = self.max_len else {
+            return rendered.join("\n\n");
+        };
+
+        let mut body = String::new();
+
+        for (index, section) in rendered.iter().enumerate() {
+            let separator = if body.is_empty() { 0 } else { 2 };
+            let remaining = rendered.len() - index - 1;
+            let notice_len = if remaining > 0 {
+                omitted_notice(remaining + 1).len() + 2
+            } else {
+                0
+            };
+
+            if body.len() + separator + section.len() + notice_len > max_len {
+                let notice = omitted_notice(rendered.len() - index);
+
+                if !body.is_empty() {
+                    body.push_str("\n\n");
+                }
+
+                body.push_str(&notice);
+                return body;
+            }
+
+            if separator > 0 {
+                body.push_str("\n\n");
+            }
+
+            body.push_str(section);
+        }
+
+        body
+    }
+}
+
+fn omitted_notice(sections: usize) -> String {
+    match sections {
+        1 => "… 1 more section".to_owned(),
+        n => format!("… {n} more sections"),
+    }
+}
+
+fn render_section(section: &Section, markdown: bool) -> String {
+    let mut lines = Vec::new();
+
+    if let Some(title) = &section.title {
+        lines.push(if markdown {
+            format!("## {}", escape_markdown(title))
+        } else {
+            title.clone()
+        });
+    }
+
+    for block in &section.blocks {
+        lines.push(match block {
+            Block::Field { label, value } if markdown => {
+                format!(
+                    "- **{}**: {}",
+                    escape_markdown(label),
+                    escape_markdown(value)
+                )
+            }
+            Block::Field { label, value } => format!("{label}: {value}"),
+            Block::Text(text) if markdown => escape_markdown(text),
+            Block::Text(text) => text.clone(),
+            Block::Code { lang, text } if markdown => format!("```{lang}\n{text}\n```"),
+            Block::Code { text, .. } => text.clone(),
+        });
+    }
+
+    lines.join("\n")
+}
@@ -0,0 +1,328 @@
+use std::{
+    collections::HashSet,
+    fmt,
+    future::Future,
+    pin::pin,
+    sync::Arc,
+    task::{Context, Poll, Wake, Waker},
+    thread::{self, Thread},
+    time::Duration,
+};
+
+use http::header::HeaderValue;
+
+pub use crate::pipeline::BarkResponse;
+use crate::{
+    BoxFuture,
+    clock::{Clock, SystemClock},
+    device_key,
+    error::{Error, Result},
+    message::{ClientMessageBuilder, Message, MessageBuilder},
+    pipeline, redact,
+    transport::{PreparedRequest, RawResponse, Transport},
+    validate,
+};
+
+/// Runs each request on the thread that polls it, so it also works under the
+/// async client, where it blocks an executor thread for the whole request.
+pub struct UreqTransport {
+    agent: ureq::Agent,
+    clock: Arc<dyn Clock>,
+}
+
+impl UreqTransport {
+    pub fn new(agent: ureq::Agent) -> Self {
+        UreqTransport {
+            agent,
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Measures the elapsed time reported on timeouts with `clock`.
+    #[must_use]
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+}
+
+impl Transport for UreqTransport {
+    fn execute(&self, request: PreparedRequest) -> BoxFuture<'_, Result<RawResponse>> {
+        Box::pin(async move { self.run(request) })
+    }
+}
+
+impl UreqTransport {
+    fn run(&self, request: PreparedRequest) -> Result<RawResponse> {
+        let started = self.clock.instant();
+        let failed = |error: ureq::Error| match error {
+            ureq::Error::Timeout(_) => Error::Timeout {
+                elapsed: self.clock.instant().saturating_duration_since(started),
+                deadline: None,
+            },
+            error => Error::Transport(Box::new(error)),
+        };
+
+        let mut http = http::Request::new(request.body);
+        *http.method_mut() = request.method;
+        *http.uri_mut() = request
+            .url
+            .as_str()
+            .parse()
+            .map_err(|error| Error::Transport(Box::new(error)))?;
+        *http.headers_mut() = request.headers;
+
+        // Statuses are mapped by the caller, which needs the body for bark error codes.
+        let mut config = self
+            .agent
+            .configure_request(http)
+            .http_status_as_error(false);
+
+        if let Some(timeout) = request.timeout {
+            config = config.timeout_global(Some(timeout));
+        }
+
+        let mut response = self.agent.run(config.build()).map_err(failed)?;
+
+        Ok(RawResponse {
+            status: response.status(),
+            headers: response.headers().clone(),
+            body: response.body_mut().read_to_vec().map_err(failed)?,
+        })
+    }
+}
+
+/// A synchronous client over ureq, for programs that want neither reqwest nor
+/// an async runtime. Sends JSON pushes to the default device keys; hooks,
+/// retries, uploads and the other async client extras are not available here.
+///
+/// Requests go through the same [`Transport`] as the async client and run on
+/// the calling thread, so a custom transport must not need a runtime.
+#[derive(Clone)]
+pub struct Client {
+    transport: Arc<dyn Transport>,
+    base_url: Arc<str>,
+    default_device_keys: Arc<HashSet<String>>,
+    clock: Arc<dyn Clock>,
+}
+
+impl fmt::Debug for Client {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut device_keys: Vec<String> = self
+            .default_device_keys
+            .iter()
+            .map(|key| redact::device_key(key))
+            .collect();
+        device_keys.sort();
+
+        f.debug_struct("Client")
+            .field("base_url", &redact::url(&self.base_url))
+            .field("default_device_keys", &device_keys)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Client {
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::default()
+    }
+
+    pub fn message(&self) -> ClientMessageBuilder<'_, Client> {
+        ClientMessageBuilder::new(self, MessageBuilder::default())
+    }
+
+    pub fn send(&self, message: &Message) -> Result<BarkResponse> {
+        self.dispatch(&mut message.clone())
+    }
+
+    /// Like [`send`](Self::send), with the request capped at `timeout`.
+    pub fn send_with_timeout(&self, message: &Message, timeout: Duration) -> Result<BarkResponse> {
+        if timeout.is_zero() {
+            return Err(Error::ZeroTimeout("timeout"));
+        }
+
+        let mut message = message.clone();
+        message.timeout = Some(timeout);
+
+        self.dispatch(&mut message)
+    }
+
+    pub fn notify(
+        &self,
+        title: impl Into<String>,
+        body: impl Into<String>,
+    ) -> Result<BarkResponse> {
+        self.dispatch(&mut Message::titled(title, body))
+    }
+
+    fn dispatch(&self, message: &mut Message) -> Result<BarkResponse> {
+        if !message.no_client_defaults && message.device_keys.is_empty() {
+            message.device_keys = (*self.default_device_keys).clone();
+        }
+
+        pipeline::require_keys(message)?;
+
+        if message.image_bytes.is_some() {
+            return Err(Error::MissingUploader);
+        }
+
+        pipeline::seal(message, &*self.clock, pipeline::DEFAULT_PAYLOAD_LIMIT)?;
+
+        let url = pipeline::push_url(&self.base_url)?;
+        let mut request = pipeline::json_request(url.clone(), serde_json::to_vec(message)?);
+        request.timeout = message.timeout;
+        let response = block_on(self.transport.execute(request))?;
+
+        pipeline::parse_response(url, response, self.clock.now())
+    }
+}
+
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Polls `future` on this thread, parking it until the future wakes it.
+fn block_on<F: Future>(future: F) -> F::Output {
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut context = Context::from_waker(&waker);
+    let mut future = pin!(future);
+
+    loop {
+        match future.as_mut().poll(&mut context) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+impl ClientMessageBuilder<'_, Client> {
+    pub fn send(self) -> Result<BarkResponse> {
+        let (client, builder) = self.into_parts();
+
+        client.dispatch(&mut builder.build()?)
+    }
+}
+
+#[derive(Default)]
+pub struct ClientBuilder {
+    base_url: Option<String>,
+    transport: Option<Arc<dyn Transport>>,
+    agent: Option<ureq::Agent>,
+    default_device_keys: HashSet<String>,
+    timeout: Option<Duration>,
+    user_agent: Option<String>,
+    clock: Option<Arc<dyn Clock>>,
+}
+
+impl ClientBuilder {
+    #[must_use]
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = Some(base_url.into());
+        self
+    }
+
+    /// Sends through `transport` instead of ureq; the HTTP options are then ignored.
+    #[must_use]
+    pub fn transport(mut self, transport: Arc<dyn Transport>) -> Self {
+        self.transport = Some(transport);
+        self
+    }
+
+    /// Reuses an existing agent; `timeout` and `user_agent` are then ignored.
+    #[must_use]
+    pub fn agent(mut self, agent: ureq::Agent) -> Self {
+        self.agent = Some(agent);
+        self
+    }
+
+    #[must_use]
+    pub fn device_key(mut self, device_key: impl Into<String>) -> Self {
+        self.default_device_keys.insert(device_key.into());
+        self
+    }
+
+    #[must_use]
+    pub fn device_keys<I, S>(mut self, device_keys: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.default_device_keys
+            .extend(device_keys.into_iter().map(Into::into));
+        self
+    }
+
+    /// Caps each request, connect through response body.
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    #[must_use]
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Reads the time for timestamps, `Retry-After` dates and timeouts from `clock`.
+    #[must_use]
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    pub fn build(self) -> Result<Client> {
+        let ClientBuilder {
+            base_url,
+            transport,
+            agent,
+            default_device_keys,
+            timeout,
+            user_agent,
+            clock,
+        } = self;
+
+        let base_url = base_url
+            .filter(|base_url| !base_url.trim().is_empty())
+            .ok_or(Error::MissingBaseUrl)?;
+        let base_url = validate::base_url("base_url", &base_url)?;
+        let default_device_keys = device_key::normalize(default_device_keys)?;
+
+        if timeout.is_some_and(|timeout| timeout.is_zero()) {
+            return Err(Error::ZeroTimeout("timeout"));
+        }
+
+        if let Some(user_agent) = &user_agent
+            && HeaderValue::from_str(user_agent).is_err()
+        {
+            return Err(Error::InvalidHeaderValue(user_agent.clone()));
+        }
+
+        let clock = clock.unwrap_or_else(|| Arc::new(SystemClock));
+        let transport = transport.unwrap_or_else(|| {
+            let agent = agent.unwrap_or_else(|| {
+                let mut config = ureq::Agent::config_builder().timeout_global(timeout);
+
+                if let Some(user_agent) = user_agent {
+                    config = config.user_agent(user_agent);
+                }
+
+                config.build().into()
+            });
+
+            Arc::new(UreqTransport::new(agent).with_clock(clock.clone()))
+        });
+
+        Ok(Client {
+            transport,
+            base_url: base_url.into(),
+            default_device_keys: Arc::new(default_device_keys),
+            clock,
+        })
+    }
+}
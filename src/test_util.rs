@@ -0,0 +1,93 @@
+mod capture;
+mod clock;
+mod compare;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod matchers;
+mod sequential;
+
+pub use capture::Captured;
+pub use clock::ManualClock;
+pub use compare::{Diff, assert_push_eq, push_semantic_eq};
+pub use sequential::SequentialIdGenerator;
+
+use serde::{Deserialize, Deserializer, de};
+
+use crate::message::Level;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ReceivedPush {
+    pub title: Option<String>,
+    pub subtitle: Option<String>,
+    pub body: Option<String>,
+    pub markdown: Option<String>,
+    #[serde(alias = "device_key", deserialize_with = "one_or_many")]
+    pub device_keys: Vec<String>,
+    pub level: Option<Level>,
+    pub volume: Option<u8>,
+    pub badge: Option<u64>,
+    #[serde(deserialize_with = "flag")]
+    pub call: bool,
+    #[serde(alias = "autoCopy", deserialize_with = "flag")]
+    pub auto_copy: bool,
+    pub copy: Option<String>,
+    pub sound: Option<String>,
+    pub icon: Option<String>,
+    pub image: Option<String>,
+    pub group: Option<String>,
+    pub ciphertext: Option<String>,
+    pub iv: Option<String>,
+    #[serde(alias = "isArchive", deserialize_with = "flag")]
+    pub is_archive: bool,
+    pub url: Option<String>,
+    pub action: Option<String>,
+    pub id: Option<String>,
+    #[serde(deserialize_with = "flag")]
+    pub delete: bool,
+}
+
+impl ReceivedPush {
+    pub fn from_slice(body: &[u8]) -> Option<Self> {
+        serde_json::from_slice(body).ok()
+    }
+}
+
+fn one_or_many<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(key) => vec![key],
+        OneOrMany::Many(keys) => keys,
+    })
+}
+
+fn flag<'de, D>(deserializer: D) -> Result<bool, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Flag {
+        Bool(bool),
+        Number(i64),
+        String(String),
+    }
+
+    match Flag::deserialize(deserializer)? {
+        Flag::Bool(value) => Ok(value),
+        Flag::Number(value) => Ok(value != 0),
+        Flag::String(value) => match value.as_str() {
+            "1" | "true" => Ok(true),
+            "0" | "false" | "" => Ok(false),
+            other => Err(de::Error::custom(format!("invalid flag ({other})"))),
+        },
+    }
+}
@@ -0,0 +1,120 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use http::{
+    StatusCode,
+    header::{CONTENT_TYPE, HeaderMap, HeaderValue},
+};
+
+use crate::{
+    BoxFuture,
+    error::{Error, Result},
+    lock,
+    message::Message,
+    transport::{PreparedRequest, RawResponse, Transport},
+};
+
+enum Scripted {
+    Response(RawResponse),
+    TransportError(String),
+}
+
+#[derive(Default)]
+struct CaptureState {
+    requests: Vec<PreparedRequest>,
+    scripted: HashMap<usize, Scripted>,
+}
+
+/// Records every request a capturing client would have sent.
+#[derive(Clone, Default)]
+pub struct Captured {
+    state: Arc<Mutex<CaptureState>>,
+}
+
+impl Captured {
+    /// A transport recording into this capture, for `ClientBuilder::transport`.
+    pub fn transport(&self) -> Arc<dyn Transport> {
+        Arc::new(CaptureTransport(self.clone()))
+    }
+
+    pub fn requests(&self) -> Vec<PreparedRequest> {
+        self.lock().requests.clone()
+    }
+
+    /// Messages decoded leniently from the recorded request bodies.
+    pub fn messages(&self) -> Vec<Message> {
+        self.lock()
+            .requests
+            .iter()
+            .filter_map(|request| std::str::from_utf8(&request.body).ok())
+            .filter_map(|body| Message::from_json_lenient(body).ok())
+            .map(|(message, _)| message)
+            .collect()
+    }
+
+    /// Makes the `n`th send (counting from 1) answer with a 500.
+    pub fn fail_send(&self, n: usize) {
+        self.respond_with(
+            n,
+            StatusCode::INTERNAL_SERVER_ERROR,
+            r#"{"code":500,"message":"scripted failure","timestamp":0}"#,
+        );
+    }
+
+    /// Makes the `n`th send answer with `status` and a JSON `body`.
+    pub fn respond_with(&self, n: usize, status: StatusCode, body: impl Into<String>) {
+        let response = json_response(status, body.into());
+        self.lock().scripted.insert(n, Scripted::Response(response));
+    }
+
+    /// Makes the `n`th send fail before reaching a server, as a connection error would.
+    pub fn transport_error(&self, n: usize, message: impl Into<String>) {
+        self.lock()
+            .scripted
+            .insert(n, Scripted::TransportError(message.into()));
+    }
+
+    pub fn clear(&self) {
+        self.lock().requests.clear();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, CaptureState> {
+        lock(&self.state)
+    }
+}
+
+fn json_response(status: StatusCode, body: String) -> RawResponse {
+    let mut headers = HeaderMap::new();
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+
+    RawResponse {
+        status,
+        headers,
+        body: body.into_bytes(),
+    }
+}
+
+struct CaptureTransport(Captured);
+
+impl Transport for CaptureTransport {
+    fn execute(&self, request: PreparedRequest) -> BoxFuture<'_, Result<RawResponse>> {
+        let mut state = self.0.lock();
+        state.requests.push(request);
+
+        let n = state.requests.len();
+        let outcome = match state.scripted.get(&n) {
+            Some(Scripted::Response(response)) => Ok(response.clone()),
+            Some(Scripted::TransportError(message)) => {
+                Err(Error::Transport(message.clone().into()))
+            }
+            None => Ok(json_response(
+                StatusCode::OK,
+                r#"{"code":200,"message":"success","timestamp":0}"#.to_owned(),
+            )),
+        };
+
+        Box::pin(async move { outcome })
+    }
+}
@@ -0,0 +1,89 @@
+use std::{
+    future,
+    sync::Mutex,
+    task::{Poll, Waker},
+    time::Duration,
+};
+
+use crate::{
+    BoxFuture,
+    clock::Clock,
+    lock,
+    time::{Instant, SystemTime},
+};
+
+/// A clock that only moves when told to. Sleeping on it returns immediately
+/// after advancing the clock to the deadline; timeouts fire only once
+/// [`advance`](Self::advance) or a sleep moves it past theirs.
+#[derive(Debug)]
+pub struct ManualClock {
+    system: SystemTime,
+    instant: Instant,
+    elapsed: Mutex<Duration>,
+    waiting: Mutex<Vec<Waker>>,
+}
+
+impl ManualClock {
+    pub fn new(start: SystemTime) -> Self {
+        ManualClock {
+            system: start,
+            instant: Instant::now(),
+            elapsed: Mutex::default(),
+            waiting: Mutex::default(),
+        }
+    }
+
+    pub fn advance(&self, by: Duration) {
+        *self.elapsed() += by;
+        self.wake();
+    }
+
+    fn elapsed(&self) -> std::sync::MutexGuard<'_, Duration> {
+        lock(&self.elapsed)
+    }
+
+    fn wake(&self) {
+        let waiting = std::mem::take(&mut *lock(&self.waiting));
+
+        waiting.into_iter().for_each(Waker::wake);
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        ManualClock::new(SystemTime::UNIX_EPOCH)
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> SystemTime {
+        self.system + *self.elapsed()
+    }
+
+    fn instant(&self) -> Instant {
+        self.instant + *self.elapsed()
+    }
+
+    fn sleep_until(&self, deadline: Instant) -> BoxFuture<'_, ()> {
+        {
+            let mut elapsed = self.elapsed();
+            *elapsed = (*elapsed).max(deadline.saturating_duration_since(self.instant));
+        }
+        self.wake();
+
+        Box::pin(async {})
+    }
+
+    fn reached(&self, deadline: Instant) -> BoxFuture<'_, ()> {
+        Box::pin(future::poll_fn(move |cx| {
+            let mut waiting = lock(&self.waiting);
+
+            if self.instant() >= deadline {
+                return Poll::Ready(());
+            }
+
+            waiting.push(cx.waker().clone());
+            Poll::Pending
+        }))
+    }
+}
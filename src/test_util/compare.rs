@@ -0,0 +1,86 @@
+use std::{collections::BTreeMap, fmt};
+
+use serde_json::Value;
+
+use crate::message::Message;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diff {
+    pub field: String,
+    pub actual: Option<Value>,
+    pub expected: Option<Value>,
+}
+
+impl fmt::Display for Diff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn side(value: &Option<Value>) -> String {
+            value
+                .as_ref()
+                .map_or_else(|| "<missing>".to_owned(), Value::to_string)
+        }
+
+        write!(
+            f,
+            "{}: actual {}, expected {}",
+            self.field,
+            side(&self.actual),
+            side(&self.expected)
+        )
+    }
+}
+
+pub fn push_semantic_eq(actual_json: &str, expected_json: &str) -> Result<(), Vec<Diff>> {
+    let (actual, expected) = match (normalize(actual_json), normalize(expected_json)) {
+        (Ok(actual), Ok(expected)) => (actual, expected),
+        (actual, expected) => {
+            return Err(vec![Diff {
+                field: "payload".to_owned(),
+                actual: actual.err().map(Value::String),
+                expected: expected.err().map(Value::String),
+            }]);
+        }
+    };
+
+    let mut fields: Vec<&String> = actual.keys().chain(expected.keys()).collect();
+    fields.sort();
+    fields.dedup();
+
+    let diffs: Vec<Diff> = fields
+        .into_iter()
+        .filter(|&field| actual.get(field) != expected.get(field))
+        .map(|field| Diff {
+            field: field.clone(),
+            actual: actual.get(field).cloned(),
+            expected: expected.get(field).cloned(),
+        })
+        .collect();
+
+    if diffs.is_empty() { Ok(()) } else { Err(diffs) }
+}
+
+#[track_caller]
+pub fn assert_push_eq(actual_json: &str, expected_json: &str) {
+    if let Err(diffs) = push_semantic_eq(actual_json, expected_json) {
+        let diffs: Vec<String> = diffs.iter().map(ToString::to_string).collect();
+        panic!("pushes differ:\n  {}", diffs.join("\n  "));
+    }
+}
+
+fn normalize(json: &str) -> Result<BTreeMap<String, Value>, String> {
+    let (message, _) = Message::from_json_lenient(json).map_err(|err| err.to_string())?;
+    let Value::Object(object) = serde_json::to_value(&message).map_err(|err| err.to_string())?
+    else {
+        return Err("message did not serialize to an object".to_owned());
+    };
+
+    Ok(object
+        .into_iter()
+        .map(|(field, value)| match (field.as_str(), value) {
+            ("device_keys", Value::Array(mut keys)) => {
+                keys.sort_by(|a, b| a.as_str().cmp(&b.as_str()));
+                (field, Value::Array(keys))
+            }
+            (_, value) => (field, value),
+        })
+        .collect())
+}
@@ -0,0 +1,35 @@
+use wiremock::{Match, Request};
+
+use crate::{message::Level, test_util::ReceivedPush};
+
+pub struct PushMatcher<F>(F);
+
+impl<F> Match for PushMatcher<F>
+where
+    F: Fn(&ReceivedPush) -> bool + Send + Sync,
+{
+    fn matches(&self, request: &Request) -> bool {
+        ReceivedPush::from_slice(&request.body).is_some_and(|push| (self.0)(&push))
+    }
+}
+
+pub fn push_matching<F>(predicate: F) -> PushMatcher<F>
+where
+    F: Fn(&ReceivedPush) -> bool + Send + Sync,
+{
+    PushMatcher(predicate)
+}
+
+pub fn push_with_title(title: &str) -> impl Match + use<> {
+    let title = title.to_owned();
+    push_matching(move |push| push.title.as_deref() == Some(title.as_str()))
+}
+
+pub fn push_with_level(level: Level) -> impl Match + use<> {
+    push_matching(move |push| push.level.as_ref() == Some(&level))
+}
+
+pub fn push_to_device(device_key: &str) -> impl Match + use<> {
+    let device_key = device_key.to_owned();
+    push_matching(move |push| push.device_keys.contains(&device_key))
+}
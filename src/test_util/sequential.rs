@@ -0,0 +1,26 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::id::IdGenerator;
+
+/// Generates `{prefix}1`, `{prefix}2`, ... for deterministic tests.
+#[derive(Debug, Default)]
+pub struct SequentialIdGenerator {
+    prefix: String,
+    next: AtomicU64,
+}
+
+impl SequentialIdGenerator {
+    pub fn new(prefix: impl Into<String>) -> Self {
+        SequentialIdGenerator {
+            prefix: prefix.into(),
+            next: AtomicU64::new(0),
+        }
+    }
+}
+
+impl IdGenerator for SequentialIdGenerator {
+    fn generate(&self) -> String {
+        let n = self.next.fetch_add(1, Ordering::Relaxed) + 1;
+        format!("{}{n}", self.prefix)
+    }
+}
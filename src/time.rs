@@ -0,0 +1,34 @@
+//! Clocks and timers that also work on `wasm32-unknown-unknown`, where std's
+//! clocks panic and tokio has no timer driver.
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use std::time::{Instant, SystemTime};
+
+#[cfg(target_arch = "wasm32")]
+pub use web_time::{Instant, SystemTime};
+
+/// Tokio's clock on native targets, so paused-time tests see sleeps elapse.
+pub(crate) fn now() -> Instant {
+    #[cfg(all(feature = "async", not(target_arch = "wasm32")))]
+    return tokio::time::Instant::now().into_std();
+
+    #[cfg(any(not(feature = "async"), target_arch = "wasm32"))]
+    return Instant::now();
+}
+
+/// Without the `async` feature nothing in the crate awaits this; it then blocks
+/// the thread, or returns at once on wasm.
+#[cfg_attr(
+    all(not(feature = "async"), target_arch = "wasm32"),
+    allow(unused_variables)
+)]
+pub(crate) async fn sleep_until(deadline: Instant) {
+    #[cfg(all(feature = "async", not(target_arch = "wasm32")))]
+    tokio::time::sleep_until(deadline.into()).await;
+
+    #[cfg(all(feature = "async", target_arch = "wasm32"))]
+    gloo_timers::future::sleep(deadline.saturating_duration_since(Instant::now())).await;
+
+    #[cfg(all(not(feature = "async"), not(target_arch = "wasm32")))]
+    std::thread::sleep(deadline.saturating_duration_since(now()));
+}
@@ -0,0 +1,86 @@
+use chrono::{
+    DateTime, FixedOffset, Local, Utc,
+    format::{Item, StrftimeItems},
+};
+
+use crate::{
+    error::{Error, Result},
+    message::{Body, BodyKind, Message},
+    time::SystemTime,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimestampPlacement {
+    Subtitle,
+    BodySuffix,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TimestampZone {
+    #[default]
+    Utc,
+    Local,
+    Fixed(FixedOffset),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Timestamp {
+    pub(crate) placement: TimestampPlacement,
+    pub(crate) format: String,
+    pub(crate) zone: TimestampZone,
+}
+
+impl Timestamp {
+    pub(crate) fn validate(&self) -> Result<()> {
+        if StrftimeItems::new(&self.format).any(|item| matches!(item, Item::Error)) {
+            return Err(Error::InvalidTimestampFormat(self.format.clone()));
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn render(&self, now: DateTime<Utc>) -> String {
+        match self.zone {
+            TimestampZone::Utc => now.format(&self.format).to_string(),
+            TimestampZone::Local => now.with_timezone(&Local).format(&self.format).to_string(),
+            TimestampZone::Fixed(offset) => {
+                now.with_timezone(&offset).format(&self.format).to_string()
+            }
+        }
+    }
+
+    pub(crate) fn apply(&self, message: &mut Message, now: SystemTime) {
+        let stamp = self.render(utc(now));
+
+        match self.placement {
+            TimestampPlacement::Subtitle => {
+                message.subtitle = Some(match message.subtitle.take() {
+                    Some(subtitle) => format!("{subtitle} · {stamp}"),
+                    None => stamp,
+                });
+            }
+            TimestampPlacement::BodySuffix => match &mut message.body {
+                Some(body) => {
+                    body.content.push('\n');
+                    body.content.push_str(&stamp);
+                }
+                None => {
+                    message.body = Some(Body {
+                        kind: BodyKind::Plaintext,
+                        content: stamp,
+                    });
+                }
+            },
+        }
+    }
+}
+
+/// chrono only converts std's `SystemTime`, which is a different type on wasm.
+fn utc(time: SystemTime) -> DateTime<Utc> {
+    let since_epoch = time
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default();
+
+    DateTime::from_timestamp(since_epoch.as_secs() as i64, since_epoch.subsec_nanos())
+        .unwrap_or_default()
+}
@@ -0,0 +1,229 @@
+#[cfg(feature = "reqwest")]
+use std::sync::Arc;
+use std::time::Duration;
+
+use http::{Method, StatusCode, header::HeaderMap};
+use url::Url;
+
+use crate::{BoxFuture, error::Result};
+#[cfg(feature = "reqwest")]
+use crate::{
+    clock::{Clock, SystemClock},
+    error::Error,
+};
+
+#[derive(Debug, Clone)]
+pub struct PreparedRequest {
+    pub method: Method,
+    pub url: Url,
+    pub headers: HeaderMap,
+    pub body: Vec<u8>,
+    /// A per-send cap covering connect through response body; transports should honor it.
+    pub timeout: Option<Duration>,
+    /// `url` with the device key in its path shortened, for logs; `None` if there is none.
+    pub redacted_url: Option<Url>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RawResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Vec<u8>,
+}
+
+/// Executes prepared requests; implement it to replace the HTTP layer.
+pub trait Transport: Send + Sync {
+    fn execute(&self, request: PreparedRequest) -> BoxFuture<'_, Result<RawResponse>>;
+}
+
+#[cfg(feature = "reqwest")]
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+    clock: Arc<dyn Clock>,
+}
+
+#[cfg(feature = "reqwest")]
+impl ReqwestTransport {
+    pub fn new(client: reqwest::Client) -> Self {
+        ReqwestTransport {
+            client,
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Measures the elapsed time reported on timeouts with `clock`.
+    #[must_use]
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+}
+
+#[cfg(feature = "reqwest")]
+impl Transport for ReqwestTransport {
+    fn execute(&self, request: PreparedRequest) -> BoxFuture<'_, Result<RawResponse>> {
+        Box::pin(execute(&self.client, &*self.clock, request))
+    }
+}
+
+#[cfg(feature = "reqwest")]
+async fn execute(
+    client: &reqwest::Client,
+    clock: &dyn Clock,
+    request: PreparedRequest,
+) -> Result<RawResponse> {
+    let started = clock.instant();
+    let timed_out = |error: reqwest::Error| {
+        if error.is_timeout() {
+            Error::Timeout {
+                elapsed: clock.instant().saturating_duration_since(started),
+                deadline: None,
+            }
+        } else {
+            Error::RequestError(error)
+        }
+    };
+
+    let mut builder = client
+        .request(request.method, request.url)
+        .headers(request.headers)
+        .body(request.body);
+
+    if let Some(timeout) = request.timeout {
+        builder = builder.timeout(timeout);
+    }
+
+    let response = builder.send().await.map_err(timed_out)?;
+
+    let status = response.status();
+    let headers = response.headers().clone();
+    let body = response.bytes().await.map_err(timed_out)?.to_vec();
+
+    Ok(RawResponse {
+        status,
+        headers,
+        body,
+    })
+}
+
+#[cfg(all(feature = "tower", not(target_arch = "wasm32")))]
+pub use service::TransportService;
+
+#[cfg(all(feature = "tower", not(target_arch = "wasm32")))]
+pub(crate) mod service {
+    use std::sync::Arc;
+
+    use tower::{BoxError, Service, ServiceExt, util::BoxCloneSyncService};
+
+    use super::{PreparedRequest, RawResponse, Transport};
+    use crate::{
+        BoxFuture,
+        error::{Error, Result},
+    };
+
+    pub type TransportService = BoxCloneSyncService<PreparedRequest, RawResponse, BoxError>;
+
+    pub(crate) type LayerFn = Box<dyn FnOnce(TransportService) -> TransportService + Send + Sync>;
+
+    pub(crate) fn transport_service(transport: Arc<dyn Transport>) -> TransportService {
+        BoxCloneSyncService::new(tower::service_fn(move |request| {
+            let transport = transport.clone();
+            async move { transport.execute(request).await.map_err(BoxError::from) }
+        }))
+    }
+
+    pub(crate) struct ServiceTransport(pub(crate) TransportService);
+
+    impl Transport for ServiceTransport {
+        fn execute(&self, request: PreparedRequest) -> BoxFuture<'_, Result<RawResponse>> {
+            let service = self.0.clone();
+
+            Box::pin(async move {
+                service
+                    .oneshot(request)
+                    .await
+                    .map_err(Error::from_transport)
+            })
+        }
+    }
+
+    impl Error {
+        fn from_transport(err: BoxError) -> Error {
+            match err.downcast::<Error>() {
+                Ok(err) => *err,
+                #[cfg(feature = "reqwest")]
+                Err(err) => match err.downcast::<reqwest::Error>() {
+                    Ok(err) => Error::RequestError(*err),
+                    Err(err) => Error::Transport(err),
+                },
+                #[cfg(not(feature = "reqwest"))]
+                Err(err) => Error::Transport(err),
+            }
+        }
+    }
+
+    pub(crate) fn boxed<S>(service: S) -> TransportService
+    where
+        S: Service<PreparedRequest, Response = RawResponse> + Clone + Send + Sync + 'static,
+        S::Error: Into<BoxError>,
+        S::Future: Send + 'static,
+    {
+        BoxCloneSyncService::new(service.map_err(Into::into))
+    }
+}
+
+#[cfg(all(feature = "async", feature = "tracing"))]
+pub(crate) mod wire_log {
+    use std::sync::Arc;
+
+    use http::header::CONTENT_ENCODING;
+
+    use super::{PreparedRequest, RawResponse, Transport};
+    use crate::{BoxFuture, error::Result, redact};
+
+    pub(crate) const DEFAULT_BODY_LIMIT: usize = 4096;
+
+    pub(crate) struct WireLogTransport {
+        pub(crate) inner: Arc<dyn Transport>,
+        pub(crate) body_limit: usize,
+    }
+
+    impl WireLogTransport {
+        fn body(&self, headers: &http::HeaderMap, body: &[u8]) -> String {
+            if headers.contains_key(CONTENT_ENCODING) {
+                return format!("<{} encoded bytes>", body.len());
+            }
+
+            let mut body = redact::json_body(body);
+            redact::truncate(&mut body, self.body_limit);
+            body
+        }
+    }
+
+    impl Transport for WireLogTransport {
+        fn execute(&self, request: PreparedRequest) -> BoxFuture<'_, Result<RawResponse>> {
+            tracing::trace!(
+                target: "bark::wire",
+                method = %request.method,
+                url = %request.redacted_url.as_ref().unwrap_or(&request.url),
+                headers = ?redact::headers(&request.headers),
+                body = %self.body(&request.headers, &request.body),
+                "request",
+            );
+
+            Box::pin(async move {
+                let response = self.inner.execute(request).await?;
+
+                tracing::trace!(
+                    target: "bark::wire",
+                    status = %response.status,
+                    headers = ?redact::headers(&response.headers),
+                    body = %self.body(&response.headers, &response.body),
+                    "response",
+                );
+
+                Ok(response)
+            })
+        }
+    }
+}
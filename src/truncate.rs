@@ -0,0 +1,138 @@
+use crate::split::{self, FENCE};
+
+/// Byte limits applied by [`MessageBuilder::truncate`](crate::message::MessageBuilder::truncate).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TruncateOptions {
+    pub max_title: Option<usize>,
+    pub max_body: Option<usize>,
+    pub ellipsis: String,
+}
+
+impl Default for TruncateOptions {
+    fn default() -> Self {
+        TruncateOptions {
+            max_title: Some(256),
+            max_body: Some(2048),
+            ellipsis: "…".to_owned(),
+        }
+    }
+}
+
+impl TruncateOptions {
+    pub(crate) fn title(&self, title: &mut String) {
+        if let Some(max) = self.max_title
+            && title.len() > max
+        {
+            title.truncate(cut(title, max.saturating_sub(self.ellipsis.len())));
+            title.push_str(&self.ellipsis);
+        }
+    }
+
+    /// Appends `… (truncated 1.2 KB)` naming how much was dropped.
+    pub(crate) fn body(&self, body: &mut String, markdown: bool) {
+        let Some(max) = self.max_body.filter(|&max| body.len() > max) else {
+            return;
+        };
+
+        let original = body.len();
+        let mut marker = self.marker(original);
+
+        // The marker's length depends on what it reports, so settle it in a couple of passes.
+        for _ in 0..3 {
+            let kept = self.keep(body, max.saturating_sub(marker.len()), markdown);
+            let next = self.marker(original - kept.len());
+
+            if next.len() <= marker.len() {
+                *body = kept + &next;
+                return;
+            }
+
+            marker = next;
+        }
+
+        *body = self.keep(body, max.saturating_sub(marker.len()), markdown) + &marker;
+    }
+
+    fn keep(&self, body: &str, budget: usize, markdown: bool) -> String {
+        let mut kept = body[..cut(body, budget)].to_owned();
+
+        if markdown && let Some(start) = open_fence(&kept) {
+            let closing = FENCE.len() + 2;
+
+            // Drop the unfinished block if that keeps most of the text, else close it.
+            if start >= budget / 2 {
+                kept.truncate(start);
+            } else {
+                kept.truncate(cut(&kept, budget.saturating_sub(closing)));
+                split::close_fence(&mut kept);
+                kept.push('\n');
+            }
+        }
+
+        kept
+    }
+
+    fn marker(&self, dropped: usize) -> String {
+        let size = if dropped < 1024 {
+            format!("{dropped} B")
+        } else {
+            format!("{:.1} KB", dropped as f64 / 1024.0)
+        };
+
+        format!("{} (truncated {size})", self.ellipsis)
+    }
+}
+
+/// The start of the last fence line when `text` ends inside a code block.
+fn open_fence(text: &str) -> Option<usize> {
+    let mut open = None;
+    let mut offset = 0;
+
+    for line in text.split_inclusive('\n') {
+        if line.trim_start().starts_with(FENCE) {
+            open = match open {
+                Some(_) => None,
+                None => Some(offset),
+            };
+        }
+
+        offset += line.len();
+    }
+
+    open
+}
+
+/// The largest cut at or below `max` that splits neither a char nor a combining sequence.
+fn cut(text: &str, max: usize) -> usize {
+    let mut index = split::floor_char_boundary(text, max);
+
+    while index > 0 {
+        let next = text[index..].chars().next();
+        let previous = text[..index].chars().next_back();
+
+        if next.is_some_and(extends) || previous == Some(ZERO_WIDTH_JOINER) {
+            index = split::floor_char_boundary(text, index - 1);
+        } else {
+            break;
+        }
+    }
+
+    index
+}
+
+const ZERO_WIDTH_JOINER: char = '\u{200D}';
+
+fn extends(c: char) -> bool {
+    matches!(
+        c,
+        '\u{0300}'..='\u{036F}'
+            | '\u{1AB0}'..='\u{1AFF}'
+            | '\u{1DC0}'..='\u{1DFF}'
+            | '\u{200D}'
+            | '\u{20D0}'..='\u{20FF}'
+            | '\u{FE00}'..='\u{FE0F}'
+            | '\u{FE20}'..='\u{FE2F}'
+            | '\u{1F3FB}'..='\u{1F3FF}'
+            | '\u{E0020}'..='\u{E007F}'
+    )
+}
@@ -0,0 +1,124 @@
+use std::{fmt, sync::Arc};
+
+#[cfg(feature = "put-upload")]
+use reqwest::header::CONTENT_TYPE;
+use url::Url;
+#[cfg(feature = "put-upload")]
+use uuid::Uuid;
+
+use crate::{BoxFuture, error::Result};
+#[cfg(feature = "put-upload")]
+use crate::{error::Error, redact, validate};
+
+pub trait Uploader: Send + Sync {
+    fn upload<'a>(&'a self, bytes: &'a [u8], content_type: &'a str) -> BoxFuture<'a, Result<Url>>;
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub(crate) struct ImageBytes {
+    pub(crate) bytes: Arc<[u8]>,
+    pub(crate) content_type: String,
+}
+
+impl fmt::Debug for ImageBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ImageBytes")
+            .field("len", &self.bytes.len())
+            .field("content_type", &self.content_type)
+            .finish()
+    }
+}
+
+#[cfg(feature = "put-upload")]
+#[derive(Debug, Clone)]
+pub struct PutUploader {
+    http: reqwest::Client,
+    endpoint: Url,
+    public_base: Option<Url>,
+}
+
+#[cfg(feature = "put-upload")]
+impl PutUploader {
+    pub fn new(endpoint: impl AsRef<str>) -> Result<Self> {
+        Ok(PutUploader {
+            http: reqwest::Client::default(),
+            endpoint: validate::http_url("upload endpoint", endpoint.as_ref())?,
+            public_base: None,
+        })
+    }
+
+    pub fn public_base(mut self, public_base: impl AsRef<str>) -> Result<Self> {
+        self.public_base = Some(validate::http_url("public base", public_base.as_ref())?);
+        Ok(self)
+    }
+
+    #[must_use]
+    pub fn http_client(mut self, http: reqwest::Client) -> Self {
+        self.http = http;
+        self
+    }
+
+    async fn put(&self, bytes: &[u8], content_type: &str) -> Result<Url> {
+        let name = format!("{}.{}", Uuid::new_v4(), extension(content_type));
+        let target = object_url(&self.endpoint, &name);
+
+        let response = self
+            .http
+            .put(target.clone())
+            .header(CONTENT_TYPE, content_type)
+            .body(bytes.to_vec())
+            .send()
+            .await?;
+
+        let mut public = match &self.public_base {
+            Some(base) => object_url(base, &name),
+            None => target,
+        };
+
+        public.set_query(None);
+
+        let status = response.status();
+
+        if !status.is_success() {
+            let body = response.bytes().await.unwrap_or_default();
+
+            return Err(Error::HttpStatus {
+                status,
+                url: public,
+                body: redact::response_body(&body),
+            });
+        }
+
+        Ok(public)
+    }
+}
+
+#[cfg(feature = "put-upload")]
+impl Uploader for PutUploader {
+    fn upload<'a>(&'a self, bytes: &'a [u8], content_type: &'a str) -> BoxFuture<'a, Result<Url>> {
+        Box::pin(self.put(bytes, content_type))
+    }
+}
+
+#[cfg(feature = "put-upload")]
+fn object_url(base: &Url, name: &str) -> Url {
+    let mut url = base.clone();
+
+    if let Ok(mut segments) = url.path_segments_mut() {
+        segments.pop_if_empty().push(name);
+    }
+
+    url
+}
+
+#[cfg(feature = "put-upload")]
+fn extension(content_type: &str) -> &str {
+    match content_type {
+        "image/jpeg" => "jpg",
+        "image/svg+xml" => "svg",
+        _ => content_type
+            .strip_prefix("image/")
+            .filter(|ext| ext.chars().all(|c| c.is_ascii_alphanumeric()))
+            .unwrap_or("bin"),
+    }
+}
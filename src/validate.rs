@@ -0,0 +1,56 @@
+use url::Url;
+
+use crate::error::{Error, Result, UrlError};
+#[cfg(all(feature = "reqwest", not(target_arch = "wasm32")))]
+use crate::redact;
+
+/// Any absolute URL, including app deep links like `shortcuts://`.
+pub(crate) fn url(field: &'static str, value: &str) -> Result<Url> {
+    Url::parse(value).map_err(|source| Error::InvalidUrl {
+        field,
+        value: value.to_owned(),
+        source: source.into(),
+    })
+}
+
+pub(crate) fn http_url(field: &'static str, value: &str) -> Result<Url> {
+    let url = self::url(field, value)?;
+
+    match url.scheme() {
+        "http" | "https" => Ok(url),
+        scheme => Err(Error::InvalidUrl {
+            field,
+            value: value.to_owned(),
+            source: UrlError::UnsupportedScheme(scheme.to_owned()),
+        }),
+    }
+}
+
+/// A server root: https is assumed without a scheme, and trailing slashes are
+/// dropped so `{base}/push` keeps any subpath mount.
+pub(crate) fn base_url(field: &'static str, value: &str) -> Result<String> {
+    let value = value.trim();
+    let url = if value.contains("://") {
+        http_url(field, value)?
+    } else {
+        http_url(field, &format!("https://{value}"))?
+    };
+
+    Ok(url.as_str().trim_end_matches('/').to_owned())
+}
+
+#[cfg(all(feature = "reqwest", not(target_arch = "wasm32")))]
+pub(crate) fn proxy_url(value: &str) -> Result<Url> {
+    let url = self::url("proxy", value)?;
+
+    match url.scheme() {
+        "http" | "https" => Ok(url),
+        #[cfg(feature = "socks")]
+        "socks5" | "socks5h" => Ok(url),
+        scheme => Err(Error::InvalidUrl {
+            field: "proxy",
+            value: redact::url(value),
+            source: UrlError::UnsupportedScheme(scheme.to_owned()),
+        }),
+    }
+}
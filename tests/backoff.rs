@@ -0,0 +1,174 @@
+use std::time::Duration;
+
+use bark::{
+    client::Client, error::Error, message::Message, retry::RetryPolicy, test_util::Captured,
+};
+use http::StatusCode;
+use tokio::time::Instant;
+use wiremock::{
+    Mock, MockServer, ResponseTemplate,
+    matchers::{method, path},
+};
+
+const SUCCESS: &str = r#"{"code":200,"message":"success","timestamp":1}"#;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+fn policy(max_attempts: u32, initial: u64, max: u64) -> RetryPolicy {
+    RetryPolicy {
+        max_attempts,
+        initial_backoff: ms(initial),
+        max_backoff: ms(max),
+        jitter: false,
+        total_deadline: None,
+    }
+}
+
+fn capturing(policy: RetryPolicy) -> (Client, Captured) {
+    Client::builder()
+        .base_url("https://bark.example.com")
+        .device_key("key1")
+        .retry(policy)
+        .build_capturing()
+        .unwrap()
+}
+
+/// Sends once and reports the paused time it took.
+async fn timed(client: &Client) -> (bark::error::Result<u32>, Duration) {
+    let started = Instant::now();
+    let result = client
+        .send_detailed(&Message::text("b"))
+        .await
+        .map(|outcome| outcome.attempts);
+    (result, started.elapsed())
+}
+
+#[tokio::test(start_paused = true)]
+async fn backoff_doubles_and_is_awaited() {
+    let (client, captured) = capturing(policy(4, 100, 1000));
+    for n in 1..=3 {
+        captured.fail_send(n);
+    }
+
+    let (attempts, elapsed) = timed(&client).await;
+
+    assert_eq!(attempts.unwrap(), 4);
+    assert_eq!(elapsed, ms(100 + 200 + 400));
+}
+
+#[tokio::test(start_paused = true)]
+async fn backoff_stops_growing_at_the_cap() {
+    let (client, captured) = capturing(policy(4, 300, 500));
+    for n in 1..=3 {
+        captured.fail_send(n);
+    }
+
+    let (_, elapsed) = timed(&client).await;
+
+    assert_eq!(elapsed, ms(300 + 500 + 500));
+}
+
+#[tokio::test(start_paused = true)]
+async fn jitter_waits_between_half_and_all_of_the_backoff() {
+    let (client, captured) = capturing(RetryPolicy {
+        jitter: true,
+        ..policy(2, 100, 1000)
+    });
+
+    for _ in 0..20 {
+        captured.fail_send(1);
+        let (attempts, elapsed) = timed(&client).await;
+        captured.clear();
+
+        assert_eq!(attempts.unwrap(), 2);
+        assert!(elapsed >= ms(50) && elapsed <= ms(100), "{elapsed:?}");
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn connection_errors_are_retried() {
+    let (client, captured) = capturing(policy(3, 100, 1000));
+    captured.transport_error(1, "connection refused");
+
+    let (attempts, elapsed) = timed(&client).await;
+
+    assert_eq!(attempts.unwrap(), 2);
+    assert_eq!(elapsed, ms(100));
+}
+
+#[tokio::test(start_paused = true)]
+async fn client_errors_are_not_retried() {
+    let (client, captured) = capturing(policy(3, 100, 1000));
+    captured.respond_with(1, StatusCode::NOT_FOUND, "not found");
+    captured.respond_with(
+        2,
+        StatusCode::BAD_REQUEST,
+        r#"{"code":400,"message":"failed to get device token","timestamp":1}"#,
+    );
+
+    let (status, elapsed) = timed(&client).await;
+    assert!(
+        matches!(status, Err(Error::HttpStatus { status, .. }) if status == StatusCode::NOT_FOUND),
+        "{status:?}"
+    );
+    assert_eq!(elapsed, Duration::ZERO);
+
+    let (server, _) = timed(&client).await;
+    assert!(
+        matches!(server, Err(Error::Server { code: 400, .. })),
+        "{server:?}"
+    );
+    assert_eq!(captured.requests().len(), 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn the_final_error_counts_the_attempts() {
+    let (client, captured) = capturing(policy(3, 100, 1000));
+    for n in 1..=3 {
+        captured.fail_send(n);
+    }
+
+    let (result, elapsed) = timed(&client).await;
+
+    let error = result.unwrap_err();
+    assert!(
+        matches!(&error, Error::RetriesExhausted { attempts: 3, source } if matches!(**source, Error::HttpStatus { .. })),
+        "{error:?}"
+    );
+    assert!(
+        error.to_string().starts_with("Gave up after 3 attempts"),
+        "{error}"
+    );
+    assert_eq!(elapsed, ms(100 + 200));
+}
+
+#[tokio::test]
+async fn a_server_failing_twice_then_succeeding() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/push"))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(2)
+        .with_priority(1)
+        .expect(2)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/push"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(SUCCESS))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let client = Client::builder()
+        .base_url(server.uri())
+        .device_key("key1")
+        .retry(policy(3, 1, 10))
+        .build()
+        .unwrap();
+
+    let outcome = client.send_detailed(&Message::text("b")).await.unwrap();
+
+    assert_eq!(outcome.attempts, 3);
+}
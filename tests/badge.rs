@@ -0,0 +1,210 @@
+use std::{
+    fs,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use bark::{
+    client::{Client, ClientBuilder},
+    error::Error,
+    message::Message,
+    test_util::Captured,
+};
+use serde_json::{Value, json};
+
+fn builder() -> ClientBuilder {
+    Client::builder()
+        .base_url("https://bark.example.com")
+        .device_key("key1")
+}
+
+fn stored(path: &Path) -> Value {
+    serde_json::from_slice(&fs::read(path).unwrap()).unwrap()
+}
+
+fn badges(captured: &Captured) -> Vec<Value> {
+    captured
+        .messages()
+        .iter()
+        .map(|message| serde_json::to_value(message).unwrap()["badge"].clone())
+        .collect()
+}
+
+/// Collects what the store reports, as `Display` strings.
+fn reported(builder: ClientBuilder) -> (ClientBuilder, Arc<Mutex<Vec<String>>>) {
+    let errors = Arc::new(Mutex::new(Vec::new()));
+    let builder = builder.on_badge_store_error({
+        let errors = errors.clone();
+        move |error: &Error| {
+            assert!(
+                matches!(
+                    error,
+                    Error::InvalidBadgeStore { .. } | Error::BadgeStore { .. }
+                ),
+                "{error:?}"
+            );
+            errors.lock().unwrap().push(error.to_string());
+        }
+    });
+
+    (builder, errors)
+}
+
+#[tokio::test]
+async fn badges_count_up_per_device_key() {
+    let (client, captured) = builder().auto_badge(true).build_capturing().unwrap();
+    let message = Message::text("b");
+
+    client.send(&message).await.unwrap();
+    client.clone().send(&message).await.unwrap();
+    client.send_to(["key2"], &message).await.unwrap();
+    client.send_to(["key1", "key2"], &message).await.unwrap();
+
+    assert_eq!(badges(&captured), [1, 2, 1, 3]);
+    assert_eq!(client.badge_count("key2"), Some(3));
+}
+
+#[tokio::test]
+async fn an_explicit_badge_sets_the_count() {
+    let (client, captured) = builder().auto_badge(true).build_capturing().unwrap();
+
+    client.send(&Message::text("b")).await.unwrap();
+    client
+        .send(&Message::builder().body("b").badge(0).build().unwrap())
+        .await
+        .unwrap();
+    client.send(&Message::text("b")).await.unwrap();
+
+    assert_eq!(badges(&captured), [1, 0, 1]);
+}
+
+#[tokio::test]
+async fn badges_are_left_alone_by_default() {
+    let (client, captured) = builder().build_capturing().unwrap();
+
+    client.send(&Message::text("b")).await.unwrap();
+
+    assert_eq!(badges(&captured), [Value::Null]);
+    assert_eq!(client.badge_count("key1"), None);
+    assert!(!client.effective_config().auto_badge);
+}
+
+#[tokio::test]
+async fn counting_carries_on_after_a_restart() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("badges.json");
+
+    let (client, _) = builder().badge_store(&path).build_capturing().unwrap();
+    client.send(&Message::text("b")).await.unwrap();
+    client.send(&Message::text("b")).await.unwrap();
+    assert_eq!(
+        client.effective_config().badge_store,
+        fs::canonicalize(&path).unwrap().to_str().map(str::to_owned)
+    );
+    drop(client);
+
+    assert_eq!(
+        stored(&path),
+        json!({ "version": 1, "badges": { "key1": 2 } })
+    );
+
+    let (client, captured) = builder().badge_store(&path).build_capturing().unwrap();
+    client.send(&Message::text("b")).await.unwrap();
+
+    assert_eq!(badges(&captured), [3]);
+}
+
+#[tokio::test]
+async fn a_corrupt_store_starts_fresh_with_a_warning() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("badges.json");
+    fs::write(&path, "{\"version\": 1, \"badges\": {\"key1\": ").unwrap();
+
+    let (builder, errors) = reported(builder().badge_store(&path));
+    let (client, captured) = builder.build_capturing().unwrap();
+    client.send(&Message::text("b")).await.unwrap();
+
+    let errors = errors.lock().unwrap();
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].starts_with("Invalid badge store"), "{errors:?}");
+    assert_eq!(badges(&captured), [1]);
+    assert_eq!(stored(&path)["badges"], json!({ "key1": 1 }));
+}
+
+#[tokio::test]
+async fn a_store_from_a_newer_version_starts_fresh() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("badges.json");
+    fs::write(&path, r#"{"version": 2, "badges": {"key1": 9}}"#).unwrap();
+
+    let (builder, errors) = reported(builder().badge_store(&path));
+    let (client, captured) = builder.build_capturing().unwrap();
+    client.send(&Message::text("b")).await.unwrap();
+
+    assert!(errors.lock().unwrap()[0].ends_with("unsupported version 2"));
+    assert_eq!(badges(&captured), [1]);
+}
+
+#[tokio::test]
+async fn a_failed_write_is_reported_and_the_send_goes_ahead() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("badges.json");
+    // The temporary file cannot be created where a directory stands.
+    fs::create_dir(dir.path().join("badges.json.tmp")).unwrap();
+
+    let (builder, errors) = reported(builder().badge_store(&path));
+    let (client, captured) = builder.build_capturing().unwrap();
+    client.send(&Message::text("b")).await.unwrap();
+
+    assert_eq!(badges(&captured), [1]);
+    assert!(errors.lock().unwrap()[0].starts_with("Badge store"));
+    assert!(!path.exists());
+}
+
+#[test]
+fn an_unreadable_store_fails_the_build() {
+    let dir = tempfile::tempdir().unwrap();
+
+    let error = builder()
+        .badge_store(dir.path().join("missing").join("badges.json"))
+        .build()
+        .unwrap_err();
+
+    assert!(matches!(error, Error::BadgeStore { .. }), "{error:?}");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn clients_sharing_a_store_never_reuse_a_badge() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("badges.json");
+    let (first, first_captured) = builder().badge_store(&path).build_capturing().unwrap();
+    // Another spelling of the same file still shares the count.
+    let (second, second_captured) = builder()
+        .badge_store(dir.path().join(".").join("badges.json"))
+        .build_capturing()
+        .unwrap();
+
+    let sends: Vec<_> = (0..100)
+        .map(|n| {
+            let client = if n % 2 == 0 { &first } else { &second }.clone();
+            tokio::spawn(async move { client.send(&Message::text("b")).await.unwrap() })
+        })
+        .collect();
+    for send in sends {
+        send.await.unwrap();
+    }
+
+    let mut sent: Vec<u64> = badges(&first_captured)
+        .into_iter()
+        .chain(badges(&second_captured))
+        .map(|badge| badge.as_u64().unwrap())
+        .collect();
+    sent.sort_unstable();
+    assert_eq!(sent, (1..=100).collect::<Vec<_>>());
+    assert_eq!(stored(&path)["badges"], json!({ "key1": 100 }));
+
+    drop((first, second));
+    let (client, captured) = builder().badge_store(&path).build_capturing().unwrap();
+    client.send(&Message::text("b")).await.unwrap();
+    assert_eq!(badges(&captured), [101]);
+}
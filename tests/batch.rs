@@ -0,0 +1,392 @@
+use std::{
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use bark::{
+    BoxFuture,
+    batch::SendAllOptions,
+    client::Client,
+    error::Result,
+    hook::HookControl,
+    message::Message,
+    rate_limit::RateLimit,
+    record::{self, StoredRecord},
+    test_util::Captured,
+    transport::{PreparedRequest, RawResponse, Transport},
+};
+use http::StatusCode;
+use tokio::time::Instant;
+
+/// Answers every push with success and records when each request started.
+#[derive(Clone, Default)]
+struct Stamped(Arc<Mutex<Vec<Instant>>>);
+
+impl Stamped {
+    /// Request starts relative to the first one, in order.
+    fn offsets(&self) -> Vec<Duration> {
+        let mut starts = self.0.lock().unwrap().clone();
+        starts.sort();
+
+        starts
+            .iter()
+            .map(|start| start.duration_since(starts[0]))
+            .collect()
+    }
+}
+
+impl Transport for Stamped {
+    fn execute(&self, _: PreparedRequest) -> BoxFuture<'_, Result<RawResponse>> {
+        self.0.lock().unwrap().push(Instant::now());
+
+        Box::pin(async {
+            Ok(RawResponse {
+                status: StatusCode::OK,
+                headers: Default::default(),
+                body: br#"{"code":200,"message":"success","timestamp":0}"#.to_vec(),
+            })
+        })
+    }
+}
+
+fn client(stamped: &Stamped) -> Client {
+    Client::builder()
+        .base_url("https://bark.example.com")
+        .device_key("key1")
+        .transport(Arc::new(stamped.clone()))
+        .build()
+        .unwrap()
+}
+
+fn messages(n: usize) -> Vec<Message> {
+    (0..n)
+        .map(|n| Message::text(format!("message {n}")))
+        .collect()
+}
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[tokio::test(start_paused = true)]
+async fn starts_are_spaced_across_concurrent_workers() {
+    let stamped = Stamped::default();
+    let options = SendAllOptions {
+        concurrency: Some(4),
+        min_interval: ms(100),
+        jitter: Duration::ZERO,
+        ..SendAllOptions::default()
+    };
+
+    let results = client(&stamped).send_all_with(messages(5), options).await;
+
+    assert!(results.all_ok());
+    assert_eq!(
+        stamped.offsets(),
+        [ms(0), ms(100), ms(200), ms(300), ms(400)]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn jitter_keeps_gaps_within_bounds() {
+    let stamped = Stamped::default();
+    let options = SendAllOptions {
+        concurrency: Some(8),
+        min_interval: ms(100),
+        jitter: ms(40),
+        ..SendAllOptions::default()
+    };
+
+    client(&stamped).send_all_with(messages(20), options).await;
+
+    let offsets = stamped.offsets();
+    assert_eq!(offsets.len(), 20);
+
+    for pair in offsets.windows(2) {
+        let gap = pair[1] - pair[0];
+        assert!(gap >= ms(60) && gap <= ms(140), "{gap:?}");
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn the_stricter_of_pacing_and_rate_limit_wins() {
+    let stamped = Stamped::default();
+    let client = Client::builder()
+        .base_url("https://bark.example.com")
+        .device_key("key1")
+        .transport(Arc::new(stamped.clone()))
+        .rate_limit(RateLimit::new(1, ms(200)))
+        .build()
+        .unwrap();
+    let options = SendAllOptions {
+        concurrency: Some(4),
+        min_interval: ms(50),
+        jitter: Duration::ZERO,
+        ..SendAllOptions::default()
+    };
+
+    client.send_all_with(messages(4), options).await;
+
+    assert_eq!(stamped.offsets(), [ms(0), ms(200), ms(400), ms(600)]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn without_an_interval_the_batch_is_not_paced() {
+    let stamped = Stamped::default();
+
+    let results = client(&stamped).send_all(messages(3)).await;
+
+    assert_eq!(results.len(), 3);
+    assert_eq!(stamped.offsets(), [ms(0); 3]);
+}
+
+const REJECTED: &str = r#"{"code":400,"message":"failed to get device token","timestamp":0}"#;
+
+fn capturing() -> (Client, Captured) {
+    Client::builder()
+        .base_url("https://bark.example.com")
+        .device_key("key1")
+        .build_capturing()
+        .unwrap()
+}
+
+/// Message 1 fails transiently and message 3 fatally.
+fn scripted(captured: &Captured) {
+    captured.fail_send(2);
+    captured.respond_with(4, StatusCode::BAD_REQUEST, REJECTED);
+}
+
+fn one_at_a_time(stop_on_fatal: bool) -> SendAllOptions {
+    SendAllOptions {
+        concurrency: Some(1),
+        stop_on_fatal,
+        ..SendAllOptions::default()
+    }
+}
+
+fn bodies(messages: &[Message]) -> Vec<String> {
+    messages
+        .iter()
+        .map(|message| serde_json::to_value(message).unwrap()["body"].to_string())
+        .collect()
+}
+
+/// What resuming after a restart sees: each message saved as a record and migrated.
+fn stored_and_read_back(messages: Vec<Message>) -> Vec<Message> {
+    messages
+        .into_iter()
+        .map(|message| {
+            let json = StoredRecord::new(message).to_json().unwrap();
+            record::migrate(&json).unwrap().message
+        })
+        .collect()
+}
+
+fn body(n: usize) -> String {
+    format!(r#""message {n}""#)
+}
+
+#[tokio::test]
+async fn a_fatal_failure_stops_the_batch_and_the_remainder_resumes_it() {
+    let (client, captured) = capturing();
+    scripted(&captured);
+
+    let report = client.send_all_with(messages(8), one_at_a_time(true)).await;
+
+    let attempted: Vec<_> = report
+        .items
+        .iter()
+        .map(|item| item.result.is_some())
+        .collect();
+    assert_eq!(
+        attempted,
+        [true, true, true, true, false, false, false, false]
+    );
+    assert!(!report.all_ok());
+    assert_eq!(captured.requests().len(), 4);
+
+    // Persisted, then read back after a restart.
+    let resumed = stored_and_read_back(report.remaining());
+    assert_eq!(bodies(&resumed), [1, 4, 5, 6, 7].map(body));
+
+    let (client, captured) = capturing();
+    let results = client.send_all(resumed).await;
+    assert!(results.iter().all(Result::is_ok));
+
+    let succeeded: Vec<_> = report
+        .items
+        .into_iter()
+        .filter(|item| matches!(item.result, Some(Ok(_))))
+        .map(|item| item.message)
+        .collect();
+    let mut delivered = bodies(&succeeded);
+    delivered.extend(bodies(&captured.messages()));
+    delivered.sort();
+    assert_eq!(delivered, [0, 1, 2, 4, 5, 6, 7].map(body));
+}
+
+#[tokio::test]
+async fn resumed_messages_keep_their_send_options() {
+    let (client, captured) = capturing();
+    let client = client.with_group("ops");
+    let own = Message::builder()
+        .body("own keys only")
+        .device_key("key2")
+        .timeout(ms(1500))
+        .no_client_defaults()
+        .build()
+        .unwrap();
+    captured.fail_send(1);
+
+    let report = client
+        .send_all_with(vec![own.clone()], one_at_a_time(true))
+        .await;
+    let resumed = stored_and_read_back(report.remaining());
+    client.send_all(resumed.clone()).await;
+
+    assert_eq!(resumed, [own]);
+    let sent = serde_json::to_value(&captured.messages()[1]).unwrap();
+    assert_eq!(sent["device_keys"], serde_json::json!(["key2"]));
+    assert_eq!(sent.get("group"), None);
+    assert_eq!(captured.requests()[1].timeout, Some(ms(1500)));
+}
+
+#[tokio::test]
+async fn without_stop_on_fatal_only_fatal_failures_are_left_out() {
+    let (client, captured) = capturing();
+    scripted(&captured);
+
+    let report = client
+        .send_all_with(messages(6), one_at_a_time(false))
+        .await;
+
+    assert!(report.items.iter().all(|item| item.result.is_some()));
+    assert_eq!(captured.requests().len(), 6);
+    assert_eq!(bodies(&report.remaining()), [body(1)]);
+}
+
+#[tokio::test]
+async fn a_skipped_message_does_not_stop_the_batch() {
+    let (client, captured) = Client::builder()
+        .base_url("https://bark.example.com")
+        .device_key("key1")
+        .before_send(|message: &mut Message| {
+            if message.to_query_string().contains("message+1") {
+                HookControl::Skip
+            } else {
+                HookControl::Continue
+            }
+        })
+        .build_capturing()
+        .unwrap();
+
+    let report = client.send_all_with(messages(3), one_at_a_time(true)).await;
+
+    assert!(report.items.iter().all(|item| item.result.is_some()));
+    assert_eq!(captured.requests().len(), 2);
+    assert!(report.remaining().is_empty());
+}
+
+/// Answers each push with its own body after a delay that shrinks with the
+/// message number, so later messages finish first; tracks requests in flight.
+#[derive(Clone, Default)]
+struct Echo {
+    in_flight: Arc<AtomicUsize>,
+    peak: Arc<AtomicUsize>,
+}
+
+impl Transport for Echo {
+    fn execute(&self, request: PreparedRequest) -> BoxFuture<'_, Result<RawResponse>> {
+        let body = serde_json::from_slice::<serde_json::Value>(&request.body).unwrap()["body"]
+            .as_str()
+            .unwrap()
+            .to_owned();
+        let n: u64 = body.trim_start_matches("message ").parse().unwrap();
+
+        Box::pin(async move {
+            let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(now, Ordering::SeqCst);
+            tokio::time::sleep(ms(100 - n)).await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+
+            Ok(RawResponse {
+                status: StatusCode::OK,
+                headers: Default::default(),
+                body: format!(r#"{{"code":200,"message":"{body}","timestamp":0}}"#).into_bytes(),
+            })
+        })
+    }
+}
+
+fn echoing(echo: &Echo) -> bark::client::ClientBuilder {
+    Client::builder()
+        .base_url("https://bark.example.com")
+        .device_key("key1")
+        .transport(Arc::new(echo.clone()))
+}
+
+#[tokio::test(start_paused = true)]
+async fn results_keep_the_input_order() {
+    let echo = Echo::default();
+    let client = echoing(&echo).build().unwrap();
+
+    let results = client.send_all(messages(20)).await;
+
+    let answered: Vec<_> = results
+        .into_iter()
+        .map(|result| result.unwrap().message)
+        .collect();
+    let expected: Vec<_> = (0..20).map(|n| format!("message {n}")).collect();
+    assert_eq!(answered, expected);
+}
+
+#[tokio::test(start_paused = true)]
+async fn concurrency_defaults_to_eight() {
+    let echo = Echo::default();
+    let client = echoing(&echo).build().unwrap();
+
+    client.send_all(messages(20)).await;
+
+    assert_eq!(
+        echo.peak.load(Ordering::SeqCst),
+        Client::DEFAULT_BATCH_CONCURRENCY
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn the_concurrency_cap_is_configurable() {
+    let echo = Echo::default();
+    let client = echoing(&echo).batch_concurrency(3).build().unwrap();
+
+    client.send_all(messages(10)).await;
+    assert_eq!(echo.peak.swap(0, Ordering::SeqCst), 3);
+
+    client
+        .send_all_with(
+            messages(10),
+            SendAllOptions {
+                concurrency: Some(2),
+                ..SendAllOptions::default()
+            },
+        )
+        .await;
+    assert_eq!(echo.peak.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn one_failure_does_not_abort_the_rest() {
+    let (client, captured) = capturing();
+    captured.fail_send(2);
+
+    let results = client.send_all(messages(3)).await;
+
+    assert_eq!(results.len(), 3);
+    assert_eq!(results.iter().filter(|result| result.is_err()).count(), 1);
+    assert!(captured.messages().iter().all(
+        |message| serde_json::to_value(message).unwrap()["device_keys"]
+            == serde_json::json!(["key1"])
+    ));
+}
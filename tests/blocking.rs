@@ -0,0 +1,54 @@
+#![cfg(feature = "blocking")]
+
+use bark::{blocking, client::Client, error::Error, message::Message, test_util::Captured};
+
+fn client(captured: &Captured) -> blocking::Client {
+    Client::builder()
+        .base_url("https://bark.example.com")
+        .device_key("key1")
+        .transport(captured.transport())
+        .build_blocking()
+        .unwrap()
+}
+
+#[test]
+fn sends_without_an_async_runtime() {
+    let captured = Captured::default();
+    let client = client(&captured);
+
+    client.notify("t", "b").unwrap();
+    client.message().title("t").body("b").send().unwrap();
+
+    assert_eq!(captured.requests().len(), 2);
+}
+
+#[test]
+fn inside_a_runtime_sends_fail_instead_of_panicking() {
+    let captured = Captured::default();
+    let client = client(&captured);
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+
+    runtime.block_on(async {
+        assert!(matches!(client.notify("t", "b"), Err(Error::InsideRuntime)));
+        assert!(matches!(
+            client.message().body("b").send(),
+            Err(Error::InsideRuntime)
+        ));
+
+        let results = client.send_all([Message::text("a"), Message::text("b")]);
+        assert_eq!(results.len(), 2);
+        assert!(
+            results
+                .iter()
+                .all(|result| matches!(result, Err(Error::InsideRuntime)))
+        );
+
+        let report = client.send_all_with([Message::text("a")], Default::default());
+        assert_eq!(report.remaining(), [Message::text("a")]);
+    });
+
+    assert!(captured.requests().is_empty());
+    client.notify("t", "b").unwrap();
+}
@@ -0,0 +1,63 @@
+use std::str::FromStr;
+
+use bark::{error::Error, message::BodyKind};
+
+#[test]
+fn aliases_parse_ignoring_case() {
+    for (name, kind) in [
+        ("markdown", BodyKind::Markdown),
+        ("Markdown", BodyKind::Markdown),
+        ("md", BodyKind::Markdown),
+        ("MD", BodyKind::Markdown),
+        ("plaintext", BodyKind::Plaintext),
+        ("PlainText", BodyKind::Plaintext),
+        ("text", BodyKind::Plaintext),
+        ("body", BodyKind::Plaintext),
+        ("BODY", BodyKind::Plaintext),
+    ] {
+        assert_eq!(BodyKind::from_str(name).unwrap(), kind, "{name}");
+    }
+}
+
+#[test]
+fn unknown_kinds_are_typed_errors() {
+    let error = BodyKind::from_str("html").unwrap_err();
+
+    assert!(
+        matches!(&error, Error::UnknownBodyKind { given } if given == "html"),
+        "{error:?}"
+    );
+    assert_eq!(
+        error.to_string(),
+        r#"Unknown body kind "html", expected markdown, md, plaintext, text or body"#
+    );
+}
+
+#[test]
+fn display_round_trips() {
+    for kind in [BodyKind::Markdown, BodyKind::Plaintext] {
+        assert_eq!(kind.to_string().parse::<BodyKind>().unwrap(), kind);
+    }
+    assert_eq!(BodyKind::Markdown.to_string(), "markdown");
+    assert_eq!(BodyKind::Plaintext.to_string(), "plaintext");
+}
+
+#[test]
+fn config_values_deserialize_through_the_aliases() {
+    let kinds: Vec<BodyKind> = serde_json::from_str(r#"["md", "Text", "markdown"]"#).unwrap();
+    let error = serde_json::from_str::<BodyKind>(r#""html""#).unwrap_err();
+
+    assert_eq!(
+        kinds,
+        [BodyKind::Markdown, BodyKind::Plaintext, BodyKind::Markdown]
+    );
+    assert!(error.to_string().contains("Unknown body kind"), "{error}");
+}
+
+#[test]
+fn checks_match_the_kind() {
+    assert!(BodyKind::Markdown.is_markdown());
+    assert!(!BodyKind::Markdown.is_plaintext());
+    assert!(BodyKind::Plaintext.is_plaintext());
+    assert_eq!(BodyKind::default(), BodyKind::Plaintext);
+}
@@ -0,0 +1,154 @@
+#![cfg(feature = "reqwest")]
+
+use std::time::Duration;
+
+use bark::{client::Client, error::Error, message::Message};
+use http::StatusCode;
+use wiremock::{
+    Mock, MockServer, ResponseTemplate,
+    matchers::{header, method, path},
+};
+
+const SUCCESS: &str = r#"{"code":200,"message":"success","timestamp":1}"#;
+
+fn success() -> ResponseTemplate {
+    ResponseTemplate::new(200).set_body_string(SUCCESS)
+}
+
+/// `/push` redirects to `/moved`, which accepts the push.
+async fn redirecting() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/push"))
+        .respond_with(ResponseTemplate::new(307).insert_header("location", "/moved"))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/moved"))
+        .respond_with(success())
+        .mount(&server)
+        .await;
+    server
+}
+
+#[tokio::test]
+async fn the_user_agent_is_sent() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/push"))
+        .and(header("user-agent", "ops-bot/1.0"))
+        .respond_with(success())
+        .expect(1)
+        .mount(&server)
+        .await;
+    let client = Client::builder()
+        .base_url(server.uri())
+        .device_key("key1")
+        .user_agent("ops-bot/1.0")
+        .build()
+        .unwrap();
+
+    client.send(&Message::text("b")).await.unwrap();
+}
+
+#[tokio::test]
+async fn the_client_timeout_caps_a_hung_server() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/push"))
+        .respond_with(success().set_delay(Duration::from_secs(5)))
+        .mount(&server)
+        .await;
+    let client = Client::builder()
+        .base_url(server.uri())
+        .device_key("key1")
+        .timeout(Duration::from_millis(50))
+        .build()
+        .unwrap();
+
+    let error = client.send(&Message::text("b")).await.unwrap_err();
+
+    assert!(matches!(error, Error::Timeout { .. }), "{error:?}");
+}
+
+#[tokio::test]
+async fn redirects_are_followed_by_default() {
+    let server = redirecting().await;
+    let client = Client::new(server.uri()).with_device_key("key1");
+
+    assert!(client.send(&Message::text("b")).await.unwrap().is_success());
+}
+
+#[tokio::test]
+async fn no_redirects_surfaces_the_redirect() {
+    let server = redirecting().await;
+    let client = Client::builder()
+        .base_url(server.uri())
+        .device_key("key1")
+        .no_redirects()
+        .build()
+        .unwrap();
+
+    let error = client.send(&Message::text("b")).await.unwrap_err();
+
+    assert!(
+        matches!(
+            error,
+            Error::HttpStatus {
+                status: StatusCode::TEMPORARY_REDIRECT,
+                ..
+            }
+        ),
+        "{error:?}"
+    );
+}
+
+#[test]
+fn invalid_options_are_typed_errors() {
+    let missing = Client::builder().build().unwrap_err();
+    let blank = Client::builder().base_url("  ").build().unwrap_err();
+    let zero = Client::builder()
+        .base_url("https://bark.example.com")
+        .timeout(Duration::ZERO)
+        .build()
+        .unwrap_err();
+    let zero_connect = Client::builder()
+        .base_url("https://bark.example.com")
+        .connect_timeout(Duration::ZERO)
+        .build()
+        .unwrap_err();
+
+    assert!(matches!(missing, Error::MissingBaseUrl), "{missing:?}");
+    assert!(matches!(blank, Error::MissingBaseUrl), "{blank:?}");
+    assert!(matches!(zero, Error::ZeroTimeout("timeout")), "{zero:?}");
+    assert!(
+        matches!(zero_connect, Error::ZeroTimeout("connect_timeout")),
+        "{zero_connect:?}"
+    );
+}
+
+#[tokio::test]
+async fn an_injected_reqwest_client_is_used() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/push"))
+        .and(header("x-app", "injected"))
+        .respond_with(success())
+        .expect(1)
+        .mount(&server)
+        .await;
+    let mut headers = reqwest::header::HeaderMap::new();
+    headers.insert("x-app", "injected".parse().unwrap());
+    let http = reqwest::Client::builder()
+        .default_headers(headers)
+        .build()
+        .unwrap();
+    let client = Client::builder()
+        .base_url(server.uri())
+        .device_key("key1")
+        .http_client(http)
+        .build()
+        .unwrap();
+
+    client.send(&Message::text("b")).await.unwrap();
+}
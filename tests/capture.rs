@@ -0,0 +1,345 @@
+use bark::{
+    client::{Client, KeyMergeStrategy},
+    error::Error,
+    hook::HookControl,
+    message::{Level, Message},
+};
+use http::{Method, StatusCode};
+
+fn capturing() -> (Client, bark::test_util::Captured) {
+    let (client, captured) = Client::capturing();
+    (client.with_device_key("key1"), captured)
+}
+
+#[tokio::test]
+async fn records_the_prepared_request_and_answers_success() {
+    let (client, captured) = capturing();
+
+    let response = client.notify("title", "body").await.unwrap();
+
+    assert!(response.is_success());
+    let requests = captured.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].method, Method::POST);
+    assert_eq!(requests[0].url.path(), "/push");
+    assert_eq!(requests[0].headers["content-type"], "application/json");
+    assert_eq!(
+        captured.messages(),
+        [Message::builder()
+            .title("title")
+            .body("body")
+            .device_key("key1")
+            .build()
+            .unwrap()]
+    );
+}
+
+#[tokio::test]
+async fn client_defaults_are_applied_before_capture() {
+    let (client, captured) = capturing();
+    let client = client
+        .with_group("ops")
+        .with_level(Level::TimeSensitive)
+        .with_sound("bell");
+
+    client.notify("t", "b").await.unwrap();
+    client
+        .message()
+        .body("own group")
+        .group("mine")
+        .send()
+        .await
+        .unwrap();
+
+    let messages = captured.messages();
+    let json: Vec<serde_json::Value> = messages
+        .iter()
+        .map(|message| serde_json::to_value(message).unwrap())
+        .collect();
+    assert_eq!(json[0]["group"], "ops");
+    assert_eq!(json[0]["level"], "timeSensitive");
+    assert_eq!(json[0]["sound"], "bell");
+    assert_eq!(json[1]["group"], "mine");
+}
+
+#[tokio::test]
+async fn default_keys_merge_by_strategy() {
+    let (client, captured) = Client::builder()
+        .base_url("https://bark.example.com")
+        .device_key("default")
+        .key_merge_strategy(KeyMergeStrategy::Union)
+        .build_capturing()
+        .unwrap();
+
+    client
+        .send(
+            &Message::builder()
+                .body("b")
+                .device_key("own")
+                .build()
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let json = serde_json::to_value(&captured.messages()[0]).unwrap();
+    let mut keys: Vec<&str> = json["device_keys"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|key| key.as_str().unwrap())
+        .collect();
+    keys.sort();
+    assert_eq!(keys, ["default", "own"]);
+}
+
+fn union() -> (Client, bark::test_util::Captured) {
+    Client::builder()
+        .base_url("https://bark.example.com")
+        .device_keys(["d1", "d2"])
+        .key_merge_strategy(KeyMergeStrategy::Union)
+        .build_capturing()
+        .unwrap()
+}
+
+/// The device keys of each captured push, sorted, in send order.
+fn sent_keys(captured: &bark::test_util::Captured) -> Vec<Vec<String>> {
+    captured
+        .messages()
+        .iter()
+        .map(|message| {
+            let json = serde_json::to_value(message).unwrap();
+            let mut keys: Vec<String> = json["device_keys"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|key| key.as_str().unwrap().to_owned())
+                .collect();
+            keys.sort();
+            keys
+        })
+        .collect()
+}
+
+fn to_own_keys() -> Message {
+    Message::builder()
+        .body("b")
+        .device_keys(["o1", "o2"])
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn union_fanout_sends_each_key_once() {
+    let (client, captured) = union();
+
+    let report = client.send_fanout(&to_own_keys()).await.unwrap();
+
+    assert!(report.all_ok());
+    let mut sent = sent_keys(&captured);
+    sent.sort();
+    assert_eq!(sent, [["d1"], ["d2"], ["o1"], ["o2"]]);
+}
+
+#[tokio::test]
+async fn union_chunks_stay_disjoint() {
+    let (client, captured) = union();
+
+    let report = client.send_chunked(&to_own_keys(), 3).await.unwrap();
+
+    assert!(report.all_ok());
+    assert_eq!(sent_keys(&captured), [vec!["d1", "d2", "o1"], vec!["o2"]]);
+}
+
+#[tokio::test]
+async fn send_to_replaces_union_defaults() {
+    let (client, captured) = union();
+
+    client.send_to(["only"], &to_own_keys()).await.unwrap();
+
+    assert_eq!(sent_keys(&captured), [["only"]]);
+}
+
+#[tokio::test]
+async fn send_to_beats_message_and_client_keys() {
+    let (client, captured) = capturing();
+    let message = to_own_keys();
+
+    client.send_to(["t1", "t2"], &message).await.unwrap();
+
+    assert_eq!(sent_keys(&captured), [["t1", "t2"]]);
+    assert_eq!(message, to_own_keys());
+}
+
+#[tokio::test]
+async fn send_to_without_keys_sends_nothing() {
+    let (client, captured) = capturing();
+
+    let error = client
+        .send_to(Vec::<String>::new(), &to_own_keys())
+        .await
+        .unwrap_err();
+
+    assert!(matches!(error, Error::MissingDeviceKey), "{error:?}");
+    assert!(captured.requests().is_empty());
+}
+
+#[tokio::test]
+async fn hooks_run_and_skipped_messages_are_not_captured() {
+    let (client, captured) = Client::builder()
+        .base_url("https://bark.example.com")
+        .device_key("key1")
+        .before_send(|message: &mut Message| {
+            if message.to_query_string().contains("title=skip") {
+                return HookControl::Skip;
+            }
+            *message = Message::builder()
+                .title("rewritten")
+                .device_key("key1")
+                .build()
+                .unwrap();
+            HookControl::Continue
+        })
+        .build_capturing()
+        .unwrap();
+
+    assert!(matches!(
+        client.notify("skip", "b").await,
+        Err(Error::Skipped)
+    ));
+    client.notify("keep", "b").await.unwrap();
+
+    let messages = captured.messages();
+    assert_eq!(messages.len(), 1);
+    assert_eq!(
+        serde_json::to_value(&messages[0]).unwrap()["title"],
+        "rewritten"
+    );
+}
+
+#[tokio::test]
+async fn validation_still_runs() {
+    let (client, captured) = Client::capturing();
+
+    assert!(matches!(
+        client.notify("no", "keys").await,
+        Err(Error::MissingDeviceKey)
+    ));
+    assert!(captured.requests().is_empty());
+}
+
+#[tokio::test]
+async fn scripted_failures_hit_only_their_send() {
+    let (client, captured) = capturing();
+    captured.fail_send(2);
+    captured.respond_with(
+        3,
+        StatusCode::BAD_REQUEST,
+        r#"{"code":400,"message":"failed to get device token","timestamp":0}"#,
+    );
+    captured.transport_error(4, "connection reset");
+
+    client.notify("1", "b").await.unwrap();
+    assert!(matches!(
+        client.notify("2", "b").await,
+        Err(Error::HttpStatus {
+            status: StatusCode::INTERNAL_SERVER_ERROR,
+            ..
+        })
+    ));
+    assert!(matches!(
+        client.notify("3", "b").await,
+        Err(Error::Server { code: 400, .. })
+    ));
+    assert!(matches!(
+        client.notify("4", "b").await,
+        Err(Error::Transport(_))
+    ));
+    client.notify("5", "b").await.unwrap();
+
+    assert_eq!(captured.requests().len(), 5);
+
+    captured.clear();
+    assert!(captured.requests().is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn records_sends_from_many_threads() {
+    let (client, captured) = capturing();
+
+    let tasks: Vec<_> = (0..32)
+        .map(|n| {
+            let client = client.clone();
+            tokio::spawn(async move { client.notify(format!("{n}"), "b").await })
+        })
+        .collect();
+
+    for task in tasks {
+        task.await.unwrap().unwrap();
+    }
+
+    let mut titles: Vec<u32> = captured
+        .messages()
+        .iter()
+        .map(|message| {
+            serde_json::to_value(message).unwrap()["title"]
+                .as_str()
+                .unwrap()
+                .parse()
+                .unwrap()
+        })
+        .collect();
+    titles.sort();
+    assert_eq!(titles, (0..32).collect::<Vec<_>>());
+}
+
+fn merging(strategy: KeyMergeStrategy) -> (Client, bark::test_util::Captured) {
+    Client::builder()
+        .base_url("https://bark.example.com")
+        .device_keys(["admin", "shared"])
+        .key_merge_strategy(strategy)
+        .build_capturing()
+        .unwrap()
+}
+
+fn to_keys(keys: &[&str]) -> Message {
+    Message::builder()
+        .body("b")
+        .device_keys(keys.iter().copied())
+        .build()
+        .unwrap()
+}
+
+/// No expected keys means the send fails for want of any.
+#[tokio::test]
+async fn each_strategy_with_and_without_message_keys() {
+    let cases: [(KeyMergeStrategy, &[&str], &[&str]); 6] = [
+        (KeyMergeStrategy::FallbackOnly, &[], &["admin", "shared"]),
+        (KeyMergeStrategy::FallbackOnly, &["own"], &["own"]),
+        (KeyMergeStrategy::Union, &[], &["admin", "shared"]),
+        (
+            KeyMergeStrategy::Union,
+            &["own", "shared"],
+            &["admin", "own", "shared"],
+        ),
+        (KeyMergeStrategy::Ignore, &[], &[]),
+        (KeyMergeStrategy::Ignore, &["own"], &["own"]),
+    ];
+
+    for (strategy, own, expected) in cases {
+        let (client, captured) = merging(strategy);
+
+        let result = client.send(&to_keys(own)).await;
+
+        if expected.is_empty() {
+            assert!(
+                matches!(result, Err(Error::MissingDeviceKey)),
+                "{strategy:?}: {result:?}"
+            );
+            assert!(captured.requests().is_empty());
+        } else {
+            result.unwrap();
+            assert_eq!(sent_keys(&captured), [expected], "{strategy:?} {own:?}");
+        }
+    }
+}
@@ -0,0 +1,224 @@
+#![cfg(feature = "cli")]
+
+use std::fs;
+
+use assert_cmd::Command;
+use bark::test_util::matchers::{push_matching, push_to_device, push_with_title};
+use serde_json::Value;
+use wiremock::{
+    Mock, MockServer, ResponseTemplate,
+    matchers::{method, path},
+};
+
+const SUCCESS: &str = r#"{"code":200,"message":"success","timestamp":1700000000}"#;
+
+/// The binary with no `BARK_*` variables and no config file, whatever the host has.
+fn bark() -> Command {
+    let mut command = Command::cargo_bin("bark").unwrap();
+
+    for name in [
+        "BARK_CONFIG",
+        "BARK_SERVER",
+        "BARK_DEVICE_KEY",
+        "BARK_LEVEL",
+        "BARK_GROUP",
+    ] {
+        command.env_remove(name);
+    }
+
+    command.env("XDG_CONFIG_HOME", "/nonexistent").arg("send");
+    command
+}
+
+async fn accepting(push: impl wiremock::Match + 'static) -> MockServer {
+    let server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/push"))
+        .and(push)
+        .respond_with(ResponseTemplate::new(200).set_body_string(SUCCESS))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    server
+}
+
+#[tokio::test]
+async fn sends_and_prints_the_response_as_json() {
+    let server = accepting(push_with_title("deploy")).await;
+
+    let output = bark()
+        .args(["--server", &server.uri(), "--device-key", "key1"])
+        .args(["--title", "deploy", "--body", "done", "--json"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let response: Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(response["code"], 200);
+    assert_eq!(response["message"], "success");
+}
+
+#[tokio::test]
+async fn prints_the_server_message_without_json() {
+    let server = accepting(push_to_device("key1")).await;
+
+    bark()
+        .args(["--server", &server.uri(), "--device-key", "key1"])
+        .args(["--body", "done"])
+        .assert()
+        .success()
+        .stdout("success\n");
+}
+
+#[tokio::test]
+async fn reads_the_body_from_stdin() {
+    let server = accepting(push_matching(|push| {
+        push.body.as_deref() == Some("line one\nline two\n")
+    }))
+    .await;
+
+    bark()
+        .args(["--server", &server.uri(), "--device-key", "key1"])
+        .args(["--body", "-"])
+        .write_stdin("line one\nline two\n")
+        .assert()
+        .success();
+}
+
+#[tokio::test]
+async fn sends_markdown_level_and_group() {
+    let server = accepting(push_matching(|push| {
+        push.markdown.as_deref() == Some("**bold**")
+            && push.body.is_none()
+            && push.level == Some(bark::message::Level::TimeSensitive)
+            && push.group.as_deref() == Some("ops")
+    }))
+    .await;
+
+    bark()
+        .args(["--server", &server.uri(), "--device-key", "key1"])
+        .args(["--body", "**bold**", "--markdown"])
+        .args(["--level", "time-sensitive", "--group", "ops"])
+        .assert()
+        .success();
+}
+
+#[tokio::test]
+async fn reads_server_and_keys_from_the_environment() {
+    let server = accepting(push_matching(|push| {
+        let mut keys = push.device_keys.clone();
+        keys.sort();
+        keys == ["key1", "key2"]
+    }))
+    .await;
+
+    bark()
+        .env("BARK_SERVER", server.uri())
+        .env("BARK_DEVICE_KEY", "key1,key2")
+        .args(["--body", "done"])
+        .assert()
+        .success();
+}
+
+#[tokio::test]
+async fn reads_defaults_from_the_config_file() {
+    let server = accepting(push_matching(|push| {
+        push.device_keys == ["from-config"] && push.group.as_deref() == Some("nightly")
+    }))
+    .await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let config = dir.path().join("config.toml");
+    fs::write(
+        &config,
+        format!(
+            "server = \"{}\"\ndevice_keys = [\"from-config\"]\ngroup = \"nightly\"\n",
+            server.uri()
+        ),
+    )
+    .unwrap();
+
+    bark()
+        .arg("--config")
+        .arg(&config)
+        .args(["--body", "done"])
+        .assert()
+        .success();
+}
+
+#[tokio::test]
+async fn flags_override_the_config_file() {
+    let server = accepting(push_to_device("from-flag")).await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let config = dir.path().join("config.toml");
+    fs::write(
+        &config,
+        "server = \"http://127.0.0.1:9\"\ndevice_keys = [\"from-config\"]\n",
+    )
+    .unwrap();
+
+    bark()
+        .arg("--config")
+        .arg(&config)
+        .args(["--server", &server.uri(), "--device-key", "from-flag"])
+        .args(["--body", "done"])
+        .assert()
+        .success();
+}
+
+#[tokio::test]
+async fn exits_non_zero_with_the_crate_error() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/push"))
+        .respond_with(ResponseTemplate::new(400).set_body_string(
+            r#"{"code":400,"message":"failed to get device token","timestamp":1}"#,
+        ))
+        .mount(&server)
+        .await;
+
+    let output = bark()
+        .args(["--server", &server.uri(), "--device-key", "revoked"])
+        .args(["--body", "done"])
+        .assert()
+        .failure()
+        .get_output()
+        .stderr
+        .clone();
+
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "error: Server rejected the push with code 400: failed to get device token\n"
+    );
+}
+
+#[test]
+fn fails_without_a_server() {
+    let output = bark()
+        .args(["--device-key", "key1", "--body", "done"])
+        .assert()
+        .failure()
+        .get_output()
+        .stderr
+        .clone();
+
+    assert!(
+        String::from_utf8(output)
+            .unwrap()
+            .contains("missing server")
+    );
+}
+
+#[test]
+fn fails_on_an_unreadable_config_file() {
+    bark()
+        .args(["--config", "/nonexistent/bark.toml", "--body", "done"])
+        .assert()
+        .failure();
+}
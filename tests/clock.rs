@@ -0,0 +1,188 @@
+use std::{future, sync::Arc, time::Duration};
+
+use bark::{
+    BoxFuture,
+    client::Client,
+    clock::Clock,
+    error::{Error, Result},
+    message::Message,
+    record::StoredRecord,
+    retry::RetryPolicy,
+    test_util::ManualClock,
+    time::SystemTime,
+    transport::{PreparedRequest, RawResponse, Transport},
+};
+use wiremock::{
+    Mock, MockServer, ResponseTemplate,
+    matchers::{method, path},
+};
+
+const SUCCESS: &str = r#"{"code":200,"message":"success","timestamp":1}"#;
+
+/// 2023-11-14 22:13:20 UTC.
+fn start() -> SystemTime {
+    SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000)
+}
+
+/// Never answers, so only a timeout can end a request.
+struct Hanging;
+
+impl Transport for Hanging {
+    fn execute(&self, _: PreparedRequest) -> BoxFuture<'_, Result<RawResponse>> {
+        Box::pin(future::pending())
+    }
+}
+
+/// A 429 whose `Retry-After` is an HTTP date 30 seconds after [`start`].
+async fn throttling_once(server: &MockServer) {
+    Mock::given(method("POST"))
+        .and(path("/push"))
+        .respond_with(
+            ResponseTemplate::new(429)
+                .insert_header("retry-after", "Tue, 14 Nov 2023 22:13:50 GMT"),
+        )
+        .up_to_n_times(1)
+        .with_priority(1)
+        .mount(server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/push"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(SUCCESS))
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+async fn retry_after_dates_are_read_against_the_client_clock() {
+    let server = MockServer::start().await;
+    throttling_once(&server).await;
+    let clock = Arc::new(ManualClock::new(start()));
+    let client = Client::builder()
+        .base_url(server.uri())
+        .device_key("key1")
+        .clock(clock.clone())
+        .retry(RetryPolicy::default())
+        .build()
+        .unwrap();
+    let started = clock.instant();
+
+    let outcome = client.send_detailed(&Message::text("b")).await.unwrap();
+
+    assert_eq!(outcome.attempts, 2);
+    assert_eq!(clock.instant() - started, Duration::from_secs(30));
+    assert_eq!(outcome.elapsed, Duration::from_secs(30));
+}
+
+#[tokio::test]
+async fn a_throttled_send_reports_the_wait_from_the_client_clock() {
+    let server = MockServer::start().await;
+    throttling_once(&server).await;
+    let client = Client::builder()
+        .base_url(server.uri())
+        .device_key("key1")
+        .clock(Arc::new(ManualClock::new(start())))
+        .build()
+        .unwrap();
+
+    let error = client.send(&Message::text("b")).await.unwrap_err();
+
+    assert!(
+        matches!(error, Error::RateLimited { retry_after: Some(wait) } if wait == Duration::from_secs(30)),
+        "{error:?}"
+    );
+}
+
+#[tokio::test]
+async fn timeouts_are_measured_on_the_client_clock() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/push"))
+        .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(5)))
+        .mount(&server)
+        .await;
+    let client = Client::builder()
+        .base_url(server.uri())
+        .device_key("key1")
+        .clock(Arc::new(ManualClock::new(start())))
+        .build()
+        .unwrap();
+
+    let error = client
+        .send_with_timeout(&Message::text("b"), Duration::from_millis(50))
+        .await
+        .unwrap_err();
+
+    // The manual clock never moved, however long the request really took.
+    assert!(
+        matches!(error, Error::Timeout { elapsed, .. } if elapsed.is_zero()),
+        "{error:?}"
+    );
+}
+
+#[test]
+fn stored_records_are_stamped_from_the_clock() {
+    let clock = ManualClock::new(start());
+    clock.advance(Duration::from_secs(5));
+
+    let record = StoredRecord::with_clock(Message::text("b"), &clock);
+
+    assert_eq!(record.created_at, Some(start() + Duration::from_secs(5)));
+    assert!(
+        record
+            .to_json()
+            .unwrap()
+            .contains(r#""created_at":1700000005"#)
+    );
+}
+
+#[cfg(feature = "ureq")]
+#[tokio::test]
+async fn the_sync_client_reads_the_clock_too() {
+    let server = MockServer::start().await;
+    throttling_once(&server).await;
+    let client = bark::sync::Client::builder()
+        .base_url(server.uri())
+        .device_key("key1")
+        .clock(Arc::new(ManualClock::new(start())))
+        .build()
+        .unwrap();
+
+    let error = tokio::task::spawn_blocking(move || client.send(&Message::text("b")))
+        .await
+        .unwrap()
+        .unwrap_err();
+
+    assert!(
+        matches!(error, Error::RateLimited { retry_after: Some(wait) } if wait == Duration::from_secs(30)),
+        "{error:?}"
+    );
+}
+
+#[tokio::test]
+async fn health_check_timeouts_fire_when_the_clock_passes_them() {
+    let clock = Arc::new(ManualClock::new(start()));
+    let client = Client::builder()
+        .base_url("https://bark.example.com")
+        .transport(Arc::new(Hanging))
+        .clock(clock.clone())
+        .health_check_timeout(Duration::from_secs(60))
+        .build()
+        .unwrap();
+    let mut check = std::pin::pin!(client.healthz());
+
+    // Real time passing does not move the manual clock.
+    assert!(
+        tokio::time::timeout(Duration::from_millis(20), &mut check)
+            .await
+            .is_err()
+    );
+
+    clock.advance(Duration::from_secs(61));
+    let error = check.await.unwrap_err();
+
+    assert!(
+        matches!(error, Error::Unhealthy { status: None, .. }),
+        "{error:?}"
+    );
+}
@@ -0,0 +1,101 @@
+use bark::{
+    client::Client,
+    message::{Level, Message, MessageBuilder},
+};
+use serde_json::{Value, json};
+
+fn sent_groups(captured: &bark::test_util::Captured) -> Vec<Value> {
+    captured
+        .messages()
+        .iter()
+        .map(|message| serde_json::to_value(message).unwrap()["group"].clone())
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn clones_share_stats_across_tasks() {
+    let (client, captured) = Client::capturing();
+    let client = client.with_device_key("key1");
+
+    let tasks: Vec<_> = (0..8)
+        .map(|_| {
+            let client = client.clone();
+            tokio::spawn(async move { client.send(&Message::text("b")).await })
+        })
+        .collect();
+    for task in tasks {
+        task.await.unwrap().unwrap();
+    }
+
+    assert_eq!(client.stats().sent, 8);
+    assert_eq!(captured.requests().len(), 8);
+}
+
+#[tokio::test]
+async fn with_calls_on_a_clone_leave_the_original_alone() {
+    let (client, captured) = Client::capturing();
+    let original = client.with_device_key("key1").with_group("ops");
+    let changed = original.clone().with_group("changed");
+
+    original.notify("t", "b").await.unwrap();
+    changed.notify("t", "b").await.unwrap();
+
+    assert_eq!(sent_groups(&captured), ["ops", "changed"]);
+}
+
+fn template() -> MessageBuilder {
+    Message::builder()
+        .group("deploys")
+        .icon("https://example.com/deploy.png")
+        .level(Level::Active)
+}
+
+#[test]
+fn cloned_builders_diverge_independently() {
+    let template = template();
+
+    let checkout = template
+        .clone()
+        .title("checkout")
+        .body("v2")
+        .build()
+        .unwrap();
+    let billing = template
+        .clone()
+        .title("billing")
+        .level(Level::Critical)
+        .build()
+        .unwrap();
+    let untouched = template.title("t").build().unwrap();
+
+    let json = |message: &Message| serde_json::to_value(message).unwrap();
+    assert_eq!(
+        json(&checkout),
+        json!({
+            "title": "checkout",
+            "body": "v2",
+            "group": "deploys",
+            "icon": "https://example.com/deploy.png",
+            "level": "active",
+        })
+    );
+    assert_eq!(json(&billing)["level"], "critical");
+    assert_eq!(json(&billing).get("body"), None);
+    assert_eq!(
+        json(&untouched),
+        json!({
+            "title": "t",
+            "group": "deploys",
+            "icon": "https://example.com/deploy.png",
+            "level": "active",
+        })
+    );
+}
+
+#[test]
+fn cloned_messages_are_equal_until_changed() {
+    let message = template().title("t").build().unwrap();
+    let copy = message.clone();
+
+    assert_eq!(copy, message);
+}
@@ -0,0 +1,89 @@
+use bark::test_util::{Diff, assert_push_eq, push_semantic_eq};
+use serde_json::json;
+
+#[test]
+fn ignores_key_order() {
+    assert_push_eq(
+        r#"{"title":"t","body":"b","group":"g"}"#,
+        r#"{"group":"g","body":"b","title":"t"}"#,
+    );
+}
+
+#[test]
+fn treats_a_single_device_key_like_a_one_element_list() {
+    assert_push_eq(r#"{"device_key":"k"}"#, r#"{"device_keys":["k"]}"#);
+    assert_push_eq(
+        r#"{"device_keys":["b","a"]}"#,
+        r#"{"device_keys":["a","b"]}"#,
+    );
+}
+
+#[test]
+fn treats_flag_and_number_encodings_alike() {
+    assert_push_eq(
+        r#"{"isArchive":1,"autoCopy":"1","call":true,"badge":"2"}"#,
+        r#"{"isArchive":"true","autoCopy":1,"call":"1","badge":2}"#,
+    );
+}
+
+#[test]
+fn false_flags_equal_absent_ones() {
+    assert_push_eq(r#"{"body":"b","call":0}"#, r#"{"body":"b"}"#);
+}
+
+#[test]
+fn reports_each_differing_field() {
+    let diffs = push_semantic_eq(
+        r#"{"title":"a","body":"same","group":"g"}"#,
+        r#"{"title":"b","body":"same","sound":"bell"}"#,
+    )
+    .unwrap_err();
+
+    assert_eq!(
+        diffs,
+        [
+            Diff {
+                field: "group".to_owned(),
+                actual: Some(json!("g")),
+                expected: None,
+            },
+            Diff {
+                field: "sound".to_owned(),
+                actual: None,
+                expected: Some(json!("bell")),
+            },
+            Diff {
+                field: "title".to_owned(),
+                actual: Some(json!("a")),
+                expected: Some(json!("b")),
+            },
+        ]
+    );
+}
+
+#[test]
+fn diffs_render_both_sides() {
+    let diff = Diff {
+        field: "title".to_owned(),
+        actual: Some(json!("a")),
+        expected: None,
+    };
+
+    assert_eq!(diff.to_string(), r#"title: actual "a", expected <missing>"#);
+}
+
+#[test]
+fn unparseable_payloads_are_a_payload_diff() {
+    let diffs = push_semantic_eq("not json", r#"{"body":"b"}"#).unwrap_err();
+
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].field, "payload");
+    assert!(diffs[0].actual.is_some());
+    assert_eq!(diffs[0].expected, None);
+}
+
+#[test]
+#[should_panic(expected = "pushes differ:\n  body: actual \"a\", expected \"b\"")]
+fn assert_push_eq_panics_with_the_diffs() {
+    assert_push_eq(r#"{"body":"a"}"#, r#"{"body":"b"}"#);
+}
@@ -0,0 +1,133 @@
+#![cfg(feature = "reqwest")]
+
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use bark::client::Client;
+use reqwest::dns::{Name, Resolve, Resolving};
+use wiremock::{
+    Mock, MockServer, ResponseTemplate,
+    matchers::{method, path},
+};
+
+const SUCCESS: &str = r#"{"code":200,"message":"success","timestamp":1}"#;
+
+async fn server() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/push"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(SUCCESS))
+        .mount(&server)
+        .await;
+    server
+}
+
+/// The server's URL under a hostname only the client's resolver knows.
+fn fake_url(server: &MockServer) -> String {
+    format!("http://bark.internal:{}", server.address().port())
+}
+
+#[tokio::test]
+async fn a_static_address_reaches_the_server_under_the_fake_name() {
+    let server = server().await;
+    let client = Client::builder()
+        .base_url(fake_url(&server))
+        .device_key("key1")
+        .resolve("bark.internal", *server.address())
+        .build()
+        .unwrap();
+
+    client.notify("t", "b").await.unwrap();
+
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(
+        requests[0].headers["host"],
+        format!("bark.internal:{}", server.address().port())
+    );
+}
+
+struct Counting {
+    target: std::net::SocketAddr,
+    lookups: AtomicUsize,
+}
+
+impl Resolve for Counting {
+    fn resolve(&self, name: Name) -> Resolving {
+        assert_eq!(name.as_str(), "bark.internal");
+        self.lookups.fetch_add(1, Ordering::SeqCst);
+        let target = self.target;
+
+        Box::pin(async move { Ok(Box::new(std::iter::once(target)) as _) })
+    }
+}
+
+#[tokio::test]
+async fn a_custom_resolver_is_asked_for_the_host() {
+    let server = server().await;
+    let resolver = Arc::new(Counting {
+        target: *server.address(),
+        lookups: AtomicUsize::new(0),
+    });
+    let client = Client::builder()
+        .base_url(fake_url(&server))
+        .device_key("key1")
+        .dns_resolver(resolver.clone())
+        .build()
+        .unwrap();
+
+    client.notify("t", "b").await.unwrap();
+
+    assert_eq!(resolver.lookups.load(Ordering::SeqCst), 1);
+    assert_eq!(server.received_requests().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn http2_and_keepalive_settings_reach_a_working_client() {
+    let server = server().await;
+    let client = Client::builder()
+        .base_url(server.uri())
+        .device_key("key1")
+        .http2_prior_knowledge()
+        .http2_keep_alive_interval(Duration::from_secs(20))
+        .http2_keep_alive_timeout(Duration::from_secs(5))
+        .tcp_keepalive(Duration::from_secs(60))
+        .build()
+        .unwrap();
+
+    client.notify("over h2", "b").await.unwrap();
+
+    let transport = client.effective_config().transport;
+    assert_eq!(transport.http_version, Some("http2_prior_knowledge"));
+    assert_eq!(
+        transport.http2_keep_alive_interval,
+        Some(Duration::from_secs(20))
+    );
+    assert_eq!(
+        transport.http2_keep_alive_timeout,
+        Some(Duration::from_secs(5))
+    );
+    assert_eq!(transport.tcp_keepalive, Some(Duration::from_secs(60)));
+}
+
+#[tokio::test]
+async fn http1_only_still_sends() {
+    let server = server().await;
+    let client = Client::builder()
+        .base_url(server.uri())
+        .device_key("key1")
+        .http1_only()
+        .build()
+        .unwrap();
+
+    client.notify("over h1", "b").await.unwrap();
+
+    assert_eq!(
+        client.effective_config().transport.http_version,
+        Some("http1_only")
+    );
+}
@@ -0,0 +1,127 @@
+use bark::{
+    client::Client,
+    error::Error,
+    message::{Level, MessageBuilder, ToMessage},
+    test_util::Captured,
+};
+use serde_json::{Value, json};
+
+fn capturing() -> (Client, Captured) {
+    Client::builder()
+        .base_url("https://bark.example.com")
+        .device_key("key1")
+        .build_capturing()
+        .unwrap()
+}
+
+fn sent(captured: &Captured) -> Vec<Value> {
+    captured
+        .requests()
+        .iter()
+        .map(|request| serde_json::from_slice(&request.body).unwrap())
+        .collect()
+}
+
+struct Deploy {
+    service: &'static str,
+    version: &'static str,
+    failed: bool,
+}
+
+impl ToMessage for Deploy {
+    fn to_message(&self) -> MessageBuilder {
+        let builder = MessageBuilder::default()
+            .title(format!("{} {}", self.service, self.version))
+            .group("deploys");
+
+        if self.failed {
+            builder.body("rollout failed").level(Level::TimeSensitive)
+        } else {
+            builder.body("rolled out")
+        }
+    }
+}
+
+#[tokio::test]
+async fn domain_types_describe_their_own_push() {
+    let (client, captured) = capturing();
+
+    for failed in [false, true] {
+        let deploy = Deploy {
+            service: "api",
+            version: "v2",
+            failed,
+        };
+        client.send_item(&deploy).await.unwrap();
+    }
+
+    assert_eq!(
+        sent(&captured),
+        [
+            json!({
+                "title": "api v2",
+                "body": "rolled out",
+                "group": "deploys",
+                "device_keys": ["key1"],
+            }),
+            json!({
+                "title": "api v2",
+                "body": "rollout failed",
+                "group": "deploys",
+                "level": "timeSensitive",
+                "device_keys": ["key1"],
+            }),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn strings_and_format_arguments_are_bodies() {
+    let (client, captured) = capturing();
+
+    client.send_item("plain").await.unwrap();
+    client.send_item(&String::from("owned")).await.unwrap();
+    client
+        .send_item(&format_args!("{} failed", 3))
+        .await
+        .unwrap();
+
+    let bodies: Vec<_> = sent(&captured)
+        .iter()
+        .map(|body| body["body"].clone())
+        .collect();
+    assert_eq!(bodies, ["plain", "owned", "3 failed"]);
+}
+
+#[tokio::test]
+async fn conversions_into_a_builder_send_as_expected() {
+    let (client, captured) = capturing();
+
+    client.send_into("just a body").await.unwrap();
+    client.send_into(String::from("owned body")).await.unwrap();
+    client.send_into(("title", "body")).await.unwrap();
+    client
+        .send_into((String::from("owned"), format!("{} items", 2)))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        sent(&captured),
+        [
+            json!({"body": "just a body", "device_keys": ["key1"]}),
+            json!({"body": "owned body", "device_keys": ["key1"]}),
+            json!({"title": "title", "body": "body", "device_keys": ["key1"]}),
+            json!({"title": "owned", "body": "2 items", "device_keys": ["key1"]}),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn a_conversion_needs_the_clients_default_keys() {
+    let (client, captured) = Client::capturing();
+
+    let error = client.send_into(("t", "b")).await.unwrap_err();
+
+    assert!(matches!(error, Error::MissingDeviceKey), "{error:?}");
+    assert!(captured.requests().is_empty());
+}
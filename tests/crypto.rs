@@ -0,0 +1,120 @@
+#![cfg(feature = "crypto")]
+
+use bark::{
+    client::Client, crypto::EncryptionConfig, error::Error, message::Message, test_util::Captured,
+};
+use serde_json::{Value, json};
+
+const KEY: &str = "1234567890123456";
+const IV: &str = "abcdefghijklmnop";
+
+// From `openssl enc -aes-128-cbc -K <hex key> -iv <hex iv> -base64` over
+// `{"title":"hi","body":"b"}`.
+const WITH_CONFIG_IV: &str = "4raEtCBa8J+4d7fN4uzAOkFihTqWnnTiNtMnhgmAQBs=";
+const WITH_MESSAGE_IV: &str = "UXGGC8bxO0XJAQ9n+qF9Re0x8Iah8upKgT1oxMVg9zw=";
+
+fn config() -> EncryptionConfig {
+    EncryptionConfig::aes128_cbc(KEY, IV).unwrap()
+}
+
+fn capturing() -> (Client, Captured) {
+    Client::builder()
+        .base_url("https://bark.example.com")
+        .device_key("key1")
+        .build_capturing()
+        .unwrap()
+}
+
+fn sent(captured: &Captured) -> Value {
+    serde_json::from_slice(&captured.requests()[0].body).unwrap()
+}
+
+#[test]
+fn encrypt_matches_openssl() {
+    assert_eq!(
+        config().encrypt(br#"{"title":"hi","body":"b"}"#),
+        WITH_CONFIG_IV
+    );
+}
+
+#[tokio::test]
+async fn without_a_message_iv_the_configured_one_is_used_and_not_sent() {
+    let (client, captured) = capturing();
+
+    client
+        .message()
+        .title("hi")
+        .body("b")
+        .encrypt(&config())
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(
+        sent(&captured),
+        json!({ "device_keys": ["key1"], "ciphertext": WITH_CONFIG_IV })
+    );
+}
+
+#[tokio::test]
+async fn a_message_iv_is_what_the_ciphertext_is_encrypted_with() {
+    let (client, captured) = capturing();
+
+    client
+        .message()
+        .title("hi")
+        .body("b")
+        .iv("0123456789abcdef")
+        .encrypt(&config())
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(
+        sent(&captured),
+        json!({
+            "device_keys": ["key1"],
+            "ciphertext": WITH_MESSAGE_IV,
+            "iv": "0123456789abcdef",
+        })
+    );
+}
+
+#[test]
+fn a_message_iv_of_the_wrong_length_is_rejected() {
+    let error = Message::builder()
+        .title("hi")
+        .iv("short")
+        .encrypt(&config())
+        .build()
+        .unwrap_err();
+
+    assert!(
+        matches!(
+            error,
+            Error::InvalidIvLength {
+                expected: 16,
+                actual: 5
+            }
+        ),
+        "{error:?}"
+    );
+}
+
+#[test]
+fn key_and_iv_lengths_are_checked() {
+    assert!(matches!(
+        EncryptionConfig::aes128_cbc("short", IV),
+        Err(Error::InvalidKeyLength {
+            expected: 16,
+            actual: 5
+        })
+    ));
+    assert!(matches!(
+        EncryptionConfig::aes256_cbc([0; 32], [0; 8]),
+        Err(Error::InvalidIvLength {
+            expected: 16,
+            actual: 8
+        })
+    ));
+}
@@ -0,0 +1,69 @@
+use bark::{client::Client, message::Message};
+
+#[test]
+fn quotes_double_quotes_and_newlines_are_escaped() {
+    let message = Message::text("it's \"down\"\nagain");
+
+    assert_eq!(
+        message.to_curl("https://bark.example.com/"),
+        r#"curl -X POST 'https://bark.example.com/push' -H 'Content-Type: application/json' -d '{"body":"it'\''s \"down\"\nagain"}'"#
+    );
+}
+
+/// The shell hands curl exactly the JSON the client would send.
+#[cfg(unix)]
+#[test]
+fn the_shell_reads_back_the_json() {
+    use std::process::Command;
+
+    let message = Message::builder()
+        .title("it's")
+        .body("\"quoted\"\nand 'single'")
+        .device_key("key1")
+        .build()
+        .unwrap();
+    let curl = message.to_curl("https://bark.example.com");
+    let data = curl.split(" -d ").nth(1).unwrap();
+
+    let output = Command::new("sh")
+        .arg("-c")
+        .arg(format!("printf %s {data}"))
+        .output()
+        .unwrap();
+
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        serde_json::to_string(&message).unwrap()
+    );
+}
+
+#[test]
+fn ciphertext_can_be_redacted() {
+    let message = Message::builder()
+        .ciphertext("c2VjcmV0")
+        .device_key("key1")
+        .build()
+        .unwrap();
+
+    let plain = message.to_curl("https://bark.example.com");
+    let redacted = message.to_curl_redacted("https://bark.example.com");
+
+    assert!(plain.contains(r#""ciphertext":"c2VjcmV0""#), "{plain}");
+    assert!(
+        redacted.contains(r#""ciphertext":"<redacted>""#),
+        "{redacted}"
+    );
+    assert!(redacted.contains(r#""device_keys":["key1"]"#), "{redacted}");
+}
+
+#[test]
+fn dry_runs_include_the_client_defaults() {
+    let client = Client::new("https://bark.example.com").with_device_key("key1");
+
+    let curl = client.dry_run_curl(&Message::text("b"));
+
+    assert_eq!(
+        curl,
+        r#"curl -X POST 'https://bark.example.com/push' -H 'Content-Type: application/json' -d '{"body":"b","device_keys":["key1"]}'"#
+    );
+}
@@ -0,0 +1,215 @@
+use std::{future, sync::Arc, time::Duration};
+
+use bark::{
+    BoxFuture,
+    client::{Client, ClientBuilder},
+    error::{Error, Result},
+    hook::FailureKind,
+    message::Message,
+    rate_limit::RateLimit,
+    retry::RetryPolicy,
+    test_util::{Captured, ManualClock},
+    transport::{PreparedRequest, RawResponse, Transport},
+};
+
+const MS: Duration = Duration::from_millis(1);
+
+/// Backs off 100ms, 200ms, 400ms, ... within `deadline`.
+fn policy(deadline: Duration) -> RetryPolicy {
+    RetryPolicy {
+        max_attempts: 5,
+        initial_backoff: 100 * MS,
+        max_backoff: Duration::from_secs(10),
+        jitter: false,
+        total_deadline: Some(deadline),
+    }
+}
+
+/// Never answers, like a transport that ignores the request timeout.
+struct Hanging;
+
+impl Transport for Hanging {
+    fn execute(&self, _: PreparedRequest) -> BoxFuture<'_, Result<RawResponse>> {
+        Box::pin(future::pending())
+    }
+}
+
+fn builder(clock: &Arc<ManualClock>) -> ClientBuilder {
+    Client::builder()
+        .base_url("https://bark.example.com")
+        .device_key("key1")
+        .clock(clock.clone())
+}
+
+fn failing(captured: &Captured, failures: usize) {
+    for n in 1..=failures {
+        captured.fail_send(n);
+    }
+}
+
+#[tokio::test]
+async fn the_budget_runs_out_mid_retry() {
+    let clock = Arc::new(ManualClock::default());
+    let (client, captured) = builder(&clock)
+        .retry(policy(500 * MS))
+        .build_capturing()
+        .unwrap();
+    failing(&captured, 10);
+
+    let error = client.send(&Message::text("b")).await.unwrap_err();
+
+    // Attempts at 0ms, 100ms and 300ms; a fourth would wait 400ms more.
+    let Error::RetriesExhausted { attempts, source } = &error else {
+        panic!("{error:?}");
+    };
+    assert_eq!(*attempts, 3);
+    assert!(
+        matches!(
+            **source,
+            Error::Timeout {
+                elapsed,
+                deadline: Some(deadline),
+            } if elapsed == 300 * MS && deadline == 500 * MS
+        ),
+        "{source:?}"
+    );
+    assert_eq!(captured.requests().len(), 3);
+    assert_eq!(FailureKind::of(&error), FailureKind::Timeout);
+    assert!(
+        error.to_string().contains(
+            "Gave up after 3 attempts: Request timed out after 300ms, past its 500ms total deadline"
+        ),
+        "{error}"
+    );
+}
+
+#[tokio::test]
+async fn a_send_just_inside_the_budget_succeeds() {
+    let clock = Arc::new(ManualClock::default());
+    let (client, captured) = builder(&clock)
+        .retry(policy(301 * MS))
+        .build_capturing()
+        .unwrap();
+    failing(&captured, 2);
+
+    let outcome = client.send_detailed(&Message::text("b")).await.unwrap();
+
+    assert_eq!(outcome.attempts, 3);
+    assert_eq!(captured.requests().len(), 3);
+}
+
+#[tokio::test]
+async fn a_retry_that_would_end_on_the_deadline_is_not_started() {
+    let clock = Arc::new(ManualClock::default());
+    let (client, captured) = builder(&clock)
+        .retry(policy(300 * MS))
+        .build_capturing()
+        .unwrap();
+    failing(&captured, 2);
+
+    let error = client.send(&Message::text("b")).await.unwrap_err();
+
+    assert!(
+        matches!(error, Error::RetriesExhausted { attempts: 2, .. }),
+        "{error:?}"
+    );
+    assert_eq!(captured.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn rate_limiter_waits_count_against_the_budget() {
+    let clock = Arc::new(ManualClock::default());
+    let (client, captured) = builder(&clock)
+        .retry(policy(500 * MS))
+        .rate_limit(RateLimit::new(1, Duration::from_secs(1)))
+        .build_capturing()
+        .unwrap();
+
+    client.notify("t", "first").await.unwrap();
+    let error = client.notify("t", "second").await.unwrap_err();
+
+    assert!(
+        matches!(
+            error,
+            Error::Timeout {
+                elapsed: Duration::ZERO,
+                deadline: Some(_),
+            }
+        ),
+        "{error:?}"
+    );
+    assert_eq!(captured.requests().len(), 1);
+
+    clock.advance(Duration::from_secs(1));
+    client.notify("t", "third").await.unwrap();
+}
+
+#[tokio::test]
+async fn each_request_timeout_is_capped_by_what_is_left() {
+    let clock = Arc::new(ManualClock::default());
+    let (client, captured) = builder(&clock)
+        .retry(policy(500 * MS))
+        .timeout(Duration::from_secs(2))
+        .build_capturing()
+        .unwrap();
+    captured.fail_send(1);
+
+    client.send(&Message::text("b")).await.unwrap();
+
+    let timeouts: Vec<_> = captured
+        .requests()
+        .iter()
+        .map(|request| request.timeout)
+        .collect();
+    assert_eq!(timeouts, [Some(500 * MS), Some(400 * MS)]);
+
+    captured.clear();
+    let short = Message::builder()
+        .body("b")
+        .timeout(50 * MS)
+        .build()
+        .unwrap();
+    client.send(&short).await.unwrap();
+    assert_eq!(captured.requests()[0].timeout, Some(50 * MS));
+}
+
+#[tokio::test]
+async fn without_a_deadline_timeouts_are_left_alone() {
+    let clock = Arc::new(ManualClock::default());
+    let (client, captured) = builder(&clock)
+        .retry(RetryPolicy {
+            total_deadline: None,
+            ..policy(Duration::ZERO)
+        })
+        .timeout(Duration::from_secs(2))
+        .build_capturing()
+        .unwrap();
+
+    client.send(&Message::text("b")).await.unwrap();
+
+    assert_eq!(captured.requests()[0].timeout, None);
+}
+
+#[tokio::test]
+async fn the_deadline_fires_when_the_clock_passes_it() {
+    let clock = Arc::new(ManualClock::default());
+    let client = builder(&clock)
+        .retry(policy(Duration::from_secs(60)))
+        .transport(Arc::new(Hanging))
+        .build()
+        .unwrap();
+    let message = Message::text("b");
+    let mut send = std::pin::pin!(client.send(&message));
+
+    // Real time passing does not move the manual clock.
+    assert!(tokio::time::timeout(20 * MS, &mut send).await.is_err());
+
+    clock.advance(Duration::from_secs(61));
+    let error = send.await.unwrap_err();
+
+    assert_eq!(FailureKind::of(&error), FailureKind::Timeout, "{error:?}");
+    assert!(
+        error.to_string().contains("past its 60s total deadline"),
+        "{error}"
+    );
+}
@@ -0,0 +1,258 @@
+use bark::{
+    client::{Client, ClientBuilder},
+    error::Error,
+    message::{Level, Message},
+    test_util::Captured,
+};
+use serde_json::{Value, json};
+
+fn builder() -> ClientBuilder {
+    Client::builder()
+        .base_url("https://bark.example.com")
+        .device_key("key1")
+}
+
+/// The JSON bodies the client sent, in order.
+fn sent(captured: &Captured) -> Vec<Value> {
+    captured
+        .requests()
+        .iter()
+        .map(|request| serde_json::from_slice(&request.body).unwrap())
+        .collect()
+}
+
+fn iconed() -> ClientBuilder {
+    builder()
+        .icon_for_level(Level::Critical, "https://example.com/critical.png")
+        .icon_for_level(Level::Active, "https://example.com/active.png")
+        .icon_for_level(Level::TimeSensitive, "https://example.com/urgent.png")
+        .icon_for_level(Level::Passive, "https://example.com/passive.png")
+}
+
+#[tokio::test]
+async fn each_level_gets_its_icon() {
+    let (client, captured) = iconed().build_capturing().unwrap();
+
+    for level in Level::ALL {
+        client
+            .send(&Message::builder().body("b").level(level).build().unwrap())
+            .await
+            .unwrap();
+    }
+
+    let icons: Vec<_> = sent(&captured)
+        .iter()
+        .map(|body| (body["level"].clone(), body["icon"].clone()))
+        .collect();
+    assert_eq!(
+        icons,
+        [
+            ("critical", "https://example.com/critical.png"),
+            ("timeSensitive", "https://example.com/urgent.png"),
+            ("active", "https://example.com/active.png"),
+            ("passive", "https://example.com/passive.png"),
+        ]
+        .map(|(level, icon)| (Value::from(level), Value::from(icon)))
+    );
+}
+
+#[tokio::test]
+async fn an_explicit_icon_wins_over_the_level_icon() {
+    let (client, captured) = iconed().build_capturing().unwrap();
+
+    client
+        .message()
+        .body("b")
+        .level(Level::Critical)
+        .icon("https://example.com/own.png")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(sent(&captured)[0]["icon"], "https://example.com/own.png");
+}
+
+#[tokio::test]
+async fn a_message_without_a_level_gets_no_icon() {
+    let (client, captured) = iconed().build_capturing().unwrap();
+
+    client.notify("t", "b").await.unwrap();
+
+    assert_eq!(sent(&captured)[0].get("icon"), None);
+}
+
+#[tokio::test]
+async fn the_default_level_picks_the_icon() {
+    let (client, captured) = iconed().build_capturing().unwrap();
+
+    client
+        .with_level(Level::Passive)
+        .notify("t", "b")
+        .await
+        .unwrap();
+
+    let body = &sent(&captured)[0];
+    assert_eq!(body["level"], "passive");
+    assert_eq!(body["icon"], "https://example.com/passive.png");
+}
+
+#[tokio::test]
+async fn levels_without_an_icon_are_left_alone() {
+    let (client, captured) = builder()
+        .icon_for_level(Level::Critical, "https://example.com/critical.png")
+        .build_capturing()
+        .unwrap();
+
+    client
+        .send(
+            &Message::builder()
+                .body("b")
+                .level(Level::Active)
+                .build()
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(sent(&captured)[0].get("icon"), None);
+}
+
+/// Every client default there is, on top of the level icons.
+fn defaulted(client: Client) -> Client {
+    client
+        .with_group("ops")
+        .with_icon("https://example.com/default.png")
+        .with_sound("bell")
+        .with_level(Level::Critical)
+}
+
+fn raw() -> Message {
+    Message::builder()
+        .body("b")
+        .device_key("own")
+        .no_client_defaults()
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn no_client_defaults_sends_the_message_as_built() {
+    let (client, captured) = iconed().build_capturing().unwrap();
+    let client = defaulted(client);
+
+    client.send(&raw()).await.unwrap();
+    client.notify("t", "b").await.unwrap();
+
+    let sent = sent(&captured);
+    assert_eq!(sent[0], json!({"body": "b", "device_keys": ["own"]}));
+    assert_eq!(sent[1]["group"], "ops");
+    assert_eq!(sent[1]["icon"], "https://example.com/default.png");
+    assert_eq!(sent[1]["sound"], "bell");
+    assert_eq!(sent[1]["level"], "critical");
+    assert_eq!(sent[1]["device_keys"], json!(["key1"]));
+}
+
+#[tokio::test]
+async fn no_client_defaults_applies_to_every_send_path() {
+    let (client, captured) = iconed().build_capturing().unwrap();
+    let client = defaulted(client);
+
+    client.send_fanout(&raw()).await.unwrap();
+    client.send_chunked(&raw(), 10).await.unwrap();
+    assert!(!client.dry_run_curl(&raw()).contains("ops"));
+
+    for body in sent(&captured) {
+        assert_eq!(body, json!({"body": "b", "device_keys": ["own"]}));
+    }
+}
+
+#[tokio::test]
+async fn no_client_defaults_still_needs_its_own_keys() {
+    let (client, captured) = builder().build_capturing().unwrap();
+    let message = Message::builder()
+        .body("b")
+        .no_client_defaults()
+        .build()
+        .unwrap();
+
+    let error = client.send(&message).await.unwrap_err();
+
+    assert!(matches!(error, Error::MissingDeviceKey), "{error:?}");
+    assert!(captured.requests().is_empty());
+}
+
+#[tokio::test]
+async fn client_defaults_fill_unset_fields() {
+    let (client, captured) = builder().build_capturing().unwrap();
+    let client = defaulted(client);
+
+    client.send(&Message::text("b")).await.unwrap();
+    client.message().body("b").send().await.unwrap();
+
+    for body in sent(&captured) {
+        assert_eq!(
+            body,
+            json!({
+                "body": "b",
+                "device_keys": ["key1"],
+                "group": "ops",
+                "icon": "https://example.com/default.png",
+                "sound": "bell",
+                "level": "critical",
+            })
+        );
+    }
+}
+
+#[tokio::test]
+async fn message_fields_win_one_by_one() {
+    let (client, captured) = builder().build_capturing().unwrap();
+    let client = defaulted(client);
+
+    client
+        .send(&Message::builder().body("b").group("mine").build().unwrap())
+        .await
+        .unwrap();
+    client
+        .message()
+        .body("b")
+        .icon("https://example.com/mine.png")
+        .send()
+        .await
+        .unwrap();
+    client
+        .message()
+        .body("b")
+        .sound("alarm")
+        .send()
+        .await
+        .unwrap();
+    client
+        .message()
+        .body("b")
+        .level(Level::Passive)
+        .send()
+        .await
+        .unwrap();
+
+    let sent = sent(&captured);
+    let fields = ["group", "icon", "sound", "level"];
+    let own = ["mine", "https://example.com/mine.png", "alarm", "passive"];
+    let defaults = ["ops", "https://example.com/default.png", "bell", "critical"];
+    for (n, body) in sent.iter().enumerate() {
+        for (field, (own, default)) in fields.iter().zip(own.iter().zip(defaults)) {
+            let expected = if fields[n] == *field { *own } else { default };
+            assert_eq!(body[field], expected, "send {n}, {field}");
+        }
+    }
+}
+
+#[tokio::test]
+async fn a_later_default_replaces_an_earlier_one() {
+    let (client, captured) = builder().build_capturing().unwrap();
+    let client = client.with_group("first").with_group("second");
+
+    client.notify("t", "b").await.unwrap();
+
+    assert_eq!(sent(&captured)[0]["group"], "second");
+}
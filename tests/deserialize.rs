@@ -0,0 +1,122 @@
+use bark::message::{Action, Level, Message, MessageBuilder};
+use bark::sound::Sound;
+
+fn round_trip(message: &Message) -> Message {
+    let json = serde_json::to_string(message).unwrap();
+    serde_json::from_str(&json).unwrap_or_else(|error| panic!("{json}: {error}"))
+}
+
+/// Every combination of a handful of independent field groups.
+fn combinations() -> Vec<Message> {
+    type Part = fn(MessageBuilder) -> MessageBuilder;
+    let parts: [Part; 7] = [
+        |builder| builder.title("deploy").subtitle("prod"),
+        |builder| builder.markdown("**done**"),
+        |builder| builder.device_keys(["key1", "key2"]),
+        |builder| builder.level(Level::Critical).volume(7),
+        |builder| builder.badge(3).call(true).auto_copy(true).copy("c"),
+        |builder| {
+            builder
+                .icon("https://example.com/i.png")
+                .group("g")
+                .is_archive(true)
+                .url("https://example.com")
+                .action(Action::None)
+        },
+        |builder| builder.id("n1").sound(Sound::Bell),
+    ];
+
+    (0..1u32 << parts.len())
+        .map(|mask| {
+            let builder = parts
+                .iter()
+                .enumerate()
+                .filter(|(bit, _)| mask & (1 << bit) != 0)
+                .fold(Message::builder().body("plain"), |builder, (_, part)| {
+                    part(builder)
+                });
+            builder.build().unwrap()
+        })
+        .collect()
+}
+
+#[test]
+fn every_field_combination_round_trips() {
+    for message in combinations() {
+        assert_eq!(round_trip(&message), message);
+    }
+}
+
+#[test]
+fn custom_values_round_trip() {
+    let message = Message::builder()
+        .body("b")
+        .level(Level::custom("shortcut"))
+        .sound(Sound::Custom("my-ringtone".into()))
+        .action(Action::from("open"))
+        .build()
+        .unwrap();
+
+    assert_eq!(round_trip(&message), message);
+}
+
+#[test]
+fn deletes_and_ciphertext_round_trip() {
+    for message in [
+        Message::builder().id("n1").delete(true).build().unwrap(),
+        Message::builder()
+            .ciphertext("Zm9v")
+            .iv("0123456789abcdef")
+            .build()
+            .unwrap(),
+    ] {
+        assert_eq!(round_trip(&message), message);
+    }
+}
+
+#[test]
+fn flags_read_as_ones_or_booleans() {
+    let message: Message =
+        serde_json::from_str(r#"{"body":"b","call":1,"autoCopy":true,"isArchive":0}"#).unwrap();
+
+    assert_eq!(
+        message,
+        Message::builder()
+            .body("b")
+            .call(true)
+            .auto_copy(true)
+            .build()
+            .unwrap()
+    );
+}
+
+#[test]
+fn loose_encodings_are_rejected() {
+    for json in [
+        r#"{"badge":"3"}"#,
+        r#"{"call":"yes"}"#,
+        r#"{"call":2}"#,
+        r#"{"title":42}"#,
+        r#"{"device_keys":"key1"}"#,
+        r#"{"autoCopy":2}"#,
+        r#"{"body":"b","markdown":"m"}"#,
+        r#"{"volume":11}"#,
+    ] {
+        assert!(serde_json::from_str::<Message>(json).is_err(), "{json}");
+    }
+}
+
+#[test]
+fn unknown_fields_are_ignored() {
+    let message: Message = serde_json::from_str(r#"{"body":"b","priority":"high"}"#).unwrap();
+    let bare: Message = serde_json::from_str(r#"{"priority":"high"}"#).unwrap();
+
+    assert_eq!(message, Message::text("b"));
+    assert_eq!(serde_json::to_value(bare).unwrap(), serde_json::json!({}));
+}
+
+#[test]
+fn errors_name_the_problem() {
+    let error = serde_json::from_str::<Message>(r#"{"icon":"not a url"}"#).unwrap_err();
+    assert!(error.to_string().contains("icon"), "{error}");
+}
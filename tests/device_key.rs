@@ -0,0 +1,113 @@
+use bark::{client::Client, device_key::DeviceKey, error::Error, message::Message};
+use serde_json::json;
+
+fn rejection(key: &str) -> (String, &'static str) {
+    match DeviceKey::new(key).unwrap_err() {
+        Error::InvalidDeviceKey { key, reason } => (key, reason),
+        error => panic!("{error:?}"),
+    }
+}
+
+#[test]
+fn surrounding_whitespace_is_trimmed() {
+    for pasted in ["abcdef123", " abcdef123", "abcdef123 ", "\tabcdef123\r\n"] {
+        assert_eq!(
+            DeviceKey::new(pasted).unwrap().as_str(),
+            "abcdef123",
+            "{pasted:?}"
+        );
+    }
+}
+
+#[test]
+fn a_trailing_newline_is_dropped() {
+    let key: DeviceKey = "abcdef123\n".parse().unwrap();
+
+    assert_eq!(key.as_str(), "abcdef123");
+    assert_eq!(String::from(key), "abcdef123");
+}
+
+#[test]
+fn obvious_mistakes_are_rejected() {
+    for (key, reason) in [
+        ("", "is empty"),
+        (" \n", "is empty"),
+        ("abcd ef123", "contains whitespace"),
+        ("abcd\nef123", "contains whitespace"),
+        (
+            "https://api.day.app/abcdef123",
+            "looks like a URL, expected only the key",
+        ),
+        ("abcdef123/title", "looks like a URL, expected only the key"),
+        (
+            "abcdef123?sound=bell",
+            "looks like a URL, expected only the key",
+        ),
+    ] {
+        assert_eq!(rejection(key).1, reason, "{key:?}");
+    }
+}
+
+#[test]
+fn errors_echo_a_redacted_key() {
+    let error = DeviceKey::new("https://api.day.app/secretkey123").unwrap_err();
+
+    assert_eq!(rejection("https://api.day.app/secretkey123").0, "http…");
+    assert_eq!(
+        error.to_string(),
+        r#"Invalid device key "http…": looks like a URL, expected only the key"#
+    );
+    assert!(!error.to_string().contains("secretkey123"));
+}
+
+#[test]
+fn debug_redacts_but_display_does_not() {
+    let key = DeviceKey::try_from("secretkey123").unwrap();
+
+    assert_eq!(format!("{key:?}"), r#"DeviceKey("secr…")"#);
+    assert_eq!(key.to_string(), "secretkey123");
+}
+
+#[test]
+fn builders_trim_and_validate_at_build_time() {
+    let message = Message::builder()
+        .body("b")
+        .device_key("abcdef123\n")
+        .build()
+        .unwrap();
+    let error = Message::builder()
+        .body("b")
+        .device_key("abc def")
+        .build()
+        .unwrap_err();
+
+    assert_eq!(
+        serde_json::to_value(&message).unwrap()["device_keys"],
+        json!(["abcdef123"])
+    );
+    assert!(matches!(error, Error::InvalidDeviceKey { .. }), "{error:?}");
+}
+
+#[tokio::test]
+async fn clients_trim_and_validate_at_send_time() {
+    let (client, captured) = Client::capturing();
+
+    client
+        .clone()
+        .with_device_key("abcdef123\n")
+        .send(&Message::text("b"))
+        .await
+        .unwrap();
+    let error = client
+        .with_device_key("https://api.day.app/abcdef123")
+        .send(&Message::text("b"))
+        .await
+        .unwrap_err();
+
+    assert!(matches!(error, Error::InvalidDeviceKey { .. }), "{error:?}");
+    assert_eq!(captured.requests().len(), 1);
+    assert_eq!(
+        serde_json::to_value(&captured.messages()[0]).unwrap()["device_keys"],
+        json!(["abcdef123"])
+    );
+}
@@ -0,0 +1,140 @@
+#![cfg(feature = "reqwest")]
+
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use bark::{client::Client, error::Error};
+
+/// Every test here touches the process environment, so they take turns.
+static ENV: Mutex<()> = Mutex::new(());
+
+/// Sets `vars` under `prefix` until dropped, holding the lock throughout.
+struct Env {
+    names: Vec<String>,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl Env {
+    fn set(prefix: &str, vars: &[(&str, &str)]) -> Env {
+        let lock = ENV.lock().unwrap_or_else(PoisonError::into_inner);
+        let names = vars
+            .iter()
+            .map(|(name, value)| {
+                let name = format!("{prefix}{name}");
+                // SAFETY: the lock keeps other tests in this binary from reading or
+                // writing the environment meanwhile.
+                unsafe { std::env::set_var(&name, value) };
+                name
+            })
+            .collect();
+
+        Env { names, _lock: lock }
+    }
+}
+
+impl Drop for Env {
+    fn drop(&mut self) {
+        for name in &self.names {
+            // SAFETY: as in `Env::set`, the lock is still held.
+            unsafe { std::env::remove_var(name) };
+        }
+    }
+}
+
+fn keys(client: &Client) -> Vec<String> {
+    client.effective_config().default_device_keys
+}
+
+#[test]
+fn the_base_url_and_keys_are_read() {
+    let _env = Env::set(
+        "BARK_",
+        &[
+            ("BASE_URL", "https://bark.example.com"),
+            ("DEVICE_KEY", "alphakey1"),
+            ("DEVICE_KEYS", "bravokey2, charliekey3,,"),
+        ],
+    );
+
+    let client = Client::from_env().unwrap();
+
+    let config = client.effective_config();
+    assert_eq!(config.base_url, "https://bark.example.com");
+    assert_eq!(keys(&client), ["alph…", "brav…", "char…"]);
+}
+
+#[test]
+fn keys_are_optional() {
+    let _env = Env::set("ONLYURL_", &[("BASE_URL", "https://bark.example.com")]);
+
+    let client = Client::from_env_prefixed("ONLYURL_").unwrap();
+
+    assert!(keys(&client).is_empty());
+}
+
+#[test]
+fn a_missing_base_url_is_named() {
+    let _env = Env::set("NOURL_", &[("DEVICE_KEY", "alphakey1")]);
+
+    let error = Client::from_env_prefixed("NOURL_").unwrap_err();
+
+    assert!(
+        matches!(&error, Error::MissingEnvVar(name) if name == "NOURL_BASE_URL"),
+        "{error:?}"
+    );
+}
+
+#[test]
+fn a_malformed_base_url_is_named() {
+    let _env = Env::set("BADURL_", &[("BASE_URL", "ftp://bark.example.com")]);
+
+    let error = Client::from_env_prefixed("BADURL_").unwrap_err();
+
+    assert!(
+        matches!(&error, Error::InvalidEnvVar { name, .. } if name == "BADURL_BASE_URL"),
+        "{error:?}"
+    );
+}
+
+#[test]
+fn a_malformed_key_is_named() {
+    let _env = Env::set(
+        "BADKEY_",
+        &[
+            ("BASE_URL", "https://bark.example.com"),
+            ("DEVICE_KEYS", "goodkey1,https://bark.example.com/badkey"),
+        ],
+    );
+
+    let error = Client::from_env_prefixed("BADKEY_").unwrap_err();
+
+    assert!(
+        matches!(&error, Error::InvalidEnvVar { name, .. } if name == "BADKEY_DEVICE_KEYS"),
+        "{error:?}"
+    );
+}
+
+#[test]
+fn prefixes_keep_two_servers_apart() {
+    let _env = Env::set(
+        "",
+        &[
+            ("PRIMARY_BASE_URL", "https://one.example.com"),
+            ("PRIMARY_DEVICE_KEY", "onekey11"),
+            ("SECONDARY_BASE_URL", "https://two.example.com"),
+        ],
+    );
+
+    let primary = Client::from_env_prefixed("PRIMARY_").unwrap();
+    let secondary = Client::from_env_prefixed("SECONDARY_").unwrap();
+
+    assert_eq!(
+        primary.effective_config().base_url,
+        "https://one.example.com"
+    );
+    assert_eq!(keys(&primary), ["onek…"]);
+    assert_eq!(
+        secondary.effective_config().base_url,
+        "https://two.example.com"
+    );
+    assert!(keys(&secondary).is_empty());
+}
@@ -0,0 +1,58 @@
+use std::collections::HashSet;
+
+use bark::message::{BodyKind, Level, Message, MessageBuilder};
+
+#[test]
+fn different_paths_build_equal_messages() {
+    let built = Message::builder()
+        .title("disk full")
+        .body("on db-1")
+        .build()
+        .unwrap();
+    let parsed: Message =
+        serde_json::from_str(r#"{"body":"on db-1","title":"disk full"}"#).unwrap();
+
+    assert_eq!(Message::titled("disk full", "on db-1"), built);
+    assert_eq!(parsed, built);
+    assert_eq!(
+        MessageBuilder::from(("disk full", "on db-1"))
+            .build()
+            .unwrap(),
+        built
+    );
+}
+
+#[test]
+fn device_key_order_does_not_matter() {
+    let forward = Message::builder()
+        .body("b")
+        .device_keys(["a", "b", "c"])
+        .build()
+        .unwrap();
+    let backward = Message::builder()
+        .body("b")
+        .device_key("c")
+        .device_key("b")
+        .device_key("a")
+        .build()
+        .unwrap();
+
+    assert_eq!(forward, backward);
+}
+
+#[test]
+fn different_body_kinds_are_not_equal() {
+    assert_ne!(Message::text("b"), Message::markdown("b"));
+    assert_ne!(Message::text("b"), Message::text("c"));
+}
+
+#[test]
+fn levels_and_body_kinds_hash() {
+    let levels: HashSet<Level> = Level::ALL.into_iter().chain(Level::ALL).collect();
+    let kinds: HashSet<BodyKind> = [BodyKind::Markdown, BodyKind::Plaintext, BodyKind::Markdown]
+        .into_iter()
+        .collect();
+
+    assert_eq!(levels.len(), 4);
+    assert_eq!(kinds.len(), 2);
+}
@@ -0,0 +1,130 @@
+#![cfg(feature = "reqwest")]
+
+use std::net::TcpListener;
+
+use bark::{
+    client::{Client, ClientBuilder},
+    error::Error,
+    message::Message,
+};
+use wiremock::{
+    Mock, MockServer, ResponseTemplate,
+    matchers::{method, path},
+};
+
+const SUCCESS: &str = r#"{"code":200,"message":"success","timestamp":1}"#;
+const REJECTED: &str = r#"{"code":400,"message":"failed to get device token","timestamp":1}"#;
+
+/// A base URL nothing listens on, so connecting is refused.
+fn down() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    drop(listener);
+
+    format!("http://{address}")
+}
+
+async fn answering(status: u16, body: &str) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/push"))
+        .respond_with(ResponseTemplate::new(status).set_body_string(body))
+        .mount(&server)
+        .await;
+
+    server
+}
+
+fn builder(primary: &str) -> ClientBuilder {
+    Client::builder().base_url(primary).device_key("key1")
+}
+
+async fn hits(server: &MockServer) -> usize {
+    server.received_requests().await.unwrap().len()
+}
+
+#[tokio::test]
+async fn an_unreachable_primary_fails_over() {
+    let fallback = answering(200, SUCCESS).await;
+    let client = builder(&down())
+        .fallback_url(fallback.uri())
+        .build()
+        .unwrap();
+
+    let outcome = client.send_detailed(&Message::text("b")).await.unwrap();
+
+    assert!(outcome.response.is_success());
+    assert_eq!(outcome.server.as_deref(), Some(fallback.uri().as_str()));
+    assert_eq!(hits(&fallback).await, 1);
+}
+
+#[tokio::test]
+async fn fallbacks_are_tried_in_order() {
+    let failing = answering(503, "unavailable").await;
+    let second = answering(200, SUCCESS).await;
+    let third = answering(200, SUCCESS).await;
+    let client = builder(&down())
+        .fallback_url(failing.uri())
+        .fallback_url(second.uri())
+        .fallback_url(third.uri())
+        .build()
+        .unwrap();
+
+    let outcome = client.send_detailed(&Message::text("b")).await.unwrap();
+
+    assert_eq!(outcome.server.as_deref(), Some(second.uri().as_str()));
+    assert_eq!(hits(&failing).await, 1);
+    assert_eq!(hits(&second).await, 1);
+    assert_eq!(hits(&third).await, 0);
+}
+
+#[tokio::test]
+async fn a_healthy_primary_is_used_alone() {
+    let primary = answering(200, SUCCESS).await;
+    let fallback = answering(200, SUCCESS).await;
+    let client = builder(&primary.uri())
+        .fallback_url(fallback.uri())
+        .build()
+        .unwrap();
+
+    let outcome = client.send_detailed(&Message::text("b")).await.unwrap();
+
+    assert_eq!(outcome.server.as_deref(), Some(primary.uri().as_str()));
+    assert_eq!(hits(&fallback).await, 0);
+}
+
+#[tokio::test]
+async fn client_errors_do_not_fail_over() {
+    for (status, body) in [(400, REJECTED), (200, REJECTED), (404, "not found")] {
+        let primary = answering(status, body).await;
+        let fallback = answering(200, SUCCESS).await;
+        let client = builder(&primary.uri())
+            .fallback_url(fallback.uri())
+            .build()
+            .unwrap();
+
+        let error = client.send(&Message::text("b")).await.unwrap_err();
+
+        assert!(
+            matches!(error, Error::Server { .. } | Error::HttpStatus { .. }),
+            "{status}: {error:?}"
+        );
+        assert_eq!(hits(&fallback).await, 0, "{status}");
+    }
+}
+
+#[tokio::test]
+async fn the_last_failure_is_returned_when_every_server_is_down() {
+    let failing = answering(502, "bad gateway").await;
+    let client = builder(&down())
+        .fallback_url(failing.uri())
+        .build()
+        .unwrap();
+
+    let error = client.send(&Message::text("b")).await.unwrap_err();
+
+    assert!(
+        matches!(error, Error::HttpStatus { status, .. } if status == 502),
+        "{error:?}"
+    );
+}
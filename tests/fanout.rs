@@ -0,0 +1,203 @@
+use std::sync::{Arc, Mutex};
+
+use bark::{
+    BoxFuture,
+    client::Client,
+    error::{Error, Result},
+    message::Message,
+    transport::{PreparedRequest, RawResponse, Transport},
+};
+use http::StatusCode;
+use serde_json::Value;
+
+/// Rejects any push addressed to the `revoked` key, like bark-server does for
+/// a key whose device token is gone; records each push's sorted keys.
+#[derive(Clone, Default)]
+struct Fleet(Arc<Mutex<Vec<Vec<String>>>>);
+
+impl Fleet {
+    fn sent(&self) -> Vec<Vec<String>> {
+        let mut sent = self.0.lock().unwrap().clone();
+        sent.sort();
+        sent
+    }
+}
+
+impl Transport for Fleet {
+    fn execute(&self, request: PreparedRequest) -> BoxFuture<'_, Result<RawResponse>> {
+        let json: Value = serde_json::from_slice(&request.body).unwrap();
+        let mut keys: Vec<String> = json["device_keys"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|key| key.as_str().unwrap().to_owned())
+            .collect();
+        keys.sort();
+        let revoked = keys.iter().any(|key| key == "revoked");
+        self.0.lock().unwrap().push(keys);
+
+        let (status, body) = if revoked {
+            (
+                StatusCode::BAD_REQUEST,
+                r#"{"code":400,"message":"failed to get device token","timestamp":0}"#,
+            )
+        } else {
+            (
+                StatusCode::OK,
+                r#"{"code":200,"message":"success","timestamp":0}"#,
+            )
+        };
+
+        Box::pin(async move {
+            Ok(RawResponse {
+                status,
+                headers: Default::default(),
+                body: body.as_bytes().to_vec(),
+            })
+        })
+    }
+}
+
+fn client(fleet: &Fleet) -> Client {
+    Client::builder()
+        .base_url("https://bark.example.com")
+        .transport(Arc::new(fleet.clone()))
+        .build()
+        .unwrap()
+}
+
+fn to(keys: &[&str]) -> Message {
+    Message::builder()
+        .body("b")
+        .device_keys(keys.iter().copied())
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn each_key_gets_its_own_request_and_result() {
+    let fleet = Fleet::default();
+
+    let report = client(&fleet)
+        .send_fanout(&to(&["alice", "bob", "revoked"]))
+        .await
+        .unwrap();
+
+    assert_eq!(fleet.sent(), [["alice"], ["bob"], ["revoked"]]);
+    assert!(!report.all_ok());
+    assert_eq!(report.failed_keys(), ["revoked"]);
+    assert!(report.results["alice"].is_ok());
+    assert!(report.results["bob"].is_ok());
+    assert!(
+        matches!(
+            report.results["revoked"],
+            Err(Error::Server { code: 400, .. })
+        ),
+        "{:?}",
+        report.results["revoked"]
+    );
+}
+
+#[tokio::test]
+async fn a_healthy_fleet_is_all_ok() {
+    let fleet = Fleet::default();
+
+    let report = client(&fleet)
+        .send_fanout(&to(&["alice", "bob"]))
+        .await
+        .unwrap();
+
+    assert!(report.all_ok());
+    assert!(report.failed_keys().is_empty());
+}
+
+#[tokio::test]
+async fn fanout_without_keys_sends_nothing() {
+    let fleet = Fleet::default();
+
+    let error = client(&fleet)
+        .send_fanout(&Message::text("b"))
+        .await
+        .unwrap_err();
+
+    assert!(matches!(error, Error::MissingDeviceKey), "{error:?}");
+    assert!(fleet.sent().is_empty());
+}
+
+fn keys(n: usize) -> Vec<String> {
+    (0..n).map(|n| format!("key{n:02}")).collect()
+}
+
+#[tokio::test]
+async fn chunks_divide_evenly() {
+    let fleet = Fleet::default();
+    let keys = keys(6);
+    let refs: Vec<&str> = keys.iter().map(String::as_str).collect();
+
+    let report = client(&fleet).send_chunked(&to(&refs), 3).await.unwrap();
+
+    let chunks: Vec<_> = report
+        .chunks
+        .iter()
+        .map(|chunk| chunk.device_keys.clone())
+        .collect();
+    assert_eq!(chunks, [keys[..3].to_vec(), keys[3..].to_vec()]);
+    assert_eq!(fleet.sent(), chunks);
+    assert!(report.all_ok());
+}
+
+#[tokio::test]
+async fn the_last_chunk_takes_the_remainder() {
+    let fleet = Fleet::default();
+    let keys = keys(7);
+    let refs: Vec<&str> = keys.iter().map(String::as_str).collect();
+
+    let report = client(&fleet).send_chunked(&to(&refs), 3).await.unwrap();
+
+    let sizes: Vec<_> = report
+        .chunks
+        .iter()
+        .map(|chunk| chunk.device_keys.len())
+        .collect();
+    assert_eq!(sizes, [3, 3, 1]);
+    let mut all: Vec<_> = fleet.sent().concat();
+    all.sort();
+    assert_eq!(all, keys);
+}
+
+#[tokio::test]
+async fn a_failing_chunk_does_not_stop_the_others() {
+    let fleet = Fleet::default();
+
+    let report = client(&fleet)
+        .send_chunked(&to(&["alice", "bob", "carol", "revoked"]), 2)
+        .await
+        .unwrap();
+
+    let results: Vec<_> = report
+        .chunks
+        .iter()
+        .map(|chunk| (chunk.device_keys.clone(), chunk.result.is_ok()))
+        .collect();
+    assert_eq!(
+        results,
+        [
+            (vec!["alice".to_owned(), "bob".to_owned()], true),
+            (vec!["carol".to_owned(), "revoked".to_owned()], false),
+        ]
+    );
+    assert!(!report.all_ok());
+    assert_eq!(report.failed_keys(), ["carol", "revoked"]);
+}
+
+#[tokio::test]
+async fn a_zero_chunk_size_sends_one_key_per_chunk() {
+    let fleet = Fleet::default();
+
+    let report = client(&fleet)
+        .send_chunked(&to(&["alice", "bob"]), 0)
+        .await
+        .unwrap();
+
+    assert_eq!(report.chunks.len(), 2);
+}
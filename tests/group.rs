@@ -0,0 +1,147 @@
+use bark::{
+    client::Client,
+    message::{Level, Message},
+    test_util::ReceivedPush,
+};
+use wiremock::{
+    Mock, MockServer, ResponseTemplate,
+    matchers::{method, path},
+};
+
+const SUCCESS: &str = r#"{"code":200,"message":"success","timestamp":1}"#;
+
+async fn server() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/push"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(SUCCESS))
+        .mount(&server)
+        .await;
+    server
+}
+
+fn client(server: &MockServer) -> Client {
+    Client::builder()
+        .base_url(server.uri())
+        .device_key("key1")
+        .build()
+        .unwrap()
+}
+
+async fn received(server: &MockServer) -> Vec<ReceivedPush> {
+    server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .map(|request| ReceivedPush::from_slice(&request.body).unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn every_send_path_carries_the_group_defaults() {
+    let server = server().await;
+    let deploys = client(&server)
+        .group("deploys")
+        .icon("https://example.com/rocket.png")
+        .sound("bell")
+        .default_level(Level::TimeSensitive);
+
+    deploys.send(&Message::text("send")).await.unwrap();
+    deploys.send_item("item").await.unwrap();
+    deploys.send_into(("into", "b")).await.unwrap();
+    deploys.message().body("builder").send().await.unwrap();
+
+    let pushes = received(&server).await;
+    assert_eq!(pushes.len(), 4);
+    for push in pushes {
+        assert_eq!(push.group.as_deref(), Some("deploys"));
+        assert_eq!(push.icon.as_deref(), Some("https://example.com/rocket.png"));
+        assert_eq!(push.sound.as_deref(), Some("bell"));
+        assert_eq!(push.level, Some(Level::TimeSensitive));
+        assert_eq!(push.device_keys, ["key1"]);
+    }
+}
+
+#[tokio::test]
+async fn notify_carries_the_group_defaults() {
+    let server = server().await;
+    let deploys = client(&server)
+        .group("deploys")
+        .icon("https://example.com/rocket.png")
+        .sound("bell")
+        .default_level(Level::Active);
+
+    deploys.notify("deployed", "v2 is live").await.unwrap();
+    deploys
+        .clone()
+        .notify_markdown("deployed", "**v2** is live")
+        .await
+        .unwrap();
+
+    let pushes = received(&server).await;
+    assert_eq!(pushes.len(), 2);
+    for push in &pushes {
+        assert_eq!(push.title.as_deref(), Some("deployed"));
+        assert_eq!(push.group.as_deref(), Some("deploys"));
+        assert_eq!(push.icon.as_deref(), Some("https://example.com/rocket.png"));
+        assert_eq!(push.sound.as_deref(), Some("bell"));
+        assert_eq!(push.level, Some(Level::Active));
+        assert_eq!(push.device_keys, ["key1"]);
+    }
+    assert_eq!(pushes[0].body.as_deref(), Some("v2 is live"));
+    assert_eq!(pushes[0].markdown, None);
+    assert_eq!(pushes[1].markdown.as_deref(), Some("**v2** is live"));
+    assert_eq!(pushes[1].body, None);
+}
+
+#[tokio::test]
+async fn message_fields_win_over_the_group_defaults() {
+    let server = server().await;
+    let deploys = client(&server)
+        .group("deploys")
+        .sound("bell")
+        .default_level(Level::Passive);
+
+    deploys
+        .send(
+            &Message::builder()
+                .body("b")
+                .group("incidents")
+                .sound("alarm")
+                .level(Level::Critical)
+                .build()
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    deploys
+        .message()
+        .body("b")
+        .group("own")
+        .send()
+        .await
+        .unwrap();
+
+    let pushes = received(&server).await;
+    assert_eq!(pushes[0].group.as_deref(), Some("incidents"));
+    assert_eq!(pushes[0].sound.as_deref(), Some("alarm"));
+    assert_eq!(pushes[0].level, Some(Level::Critical));
+    assert_eq!(pushes[1].group.as_deref(), Some("own"));
+    assert_eq!(pushes[1].sound.as_deref(), Some("bell"));
+}
+
+#[tokio::test]
+async fn group_defaults_win_over_the_client_defaults() {
+    let server = server().await;
+    let client = client(&server).with_group("general").with_sound("chime");
+    let deploys = client.group("deploys");
+
+    deploys.send(&Message::text("grouped")).await.unwrap();
+    client.send(&Message::text("plain")).await.unwrap();
+
+    let pushes = received(&server).await;
+    assert_eq!(pushes[0].group.as_deref(), Some("deploys"));
+    assert_eq!(pushes[0].sound.as_deref(), Some("chime"));
+    assert_eq!(pushes[1].group.as_deref(), Some("general"));
+}
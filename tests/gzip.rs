@@ -0,0 +1,117 @@
+#![cfg(feature = "gzip")]
+
+use bark::{client::Client, error::Error};
+use wiremock::{
+    Mock, MockServer, ResponseTemplate,
+    matchers::{header, method, path},
+};
+
+const SUCCESS: &str = r#"{"code":200,"message":"success","timestamp":1}"#;
+
+/// Answers gzip requests with `refusal` and plain ones with success.
+async fn server(refusal: ResponseTemplate) -> MockServer {
+    let server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/push"))
+        .and(header("content-encoding", "gzip"))
+        .respond_with(refusal)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/push"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(SUCCESS))
+        .mount(&server)
+        .await;
+
+    server
+}
+
+fn client(server: &MockServer) -> Client {
+    Client::builder()
+        .base_url(server.uri())
+        .device_key("key1")
+        .compress_requests(0)
+        .build()
+        .unwrap()
+}
+
+async fn encodings(server: &MockServer) -> Vec<bool> {
+    server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .map(|request| request.headers.contains_key("content-encoding"))
+        .collect()
+}
+
+#[tokio::test]
+async fn a_plain_400_falls_back_and_stays_uncompressed() {
+    let server = server(ResponseTemplate::new(400).set_body_string("bad request")).await;
+    let client = client(&server);
+
+    client.notify("t", "first").await.unwrap();
+    client.notify("t", "second").await.unwrap();
+
+    assert_eq!(encodings(&server).await, [true, false, false]);
+}
+
+#[tokio::test]
+async fn a_415_falls_back() {
+    let server = server(ResponseTemplate::new(415)).await;
+    let client = client(&server);
+
+    client.notify("t", "b").await.unwrap();
+
+    assert_eq!(encodings(&server).await, [true, false]);
+}
+
+#[tokio::test]
+async fn a_bark_error_is_final_and_keeps_compression_on() {
+    let server =
+        server(ResponseTemplate::new(400).set_body_string(
+            r#"{"code":400,"message":"failed to get device token","timestamp":1}"#,
+        ))
+        .await;
+    let client = client(&server);
+
+    let error = client.notify("t", "b").await.unwrap_err();
+    assert!(
+        matches!(error, Error::Server { code: 400, .. }),
+        "{error:?}"
+    );
+
+    client.notify("t", "again").await.unwrap_err();
+    assert_eq!(encodings(&server).await, [true, true]);
+}
+
+#[tokio::test]
+async fn a_bark_error_about_the_encoding_falls_back() {
+    let server = server(ResponseTemplate::new(400).set_body_string(
+        r#"{"code":400,"message":"unsupported Content-Encoding: gzip","timestamp":1}"#,
+    ))
+    .await;
+    let client = client(&server);
+
+    client.notify("t", "b").await.unwrap();
+
+    assert_eq!(encodings(&server).await, [true, false]);
+}
+
+#[tokio::test]
+async fn small_bodies_are_never_compressed() {
+    let server = server(ResponseTemplate::new(415)).await;
+    let client = Client::builder()
+        .base_url(server.uri())
+        .device_key("key1")
+        .compress_requests(4096)
+        .build()
+        .unwrap();
+
+    client.notify("t", "b").await.unwrap();
+
+    assert_eq!(encodings(&server).await, [false]);
+}
@@ -0,0 +1,175 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use bark::{
+    client::{Client, ClientBuilder},
+    error::Error,
+    hook::{HookControl, SendReport},
+    message::Message,
+    retry::RetryPolicy,
+    test_util::Captured,
+};
+
+fn builder() -> ClientBuilder {
+    Client::builder()
+        .base_url("https://bark.example.com")
+        .device_key("key1")
+}
+
+fn titles(captured: &Captured) -> Vec<String> {
+    captured
+        .requests()
+        .iter()
+        .map(|request| {
+            let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+            body["title"].as_str().unwrap_or_default().to_owned()
+        })
+        .collect()
+}
+
+fn retitled(message: &Message, suffix: &str) -> Message {
+    let json = serde_json::to_value(message).unwrap();
+    let title = json["title"].as_str().unwrap_or_default();
+
+    Message::builder()
+        .title(format!("{title}{suffix}"))
+        .body(json["body"].as_str().unwrap_or_default())
+        .device_key("key1")
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn hooks_mutate_the_message_in_the_order_they_were_added() {
+    let (client, captured) = builder()
+        .before_send(|message: &mut Message| *message = retitled(message, " [a]"))
+        .before_send(|message: &mut Message| *message = retitled(message, " [b]"))
+        .build_capturing()
+        .unwrap();
+
+    client.notify("deploy", "b").await.unwrap();
+
+    assert_eq!(titles(&captured), ["deploy [a] [b]"]);
+}
+
+#[tokio::test]
+async fn the_callers_message_is_left_alone() {
+    let (client, captured) = builder()
+        .before_send(|message: &mut Message| *message = retitled(message, "!"))
+        .build_capturing()
+        .unwrap();
+    let message = Message::titled("t", "b");
+
+    client.send(&message).await.unwrap();
+    client.send(&message).await.unwrap();
+
+    assert_eq!(titles(&captured), ["t!", "t!"]);
+    assert_eq!(message, Message::titled("t", "b"));
+}
+
+#[tokio::test]
+async fn a_skip_stops_the_later_hooks_and_the_send() {
+    let (client, captured) = builder()
+        .before_send(|_: &mut Message| HookControl::Skip)
+        .before_send(|_: &mut Message| -> HookControl { panic!("must not run") })
+        .build_capturing()
+        .unwrap();
+
+    let error = client.notify("t", "b").await.unwrap_err();
+
+    assert!(matches!(error, Error::Skipped), "{error:?}");
+    assert!(captured.requests().is_empty());
+}
+
+#[tokio::test]
+async fn a_failing_hook_fails_the_send_with_its_reason() {
+    let (client, captured) = builder()
+        .before_send(|_: &mut Message| HookControl::Fail("quiet hours".into()))
+        .build_capturing()
+        .unwrap();
+
+    let error = client.notify("t", "b").await.unwrap_err();
+
+    assert!(
+        matches!(&error, Error::HookFailed(reason) if reason == "quiet hours"),
+        "{error:?}"
+    );
+    assert!(captured.requests().is_empty());
+}
+
+#[tokio::test]
+async fn a_panicking_hook_fails_only_that_send() {
+    let (client, captured) = builder()
+        .before_send(|message: &mut Message| {
+            if message.to_query_string().contains("title=boom") {
+                panic!("hook bug");
+            }
+        })
+        .build_capturing()
+        .unwrap();
+
+    let error = client.notify("boom", "b").await.unwrap_err();
+    client.notify("fine", "b").await.unwrap();
+
+    assert!(
+        matches!(&error, Error::HookPanicked(message) if message == "hook bug"),
+        "{error:?}"
+    );
+    assert_eq!(titles(&captured), ["fine"]);
+}
+
+/// Each report's attempts and whether it succeeded.
+type Reports = Arc<Mutex<Vec<(u32, bool)>>>;
+
+fn reporting(reports: &Reports) -> impl Fn(&SendReport<'_>) + Send + Sync + 'static {
+    let reports = reports.clone();
+
+    move |report: &SendReport<'_>| {
+        reports
+            .lock()
+            .unwrap()
+            .push((report.attempts, report.outcome.is_ok()));
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn after_send_fires_once_per_send_after_its_retries() {
+    let reports = Reports::default();
+    let (client, captured) = builder()
+        .retry(RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(100),
+            jitter: false,
+            ..RetryPolicy::default()
+        })
+        .after_send(reporting(&reports))
+        .build_capturing()
+        .unwrap();
+    for n in 2..=4 {
+        captured.fail_send(n);
+    }
+
+    client.notify("ok", "b").await.unwrap();
+    client.notify("down", "b").await.unwrap_err();
+
+    assert_eq!(*reports.lock().unwrap(), [(1, true), (3, false)]);
+    assert_eq!(captured.requests().len(), 4);
+}
+
+#[tokio::test]
+async fn every_after_send_hook_sees_the_same_send() {
+    let first = Reports::default();
+    let second = Reports::default();
+    let (client, _) = builder()
+        .after_send(reporting(&first))
+        .after_send(reporting(&second))
+        .build_capturing()
+        .unwrap();
+
+    client.notify("t", "b").await.unwrap();
+
+    assert_eq!(*first.lock().unwrap(), [(1, true)]);
+    assert_eq!(*second.lock().unwrap(), [(1, true)]);
+}
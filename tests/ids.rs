@@ -0,0 +1,149 @@
+use std::sync::Arc;
+
+use bark::{
+    client::{Client, ClientBuilder},
+    id::{IdGenerator, UuidV4},
+    message::Message,
+    split::SplitOptions,
+    test_util::{Captured, SequentialIdGenerator},
+};
+use serde_json::Value;
+
+fn builder() -> ClientBuilder {
+    Client::builder()
+        .base_url("https://bark.example.com")
+        .device_key("key1")
+        .id_generator(Arc::new(SequentialIdGenerator::new("id-")))
+}
+
+fn sent_ids(captured: &Captured) -> Vec<Value> {
+    captured
+        .requests()
+        .iter()
+        .map(|request| serde_json::from_slice::<Value>(&request.body).unwrap()["id"].clone())
+        .collect()
+}
+
+#[test]
+fn sequential_ids_count_up_from_one() {
+    let ids = SequentialIdGenerator::new("n");
+
+    assert_eq!([ids.generate(), ids.generate()], ["n1", "n2"]);
+}
+
+#[test]
+fn closures_are_generators() {
+    let fixed = || "fixed".to_owned();
+
+    assert_eq!(IdGenerator::generate(&fixed), "fixed");
+}
+
+#[test]
+fn uuids_are_version_4() {
+    let id = UuidV4.generate();
+
+    assert_eq!(id.len(), 36);
+    assert_eq!(id.as_bytes()[14], b'4');
+    assert_ne!(id, UuidV4.generate());
+}
+
+#[cfg(feature = "ulid")]
+#[test]
+fn ulids_sort_by_creation_time() {
+    use bark::id::Ulid;
+
+    let first = Ulid.generate();
+    std::thread::sleep(std::time::Duration::from_millis(2));
+    let second = Ulid.generate();
+
+    assert_eq!(first.len(), 26);
+    assert!(first < second, "{first} {second}");
+}
+
+#[tokio::test]
+async fn with_generated_id_uses_the_generator() {
+    let (client, captured) = builder().build_capturing().unwrap();
+
+    client
+        .message()
+        .body("b")
+        .with_generated_id()
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(sent_ids(&captured), ["id-1"]);
+}
+
+#[tokio::test]
+async fn split_parts_share_a_generated_id() {
+    let (client, captured) = builder().build_capturing().unwrap();
+    let options = SplitOptions {
+        max_bytes: 4,
+        max_parts: 5,
+    };
+
+    client
+        .send_split(&Message::text("aaa\nbbb\n"), options)
+        .await
+        .unwrap();
+
+    assert_eq!(sent_ids(&captured), ["id-1-1", "id-1-2"]);
+}
+
+#[tokio::test]
+async fn request_ids_fall_back_to_the_generator() {
+    let (client, captured) = builder()
+        .request_id_header("x-request-id")
+        .build_capturing()
+        .unwrap();
+
+    client.notify("t", "b").await.unwrap();
+
+    assert_eq!(captured.requests()[0].headers["x-request-id"], "id-1");
+}
+
+#[tokio::test]
+async fn auto_ids_are_sent_and_reported() {
+    let (client, captured) = builder().auto_ids(true).build_capturing().unwrap();
+
+    let outcome = client.send_detailed(&Message::text("b")).await.unwrap();
+
+    assert_eq!(sent_ids(&captured), ["id-1"]);
+    assert_eq!(outcome.id.as_deref(), Some("id-1"));
+}
+
+#[tokio::test]
+async fn an_explicit_id_wins_over_auto_ids() {
+    let (client, captured) = builder().auto_ids(true).build_capturing().unwrap();
+    let message = Message::builder().body("b").id("mine").build().unwrap();
+
+    let outcome = client.send_detailed(&message).await.unwrap();
+
+    assert_eq!(sent_ids(&captured), ["mine"]);
+    assert_eq!(outcome.id.as_deref(), Some("mine"));
+}
+
+#[tokio::test]
+async fn auto_ids_are_off_by_default() {
+    let (client, captured) = builder().build_capturing().unwrap();
+
+    let outcome = client.send_detailed(&Message::text("b")).await.unwrap();
+
+    assert_eq!(sent_ids(&captured), [Value::Null]);
+    assert_eq!(outcome.id, None);
+}
+
+#[tokio::test]
+async fn auto_ids_default_to_uuids() {
+    let (client, _) = Client::builder()
+        .base_url("https://bark.example.com")
+        .device_key("key1")
+        .auto_ids(true)
+        .build_capturing()
+        .unwrap();
+
+    let outcome = client.send_detailed(&Message::text("b")).await.unwrap();
+
+    assert_eq!(outcome.id.unwrap().len(), 36);
+}
@@ -0,0 +1,189 @@
+use bark::{
+    import::ImportWarning,
+    message::{Level, Message},
+};
+
+fn coerced(field: &str, value: &str) -> ImportWarning {
+    ImportWarning::Coerced {
+        field: field.to_owned(),
+        value: value.to_owned(),
+    }
+}
+
+fn dropped(field: &str, reason: &str) -> ImportWarning {
+    ImportWarning::Dropped {
+        field: field.to_owned(),
+        reason: reason.to_owned(),
+    }
+}
+
+/// What `curl -d` scripts commonly post: every value a string.
+#[test]
+fn shell_script_payload() {
+    let (message, warnings) = Message::from_json_lenient(
+        r#"{
+            "title": "backup",
+            "body": "finished",
+            "device_key": "key1",
+            "badge": "3",
+            "isArchive": "1",
+            "call": "yes",
+            "level": "timeSensitive"
+        }"#,
+    )
+    .unwrap();
+
+    let expected = Message::builder()
+        .title("backup")
+        .body("finished")
+        .device_key("key1")
+        .badge(3)
+        .is_archive(true)
+        .call(true)
+        .level(Level::TimeSensitive)
+        .build()
+        .unwrap();
+
+    assert_eq!(message, expected);
+    assert_eq!(
+        warnings,
+        [
+            coerced("badge", "3"),
+            coerced("call", "yes"),
+            coerced("isArchive", "1"),
+        ]
+    );
+}
+
+/// Another SDK's output: snake_case names, real booleans, a key list.
+#[test]
+fn sdk_payload_with_snake_case_names() {
+    let (message, warnings) = Message::from_json_lenient(
+        r#"{
+            "title": "deploy",
+            "markdown": "**done**",
+            "device_keys": ["key1", "key2"],
+            "auto_copy": true,
+            "is_archive": false,
+            "volume": 5,
+            "level": "critical"
+        }"#,
+    )
+    .unwrap();
+
+    let expected = Message::builder()
+        .title("deploy")
+        .markdown("**done**")
+        .device_keys(["key1", "key2"])
+        .auto_copy(true)
+        .volume(5)
+        .level(Level::Critical)
+        .build()
+        .unwrap();
+
+    assert_eq!(message, expected);
+    assert!(warnings.is_empty(), "{warnings:?}");
+}
+
+#[test]
+fn numbers_become_strings_for_text_fields() {
+    let (message, warnings) =
+        Message::from_json_lenient(r#"{"title": 42, "body": true, "group": 7}"#).unwrap();
+
+    assert_eq!(
+        message,
+        Message::builder()
+            .title("42")
+            .body("true")
+            .group("7")
+            .build()
+            .unwrap()
+    );
+    assert_eq!(
+        warnings,
+        [
+            coerced("body", "true"),
+            coerced("group", "7"),
+            coerced("title", "42"),
+        ]
+    );
+}
+
+#[test]
+fn markdown_wins_over_body() {
+    let (message, warnings) =
+        Message::from_json_lenient(r#"{"body": "plain", "markdown": "*rich*"}"#).unwrap();
+
+    assert_eq!(message, Message::markdown("*rich*"));
+    assert_eq!(warnings, [dropped("body", "markdown takes precedence")]);
+}
+
+#[test]
+fn unknown_and_malformed_fields_are_dropped_with_warnings() {
+    let (message, warnings) = Message::from_json_lenient(
+        r#"{
+            "body": "b",
+            "priority": "high",
+            "badge": "lots",
+            "volume": 11,
+            "call": "sometimes",
+            "title": ["a", "b"]
+        }"#,
+    )
+    .unwrap();
+
+    assert_eq!(message, Message::text("b"));
+    assert_eq!(
+        warnings,
+        [
+            dropped("badge", "expected a non-negative integer"),
+            dropped("call", "expected a flag"),
+            dropped("priority", "unknown field"),
+            dropped("title", "expected a string"),
+            dropped("volume", "out of range: got (11), max (10)"),
+        ]
+    );
+}
+
+#[test]
+fn loose_level_names_are_normalized() {
+    let (message, warnings) = Message::from_json_lenient(r#"{"level": "Time-Sensitive"}"#).unwrap();
+
+    assert_eq!(
+        message,
+        Message::builder()
+            .level(Level::TimeSensitive)
+            .build()
+            .unwrap()
+    );
+    assert_eq!(warnings, [coerced("level", "Time-Sensitive")]);
+}
+
+#[test]
+fn unknown_levels_are_kept_as_custom() {
+    let (message, _) = Message::from_json_lenient(r#"{"level": "shortcut"}"#).unwrap();
+
+    assert_eq!(
+        message,
+        Message::builder()
+            .level(Level::custom("shortcut"))
+            .build()
+            .unwrap()
+    );
+}
+
+#[test]
+fn nulls_are_ignored() {
+    let (message, warnings) =
+        Message::from_json_lenient(r#"{"body": "b", "title": null, "badge": null}"#).unwrap();
+
+    assert_eq!(message, Message::text("b"));
+    assert!(warnings.is_empty());
+}
+
+#[test]
+fn results_still_pass_builder_validation() {
+    assert!(Message::from_json_lenient(r#"{"delete": 1}"#).is_err());
+    assert!(Message::from_json_lenient(r#"{"icon": "not a url"}"#).is_err());
+    assert!(Message::from_json_lenient("[1, 2]").is_err());
+}
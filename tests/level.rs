@@ -0,0 +1,131 @@
+use std::str::FromStr;
+
+use bark::message::{Level, Message};
+
+#[test]
+fn built_in_levels_serialize_as_stock_bark_names() {
+    for (level, name) in [
+        (Level::Critical, "critical"),
+        (Level::Active, "active"),
+        (Level::TimeSensitive, "timeSensitive"),
+        (Level::Passive, "passive"),
+    ] {
+        assert_eq!(serde_json::to_value(&level).unwrap(), name);
+        assert_eq!(level.to_string(), name);
+    }
+}
+
+#[test]
+fn custom_levels_pass_through_verbatim() {
+    let message = Message::builder()
+        .body("b")
+        .level(Level::custom("Shortcut-Trigger"))
+        .build()
+        .unwrap();
+
+    let json = serde_json::to_value(&message).unwrap();
+    assert_eq!(json["level"], "Shortcut-Trigger");
+    assert!(message.to_query_string().contains("level=Shortcut-Trigger"));
+}
+
+#[test]
+fn from_str_ignores_case_and_separators() {
+    for name in [
+        "timeSensitive",
+        "time-sensitive",
+        "TIME_SENSITIVE",
+        "Time Sensitive",
+    ] {
+        assert_eq!(
+            Level::from_str(name).unwrap(),
+            Level::TimeSensitive,
+            "{name}"
+        );
+    }
+
+    assert!(Level::from_str("shortcut").is_err());
+}
+
+#[test]
+fn parse_lenient_keeps_unknown_names_as_custom() {
+    assert_eq!(Level::parse_lenient("Critical"), Level::Critical);
+    assert_eq!(Level::parse_lenient("time-sensitive"), Level::TimeSensitive);
+    assert_eq!(
+        Level::parse_lenient("Shortcut-Trigger"),
+        Level::custom("Shortcut-Trigger")
+    );
+}
+
+#[test]
+fn custom_levels_sort_below_built_in_ones() {
+    let mut levels = vec![
+        Level::Critical,
+        Level::custom("b"),
+        Level::Passive,
+        Level::custom("a"),
+        Level::TimeSensitive,
+        Level::Active,
+    ];
+    levels.sort();
+
+    assert_eq!(
+        levels,
+        [
+            Level::custom("a"),
+            Level::custom("b"),
+            Level::Passive,
+            Level::Active,
+            Level::TimeSensitive,
+            Level::Critical,
+        ]
+    );
+    assert!(!Level::custom("x").is_at_least(&Level::Passive));
+}
+
+#[test]
+fn every_level_round_trips_through_its_name() {
+    for level in Level::ALL {
+        assert_eq!(Level::from_str(&level.to_string()).unwrap(), level);
+        assert_eq!(
+            Level::from_str(&level.to_string().to_uppercase()).unwrap(),
+            level
+        );
+    }
+}
+
+#[test]
+fn unknown_names_list_the_valid_ones() {
+    let error = Level::from_str("urgent").unwrap_err();
+
+    assert!(
+        matches!(&error, bark::error::Error::UnknownLevel { given } if given == "urgent"),
+        "{error:?}"
+    );
+    assert_eq!(
+        error.to_string(),
+        r#"Unknown level "urgent", expected one of: critical, timeSensitive, active, passive"#
+    );
+}
+
+#[test]
+fn severity_runs_passive_to_critical() {
+    assert!(Level::Passive < Level::Active);
+    assert!(Level::Active < Level::TimeSensitive);
+    assert!(Level::TimeSensitive < Level::Critical);
+    assert_eq!(
+        Level::ALL.iter().max(),
+        Some(&Level::Critical),
+        "declaration order must not leak into the ordering"
+    );
+}
+
+#[test]
+fn is_at_least_splits_batched_from_immediate() {
+    let immediate: Vec<_> = Level::ALL
+        .into_iter()
+        .filter(|level| level.is_at_least(&Level::TimeSensitive))
+        .collect();
+
+    assert_eq!(immediate, [Level::Critical, Level::TimeSensitive]);
+    assert!(Level::Passive.is_at_least(&Level::Passive));
+}
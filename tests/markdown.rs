@@ -0,0 +1,88 @@
+use bark::{markdown::escape_markdown, message::Message};
+
+const HOSTILE: &str = "**admin**_[click](https://evil.example)_<img src=x>|`rm`";
+
+fn body(message: &Message) -> String {
+    let json = serde_json::to_value(message).unwrap();
+    json["markdown"].as_str().unwrap().to_owned()
+}
+
+#[test]
+fn escapes_every_markdown_special_character() {
+    assert_eq!(
+        escape_markdown(r"\ ` * _ ~ [ ] ( ) | < > & # !"),
+        r"\\ \` \* \_ \~ \[ \] \( \) \| \< \> \& \# \!"
+    );
+    assert_eq!(escape_markdown("plain text 123"), "plain text 123");
+}
+
+#[test]
+fn a_hostile_username_renders_literally() {
+    let message = Message::builder()
+        .markdown_safe()
+        .append("New login by ")
+        .append(HOSTILE)
+        .kv("user", HOSTILE)
+        .build()
+        .unwrap();
+
+    let escaped = escape_markdown(HOSTILE);
+    assert_eq!(
+        body(&message),
+        format!("New login by {escaped}\n- **user**: {escaped}")
+    );
+    assert!(!body(&message).contains("[click]("));
+}
+
+#[test]
+fn literal_markdown_is_kept_as_written() {
+    let message = Message::builder()
+        .markdown_safe()
+        .markdown("## Deploy *done*\n")
+        .kv("by", "a_b")
+        .append("\n[details](https://ci.example)")
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        body(&message),
+        "## Deploy *done*\n- **by**: a\\_b\n\\[details\\]\\(https://ci.example\\)"
+    );
+}
+
+#[test]
+fn without_markdown_safe_appended_text_is_raw() {
+    let message = Message::builder()
+        .markdown("")
+        .kv("user", "a_b")
+        .append(" **bold**")
+        .build()
+        .unwrap();
+
+    assert_eq!(body(&message), "- **user**: a_b **bold**");
+}
+
+#[test]
+fn plaintext_kv_lines_have_no_markup() {
+    let message = Message::builder()
+        .kv("host", "db-1")
+        .kv("disk", "95%")
+        .build()
+        .unwrap();
+
+    let json = serde_json::to_value(&message).unwrap();
+    assert_eq!(json["body"], "host: db-1\ndisk: 95%");
+}
+
+#[test]
+fn markdown_safe_marks_the_body_markdown() {
+    let message = Message::builder()
+        .markdown_safe()
+        .append("x")
+        .build()
+        .unwrap();
+
+    let json = serde_json::to_value(&message).unwrap();
+    assert_eq!(json["markdown"], "x");
+    assert!(json.get("body").is_none());
+}
@@ -0,0 +1,177 @@
+use bark::{
+    client::Client,
+    message::{Level, Message},
+    test_util::{
+        ReceivedPush,
+        matchers::{push_matching, push_to_device, push_with_level, push_with_title},
+    },
+};
+use wiremock::{
+    Mock, MockServer, ResponseTemplate,
+    matchers::{method, path},
+};
+
+const SUCCESS: &str = r#"{"code":200,"message":"success","timestamp":1700000000}"#;
+
+async fn expect(server: &MockServer, matcher: impl wiremock::Match + 'static) {
+    Mock::given(method("POST"))
+        .and(path("/push"))
+        .and(matcher)
+        .respond_with(ResponseTemplate::new(200).set_body_string(SUCCESS))
+        .expect(1)
+        .mount(server)
+        .await;
+}
+
+fn client(server: &MockServer) -> Client {
+    Client::builder()
+        .base_url(server.uri())
+        .device_key("key1")
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn title_level_and_device_match_what_the_client_sends() {
+    let server = MockServer::start().await;
+    expect(&server, push_with_title("Disk full")).await;
+    expect(&server, push_with_level(Level::TimeSensitive)).await;
+    expect(&server, push_to_device("key2")).await;
+
+    let client = client(&server);
+    client.notify("Disk full", "95% used").await.unwrap();
+    client
+        .message()
+        .body("urgent")
+        .level(Level::TimeSensitive)
+        .send()
+        .await
+        .unwrap();
+    client
+        .message()
+        .body("other phone")
+        .device_key("key2")
+        .send()
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn push_matching_sees_every_field_the_client_sends() {
+    let server = MockServer::start().await;
+    expect(
+        &server,
+        push_matching(|push| {
+            push.title.as_deref() == Some("t")
+                && push.subtitle.as_deref() == Some("s")
+                && push.body.as_deref() == Some("b")
+                && push.device_keys == ["key1"]
+                && push.level == Some(Level::Critical)
+                && push.volume == Some(5)
+                && push.badge == Some(3)
+                && push.call
+                && push.auto_copy
+                && push.copy.as_deref() == Some("copied")
+                && push.sound.as_deref() == Some("minuet")
+                && push.icon.as_deref() == Some("https://example.com/icon.png")
+                && push.group.as_deref() == Some("g")
+                && push.is_archive
+                && push.url.as_deref() == Some("https://example.com")
+                && push.action.as_deref() == Some("none")
+                && push.id.as_deref() == Some("n-1")
+        }),
+    )
+    .await;
+
+    client(&server)
+        .message()
+        .title("t")
+        .subtitle("s")
+        .body("b")
+        .level(Level::Critical)
+        .volume(5)
+        .badge(3)
+        .call(true)
+        .auto_copy(true)
+        .copy("copied")
+        .sound("minuet")
+        .icon("https://example.com/icon.png")
+        .group("g")
+        .is_archive(true)
+        .url("https://example.com")
+        .action("none")
+        .id("n-1")
+        .send()
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn markdown_bodies_arrive_under_markdown() {
+    let server = MockServer::start().await;
+    expect(
+        &server,
+        push_matching(|push| push.markdown.as_deref() == Some("# hi") && push.body.is_none()),
+    )
+    .await;
+
+    client(&server)
+        .send(&Message::markdown("# hi"))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn non_matching_pushes_are_not_matched() {
+    let server = MockServer::start().await;
+    expect(&server, push_with_title("expected")).await;
+
+    Mock::given(method("POST"))
+        .and(path("/push"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&server)
+        .await;
+
+    let client = client(&server);
+    assert!(client.notify("unexpected", "body").await.is_err());
+    client.notify("expected", "body").await.unwrap();
+}
+
+#[test]
+fn received_push_accepts_a_single_device_key() {
+    let push = ReceivedPush::from_slice(br#"{"device_key":"key1","body":"b"}"#).unwrap();
+    assert_eq!(push.device_keys, ["key1"]);
+
+    let push = ReceivedPush::from_slice(br#"{"device_keys":["key1","key2"]}"#).unwrap();
+    assert_eq!(push.device_keys, ["key1", "key2"]);
+}
+
+#[test]
+fn received_push_reads_every_flag_encoding() {
+    for (flag, expected) in [
+        ("1", true),
+        ("0", false),
+        ("true", true),
+        ("false", false),
+        (r#""1""#, true),
+        (r#""true""#, true),
+        (r#""0""#, false),
+        (r#""""#, false),
+    ] {
+        let json = format!(r#"{{"call":{flag},"autoCopy":{flag},"isArchive":{flag}}}"#);
+        let push = ReceivedPush::from_slice(json.as_bytes()).unwrap();
+
+        assert_eq!(
+            (push.call, push.auto_copy, push.is_archive),
+            (expected, expected, expected),
+            "{flag}"
+        );
+    }
+
+    assert!(ReceivedPush::from_slice(br#"{"call":"maybe"}"#).is_none());
+}
+
+#[test]
+fn received_push_rejects_non_json() {
+    assert!(ReceivedPush::from_slice(b"title=hi").is_none());
+}
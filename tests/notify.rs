@@ -0,0 +1,86 @@
+use bark::{client::Client, error::Error, message::Level};
+use serde_json::{Value, json};
+use wiremock::{
+    Mock, MockServer, ResponseTemplate,
+    matchers::{body_json, method, path},
+};
+
+const SUCCESS: &str = r#"{"code":200,"message":"success","timestamp":1}"#;
+
+async fn expecting(body: Value) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/push"))
+        .and(body_json(body))
+        .respond_with(ResponseTemplate::new(200).set_body_string(SUCCESS))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    server
+}
+
+#[tokio::test]
+async fn notify_sends_to_the_default_devices() {
+    let server = expecting(json!({
+        "title": "disk full",
+        "body": "on db-1",
+        "device_keys": ["key1"],
+    }))
+    .await;
+    let client = Client::new(server.uri()).with_device_key("key1");
+
+    let response = client.notify("disk full", "on db-1").await.unwrap();
+
+    assert!(response.is_success());
+}
+
+#[tokio::test]
+async fn notify_markdown_sends_a_markdown_body() {
+    let server = expecting(json!({
+        "title": "disk full",
+        "markdown": "**db-1**",
+        "device_keys": ["key1"],
+    }))
+    .await;
+    let client = Client::new(server.uri()).with_device_key("key1");
+
+    client
+        .notify_markdown("disk full", "**db-1**")
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn notify_applies_the_client_defaults() {
+    let server = expecting(json!({
+        "title": "t",
+        "body": "b",
+        "device_keys": ["key1"],
+        "group": "ops",
+        "level": "critical",
+        "icon": "https://example.com/critical.png",
+    }))
+    .await;
+    let client = Client::builder()
+        .base_url(server.uri())
+        .device_key("key1")
+        .icon_for_level(Level::Critical, "https://example.com/critical.png")
+        .build()
+        .unwrap()
+        .with_group("ops")
+        .with_level(Level::Critical);
+
+    client.notify("t", "b").await.unwrap();
+}
+
+#[tokio::test]
+async fn notify_without_default_devices_sends_nothing() {
+    let server = MockServer::start().await;
+    let client = Client::new(server.uri());
+
+    let error = client.notify("t", "b").await.unwrap_err();
+
+    assert!(matches!(error, Error::MissingDeviceKey), "{error:?}");
+    assert!(server.received_requests().await.unwrap().is_empty());
+}
@@ -0,0 +1,160 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use bark::{
+    client::{Client, ClientBuilder},
+    hook::{FailureKind, SendReport},
+    message::Message,
+    retry::RetryPolicy,
+};
+use http::StatusCode;
+
+/// What an `on_result` observer saw of one send.
+#[derive(Debug, PartialEq)]
+struct Seen {
+    attempts: u32,
+    elapsed: Duration,
+    device_keys: usize,
+    code: Option<i64>,
+    failure: Option<FailureKind>,
+    id: Option<String>,
+    group: Option<String>,
+}
+
+fn recording(seen: &Arc<Mutex<Vec<Seen>>>) -> impl Fn(&SendReport<'_>) + Send + Sync + 'static {
+    let seen = seen.clone();
+
+    move |report: &SendReport<'_>| {
+        seen.lock().unwrap().push(Seen {
+            attempts: report.attempts,
+            elapsed: report.elapsed,
+            device_keys: report.summary.device_keys,
+            code: report.code(),
+            failure: report.failure(),
+            id: report.summary.id.clone(),
+            group: report.summary.group.clone(),
+        });
+    }
+}
+
+fn builder() -> ClientBuilder {
+    Client::builder()
+        .base_url("https://bark.example.com")
+        .device_keys(["key1", "key2"])
+        .retry(RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(100),
+            jitter: false,
+            ..RetryPolicy::default()
+        })
+}
+
+fn tagged() -> Message {
+    Message::builder()
+        .body("b")
+        .id("n1")
+        .group("deploys")
+        .build()
+        .unwrap()
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_retried_success_is_seen_once_with_its_attempts() {
+    let seen = Arc::default();
+    let (client, captured) = builder()
+        .on_result(recording(&seen))
+        .build_capturing()
+        .unwrap();
+    captured.fail_send(1);
+    captured.transport_error(2, "connection reset");
+
+    client.send(&tagged()).await.unwrap();
+
+    assert_eq!(
+        *seen.lock().unwrap(),
+        [Seen {
+            attempts: 3,
+            elapsed: Duration::from_millis(300),
+            device_keys: 2,
+            code: Some(200),
+            failure: None,
+            id: Some("n1".to_owned()),
+            group: Some("deploys".to_owned()),
+        }]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn failures_are_seen_with_their_classification() {
+    let seen = Arc::default();
+    let (client, captured) = builder()
+        .on_result(recording(&seen))
+        .build_capturing()
+        .unwrap();
+    captured.respond_with(
+        1,
+        StatusCode::BAD_REQUEST,
+        r#"{"code":400,"message":"failed to get device token","timestamp":1}"#,
+    );
+    for n in 2..=4 {
+        captured.transport_error(n, "connection refused");
+    }
+
+    client.send(&tagged()).await.unwrap_err();
+    client.send(&tagged()).await.unwrap_err();
+
+    let seen = seen.lock().unwrap();
+    let outcomes: Vec<_> = seen
+        .iter()
+        .map(|seen| (seen.attempts, seen.code, seen.failure))
+        .collect();
+    assert_eq!(
+        outcomes,
+        [
+            (1, Some(400), Some(FailureKind::Server)),
+            (3, None, Some(FailureKind::Transport)),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn skipped_and_invalid_sends_are_seen_too() {
+    let seen = Arc::default();
+    let (client, _) = Client::builder()
+        .base_url("https://bark.example.com")
+        .before_send(|_: &mut Message| bark::hook::HookControl::Skip)
+        .on_result(recording(&seen))
+        .build_capturing()
+        .unwrap();
+
+    client
+        .send_to(["key1"], &Message::text("b"))
+        .await
+        .unwrap_err();
+
+    let failures: Vec<_> = seen
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|seen| seen.failure)
+        .collect();
+    assert_eq!(failures, [Some(FailureKind::Skipped)]);
+}
+
+#[tokio::test]
+async fn a_panicking_hook_does_not_change_the_result() {
+    let seen = Arc::default();
+    let (client, captured) = builder()
+        .after_send(|_: &SendReport<'_>| panic!("metrics backend down"))
+        .on_result(recording(&seen))
+        .build_capturing()
+        .unwrap();
+
+    let response = client.send(&tagged()).await.unwrap();
+
+    assert!(response.is_success());
+    assert_eq!(seen.lock().unwrap().len(), 1);
+    assert_eq!(captured.requests().len(), 1);
+}
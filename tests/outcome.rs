@@ -0,0 +1,86 @@
+use std::time::Duration;
+
+use bark::{client::Client, message::Message, retry::RetryPolicy};
+use wiremock::{
+    Mock, MockServer, ResponseTemplate,
+    matchers::{method, path},
+};
+
+const SUCCESS: &str = r#"{"code":200,"message":"success","timestamp":1}"#;
+const LATENCY: Duration = Duration::from_millis(150);
+
+#[tokio::test]
+async fn the_outcome_carries_the_response_and_latency() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/push"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string(SUCCESS)
+                .set_delay(LATENCY),
+        )
+        .mount(&server)
+        .await;
+    let client = Client::new(server.uri()).with_device_key("key1");
+
+    let outcome = client.send_detailed(&Message::text("b")).await.unwrap();
+
+    assert!(outcome.response.is_success());
+    assert_eq!(outcome.attempts, 1);
+    assert!(outcome.elapsed >= LATENCY, "{:?}", outcome.elapsed);
+    assert_eq!(outcome.request_id, None);
+    assert_eq!(outcome.server.as_deref(), Some(server.uri().as_str()));
+}
+
+#[tokio::test]
+async fn retries_count_toward_attempts_and_elapsed() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/push"))
+        .respond_with(ResponseTemplate::new(500).set_delay(LATENCY))
+        .up_to_n_times(1)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/push"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string(SUCCESS)
+                .set_delay(LATENCY),
+        )
+        .mount(&server)
+        .await;
+    let client = Client::builder()
+        .base_url(server.uri())
+        .device_key("key1")
+        .retry(RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(10),
+            jitter: false,
+            ..RetryPolicy::default()
+        })
+        .build()
+        .unwrap();
+
+    let outcome = client.send_detailed(&Message::text("b")).await.unwrap();
+
+    assert_eq!(outcome.attempts, 2);
+    assert!(
+        outcome.elapsed >= 2 * LATENCY + Duration::from_millis(10),
+        "{:?}",
+        outcome.elapsed
+    );
+}
+
+#[tokio::test]
+async fn send_returns_the_same_response() {
+    let (client, _) = Client::capturing();
+    let client = client.with_device_key("key1");
+
+    let response = client.send(&Message::text("b")).await.unwrap();
+    let outcome = client.send_detailed(&Message::text("b")).await.unwrap();
+
+    assert_eq!(response.code, outcome.response.code);
+    assert_eq!(response.message, outcome.response.message);
+}
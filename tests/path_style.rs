@@ -0,0 +1,170 @@
+use std::{sync::Arc, time::Duration};
+
+use bark::{
+    client::{ApiStyle, Client, ClientBuilder},
+    error::Error,
+    message::Message,
+    retry::RetryPolicy,
+    test_util::{Captured, ManualClock},
+};
+use http::StatusCode;
+
+const KEY_A: &str = "aaaaSecretKeyOne";
+const KEY_B: &str = "bbbbSecretKeyTwo";
+
+fn builder() -> ClientBuilder {
+    Client::builder()
+        .base_url("https://bark.example.com")
+        .api_style(ApiStyle::Path)
+        .device_keys([KEY_A, KEY_B])
+}
+
+fn retrying() -> ClientBuilder {
+    builder()
+        .clock(Arc::new(ManualClock::default()))
+        .retry(RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(1),
+            jitter: false,
+            total_deadline: None,
+        })
+}
+
+/// The device key each request went to, from its first path segment.
+fn keys(captured: &Captured) -> Vec<String> {
+    captured
+        .requests()
+        .iter()
+        .map(|request| {
+            let host = request.url.host_str().unwrap().to_owned();
+            let key = request.url.path_segments().unwrap().next().unwrap();
+            format!("{host}/{key}")
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn logs_see_the_url_with_the_key_shortened() {
+    let (client, captured) = builder().build_capturing().unwrap();
+
+    client.notify("t", "b").await.unwrap();
+
+    for request in captured.requests() {
+        let redacted = request.redacted_url.unwrap().to_string();
+        assert!(request.url.as_str().contains("SecretKey"));
+        assert!(!redacted.contains("SecretKey"), "{redacted}");
+        assert!(redacted.ends_with("/t/b"), "{redacted}");
+    }
+}
+
+#[tokio::test]
+async fn errors_do_not_repeat_the_key() {
+    let (client, captured) = Client::builder()
+        .base_url("https://bark.example.com")
+        .api_style(ApiStyle::Path)
+        .device_key(KEY_A)
+        .build_capturing()
+        .unwrap();
+    captured.respond_with(1, StatusCode::BAD_GATEWAY, "upstream down");
+    captured.respond_with(
+        2,
+        StatusCode::BAD_REQUEST,
+        format!(r#"{{"code":400,"message":"no device for {KEY_A}","timestamp":0}}"#),
+    );
+
+    let status = client.notify("t", "b").await.unwrap_err();
+    let server = client.notify("t", "b").await.unwrap_err();
+
+    assert!(matches!(status, Error::HttpStatus { .. }), "{status:?}");
+    assert!(matches!(server, Error::Server { .. }), "{server:?}");
+    for error in [status, server] {
+        let text = format!("{error} {error:?}");
+        assert!(!text.contains("SecretKey"), "{text}");
+    }
+}
+
+#[tokio::test]
+async fn each_key_reports_its_own_result() {
+    let (client, captured) = builder().build_capturing().unwrap();
+    captured.respond_with(
+        2,
+        StatusCode::BAD_REQUEST,
+        r#"{"code":400,"message":"failed to get device token","timestamp":0}"#,
+    );
+
+    let error = client.notify("t", "b").await.unwrap_err();
+
+    let Error::DeviceKeysFailed { delivered, failed } = &error else {
+        panic!("{error:?}");
+    };
+    assert_eq!(delivered, &[KEY_A]);
+    assert_eq!(failed.len(), 1);
+    assert_eq!(failed[0].0, KEY_B);
+    assert!(matches!(failed[0].1, Error::Server { code: 400, .. }));
+    assert!(
+        error.to_string().starts_with("1 of 2 device keys failed"),
+        "{error}"
+    );
+}
+
+#[tokio::test]
+async fn a_retry_resends_only_the_failed_key() {
+    let (client, captured) = retrying().build_capturing().unwrap();
+    captured.fail_send(2);
+
+    let outcome = client.send_detailed(&Message::text("b")).await.unwrap();
+
+    assert_eq!(outcome.attempts, 2);
+    assert_eq!(
+        keys(&captured),
+        [
+            format!("bark.example.com/{KEY_A}"),
+            format!("bark.example.com/{KEY_B}"),
+            format!("bark.example.com/{KEY_B}"),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn failover_resends_only_the_failed_key() {
+    let (client, captured) = builder()
+        .fallback_url("https://backup.example.com")
+        .build_capturing()
+        .unwrap();
+    captured.fail_send(2);
+
+    client.notify("t", "b").await.unwrap();
+
+    assert_eq!(
+        keys(&captured),
+        [
+            format!("bark.example.com/{KEY_A}"),
+            format!("bark.example.com/{KEY_B}"),
+            format!("backup.example.com/{KEY_B}"),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn a_key_failing_every_attempt_is_reported_with_the_delivered_ones() {
+    let (client, captured) = retrying().build_capturing().unwrap();
+    for n in [2, 3, 4] {
+        captured.fail_send(n);
+    }
+
+    let error = client.send(&Message::text("b")).await.unwrap_err();
+
+    let Error::RetriesExhausted {
+        attempts: 3,
+        source,
+    } = &error
+    else {
+        panic!("{error:?}");
+    };
+    assert!(
+        matches!(&**source, Error::DeviceKeysFailed { delivered, failed } if delivered == &[KEY_A] && failed[0].0 == KEY_B),
+        "{source:?}"
+    );
+    assert_eq!(captured.requests().len(), 4);
+}
@@ -0,0 +1,100 @@
+use bark::{
+    client::{Client, ClientBuilder},
+    error::Error,
+    message::Message,
+};
+
+/// `{"markdown":""}` around the body.
+const ENVELOPE: usize = 15;
+
+fn builder() -> ClientBuilder {
+    Client::builder()
+        .base_url("https://bark.example.com")
+        .device_key("key1")
+}
+
+fn markdown_of_size(size: usize) -> Message {
+    let message = Message::markdown("#".repeat(size - ENVELOPE));
+    assert_eq!(message.estimated_size(), size);
+    message
+}
+
+#[test]
+fn the_estimate_is_the_json_length_without_device_keys() {
+    let message = Message::builder()
+        .title("disk full")
+        .markdown("**db-1** at 99%")
+        .device_keys(["a-rather-long-device-key", "another-long-device-key"])
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        message.estimated_size(),
+        r#"{"title":"disk full","markdown":"**db-1** at 99%"}"#.len()
+    );
+}
+
+#[test]
+fn escapes_count_toward_the_estimate() {
+    assert_eq!(
+        Message::text("\"\n").estimated_size(),
+        r#"{"body":"\"\n"}"#.len()
+    );
+    assert_eq!(
+        Message::text("é").estimated_size(),
+        r#"{"body":""}"#.len() + 2
+    );
+}
+
+#[tokio::test]
+async fn a_multi_kilobyte_markdown_body_is_rejected_unsent() {
+    let (client, captured) = builder().build_capturing().unwrap();
+    let message = Message::markdown("| col | col |\n|---|---|\n".repeat(300));
+
+    let error = client.send(&message).await.unwrap_err();
+
+    assert!(
+        matches!(error, Error::PayloadTooLarge { size, limit: Client::DEFAULT_PAYLOAD_LIMIT } if size == message.estimated_size()),
+        "{error:?}"
+    );
+    assert!(captured.requests().is_empty());
+}
+
+#[tokio::test]
+async fn a_payload_exactly_at_the_limit_is_sent() {
+    let (client, captured) = builder().build_capturing().unwrap();
+
+    client
+        .send(&markdown_of_size(Client::DEFAULT_PAYLOAD_LIMIT))
+        .await
+        .unwrap();
+    let error = client
+        .send(&markdown_of_size(Client::DEFAULT_PAYLOAD_LIMIT + 1))
+        .await
+        .unwrap_err();
+
+    assert!(
+        matches!(
+            error,
+            Error::PayloadTooLarge {
+                size: 4097,
+                limit: 4096
+            }
+        ),
+        "{error:?}"
+    );
+    assert_eq!(captured.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn the_limit_can_be_raised_for_self_hosted_servers() {
+    let (client, _) = builder().payload_limit(8192).build_capturing().unwrap();
+
+    client.send(&markdown_of_size(8192)).await.unwrap();
+    let error = client.send(&markdown_of_size(8193)).await.unwrap_err();
+
+    assert_eq!(
+        error.to_string(),
+        "Payload too large: got (8193) bytes, limit (8192)"
+    );
+}
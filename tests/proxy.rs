@@ -0,0 +1,145 @@
+#![cfg(feature = "reqwest")]
+
+use std::{
+    net::TcpListener,
+    sync::{Mutex, PoisonError},
+};
+
+use bark::{
+    client::{Client, ClientBuilder},
+    error::Error,
+    message::Message,
+};
+use wiremock::{
+    Mock, MockServer, ResponseTemplate,
+    matchers::{header, method, path},
+};
+
+const SUCCESS: &str = r#"{"code":200,"message":"success","timestamp":1}"#;
+
+/// A host only the proxy can reach, since it never resolves.
+const TARGET: &str = "http://bark.invalid";
+
+/// Clients read proxy variables when built, and one test sets them.
+static ENV: Mutex<()> = Mutex::new(());
+
+fn build(builder: ClientBuilder) -> Client {
+    let _lock = ENV.lock().unwrap_or_else(PoisonError::into_inner);
+
+    builder.device_key("key1").build().unwrap()
+}
+
+fn down() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    drop(listener);
+
+    format!("http://{address}")
+}
+
+/// A mock that answers pushes for [`TARGET`], the way a forwarding proxy would.
+async fn forwarding() -> MockServer {
+    let proxy = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/push"))
+        .and(header("host", "bark.invalid"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(SUCCESS))
+        .mount(&proxy)
+        .await;
+
+    proxy
+}
+
+#[tokio::test]
+async fn pushes_go_through_the_proxy() {
+    let proxy = forwarding().await;
+    let client = build(Client::builder().base_url(TARGET).proxy(proxy.uri()));
+
+    client.send(&Message::text("b")).await.unwrap();
+
+    let requests = proxy.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].headers.get("proxy-authorization"), None);
+}
+
+#[tokio::test]
+async fn proxy_credentials_are_sent_to_the_proxy() {
+    let proxy = forwarding().await;
+    let client = build(
+        Client::builder()
+            .base_url(TARGET)
+            .proxy(proxy.uri())
+            .proxy_auth("proxyuser", "proxypass"),
+    );
+
+    client.send(&Message::text("b")).await.unwrap();
+
+    let requests = proxy.received_requests().await.unwrap();
+    // base64 of "proxyuser:proxypass"
+    assert_eq!(
+        requests[0].headers["proxy-authorization"],
+        "Basic cHJveHl1c2VyOnByb3h5cGFzcw=="
+    );
+}
+
+#[tokio::test]
+async fn no_proxy_ignores_the_environment() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/push"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(SUCCESS))
+        .mount(&server)
+        .await;
+
+    let client = {
+        let _lock = ENV.lock().unwrap_or_else(PoisonError::into_inner);
+        let proxy = down();
+        // SAFETY: the lock keeps the other tests here from building clients meanwhile.
+        unsafe {
+            std::env::set_var("HTTP_PROXY", &proxy);
+            std::env::set_var("http_proxy", &proxy);
+        }
+        let client = Client::builder()
+            .base_url(server.uri())
+            .device_key("key1")
+            .no_proxy()
+            .build();
+        // SAFETY: as above.
+        unsafe {
+            std::env::remove_var("HTTP_PROXY");
+            std::env::remove_var("http_proxy");
+        }
+        client.unwrap()
+    };
+
+    client.send(&Message::text("b")).await.unwrap();
+
+    assert_eq!(server.received_requests().await.unwrap().len(), 1);
+}
+
+#[test]
+fn unsupported_proxy_schemes_fail_the_build() {
+    let error = Client::builder()
+        .base_url(TARGET)
+        .proxy("ftp://proxy.internal:21")
+        .build()
+        .unwrap_err();
+
+    assert!(
+        matches!(error, Error::InvalidUrl { field: "proxy", .. }),
+        "{error:?}"
+    );
+}
+
+#[cfg(feature = "socks")]
+#[test]
+fn socks5_proxies_are_accepted() {
+    for proxy in ["socks5://127.0.0.1:1080", "socks5h://127.0.0.1:1080"] {
+        let client = build(Client::builder().base_url(TARGET).proxy(proxy));
+
+        assert_eq!(
+            client.effective_config().transport.proxy.as_deref(),
+            Some(proxy)
+        );
+    }
+}
@@ -0,0 +1,77 @@
+use bark::message::{Level, Message};
+use url::form_urlencoded;
+
+fn pairs(query: &str) -> Vec<(String, String)> {
+    form_urlencoded::parse(query.as_bytes())
+        .into_owned()
+        .collect()
+}
+
+#[test]
+fn fields_become_pairs_in_order() {
+    let message = Message::builder()
+        .title("t")
+        .subtitle("s")
+        .body("b")
+        .level(Level::Critical)
+        .volume(5)
+        .badge(2)
+        .call(true)
+        .group("ops")
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        message.to_query_string(),
+        "title=t&subtitle=s&body=b&level=critical&volume=5&badge=2&call=1&group=ops"
+    );
+}
+
+#[test]
+fn markdown_bodies_use_the_markdown_name() {
+    let query = Message::markdown("**disk** _full_").to_query_string();
+
+    assert_eq!(query, "markdown=**disk**+_full_");
+    assert_eq!(
+        pairs(&query),
+        [("markdown".to_owned(), "**disk** _full_".to_owned())]
+    );
+}
+
+#[test]
+fn unicode_is_percent_encoded() {
+    let query = Message::titled("磁盘已满", "🔥 café").to_query_string();
+
+    assert_eq!(
+        query,
+        "title=%E7%A3%81%E7%9B%98%E5%B7%B2%E6%BB%A1&body=%F0%9F%94%A5+caf%C3%A9"
+    );
+    assert_eq!(
+        pairs(&query),
+        [
+            ("title".to_owned(), "磁盘已满".to_owned()),
+            ("body".to_owned(), "🔥 café".to_owned()),
+        ]
+    );
+}
+
+#[test]
+fn reserved_characters_survive_a_round_trip() {
+    let body = "a&b=c+d?e#f/g%h";
+    let query = Message::text(body).to_query_string();
+
+    assert_eq!(query, "body=a%26b%3Dc%2Bd%3Fe%23f%2Fg%25h");
+    assert_eq!(pairs(&query), [("body".to_owned(), body.to_owned())]);
+}
+
+#[test]
+fn device_keys_and_unset_fields_are_left_out() {
+    let message = Message::builder()
+        .body("b")
+        .device_keys(["key1", "key2"])
+        .call(false)
+        .build()
+        .unwrap();
+
+    assert_eq!(message.to_query_string(), "body=b");
+}
@@ -0,0 +1,194 @@
+use bark::{
+    client::Client,
+    error::Error,
+    message::{Level, Message},
+    queue::QueueOptions,
+    test_util::Captured,
+};
+use serde_json::Value;
+
+const COALESCING: QueueOptions = QueueOptions {
+    coalesce_by_group: true,
+    coalesce_critical: false,
+    count_superseded: true,
+};
+
+fn client() -> (Client, Captured) {
+    Client::builder()
+        .base_url("https://bark.example.com")
+        .device_key("key1")
+        .build_capturing()
+        .unwrap()
+}
+
+fn update(group: &str, body: &str) -> Message {
+    Message::builder().group(group).body(body).build().unwrap()
+}
+
+fn bodies(captured: &Captured) -> Vec<Value> {
+    captured
+        .messages()
+        .iter()
+        .map(|message| serde_json::to_value(message).unwrap()["body"].clone())
+        .collect()
+}
+
+#[tokio::test]
+async fn only_the_latest_update_per_group_is_delivered() {
+    let (client, captured) = client();
+    let queue = client.queue(COALESCING);
+
+    for n in 1..=40 {
+        queue
+            .push(update("status", &format!("degraded #{n}")))
+            .unwrap();
+    }
+    queue.push(update("deploys", "v2 rolled out")).unwrap();
+    assert_eq!(queue.len(), 2);
+    assert_eq!(queue.superseded(), 39);
+
+    queue.close();
+    queue.run().await;
+
+    assert_eq!(
+        bodies(&captured),
+        ["degraded #40\n…and 39 earlier updates", "v2 rolled out"]
+    );
+}
+
+#[tokio::test]
+async fn the_latest_update_takes_the_place_at_the_back() {
+    let (client, captured) = client();
+    let queue = client.queue(COALESCING);
+
+    queue.push(update("status", "degraded")).unwrap();
+    queue.push(update("deploys", "v2 rolled out")).unwrap();
+    queue.push(update("status", "recovered")).unwrap();
+    queue.close();
+    queue.run().await;
+
+    assert_eq!(
+        bodies(&captured),
+        ["v2 rolled out", "recovered\n…and 1 earlier update"]
+    );
+}
+
+#[tokio::test]
+async fn critical_messages_are_exempt_by_default() {
+    let critical = |body: &str| {
+        Message::builder()
+            .group("status")
+            .level(Level::Critical)
+            .body(body)
+            .build()
+            .unwrap()
+    };
+    let (client, captured) = client();
+    let queue = client.queue(COALESCING);
+
+    queue.push(critical("db down")).unwrap();
+    queue.push(critical("cache down")).unwrap();
+    queue.push(update("status", "degraded")).unwrap();
+    queue.close();
+    queue.run().await;
+
+    assert_eq!(bodies(&captured), ["db down", "cache down", "degraded"]);
+
+    let (client, captured) = self::client();
+    let queue = client.queue(QueueOptions {
+        coalesce_critical: true,
+        ..COALESCING
+    });
+
+    queue.push(critical("db down")).unwrap();
+    queue.push(critical("cache down")).unwrap();
+    queue.close();
+    queue.run().await;
+
+    assert_eq!(bodies(&captured), ["cache down\n…and 1 earlier update"]);
+}
+
+#[tokio::test]
+async fn the_client_default_group_counts() {
+    let (client, captured) = client();
+    let client = client.with_group("status");
+    let queue = client.queue(COALESCING);
+
+    queue.push(Message::text("degraded")).unwrap();
+    queue.push(Message::text("recovered")).unwrap();
+    queue.close();
+    queue.run().await;
+
+    assert_eq!(bodies(&captured), ["recovered\n…and 1 earlier update"]);
+}
+
+#[tokio::test]
+async fn messages_without_a_group_are_all_delivered() {
+    let (client, captured) = client();
+    let queue = client.queue(COALESCING);
+
+    queue.push(Message::text("one")).unwrap();
+    queue.push(Message::text("two")).unwrap();
+    queue.close();
+    queue.run().await;
+
+    assert_eq!(bodies(&captured), ["one", "two"]);
+}
+
+#[tokio::test]
+async fn without_coalescing_every_message_is_delivered() {
+    let (client, captured) = client();
+    let queue = client.queue(QueueOptions::default());
+
+    queue.push(update("status", "degraded")).unwrap();
+    queue.push(update("status", "recovered")).unwrap();
+    queue.close();
+    queue.run().await;
+
+    assert_eq!(bodies(&captured), ["degraded", "recovered"]);
+}
+
+#[tokio::test]
+async fn the_counter_is_optional() {
+    let (client, captured) = client();
+    let queue = client.queue(QueueOptions {
+        count_superseded: false,
+        ..COALESCING
+    });
+
+    queue.push(update("status", "degraded")).unwrap();
+    queue.push(update("status", "recovered")).unwrap();
+    queue.close();
+    queue.run().await;
+
+    assert_eq!(bodies(&captured), ["recovered"]);
+}
+
+#[tokio::test]
+async fn run_waits_for_pushes_until_closed() {
+    let (client, captured) = client();
+    let queue = client.queue(COALESCING);
+    let running = tokio::spawn({
+        let queue = queue.clone();
+        async move { queue.run().await }
+    });
+
+    queue.push(Message::text("one")).unwrap();
+    tokio::task::yield_now().await;
+    queue.push(Message::text("two")).unwrap();
+    queue.close();
+    running.await.unwrap();
+
+    assert_eq!(bodies(&captured), ["one", "two"]);
+}
+
+#[test]
+fn a_closed_queue_refuses_pushes() {
+    let (client, _) = client();
+    let queue = client.queue(COALESCING);
+
+    queue.close();
+    let error = queue.push(Message::text("b")).unwrap_err();
+
+    assert!(matches!(error, Error::QueueClosed), "{error:?}");
+}
@@ -0,0 +1,273 @@
+use std::{sync::Arc, time::Duration};
+
+use bark::{
+    client::{Client, ClientBuilder},
+    clock::Clock,
+    error::Error,
+    message::Message,
+    rate_limit::RateLimit,
+    test_util::{Captured, ManualClock},
+};
+
+fn secs(secs: u64) -> Duration {
+    Duration::from_secs(secs)
+}
+
+fn builder(clock: &Arc<ManualClock>) -> ClientBuilder {
+    Client::builder()
+        .base_url("https://bark.example.com")
+        .clock(clock.clone())
+}
+
+fn to(keys: &[&str]) -> Message {
+    Message::builder()
+        .body("b")
+        .device_keys(keys.iter().copied())
+        .build()
+        .unwrap()
+}
+
+fn throttled(result: bark::error::Result<bark::client::BarkResponse>) -> bool {
+    matches!(result, Err(Error::RateLimitExceeded { .. }))
+}
+
+fn one_per(secs: u64) -> RateLimit {
+    RateLimit::new(1, Duration::from_secs(secs)).fail_fast()
+}
+
+#[tokio::test]
+async fn each_key_has_its_own_bucket() {
+    let clock = Arc::new(ManualClock::default());
+    let (client, captured): (Client, Captured) = builder(&clock)
+        .rate_limit_per_device(one_per(10))
+        .build_capturing()
+        .unwrap();
+
+    client.send(&to(&["phone"])).await.unwrap();
+    assert!(throttled(client.send(&to(&["phone"])).await));
+    client.send(&to(&["tablet"])).await.unwrap();
+
+    clock.advance(secs(10));
+    client.send(&to(&["phone"])).await.unwrap();
+
+    assert_eq!(captured.requests().len(), 3);
+}
+
+#[tokio::test]
+async fn a_push_to_several_keys_charges_each_bucket() {
+    let clock = Arc::new(ManualClock::default());
+    let client = builder(&clock)
+        .rate_limit_per_device(one_per(10))
+        .build_capturing()
+        .unwrap()
+        .0;
+
+    client.send(&to(&["phone", "tablet"])).await.unwrap();
+
+    assert!(throttled(client.send(&to(&["phone"])).await));
+    assert!(throttled(client.send(&to(&["tablet"])).await));
+    assert!(throttled(client.send(&to(&["tablet", "watch"])).await));
+    client.send(&to(&["watch"])).await.unwrap();
+}
+
+#[tokio::test]
+async fn fanout_charges_every_key() {
+    let clock = Arc::new(ManualClock::default());
+    let client = builder(&clock)
+        .rate_limit_per_device(one_per(10))
+        .build_capturing()
+        .unwrap()
+        .0;
+
+    let report = client.send_fanout(&to(&["phone", "tablet"])).await.unwrap();
+    assert!(report.all_ok());
+
+    let report = client.send_fanout(&to(&["phone", "tablet"])).await.unwrap();
+    assert_eq!(report.failed_keys(), ["phone", "tablet"]);
+}
+
+#[tokio::test]
+async fn the_most_restrictive_limit_sets_the_wait() {
+    let clock = Arc::new(ManualClock::default());
+    let client = builder(&clock)
+        .rate_limit_per_device(RateLimit::new(1, secs(2)))
+        .rate_limit_for_device("pager", RateLimit::new(1, secs(30)))
+        .build_capturing()
+        .unwrap()
+        .0;
+    let started = clock.instant();
+
+    client.send(&to(&["phone", "pager"])).await.unwrap();
+    client.send(&to(&["phone", "pager"])).await.unwrap();
+
+    assert_eq!(clock.instant() - started, secs(30));
+}
+
+#[tokio::test]
+async fn without_a_default_only_listed_keys_are_limited() {
+    let clock = Arc::new(ManualClock::default());
+    let client = builder(&clock)
+        .rate_limit_for_device("noisy", one_per(10))
+        .build_capturing()
+        .unwrap()
+        .0;
+
+    client.send(&to(&["noisy"])).await.unwrap();
+    assert!(throttled(client.send(&to(&["noisy"])).await));
+
+    for _ in 0..5 {
+        client.send(&to(&["quiet"])).await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn the_global_limit_still_applies() {
+    let clock = Arc::new(ManualClock::default());
+    let client = builder(&clock)
+        .rate_limit(one_per(10))
+        .rate_limit_per_device(RateLimit::new(100, secs(1)))
+        .build_capturing()
+        .unwrap()
+        .0;
+
+    client.send(&to(&["phone"])).await.unwrap();
+    assert!(throttled(client.send(&to(&["tablet"])).await));
+}
+
+#[tokio::test]
+async fn least_recently_used_buckets_are_evicted() {
+    let clock = Arc::new(ManualClock::default());
+    let client = builder(&clock)
+        .rate_limit_per_device(one_per(10))
+        .device_rate_limit_capacity(2)
+        .build_capturing()
+        .unwrap()
+        .0;
+
+    client.send(&to(&["a"])).await.unwrap();
+    client.send(&to(&["b"])).await.unwrap();
+    client.send(&to(&["c"])).await.unwrap();
+
+    // `a` was dropped to make room for `c`, so it starts over with a full bucket.
+    client.send(&to(&["a"])).await.unwrap();
+    assert!(throttled(client.send(&to(&["c"])).await));
+}
+
+#[tokio::test]
+async fn idle_buckets_are_evicted_before_busy_ones() {
+    let clock = Arc::new(ManualClock::default());
+    let client = builder(&clock)
+        .rate_limit_per_device(one_per(1))
+        .rate_limit_for_device("slow", one_per(100))
+        .device_rate_limit_capacity(2)
+        .build_capturing()
+        .unwrap()
+        .0;
+
+    client.send(&to(&["slow"])).await.unwrap();
+    client.send(&to(&["fast"])).await.unwrap();
+    clock.advance(secs(5));
+    client.send(&to(&["new"])).await.unwrap();
+
+    // `slow` is the least recently used, but `fast` had refilled and went instead.
+    assert!(throttled(client.send(&to(&["slow"])).await));
+}
+
+#[test]
+fn effective_config_shows_per_device_limits() {
+    let clock = Arc::new(ManualClock::default());
+    let client = builder(&clock)
+        .rate_limit_per_device(one_per(10))
+        .rate_limit_for_device("abcdefgh", one_per(60))
+        .build()
+        .unwrap();
+
+    let config = client.effective_config();
+    assert_eq!(config.rate_limit_per_device, Some(one_per(10)));
+    assert_eq!(config.device_rate_limits["abcd…"], one_per(60));
+}
+
+/// A client on tokio's clock, so paused time drives the limiter.
+fn paused(limit: RateLimit) -> (Client, Captured) {
+    Client::builder()
+        .base_url("https://bark.example.com")
+        .device_key("key1")
+        .rate_limit(limit)
+        .build_capturing()
+        .unwrap()
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_full_bucket_waits_for_the_next_slot() {
+    let (client, captured) = paused(RateLimit::new(2, secs(1)));
+    let started = tokio::time::Instant::now();
+    let mut sent_at = Vec::new();
+
+    for _ in 0..4 {
+        client.send(&Message::text("b")).await.unwrap();
+        sent_at.push(started.elapsed());
+    }
+
+    let ms = Duration::from_millis;
+    assert_eq!(sent_at, [ms(0), ms(0), ms(500), ms(1000)]);
+    assert_eq!(captured.requests().len(), 4);
+}
+
+#[tokio::test(start_paused = true)]
+async fn concurrent_sends_are_spaced_out() {
+    let (client, captured) = paused(RateLimit::new(1, secs(1)));
+    let clone = client.clone();
+    let message = Message::text("b");
+    let started = tokio::time::Instant::now();
+
+    let (a, b, c) = tokio::join!(
+        client.send(&message),
+        client.send(&message),
+        clone.send(&message),
+    );
+
+    assert!(a.is_ok() && b.is_ok() && c.is_ok());
+    assert_eq!(started.elapsed(), secs(2));
+    assert_eq!(captured.requests().len(), 3);
+}
+
+#[tokio::test(start_paused = true)]
+async fn fail_fast_reports_when_the_next_slot_frees_up() {
+    let (client, captured) = paused(RateLimit::new(2, secs(1)).fail_fast());
+
+    client.send(&Message::text("b")).await.unwrap();
+    client.send(&Message::text("b")).await.unwrap();
+    let error = client.send(&Message::text("b")).await.unwrap_err();
+
+    assert!(
+        matches!(error, Error::RateLimitExceeded { retry_after } if retry_after == Duration::from_millis(500)),
+        "{error:?}"
+    );
+    assert_eq!(captured.requests().len(), 2);
+
+    tokio::time::advance(Duration::from_millis(500)).await;
+    client.send(&Message::text("b")).await.unwrap();
+    assert_eq!(captured.requests().len(), 3);
+}
+
+#[tokio::test(start_paused = true)]
+async fn retries_take_a_slot_too() {
+    let (client, captured) = Client::builder()
+        .base_url("https://bark.example.com")
+        .device_key("key1")
+        .rate_limit(RateLimit::new(1, secs(1)))
+        .retry(bark::retry::RetryPolicy {
+            initial_backoff: Duration::from_millis(10),
+            jitter: false,
+            ..bark::retry::RetryPolicy::default()
+        })
+        .build_capturing()
+        .unwrap();
+    captured.fail_send(1);
+    let started = tokio::time::Instant::now();
+
+    let outcome = client.send_detailed(&Message::text("b")).await.unwrap();
+
+    assert_eq!(outcome.attempts, 2);
+    assert_eq!(started.elapsed(), secs(1));
+}
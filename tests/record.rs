@@ -0,0 +1,184 @@
+use std::time::Duration;
+
+use bark::{
+    error::Error,
+    message::{Level, Message},
+    record::{CURRENT_VERSION, StoredRecord, migrate},
+    test_util::ManualClock,
+    time::SystemTime,
+};
+use serde_json::Value;
+
+#[cfg(feature = "async")]
+use bark::retry::RetryPolicy;
+
+const V0: &str = include_str!("fixtures/records/v0.json");
+const V1: &str = include_str!("fixtures/records/v1.json");
+const V2: &str = include_str!("fixtures/records/v2.json");
+const V3: &str = include_str!("fixtures/records/v3.json");
+
+fn disk_full() -> Message {
+    Message::builder()
+        .title("disk full")
+        .body("on db-1")
+        .device_key("key1")
+        .level(Level::TimeSensitive)
+        .group("ops")
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn bare_messages_migrate_as_version_0() {
+    let record = migrate(V0).unwrap();
+
+    assert_eq!(record.message, disk_full());
+    assert_eq!(record.created_at, None);
+    assert_eq!(record.attempts, 0);
+}
+
+#[test]
+fn version_1_envelopes_are_read() {
+    let record = migrate(V1).unwrap();
+
+    assert_eq!(record.message, disk_full());
+    assert_eq!(
+        record.created_at,
+        Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000))
+    );
+    assert_eq!(record.attempts, 2);
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn version_2_envelopes_keep_the_send_options() {
+    let record = migrate(V2).unwrap();
+
+    let expected = Message::builder()
+        .title("disk full")
+        .body("on db-1")
+        .device_key("key1")
+        .level(Level::TimeSensitive)
+        .group("ops")
+        .timeout(Duration::from_secs(2))
+        .no_client_defaults()
+        .retry_policy(RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(1),
+            jitter: false,
+            total_deadline: Some(Duration::from_secs(5)),
+        })
+        .build()
+        .unwrap();
+    assert_eq!(record.message, expected);
+    assert_eq!(record.attempts, 1);
+}
+
+#[test]
+fn newer_versions_are_rejected() {
+    let error = migrate(V3).unwrap_err();
+
+    assert!(
+        matches!(
+            error,
+            Error::UnsupportedRecordVersion {
+                found: 3,
+                supported: CURRENT_VERSION
+            }
+        ),
+        "{error:?}"
+    );
+    assert_eq!(
+        error.to_string(),
+        "Stored record version 3 is newer than the supported version 2"
+    );
+}
+
+#[test]
+fn saving_writes_the_current_version() {
+    let clock = ManualClock::new(SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000));
+    let record = StoredRecord::with_clock(disk_full(), &clock);
+
+    let json: Value = serde_json::from_str(&record.to_json().unwrap()).unwrap();
+
+    assert_eq!(json["version"], CURRENT_VERSION);
+    assert_eq!(json["created_at"], 1_700_000_000);
+    assert_eq!(json["attempts"], 0);
+    assert_eq!(json["message"]["title"], "disk full");
+}
+
+#[test]
+fn saved_records_round_trip() {
+    let mut record = StoredRecord::new(disk_full());
+    record.attempts = 3;
+
+    let migrated = migrate(&record.to_json().unwrap()).unwrap();
+
+    assert_eq!(migrated.message, record.message);
+    assert_eq!(migrated.attempts, 3);
+    assert!(migrated.created_at.is_some());
+}
+
+#[test]
+fn send_options_round_trip() {
+    let message = Message::builder()
+        .body("b")
+        .timeout(Duration::from_millis(1500))
+        .no_client_defaults()
+        .build()
+        .unwrap();
+
+    let json = StoredRecord::new(message.clone()).to_json().unwrap();
+
+    assert!(
+        json.contains(r#""options":{"timeout_ms":1500,"no_client_defaults":true}"#),
+        "{json}"
+    );
+    assert_eq!(migrate(&json).unwrap().message, message);
+}
+
+#[test]
+fn plain_messages_write_no_options() {
+    let json = StoredRecord::new(disk_full()).to_json().unwrap();
+
+    assert!(!json.contains("options"), "{json}");
+}
+
+#[cfg(feature = "crypto")]
+#[test]
+fn messages_to_encrypt_are_not_stored() {
+    let config = bark::crypto::EncryptionConfig::aes128_cbc([7; 16], [9; 16]).unwrap();
+    let message = Message::builder()
+        .body("b")
+        .encrypt(&config)
+        .build()
+        .unwrap();
+
+    let error = StoredRecord::new(message).to_json().unwrap_err();
+
+    assert!(
+        matches!(error, Error::UnstorableMessage("encryption")),
+        "{error:?}"
+    );
+    assert_eq!(
+        error.to_string(),
+        "Cannot store a message with encryption set; reading it back would lose it"
+    );
+}
+
+#[test]
+fn malformed_envelopes_are_invalid() {
+    for json in [
+        r#"{"version":"one","message":{"body":"b"}}"#,
+        r#"{"version":1,"created_at":"yesterday","message":{"body":"b"}}"#,
+        r#"{"version":1,"attempts":-1,"message":{"body":"b"}}"#,
+        r#"{"version":1}"#,
+        r#"{"version":2,"message":{"body":"b"},"options":{"timeout_ms":"soon"}}"#,
+    ] {
+        assert!(
+            matches!(migrate(json), Err(Error::InvalidRecord(_))),
+            "{json}"
+        );
+    }
+}
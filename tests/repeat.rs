@@ -0,0 +1,183 @@
+use std::{
+    sync::{Arc, OnceLock},
+    time::Duration,
+};
+
+use bark::{
+    client::{Client, ClientBuilder},
+    clock::Clock,
+    hook::SendReport,
+    message::Message,
+    repeat::{RepeatCancel, RepeatIds, RepeatOptions, StopCondition},
+    test_util::{Captured, ManualClock, SequentialIdGenerator},
+    time::SystemTime,
+};
+use serde_json::Value;
+
+fn builder(clock: &Arc<ManualClock>) -> ClientBuilder {
+    Client::builder()
+        .base_url("https://bark.example.com")
+        .device_key("key1")
+        .clock(clock.clone())
+        .id_generator(Arc::new(SequentialIdGenerator::new("id-")))
+}
+
+fn options(times: u32, ids: RepeatIds) -> RepeatOptions {
+    RepeatOptions {
+        times,
+        interval: Duration::from_secs(60),
+        stop_on: StopCondition::Never,
+        ids,
+    }
+}
+
+fn sent_ids(captured: &Captured) -> Vec<Value> {
+    captured
+        .requests()
+        .iter()
+        .map(|request| serde_json::from_slice::<Value>(&request.body).unwrap()["id"].clone())
+        .collect()
+}
+
+#[tokio::test]
+async fn every_repetition_is_delivered_an_interval_apart() {
+    let clock = Arc::new(ManualClock::new(SystemTime::UNIX_EPOCH));
+    let (client, captured) = builder(&clock).build_capturing().unwrap();
+
+    let outcomes = client
+        .send_repeated(&Message::text("pizza"), options(3, RepeatIds::Same))
+        .run()
+        .await;
+
+    assert_eq!(outcomes.len(), 3);
+    assert!(outcomes.iter().all(Result::is_ok));
+    assert_eq!(captured.requests().len(), 3);
+    assert_eq!(
+        clock.now(),
+        SystemTime::UNIX_EPOCH + Duration::from_secs(120)
+    );
+}
+
+#[tokio::test]
+async fn nothing_is_sent_until_run() {
+    let clock = Arc::new(ManualClock::default());
+    let (client, captured) = builder(&clock).build_capturing().unwrap();
+
+    let handle = client.send_repeated(&Message::text("b"), options(2, RepeatIds::Same));
+
+    assert!(captured.requests().is_empty());
+    drop(handle);
+    assert!(captured.requests().is_empty());
+}
+
+#[tokio::test]
+async fn same_ids_refresh_one_notification() {
+    let clock = Arc::new(ManualClock::default());
+    let (client, captured) = builder(&clock).build_capturing().unwrap();
+
+    client
+        .send_repeated(&Message::text("b"), options(3, RepeatIds::Same))
+        .run()
+        .await;
+
+    assert_eq!(sent_ids(&captured), ["id-1", "id-1", "id-1"]);
+}
+
+#[tokio::test]
+async fn same_ids_keep_the_message_id() {
+    let clock = Arc::new(ManualClock::default());
+    let (client, captured) = builder(&clock).build_capturing().unwrap();
+    let message = Message::builder().body("b").id("oven").build().unwrap();
+
+    client
+        .send_repeated(&message, options(2, RepeatIds::Same))
+        .run()
+        .await;
+
+    assert_eq!(sent_ids(&captured), ["oven", "oven"]);
+}
+
+#[tokio::test]
+async fn fresh_ids_stack_new_banners() {
+    let clock = Arc::new(ManualClock::default());
+    let (client, captured) = builder(&clock).build_capturing().unwrap();
+
+    let outcomes = client
+        .send_repeated(&Message::text("b"), options(3, RepeatIds::Fresh))
+        .run()
+        .await;
+
+    assert_eq!(sent_ids(&captured), ["id-1", "id-2", "id-3"]);
+    assert_eq!(outcomes[2].as_ref().unwrap().id.as_deref(), Some("id-3"));
+}
+
+#[tokio::test]
+async fn cancelling_mid_way_stops_later_repetitions() {
+    let clock = Arc::new(ManualClock::default());
+    let canceller = Arc::new(OnceLock::<RepeatCancel>::new());
+    let hook = canceller.clone();
+    let (client, captured) = builder(&clock)
+        .after_send(move |_: &SendReport<'_>| {
+            if let Some(cancel) = hook.get() {
+                cancel.cancel();
+            }
+        })
+        .build_capturing()
+        .unwrap();
+    let handle = client.send_repeated(&Message::text("b"), options(5, RepeatIds::Same));
+    canceller.set(handle.canceller()).unwrap();
+
+    let outcomes = handle.run().await;
+
+    assert_eq!(outcomes.len(), 1);
+    assert_eq!(captured.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn cancelling_before_run_sends_nothing() {
+    let clock = Arc::new(ManualClock::default());
+    let (client, captured) = builder(&clock).build_capturing().unwrap();
+    let handle = client.send_repeated(&Message::text("b"), options(3, RepeatIds::Same));
+
+    handle.cancel();
+
+    assert!(handle.run().await.is_empty());
+    assert!(captured.requests().is_empty());
+}
+
+#[tokio::test]
+async fn a_failed_repetition_does_not_stop_the_rest() {
+    let clock = Arc::new(ManualClock::default());
+    let (client, captured) = builder(&clock).build_capturing().unwrap();
+    captured.fail_send(2);
+
+    let outcomes = client
+        .send_repeated(&Message::text("b"), options(3, RepeatIds::Same))
+        .run()
+        .await;
+
+    let failed: Vec<_> = outcomes.iter().map(Result::is_err).collect();
+    assert_eq!(failed, [false, true, false]);
+}
+
+#[tokio::test]
+async fn stop_on_error_ends_at_the_first_failure() {
+    let clock = Arc::new(ManualClock::default());
+    let (client, captured) = builder(&clock).build_capturing().unwrap();
+    captured.fail_send(2);
+
+    let outcomes = client
+        .send_repeated(
+            &Message::text("b"),
+            RepeatOptions {
+                stop_on: StopCondition::OnError,
+                ..options(3, RepeatIds::Same)
+            },
+        )
+        .run()
+        .await;
+
+    assert_eq!(outcomes.len(), 2);
+    assert!(outcomes[1].is_err());
+    assert_eq!(captured.requests().len(), 2);
+}
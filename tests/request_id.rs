@@ -0,0 +1,102 @@
+use std::time::Duration;
+
+use bark::{
+    client::{Client, ClientBuilder},
+    error::Error,
+    message::Message,
+    retry::RetryPolicy,
+    test_util::SequentialIdGenerator,
+};
+use wiremock::{
+    Mock, MockServer, ResponseTemplate,
+    matchers::{header, method, path},
+};
+
+const SUCCESS: &str = r#"{"code":200,"message":"success","timestamp":1}"#;
+
+fn builder() -> ClientBuilder {
+    Client::builder()
+        .base_url("https://bark.example.com")
+        .device_key("key1")
+        .request_id_header("x-request-id")
+        .request_id_generator(SequentialIdGenerator::new("req-"))
+}
+
+#[tokio::test]
+async fn each_push_carries_a_fresh_id_that_the_response_reports() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/push"))
+        .and(header("x-request-id", "req-1"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(SUCCESS))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/push"))
+        .and(header("x-request-id", "req-2"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(SUCCESS))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let client = builder().base_url(server.uri()).build().unwrap();
+
+    let first = client.notify("t", "b").await.unwrap();
+    let second = client.notify("t", "b").await.unwrap();
+
+    assert_eq!(first.request_id.as_deref(), Some("req-1"));
+    assert_eq!(second.request_id.as_deref(), Some("req-2"));
+}
+
+#[tokio::test(start_paused = true)]
+async fn retries_reuse_the_sends_id() {
+    let (client, captured) = builder()
+        .retry(RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(10),
+            jitter: false,
+            ..RetryPolicy::default()
+        })
+        .build_capturing()
+        .unwrap();
+    captured.fail_send(1);
+
+    let outcome = client.send_detailed(&Message::text("b")).await.unwrap();
+
+    let ids: Vec<_> = captured
+        .requests()
+        .iter()
+        .map(|request| request.headers["x-request-id"].clone())
+        .collect();
+    assert_eq!(ids, ["req-1", "req-1"]);
+    assert_eq!(outcome.request_id.as_deref(), Some("req-1"));
+}
+
+#[tokio::test]
+async fn errors_name_the_request_id() {
+    let (client, captured) = builder().build_capturing().unwrap();
+    captured.fail_send(1);
+
+    let error = client.notify("t", "b").await.unwrap_err();
+
+    assert!(
+        matches!(&error, Error::WithRequestId { request_id, source }
+            if request_id == "req-1" && matches!(**source, Error::HttpStatus { .. })),
+        "{error:?}"
+    );
+    assert!(error.to_string().starts_with("Request req-1 failed: "));
+}
+
+#[tokio::test]
+async fn without_the_header_option_nothing_is_added() {
+    let (client, captured) = Client::builder()
+        .base_url("https://bark.example.com")
+        .device_key("key1")
+        .build_capturing()
+        .unwrap();
+
+    let response = client.notify("t", "b").await.unwrap();
+
+    assert_eq!(response.request_id, None);
+    assert!(!captured.requests()[0].headers.contains_key("x-request-id"));
+}
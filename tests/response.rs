@@ -0,0 +1,165 @@
+use bark::{
+    client::{Client, ClientBuilder},
+    error::Error,
+    message::Message,
+};
+use http::StatusCode;
+use wiremock::{
+    Mock, MockServer, ResponseTemplate,
+    matchers::{method, path},
+};
+
+fn builder() -> ClientBuilder {
+    Client::builder()
+        .base_url("https://bark.example.com")
+        .device_key("key1")
+}
+
+async fn answered(
+    status: StatusCode,
+    body: &str,
+) -> bark::error::Result<bark::client::BarkResponse> {
+    let (client, captured) = builder().build_capturing().unwrap();
+    captured.respond_with(1, status, body);
+
+    client.send(&Message::text("b")).await
+}
+
+#[tokio::test]
+async fn a_200_with_a_400_code_is_a_server_error() {
+    let error = answered(
+        StatusCode::OK,
+        r#"{"code":400,"message":"device key is invalid","timestamp":1700000000}"#,
+    )
+    .await
+    .unwrap_err();
+
+    assert!(
+        matches!(
+            &error,
+            Error::Server { code: 400, message, timestamp: 1_700_000_000 }
+                if message == "device key is invalid"
+        ),
+        "{error:?}"
+    );
+    assert_eq!(
+        error.to_string(),
+        "Server rejected the push with code 400: device key is invalid"
+    );
+}
+
+#[tokio::test]
+async fn a_200_with_a_200_code_succeeds() {
+    let response = answered(
+        StatusCode::OK,
+        r#"{"code":200,"message":"success","timestamp":1700000000}"#,
+    )
+    .await
+    .unwrap();
+
+    assert!(response.is_success());
+    assert_eq!(response.message, "success");
+    assert_eq!(response.timestamp, 1_700_000_000);
+}
+
+#[tokio::test]
+async fn malformed_codes_are_invalid_responses() {
+    for body in [
+        r#"{"code":"200","message":"success","timestamp":0}"#,
+        r#"{"code":200.5,"message":"success","timestamp":0}"#,
+        r#"{"code":null,"message":"success","timestamp":0}"#,
+        r#"{"message":"success","timestamp":0}"#,
+    ] {
+        let error = answered(StatusCode::OK, body).await.unwrap_err();
+
+        assert!(
+            matches!(&error, Error::InvalidResponse { status: StatusCode::OK, body: got, .. } if got == body),
+            "{body}: {error:?}"
+        );
+    }
+}
+
+#[test]
+fn only_code_200_is_success() {
+    let response = |code| bark::client::BarkResponse {
+        code,
+        message: String::new(),
+        timestamp: 0,
+        request_id: None,
+    };
+
+    assert!(response(200).is_success());
+    assert!(!response(201).is_success());
+    assert!(!response(400).is_success());
+}
+
+/// Sends one push to a mock server answering with `status` and `body`.
+async fn served(status: u16, body: &str) -> Error {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/push"))
+        .respond_with(ResponseTemplate::new(status).set_body_string(body))
+        .mount(&server)
+        .await;
+
+    Client::new(server.uri())
+        .with_device_key("key1")
+        .send(&Message::text("b"))
+        .await
+        .unwrap_err()
+}
+
+#[tokio::test]
+async fn an_html_error_page_keeps_its_status_and_body() {
+    let page = "<html><body><h1>502 Bad Gateway</h1></body></html>";
+
+    let error = served(502, page).await;
+
+    assert!(
+        matches!(&error, Error::HttpStatus { status: StatusCode::BAD_GATEWAY, body, .. } if body == page),
+        "{error:?}"
+    );
+    assert!(error.to_string().contains("502 Bad Gateway"), "{error}");
+}
+
+#[tokio::test]
+async fn an_html_page_with_a_200_is_an_invalid_response() {
+    let error = served(200, "<html>maintenance</html>").await;
+
+    assert!(
+        matches!(&error, Error::InvalidResponse { status: StatusCode::OK, body, .. } if body == "<html>maintenance</html>"),
+        "{error:?}"
+    );
+}
+
+#[tokio::test]
+async fn an_empty_body_is_an_invalid_response() {
+    let error = served(200, "").await;
+
+    assert!(
+        matches!(&error, Error::InvalidResponse { status: StatusCode::OK, body, .. } if body.is_empty()),
+        "{error:?}"
+    );
+}
+
+#[tokio::test]
+async fn truncated_json_is_an_invalid_response() {
+    let error = served(200, r#"{"code":200,"mess"#).await;
+
+    let Error::InvalidResponse { body, source, .. } = &error else {
+        panic!("{error:?}");
+    };
+    assert_eq!(body, r#"{"code":200,"mess"#);
+    assert!(source.is_eof(), "{source}");
+}
+
+#[tokio::test]
+async fn long_bodies_are_cut_in_the_error() {
+    let error = served(502, &"x".repeat(2000)).await;
+
+    let Error::HttpStatus { body, .. } = &error else {
+        panic!("{error:?}");
+    };
+    assert!(body.len() < 600, "{} bytes", body.len());
+    assert!(body.ends_with("… (1488 bytes truncated)"), "{body}");
+}
@@ -0,0 +1,315 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use bark::{
+    client::{Client, ClientBuilder},
+    hook::{FailureKind, RetryAttempt},
+    message::{Level, Message},
+    retry::RetryPolicy,
+    test_util::{Captured, ManualClock},
+};
+use tokio::time::Instant;
+
+fn policy(max_attempts: u32) -> RetryPolicy {
+    RetryPolicy {
+        max_attempts,
+        initial_backoff: Duration::from_millis(100),
+        max_backoff: Duration::from_secs(1),
+        jitter: false,
+        total_deadline: None,
+    }
+}
+
+fn builder() -> ClientBuilder {
+    Client::builder()
+        .base_url("https://bark.example.com")
+        .device_key("key1")
+        .clock(Arc::new(ManualClock::default()))
+}
+
+/// Fails the first `failures` sends, then lets the rest through.
+fn failing(captured: &Captured, failures: usize) {
+    for n in 1..=failures {
+        captured.fail_send(n);
+    }
+}
+
+/// Scripted failures are keyed on the request count, which `clear` resets.
+async fn attempts(client: &Client, captured: &Captured, message: Message) -> usize {
+    captured.clear();
+    let _ = client.send(&message).await;
+    captured.requests().len()
+}
+
+fn at(level: Option<Level>) -> Message {
+    let builder = Message::builder().body("b");
+
+    match level {
+        Some(level) => builder.level(level),
+        None => builder,
+    }
+    .build()
+    .unwrap()
+}
+
+#[tokio::test]
+async fn each_level_uses_its_own_policy() {
+    let (client, captured) = builder()
+        .retry(policy(2))
+        .retry_for_level(Level::Critical, policy(5))
+        .retry_for_level(Level::Passive, policy(1))
+        .build_capturing()
+        .unwrap();
+    failing(&captured, 10);
+
+    for (level, expected) in [
+        (Some(Level::Critical), 5),
+        (Some(Level::Active), 2),
+        (Some(Level::Passive), 1),
+        (None, 2),
+    ] {
+        assert_eq!(
+            attempts(&client, &captured, at(level.clone())).await,
+            expected,
+            "{level:?}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn level_policies_apply_without_a_client_policy() {
+    let (client, captured) = builder()
+        .retry_for_level(Level::Critical, policy(3))
+        .build_capturing()
+        .unwrap();
+    failing(&captured, 10);
+
+    assert_eq!(
+        attempts(&client, &captured, at(Some(Level::Critical))).await,
+        3
+    );
+    assert_eq!(
+        attempts(&client, &captured, at(Some(Level::Active))).await,
+        1
+    );
+}
+
+#[tokio::test]
+async fn the_default_level_picks_the_policy() {
+    let (client, captured) = builder()
+        .retry_for_level(Level::Critical, policy(4))
+        .build_capturing()
+        .unwrap();
+    let client = client.with_level(Level::Critical);
+    failing(&captured, 10);
+
+    assert_eq!(attempts(&client, &captured, at(None)).await, 4);
+}
+
+#[tokio::test]
+async fn a_level_policy_stops_once_a_send_succeeds() {
+    let (client, captured) = builder()
+        .retry_for_level(Level::Critical, policy(5))
+        .build_capturing()
+        .unwrap();
+    failing(&captured, 2);
+
+    let outcome = client
+        .send_detailed(&at(Some(Level::Critical)))
+        .await
+        .unwrap();
+    assert_eq!(outcome.attempts, 3);
+}
+
+#[test]
+fn effective_config_lists_level_policies() {
+    let client = builder()
+        .retry_for_level(Level::Critical, policy(5))
+        .build()
+        .unwrap();
+
+    let config = client.effective_config();
+    assert_eq!(config.level_retries["critical"], policy(5));
+    assert_eq!(config.retry, None);
+}
+
+/// What an `on_retry` hook saw, and when by tokio's paused clock.
+type Seen = Arc<Mutex<Vec<(u32, u32, FailureKind, Duration, Option<String>, Instant)>>>;
+
+fn recording(seen: &Seen) -> impl Fn(&RetryAttempt<'_>) + Send + Sync + 'static {
+    let seen = seen.clone();
+
+    move |attempt: &RetryAttempt<'_>| {
+        seen.lock().unwrap().push((
+            attempt.attempt,
+            attempt.max_attempts,
+            attempt.failure,
+            attempt.delay,
+            attempt.summary.title.clone(),
+            Instant::now(),
+        ));
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn on_retry_runs_before_each_wait() {
+    let seen = Seen::default();
+    let (client, captured) = Client::builder()
+        .base_url("https://bark.example.com")
+        .device_key("key1")
+        .retry(policy(4))
+        .on_retry(recording(&seen))
+        .build_capturing()
+        .unwrap();
+    captured.fail_send(1);
+    captured.transport_error(2, "connection reset");
+    let started = Instant::now();
+
+    client.notify("flaky", "b").await.unwrap();
+
+    let seen = seen.lock().unwrap();
+    let attempts: Vec<_> = seen
+        .iter()
+        .map(|(attempt, max, failure, delay, title, at)| {
+            (
+                *attempt,
+                *max,
+                *failure,
+                *delay,
+                title.as_deref(),
+                *at - started,
+            )
+        })
+        .collect();
+    assert_eq!(
+        attempts,
+        [
+            (
+                1,
+                4,
+                FailureKind::Http,
+                Duration::from_millis(100),
+                Some("flaky"),
+                Duration::ZERO
+            ),
+            (
+                2,
+                4,
+                FailureKind::Transport,
+                Duration::from_millis(200),
+                Some("flaky"),
+                Duration::from_millis(100)
+            ),
+        ]
+    );
+    assert_eq!(started.elapsed(), Duration::from_millis(300));
+    assert_eq!(captured.requests().len(), 3);
+}
+
+#[tokio::test(start_paused = true)]
+async fn on_retry_is_not_called_for_the_final_failure() {
+    let seen = Seen::default();
+    let (client, captured) = Client::builder()
+        .base_url("https://bark.example.com")
+        .device_key("key1")
+        .retry(policy(2))
+        .on_retry(recording(&seen))
+        .build_capturing()
+        .unwrap();
+    failing(&captured, 10);
+
+    client.notify("t", "b").await.unwrap_err();
+
+    assert_eq!(seen.lock().unwrap().len(), 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_panicking_on_retry_hook_does_not_stop_the_retry() {
+    let seen = Seen::default();
+    let (client, captured) = Client::builder()
+        .base_url("https://bark.example.com")
+        .device_key("key1")
+        .retry(policy(3))
+        .on_retry(|_: &RetryAttempt<'_>| panic!("metrics backend down"))
+        .on_retry(recording(&seen))
+        .build_capturing()
+        .unwrap();
+    captured.fail_send(1);
+
+    let outcome = client.send_detailed(&Message::text("b")).await.unwrap();
+
+    assert_eq!(outcome.attempts, 2);
+    assert_eq!(seen.lock().unwrap().len(), 1);
+}
+
+fn with_policy(level: Option<Level>, policy: RetryPolicy) -> Message {
+    let builder = Message::builder().body("b").retry_policy(policy);
+
+    match level {
+        Some(level) => builder.level(level),
+        None => builder,
+    }
+    .build()
+    .unwrap()
+}
+
+#[tokio::test]
+async fn a_message_policy_beats_its_level_and_the_client() {
+    let (client, captured) = builder()
+        .retry(policy(2))
+        .retry_for_level(Level::Critical, policy(5))
+        .build_capturing()
+        .unwrap();
+    failing(&captured, 10);
+
+    for (level, expected) in [(Some(Level::Critical), 3), (None, 3)] {
+        let message = with_policy(level.clone(), policy(3));
+        assert_eq!(
+            attempts(&client, &captured, message).await,
+            expected,
+            "{level:?}"
+        );
+    }
+
+    let once = with_policy(Some(Level::Critical), policy(1));
+    assert_eq!(attempts(&client, &captured, once).await, 1);
+}
+
+#[tokio::test]
+async fn a_message_policy_applies_without_client_policies() {
+    let (client, captured) = builder().build_capturing().unwrap();
+    failing(&captured, 10);
+
+    assert_eq!(
+        attempts(&client, &captured, with_policy(None, policy(4))).await,
+        4
+    );
+
+    captured.clear();
+    let _ = client
+        .message()
+        .body("b")
+        .retry_policy(policy(2))
+        .send()
+        .await;
+    assert_eq!(captured.requests().len(), 2);
+}
+
+#[test]
+fn a_message_policy_is_not_serialized() {
+    let plain = Message::builder().title("t").body("b").build().unwrap();
+    let retried = Message::builder()
+        .title("t")
+        .body("b")
+        .retry_policy(policy(5))
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        serde_json::to_string(&retried).unwrap(),
+        serde_json::to_string(&plain).unwrap()
+    );
+    assert_eq!(retried.to_query_string(), plain.to_query_string());
+}
@@ -0,0 +1,132 @@
+use std::{sync::Arc, time::Duration};
+
+use bark::{
+    client::Client, clock::Clock, error::Error, message::Message, retry::RetryPolicy,
+    test_util::ManualClock, time::SystemTime,
+};
+use wiremock::{
+    Mock, MockServer, ResponseTemplate,
+    matchers::{method, path},
+};
+
+const SUCCESS: &str = r#"{"code":200,"message":"success","timestamp":1}"#;
+
+/// 2023-11-14 22:13:20 UTC.
+fn start() -> SystemTime {
+    SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000)
+}
+
+/// A server that answers the first push with 429 and, if given, this `Retry-After`.
+async fn throttling_once(retry_after: Option<&str>) -> MockServer {
+    let server = MockServer::start().await;
+    let throttled = match retry_after {
+        Some(value) => ResponseTemplate::new(429).insert_header("retry-after", value),
+        None => ResponseTemplate::new(429),
+    };
+    Mock::given(method("POST"))
+        .and(path("/push"))
+        .respond_with(throttled)
+        .up_to_n_times(1)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/push"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(SUCCESS))
+        .mount(&server)
+        .await;
+
+    server
+}
+
+/// Sends once with a 100ms backoff policy and returns the attempts and the clock's wait.
+async fn waited(retry_after: Option<&str>) -> (u32, Duration) {
+    let server = throttling_once(retry_after).await;
+    let clock = Arc::new(ManualClock::new(start()));
+    let client = Client::builder()
+        .base_url(server.uri())
+        .device_key("key1")
+        .clock(clock.clone())
+        .retry(RetryPolicy {
+            initial_backoff: Duration::from_millis(100),
+            jitter: false,
+            ..RetryPolicy::default()
+        })
+        .build()
+        .unwrap();
+    let started = clock.instant();
+
+    let outcome = client.send_detailed(&Message::text("b")).await.unwrap();
+
+    (outcome.attempts, clock.instant() - started)
+}
+
+async fn rate_limited(retry_after: Option<&str>) -> Error {
+    let server = throttling_once(retry_after).await;
+    let client = Client::builder()
+        .base_url(server.uri())
+        .device_key("key1")
+        .clock(Arc::new(ManualClock::new(start())))
+        .build()
+        .unwrap();
+
+    client.send(&Message::text("b")).await.unwrap_err()
+}
+
+#[tokio::test]
+async fn delta_seconds_are_waited_out() {
+    assert_eq!(waited(Some("2")).await, (2, Duration::from_secs(2)));
+}
+
+#[tokio::test]
+async fn http_dates_are_waited_out() {
+    assert_eq!(
+        waited(Some("Tue, 14 Nov 2023 22:13:25 GMT")).await,
+        (2, Duration::from_secs(5))
+    );
+}
+
+#[tokio::test]
+async fn a_date_in_the_past_retries_at_once() {
+    assert_eq!(
+        waited(Some("Tue, 14 Nov 2023 22:00:00 GMT")).await,
+        (2, Duration::ZERO)
+    );
+}
+
+#[tokio::test]
+async fn the_header_beats_the_backoff_cap() {
+    assert_eq!(waited(Some("120")).await, (2, Duration::from_secs(120)));
+}
+
+#[tokio::test]
+async fn a_missing_header_falls_back_to_the_backoff() {
+    assert_eq!(waited(None).await, (2, Duration::from_millis(100)));
+}
+
+#[tokio::test]
+async fn an_unparseable_header_falls_back_to_the_backoff() {
+    for value in ["soon", "-5", "1.5", "Someday, 99 Nov 2023"] {
+        assert_eq!(
+            waited(Some(value)).await,
+            (2, Duration::from_millis(100)),
+            "{value}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn without_a_policy_the_wait_is_reported() {
+    let error = rate_limited(Some("2")).await;
+    assert!(
+        matches!(error, Error::RateLimited { retry_after: Some(wait) } if wait == Duration::from_secs(2)),
+        "{error:?}"
+    );
+    assert!(error.to_string().contains("retry after 2s"), "{error}");
+
+    let error = rate_limited(Some("soon")).await;
+    assert!(
+        matches!(error, Error::RateLimited { retry_after: None }),
+        "{error:?}"
+    );
+}
@@ -0,0 +1,73 @@
+use bark::{
+    client::{Client, ClientBuilder},
+    message::Message,
+};
+use serde_json::{Value, json};
+
+fn builder(device_key: &str) -> ClientBuilder {
+    Client::builder()
+        .base_url("https://bark.example.com")
+        .device_key(device_key)
+}
+
+fn keys(message: &Message) -> Value {
+    serde_json::to_value(message).unwrap()["device_keys"].clone()
+}
+
+#[tokio::test]
+async fn send_leaves_the_device_keys_alone() {
+    let (client, captured) = builder("client-key").build_capturing().unwrap();
+    let message = Message::text("b");
+
+    client.send(&message).await.unwrap();
+
+    assert_eq!(keys(&message), Value::Null);
+    assert_eq!(keys(&captured.messages()[0]), json!(["client-key"]));
+}
+
+#[tokio::test]
+async fn every_send_path_leaves_the_message_alone() {
+    let (client, _) = builder("client-key").build_capturing().unwrap();
+    let message = Message::builder()
+        .body("b")
+        .device_key("own-key")
+        .build()
+        .unwrap();
+    let before = message.clone();
+
+    client.send(&message).await.unwrap();
+    client.send_detailed(&message).await.unwrap();
+    client
+        .send_with_timeout(&message, std::time::Duration::from_secs(5))
+        .await
+        .unwrap();
+    client.send_to(["other"], &message).await.unwrap();
+
+    assert_eq!(message, before);
+}
+
+#[tokio::test]
+#[allow(deprecated)]
+async fn send_mut_still_writes_the_defaults_back() {
+    let (client, _) = builder("client-key").build_capturing().unwrap();
+    let mut message = Message::text("b");
+
+    client.send_mut(&mut message).await.unwrap();
+
+    assert_eq!(keys(&message), json!(["client-key"]));
+}
+
+#[tokio::test]
+async fn one_message_through_two_clients_gets_each_clients_keys() {
+    let (first, first_captured) = builder("first-key").build_capturing().unwrap();
+    let (second, second_captured) = builder("second-key").build_capturing().unwrap();
+    let message = Message::text("b");
+
+    first.send(&message).await.unwrap();
+    second.send(&message).await.unwrap();
+    first.send(&message).await.unwrap();
+
+    let first_keys: Vec<_> = first_captured.messages().iter().map(keys).collect();
+    assert_eq!(first_keys, [json!(["first-key"]), json!(["first-key"])]);
+    assert_eq!(keys(&second_captured.messages()[0]), json!(["second-key"]));
+}
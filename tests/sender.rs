@@ -0,0 +1,116 @@
+use std::sync::{Arc, Mutex};
+
+use bark::{
+    BoxFuture,
+    client::{BarkResponse, Client},
+    error::Result,
+    message::Message,
+    sender::{BarkSender, BarkSenderExt},
+};
+
+/// Records titles instead of delivering anything.
+#[derive(Default)]
+struct Fake(Mutex<Vec<Option<String>>>);
+
+impl BarkSender for Fake {
+    fn send<'a>(&'a self, message: &'a Message) -> BoxFuture<'a, Result<BarkResponse>> {
+        let title = serde_json::to_value(message).unwrap()["title"].clone();
+        self.0
+            .lock()
+            .unwrap()
+            .push(title.as_str().map(str::to_owned));
+
+        Box::pin(async {
+            Ok(BarkResponse {
+                code: 200,
+                message: "fake".to_owned(),
+                timestamp: 0,
+                request_id: None,
+            })
+        })
+    }
+}
+
+/// Application code that only knows the trait.
+async fn report_deploy(sender: &dyn BarkSender, service: &str) -> Result<BarkResponse> {
+    sender.notify("deployed", service).await
+}
+
+#[tokio::test]
+async fn a_fake_can_stand_in_for_the_client() {
+    let fake = Arc::new(Fake::default());
+    let sender: Arc<dyn BarkSender> = fake.clone();
+
+    let response = report_deploy(&*sender, "checkout").await.unwrap();
+
+    assert_eq!(response.message, "fake");
+    assert_eq!(*fake.0.lock().unwrap(), [Some("deployed".to_owned())]);
+}
+
+#[tokio::test]
+async fn the_extension_methods_build_before_sending() {
+    let fake = Fake::default();
+
+    fake.send_into(Message::builder().title("built").body("b"))
+        .await
+        .unwrap();
+    fake.send_item("plain").await.unwrap();
+    let error = fake
+        .send_into(Message::builder().body("b").id(""))
+        .await
+        .unwrap_err();
+
+    assert_eq!(*fake.0.lock().unwrap(), [Some("built".to_owned()), None]);
+    assert!(matches!(error, bark::error::Error::EmptyId), "{error:?}");
+}
+
+#[tokio::test]
+async fn clients_and_groups_are_senders() {
+    let (client, captured) = Client::capturing();
+    let client = client.with_device_key("key1");
+    let senders: Vec<Arc<dyn BarkSender>> =
+        vec![Arc::new(client.clone()), Arc::new(client.group("ops"))];
+
+    for sender in &senders {
+        report_deploy(&**sender, "checkout").await.unwrap();
+    }
+
+    let groups: Vec<_> = captured
+        .messages()
+        .iter()
+        .map(|message| serde_json::to_value(message).unwrap()["group"].clone())
+        .collect();
+    assert_eq!(groups, [serde_json::Value::Null, "ops".into()]);
+}
+
+#[tokio::test]
+async fn message_builds_and_sends_through_the_trait_object() {
+    let fake = Arc::new(Fake::default());
+    let sender: Arc<dyn BarkSender> = fake.clone();
+
+    let response = sender
+        .message()
+        .title("built")
+        .body("b")
+        .send()
+        .await
+        .unwrap();
+    let error = sender.message().body("b").id("").send().await.unwrap_err();
+
+    assert_eq!(response.message, "fake");
+    assert_eq!(*fake.0.lock().unwrap(), [Some("built".to_owned())]);
+    assert!(matches!(error, bark::error::Error::EmptyId), "{error:?}");
+}
+
+#[tokio::test]
+async fn message_on_a_client_behind_the_trait_keeps_its_defaults() {
+    let (client, captured) = Client::capturing();
+    let sender: Arc<dyn BarkSender> = Arc::new(client.with_device_key("key1").group("ops"));
+
+    sender.message().title("t").body("b").send().await.unwrap();
+
+    let sent = serde_json::to_value(&captured.messages()[0]).unwrap();
+    assert_eq!(sent["title"], "t");
+    assert_eq!(sent["group"], "ops");
+    assert_eq!(sent["device_keys"], serde_json::json!(["key1"]));
+}
@@ -0,0 +1,209 @@
+use bark::{
+    message::{Action, Level, Message},
+    test_util::assert_push_eq,
+};
+
+#[track_caller]
+fn assert_serializes_to(message: &Message, expected: &str) {
+    assert_push_eq(&serde_json::to_string(message).unwrap(), expected);
+}
+
+/// bark-server reads camelCase names for the multi-word options.
+#[test]
+fn a_message_with_every_field_uses_the_server_names() {
+    let message = Message::builder()
+        .title("t")
+        .subtitle("s")
+        .body("b")
+        .device_keys(["key2", "key1"])
+        .level(Level::TimeSensitive)
+        .volume(5)
+        .badge(3)
+        .call(true)
+        .auto_copy(true)
+        .copy("copied")
+        .sound("minuet")
+        .icon("https://example.com/icon.png")
+        .image("https://example.com/image.png")
+        .group("g")
+        .is_archive(true)
+        .url("https://example.com")
+        .action("none")
+        .id("n-1")
+        .build()
+        .unwrap();
+
+    assert_serializes_to(
+        &message,
+        r#"{
+            "title": "t",
+            "subtitle": "s",
+            "body": "b",
+            "device_keys": ["key1", "key2"],
+            "level": "timeSensitive",
+            "volume": 5,
+            "badge": 3,
+            "call": 1,
+            "autoCopy": 1,
+            "copy": "copied",
+            "sound": "minuet",
+            "icon": "https://example.com/icon.png",
+            "image": "https://example.com/image.png",
+            "group": "g",
+            "isArchive": 1,
+            "url": "https://example.com",
+            "action": "none",
+            "id": "n-1"
+        }"#,
+    );
+    // The comparison reads either spelling, so check the names themselves.
+    let json = serde_json::to_value(&message).unwrap();
+    let mut names: Vec<&str> = json
+        .as_object()
+        .unwrap()
+        .keys()
+        .map(String::as_str)
+        .collect();
+    names.sort_unstable();
+    assert_eq!(
+        names,
+        [
+            "action",
+            "autoCopy",
+            "badge",
+            "body",
+            "call",
+            "copy",
+            "device_keys",
+            "group",
+            "icon",
+            "id",
+            "image",
+            "isArchive",
+            "level",
+            "sound",
+            "subtitle",
+            "title",
+            "url",
+            "volume",
+        ]
+    );
+}
+
+#[test]
+fn markdown_lands_under_markdown_and_not_body() {
+    assert_serializes_to(&Message::markdown("**b**"), r#"{"markdown":"**b**"}"#);
+}
+
+#[test]
+fn unset_flags_and_options_are_left_out() {
+    let message = Message::builder()
+        .body("b")
+        .auto_copy(false)
+        .is_archive(false)
+        .call(false)
+        .build()
+        .unwrap();
+
+    // `"call": 0` would compare equal as a push, so this checks the bytes.
+    assert_eq!(serde_json::to_string(&message).unwrap(), r#"{"body":"b"}"#);
+}
+
+#[test]
+fn ciphertext_travels_with_its_iv_and_device_key() {
+    let message = Message::builder()
+        .ciphertext("c2VjcmV0")
+        .iv("0123456789abcdef")
+        .device_key("key1")
+        .build()
+        .unwrap();
+
+    assert_serializes_to(
+        &message,
+        r#"{"device_keys":["key1"],"ciphertext":"c2VjcmV0","iv":"0123456789abcdef"}"#,
+    );
+}
+
+#[test]
+fn the_query_string_uses_the_same_names() {
+    let message = Message::builder()
+        .body("b")
+        .auto_copy(true)
+        .is_archive(true)
+        .build()
+        .unwrap();
+
+    let query = message.to_query_string();
+    assert!(query.contains("autoCopy=1"), "{query}");
+    assert!(query.contains("isArchive=1"), "{query}");
+    assert!(
+        !query.contains("auto_copy") && !query.contains("is_archive"),
+        "{query}"
+    );
+}
+
+#[test]
+fn an_iv_without_ciphertext_is_rejected() {
+    let error = Message::builder()
+        .body("b")
+        .iv("0123456789abcdef")
+        .build()
+        .unwrap_err();
+
+    assert_eq!(
+        error.to_string(),
+        "IV is only meaningful together with ciphertext"
+    );
+}
+
+#[test]
+fn actions_serialize_as_plain_strings() {
+    for (action, expected) in [
+        (Action::None, "none"),
+        (Action::from("none"), "none"),
+        (Action::Other("open".to_owned()), "open"),
+        (Action::from("open"), "open"),
+    ] {
+        let message = Message::builder().body("b").action(action).build().unwrap();
+
+        assert_eq!(serde_json::to_value(&message).unwrap()["action"], expected);
+    }
+}
+
+#[test]
+fn a_silent_tap_keeps_its_url() {
+    let message = Message::builder()
+        .body("b")
+        .url("https://example.com/runbook")
+        .action(Action::None)
+        .build()
+        .unwrap();
+
+    assert_serializes_to(
+        &message,
+        r#"{"body":"b","url":"https://example.com/runbook","action":"none"}"#,
+    );
+}
+
+#[test]
+fn the_quick_constructors_set_nothing_else() {
+    for (message, expected) in [
+        (Message::text("b"), r#"{"body":"b"}"#),
+        (Message::markdown("**b**"), r#"{"markdown":"**b**"}"#),
+        (Message::titled("t", "b"), r#"{"title":"t","body":"b"}"#),
+    ] {
+        assert_serializes_to(&message, expected);
+    }
+}
+
+#[test]
+fn the_quick_constructors_match_the_builder() {
+    assert_eq!(
+        Message::text("b"),
+        Message::builder().body("b").build().unwrap()
+    );
+    assert_eq!(
+        Message::markdown("**b**"),
+        Message::builder().markdown("**b**").build().unwrap()
+    );
+}
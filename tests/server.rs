@@ -0,0 +1,298 @@
+use std::time::Duration;
+
+use bark::{
+    client::{ApiStyle, Client},
+    error::Error,
+    register::RegisterRequest,
+};
+use serde_json::json;
+use wiremock::{
+    Mock, MockServer, ResponseTemplate,
+    matchers::{body_json, method, path, query_param},
+};
+
+#[tokio::test]
+async fn register_posts_the_token_and_reads_the_assigned_key() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/register"))
+        .and(body_json(json!({ "device_token": "apns-token" })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "code": 200,
+            "message": "success",
+            "data": {
+                "key": "assigned",
+                "device_key": "assigned",
+                "device_token": "apns-token",
+            },
+            "timestamp": 1_700_000_000,
+        })))
+        .mount(&server)
+        .await;
+
+    let registered = Client::new(server.uri())
+        .register_device(&RegisterRequest::new("apns-token"))
+        .await
+        .unwrap();
+
+    assert_eq!(registered.device_key, "assigned");
+    assert_eq!(registered.device_token.as_deref(), Some("apns-token"));
+    assert!(registered.response.is_success());
+    assert_eq!(registered.response.timestamp, 1_700_000_000);
+}
+
+#[tokio::test]
+async fn older_servers_send_only_key() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/register"))
+        .and(body_json(
+            json!({ "device_token": "apns-token", "key": "mine" }),
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "code": 200,
+            "message": "success",
+            "data": { "key": "mine" },
+            "timestamp": 0,
+        })))
+        .mount(&server)
+        .await;
+
+    let registered = Client::new(server.uri())
+        .register_device(&RegisterRequest::new("apns-token").key("mine"))
+        .await
+        .unwrap();
+
+    assert_eq!(registered.device_key, "mine");
+    assert_eq!(registered.device_token, None);
+}
+
+#[tokio::test]
+async fn path_style_registers_with_a_query() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/register"))
+        .and(query_param("devicetoken", "apns-token"))
+        .and(query_param("key", "mine"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "code": 200,
+            "message": "success",
+            "data": { "key": "mine" },
+            "timestamp": 0,
+        })))
+        .mount(&server)
+        .await;
+    let client = Client::builder()
+        .base_url(server.uri())
+        .api_style(ApiStyle::Path)
+        .build()
+        .unwrap();
+
+    let registered = client
+        .register_device(&RegisterRequest::new("apns-token").key("mine"))
+        .await
+        .unwrap();
+
+    assert_eq!(registered.device_key, "mine");
+}
+
+#[tokio::test]
+async fn register_failures_are_typed() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/register"))
+        .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+            "code": 400,
+            "message": "device token is empty",
+            "timestamp": 0,
+        })))
+        .mount(&server)
+        .await;
+    let client = Client::new(server.uri());
+
+    let rejected = client
+        .register_device(&RegisterRequest::new("apns-token"))
+        .await
+        .unwrap_err();
+    let empty = client
+        .register_device(&RegisterRequest::new("  "))
+        .await
+        .unwrap_err();
+
+    assert!(
+        matches!(&rejected, Error::Server { code: 400, message, .. } if message == "device token is empty"),
+        "{rejected:?}"
+    );
+    assert!(matches!(empty, Error::EmptyDeviceToken), "{empty:?}");
+    assert_eq!(server.received_requests().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn a_success_without_a_key_is_an_invalid_response() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/register"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "code": 200,
+            "message": "success",
+            "data": {},
+            "timestamp": 0,
+        })))
+        .mount(&server)
+        .await;
+
+    let error = Client::new(server.uri())
+        .register_device(&RegisterRequest::new("apns-token"))
+        .await
+        .unwrap_err();
+
+    assert!(matches!(error, Error::InvalidResponse { .. }), "{error:?}");
+}
+
+async fn answering(route: &str, response: ResponseTemplate) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(route))
+        .respond_with(response)
+        .mount(&server)
+        .await;
+    server
+}
+
+#[tokio::test]
+async fn healthz_accepts_ok() {
+    let server = answering(
+        "/healthz",
+        ResponseTemplate::new(200).set_body_string("ok\n"),
+    )
+    .await;
+
+    Client::new(server.uri()).healthz().await.unwrap();
+}
+
+#[tokio::test]
+async fn healthz_reports_anything_else_as_unhealthy() {
+    let down = answering(
+        "/healthz",
+        ResponseTemplate::new(503).set_body_string("down"),
+    )
+    .await;
+    let odd = answering(
+        "/healthz",
+        ResponseTemplate::new(200).set_body_string("<html>"),
+    )
+    .await;
+
+    let down = Client::new(down.uri()).healthz().await.unwrap_err();
+    let odd = Client::new(odd.uri()).healthz().await.unwrap_err();
+
+    assert!(
+        matches!(&down, Error::Unhealthy { status: Some(status), body } if *status == 503 && body == "down"),
+        "{down:?}"
+    );
+    assert!(
+        matches!(&odd, Error::Unhealthy { status: Some(status), body } if *status == 200 && body == "<html>"),
+        "{odd:?}"
+    );
+    assert_eq!(
+        down.to_string(),
+        r#"Server is unhealthy (HTTP status 503 Service Unavailable): "down""#
+    );
+}
+
+#[tokio::test]
+async fn ping_parses_the_envelope() {
+    let server = answering(
+        "/ping",
+        ResponseTemplate::new(200).set_body_json(json!({
+            "code": 200,
+            "message": "pong",
+            "timestamp": 1_700_000_000,
+        })),
+    )
+    .await;
+
+    let pong = Client::new(server.uri()).ping().await.unwrap();
+
+    assert_eq!(pong.message, "pong");
+    assert_eq!(pong.timestamp, 1_700_000_000);
+}
+
+#[tokio::test]
+async fn health_checks_have_their_own_timeout() {
+    let server = answering(
+        "/healthz",
+        ResponseTemplate::new(200)
+            .set_body_string("ok")
+            .set_delay(Duration::from_secs(5)),
+    )
+    .await;
+    let client = Client::builder()
+        .base_url(server.uri())
+        .health_check_timeout(Duration::from_millis(50))
+        .build()
+        .unwrap();
+
+    let error = client.healthz().await.unwrap_err();
+
+    assert!(
+        matches!(&error, Error::Unhealthy { status: None, body } if body.starts_with("no response within")),
+        "{error:?}"
+    );
+}
+
+#[test]
+fn a_zero_health_check_timeout_is_rejected() {
+    let error = Client::builder()
+        .base_url("https://bark.example.com")
+        .health_check_timeout(Duration::ZERO)
+        .build()
+        .unwrap_err();
+
+    assert!(
+        matches!(error, Error::ZeroTimeout("health_check_timeout")),
+        "{error:?}"
+    );
+}
+
+/// `/info` in the shape bark-server answers it.
+const INFO: &str = r#"{"version":"v2.1.5","build":"2024-06-18 09:31:40","arch":"linux/amd64","commit":"4a7c4f9e","devices":42}"#;
+
+#[tokio::test]
+async fn server_info_reads_every_field() {
+    let server = answering("/info", ResponseTemplate::new(200).set_body_string(INFO)).await;
+
+    let info = Client::new(server.uri()).server_info().await.unwrap();
+
+    assert_eq!(info.version.as_deref(), Some("v2.1.5"));
+    assert_eq!(info.build.as_deref(), Some("2024-06-18 09:31:40"));
+    assert_eq!(info.arch.as_deref(), Some("linux/amd64"));
+    assert_eq!(info.commit.as_deref(), Some("4a7c4f9e"));
+    assert_eq!(info.devices, Some(42));
+}
+
+#[tokio::test]
+async fn server_info_tolerates_missing_and_unknown_fields() {
+    let server = answering(
+        "/info",
+        ResponseTemplate::new(200).set_body_string(r#"{"version":"v3","uptime":12}"#),
+    )
+    .await;
+
+    let info = Client::new(server.uri()).server_info().await.unwrap();
+
+    assert_eq!(info.version.as_deref(), Some("v3"));
+    assert_eq!((info.build, info.devices), (None, None));
+}
+
+#[tokio::test]
+async fn a_disabled_info_endpoint_is_its_own_error() {
+    let server = MockServer::start().await;
+
+    let error = Client::new(server.uri()).server_info().await.unwrap_err();
+
+    assert!(
+        matches!(error, Error::EndpointDisabled { endpoint: "info" }),
+        "{error:?}"
+    );
+}
@@ -0,0 +1,98 @@
+use bark::{
+    client::Client,
+    message::{BodyKind, Message, MessageBuilder},
+};
+use serde_json::{Value, json};
+
+fn json(builder: MessageBuilder) -> Value {
+    serde_json::to_value(builder.build().unwrap()).unwrap()
+}
+
+#[test]
+fn markdown_sets_the_body_and_kind_together() {
+    assert_eq!(
+        json(Message::builder().markdown("**b**")),
+        json!({ "markdown": "**b**" })
+    );
+    assert_eq!(
+        json(Message::builder().plaintext("b")),
+        json!({ "body": "b" })
+    );
+}
+
+#[test]
+fn a_later_shortcut_replaces_content_and_kind() {
+    assert_eq!(
+        json(Message::builder().body("old").markdown("**new**")),
+        json!({ "markdown": "**new**" })
+    );
+    assert_eq!(
+        json(Message::builder().markdown("**old**").plaintext("new")),
+        json!({ "body": "new" })
+    );
+}
+
+#[test]
+fn a_later_body_keeps_the_kind() {
+    assert_eq!(
+        json(Message::builder().markdown("**old**").body("_new_")),
+        json!({ "markdown": "_new_" })
+    );
+}
+
+#[test]
+fn body_kind_still_works_afterwards() {
+    assert_eq!(
+        json(
+            Message::builder()
+                .markdown("**b**")
+                .body_kind(BodyKind::Plaintext)
+        ),
+        json!({ "body": "**b**" })
+    );
+    assert_eq!(
+        json(
+            Message::builder()
+                .body("**b**")
+                .body_kind(BodyKind::Markdown)
+        ),
+        json!({ "markdown": "**b**" })
+    );
+}
+
+#[tokio::test]
+async fn the_client_builder_mirrors_the_shortcuts() {
+    let (client, captured) = Client::builder()
+        .base_url("https://bark.example.com")
+        .device_key("key1")
+        .build_capturing()
+        .unwrap();
+
+    client
+        .message()
+        .body("old")
+        .markdown("**b**")
+        .send()
+        .await
+        .unwrap();
+    client
+        .message()
+        .markdown("**b**")
+        .plaintext("b")
+        .send()
+        .await
+        .unwrap();
+
+    let bodies: Vec<_> = captured
+        .messages()
+        .iter()
+        .map(|message| {
+            let json = serde_json::to_value(message).unwrap();
+            (json["markdown"].clone(), json["body"].clone())
+        })
+        .collect();
+    assert_eq!(
+        bodies,
+        [(json!("**b**"), Value::Null), (Value::Null, json!("b"))]
+    );
+}
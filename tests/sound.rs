@@ -0,0 +1,89 @@
+use bark::{
+    error::Error,
+    message::Message,
+    sound::{SOUNDS, Sound},
+};
+
+#[test]
+fn every_listed_name_is_a_built_in_variant() {
+    assert_eq!(SOUNDS.len(), 32);
+
+    for name in SOUNDS {
+        let sound = Sound::from(*name);
+
+        assert!(!matches!(sound, Sound::Custom(_)), "{name}");
+        assert_eq!(sound.as_str(), *name);
+        assert_eq!(String::from(sound.clone()), *name);
+        assert_eq!(serde_json::to_value(&sound).unwrap(), *name);
+    }
+}
+
+#[test]
+fn names_are_matched_loosely() {
+    assert_eq!(Sound::from("Bell"), Sound::Bell);
+    assert_eq!(Sound::from(" minuet.caf "), Sound::Minuet);
+    assert_eq!(Sound::from("GoToSleep".to_owned()), Sound::GoToSleep);
+}
+
+#[test]
+fn other_names_are_custom_and_verbatim() {
+    let sound = Sound::from("MyTone");
+
+    assert_eq!(sound, Sound::Custom("MyTone".to_owned()));
+    assert_eq!(sound.to_string(), "MyTone");
+}
+
+#[test]
+fn unknown_sounds_fail_with_a_suggestion() {
+    let error = Message::builder().sound("minuett").build().unwrap_err();
+
+    assert!(
+        matches!(
+            &error,
+            Error::UnknownSound { given, did_you_mean: Some(known) }
+                if given == "minuett" && known == "minuet"
+        ),
+        "{error:?}"
+    );
+}
+
+#[test]
+fn every_variant_serializes_to_its_name() {
+    for name in SOUNDS {
+        let message = Message::builder()
+            .body("b")
+            .sound(Sound::from(*name))
+            .build()
+            .unwrap();
+
+        assert_eq!(serde_json::to_value(&message).unwrap()["sound"], *name);
+    }
+}
+
+#[test]
+fn custom_sounds_are_sent_as_is() {
+    let message = Message::builder()
+        .body("b")
+        .sound(Sound::Custom("MyTone.caf".to_owned()))
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        serde_json::to_value(&message).unwrap()["sound"],
+        "MyTone.caf"
+    );
+}
+
+#[test]
+fn names_given_as_strings_are_still_checked() {
+    let name = "minuett".to_owned();
+
+    assert!(Message::builder().sound(&name).build().is_err());
+    assert!(Message::builder().sound(name).build().is_err());
+    assert!(
+        Message::builder()
+            .sound(Sound::Custom("minuett".to_owned()))
+            .build()
+            .is_ok()
+    );
+}
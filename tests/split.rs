@@ -0,0 +1,147 @@
+use std::sync::Arc;
+
+use bark::{
+    client::Client,
+    message::Message,
+    split::SplitOptions,
+    test_util::{Captured, SequentialIdGenerator},
+};
+use serde_json::Value;
+
+fn capturing() -> (Client, Captured) {
+    Client::builder()
+        .base_url("https://bark.example.com")
+        .device_key("key1")
+        .id_generator(Arc::new(SequentialIdGenerator::new("n")))
+        .build_capturing()
+        .unwrap()
+}
+
+fn sent(captured: &Captured) -> Vec<Value> {
+    captured
+        .requests()
+        .iter()
+        .map(|request| serde_json::from_slice(&request.body).unwrap())
+        .collect()
+}
+
+fn field(parts: &[Value], name: &str) -> Vec<String> {
+    parts
+        .iter()
+        .map(|part| part[name].as_str().unwrap().to_owned())
+        .collect()
+}
+
+fn options(max_bytes: usize, max_parts: usize) -> SplitOptions {
+    SplitOptions {
+        max_bytes,
+        max_parts,
+    }
+}
+
+#[tokio::test]
+async fn a_body_that_fits_is_sent_once_unchanged() {
+    let (client, captured) = capturing();
+
+    let outcomes = client
+        .send_split(&Message::titled("t", "short"), options(64, 5))
+        .await
+        .unwrap();
+
+    assert_eq!(outcomes.len(), 1);
+    assert_eq!(
+        sent(&captured),
+        [serde_json::json!({"title": "t", "body": "short", "device_keys": ["key1"]})]
+    );
+}
+
+#[tokio::test]
+async fn bodies_split_at_line_ends_into_numbered_parts() {
+    let (client, captured) = capturing();
+    let body = "line one\nline two\nline three\n";
+
+    let outcomes = client
+        .send_split(&Message::titled("log", body), options(20, 5))
+        .await
+        .unwrap();
+
+    let parts = sent(&captured);
+    assert_eq!(outcomes.len(), 2);
+    assert_eq!(field(&parts, "title"), ["log (1/2)", "log (2/2)"]);
+    assert_eq!(
+        field(&parts, "body"),
+        ["line one\nline two\n", "line three\n"]
+    );
+    assert_eq!(field(&parts, "id"), ["n1-1", "n1-2"]);
+    assert_eq!(field(&parts, "body").concat(), body);
+}
+
+#[tokio::test]
+async fn a_message_id_numbers_the_parts() {
+    let (client, captured) = capturing();
+    let message = Message::builder()
+        .body("aaaa\nbbbb\n")
+        .id("report")
+        .build()
+        .unwrap();
+
+    client.send_split(&message, options(6, 5)).await.unwrap();
+
+    let parts = sent(&captured);
+    assert_eq!(field(&parts, "title"), ["(1/2)", "(2/2)"]);
+    assert_eq!(field(&parts, "id"), ["report-1", "report-2"]);
+}
+
+#[tokio::test]
+async fn a_line_longer_than_a_part_is_cut_on_a_char_boundary() {
+    let (client, captured) = capturing();
+
+    client
+        .send_split(&Message::text("ééééé"), options(4, 5))
+        .await
+        .unwrap();
+
+    assert_eq!(field(&sent(&captured), "body"), ["éé", "éé", "é"]);
+}
+
+#[tokio::test]
+async fn markdown_fences_are_closed_and_reopened_across_parts() {
+    let (client, captured) = capturing();
+    let body = "intro\n```rust\nlet a = 1;\nlet b = 2;\n```\n";
+
+    client
+        .send_split(&Message::markdown(body), options(28, 5))
+        .await
+        .unwrap();
+
+    let bodies = field(&sent(&captured), "markdown");
+    assert_eq!(
+        bodies,
+        [
+            "intro\n",
+            "```rust\nlet a = 1;\n```",
+            "```rust\nlet b = 2;\n```\n"
+        ]
+    );
+    for body in &bodies {
+        assert!(body.len() <= 28, "{body:?}");
+        assert_eq!(body.matches("```").count() % 2, 0, "{body:?}");
+    }
+}
+
+#[tokio::test]
+async fn parts_past_the_cap_are_dropped_with_a_marker() {
+    let (client, captured) = capturing();
+    let body: String = (0..10).map(|n| format!("line {n}\n")).collect();
+
+    let outcomes = client
+        .send_split(&Message::text(body), options(20, 2))
+        .await
+        .unwrap();
+
+    let bodies = field(&sent(&captured), "body");
+    assert_eq!(outcomes.len(), 2);
+    assert!(bodies[1].ends_with("\n… (truncated)"), "{bodies:?}");
+    assert!(bodies.iter().all(|body| body.len() <= 20), "{bodies:?}");
+    assert_eq!(field(&sent(&captured), "title"), ["(1/2)", "(2/2)"]);
+}
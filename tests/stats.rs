@@ -0,0 +1,138 @@
+use std::time::Duration;
+
+use bark::{
+    client::Client, hook::HookControl, message::Message, retry::RetryPolicy,
+    test_util::matchers::push_with_title,
+};
+use wiremock::{
+    Mock, MockServer, ResponseTemplate,
+    matchers::{method, path},
+};
+
+const SUCCESS: &str = r#"{"code":200,"message":"success","timestamp":1}"#;
+
+async fn server() -> MockServer {
+    let server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/push"))
+        .and(push_with_title("rejected"))
+        .respond_with(ResponseTemplate::new(400).set_body_string(
+            r#"{"code":400,"message":"failed to get device token","timestamp":1}"#,
+        ))
+        .mount(&server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/push"))
+        .and(push_with_title("flaky"))
+        .respond_with(ResponseTemplate::new(502))
+        .up_to_n_times(1)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/push"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(SUCCESS))
+        .mount(&server)
+        .await;
+
+    server
+}
+
+fn client(server: &MockServer) -> Client {
+    Client::builder()
+        .base_url(server.uri())
+        .device_key("key1")
+        .retry(RetryPolicy {
+            initial_backoff: Duration::from_millis(1),
+            jitter: false,
+            ..RetryPolicy::default()
+        })
+        .before_send(|message: &mut Message| {
+            if message.to_query_string().contains("title=filtered") {
+                HookControl::Skip
+            } else {
+                HookControl::Continue
+            }
+        })
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn every_send_path_updates_the_counters() {
+    let server = server().await;
+    let client = client(&server);
+
+    client.notify("direct", "b").await.unwrap();
+    client.notify("flaky", "b").await.unwrap();
+    client.notify("rejected", "b").await.unwrap_err();
+    client.notify("filtered", "b").await.unwrap_err();
+
+    let batch = client
+        .send_all((0..3).map(|n| Message::titled(format!("batch {n}"), "b")))
+        .await;
+    assert!(batch.iter().all(Result::is_ok));
+
+    let fanout = client
+        .send_fanout(
+            &Message::builder()
+                .title("fanout")
+                .device_keys(["key2", "key3"])
+                .build()
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert!(fanout.all_ok());
+
+    let stats = client.stats();
+    assert_eq!(stats.sent, 7);
+    assert_eq!(stats.failed, 1);
+    assert_eq!(stats.filtered, 1);
+    assert!(
+        stats
+            .last_error
+            .as_deref()
+            .is_some_and(|error| error.contains("code 400")),
+        "{stats:?}"
+    );
+    assert!(stats.latency_p50.is_some());
+    assert!(stats.latency_p95 >= stats.latency_p50);
+}
+
+#[tokio::test]
+async fn clones_share_stats_and_reset_clears_them() {
+    let server = server().await;
+    let client = client(&server);
+    let clone = client.clone().with_device_key("key2");
+
+    client.notify("direct", "b").await.unwrap();
+    clone.notify("rejected", "b").await.unwrap_err();
+
+    assert_eq!(client.stats().sent, 1);
+    assert_eq!(client.stats().failed, 1);
+    assert_eq!(clone.stats().sent, 1);
+
+    clone.reset_stats();
+
+    let stats = client.stats();
+    assert_eq!((stats.sent, stats.failed, stats.filtered), (0, 0, 0));
+    assert_eq!(stats.last_error, None);
+    assert_eq!(stats.latency_p50, None);
+}
+
+#[tokio::test]
+async fn features_the_client_lacks_report_none() {
+    let server = server().await;
+    let client = client(&server);
+    client.notify("direct", "b").await.unwrap();
+
+    let stats = serde_json::to_value(client.stats()).unwrap();
+    assert_eq!(stats["deduplicated"], serde_json::Value::Null);
+    assert_eq!(stats["queue_depth"], serde_json::Value::Null);
+    assert_eq!(stats["circuit_breaker"], serde_json::Value::Null);
+    assert_eq!(stats["sent"], 1);
+}
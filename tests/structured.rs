@@ -0,0 +1,100 @@
+use bark::{
+    message::{BodyKind, Message},
+    structured::StructuredBody,
+};
+
+fn incident() -> StructuredBody {
+    StructuredBody::new()
+        .text("Checkout is failing for some users.")
+        .section("Details")
+        .field("service", "checkout_api")
+        .field("error rate", "12%")
+        .section("Trace")
+        .code("text", "panic at src/pay.rs:42")
+}
+
+#[test]
+fn markdown_rendering() {
+    assert_eq!(
+        incident().render(&BodyKind::Markdown),
+        "Checkout is failing for some users.\n\
+         \n\
+         ## Details\n\
+         - **service**: checkout\\_api\n\
+         - **error rate**: 12%\n\
+         \n\
+         ## Trace\n\
+         ```text\n\
+         panic at src/pay.rs:42\n\
+         ```"
+    );
+}
+
+#[test]
+fn plaintext_rendering() {
+    assert_eq!(
+        incident().render(&BodyKind::Plaintext),
+        "Checkout is failing for some users.\n\
+         \n\
+         Details\n\
+         service: checkout_api\n\
+         error rate: 12%\n\
+         \n\
+         Trace\n\
+         panic at src/pay.rs:42"
+    );
+}
+
+#[test]
+fn trailing_sections_are_dropped_past_the_limit() {
+    let body = incident().max_len(60).render(&BodyKind::Plaintext);
+
+    assert_eq!(
+        body,
+        "Checkout is failing for some users.\n\
+         \n\
+         … 2 more sections"
+    );
+    assert!(body.len() <= 60);
+}
+
+#[test]
+fn one_byte_too_many_drops_the_last_section() {
+    let full = incident().render(&BodyKind::Plaintext);
+
+    assert_eq!(
+        incident().max_len(full.len()).render(&BodyKind::Plaintext),
+        full
+    );
+    assert_eq!(
+        incident()
+            .max_len(full.len() - 1)
+            .render(&BodyKind::Plaintext),
+        "Checkout is failing for some users.\n\
+         \n\
+         Details\n\
+         service: checkout_api\n\
+         error rate: 12%\n\
+         \n\
+         … 1 more section"
+    );
+}
+
+#[test]
+fn the_builder_renders_for_the_body_kind() {
+    let markdown = Message::builder()
+        .structured_body(StructuredBody::new().field("a_b", "c"))
+        .body_kind(BodyKind::Markdown)
+        .build()
+        .unwrap();
+    let plaintext = Message::builder()
+        .structured_body(StructuredBody::new().field("a_b", "c"))
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        serde_json::to_value(markdown).unwrap()["markdown"],
+        "- **a\\_b**: c"
+    );
+    assert_eq!(serde_json::to_value(plaintext).unwrap()["body"], "a_b: c");
+}
@@ -0,0 +1,190 @@
+#![cfg(all(feature = "ureq", feature = "reqwest"))]
+
+use std::time::Duration;
+
+use bark::{
+    error::Error,
+    message::{Level, Message},
+    test_util::{Captured, assert_push_eq},
+};
+use http::StatusCode;
+use wiremock::{
+    Mock, MockServer, ResponseTemplate,
+    matchers::{method, path},
+};
+
+const SUCCESS: &str = r#"{"code":200,"message":"success","timestamp":1}"#;
+
+/// An async and a sync client over the same capture, so both see the same scripts.
+fn clients() -> (bark::client::Client, bark::sync::Client, Captured) {
+    let captured = Captured::default();
+    let client = bark::client::Client::builder()
+        .base_url("https://bark.example.com")
+        .device_key("key1")
+        .transport(captured.transport())
+        .build()
+        .unwrap();
+    let sync = bark::sync::Client::builder()
+        .base_url("https://bark.example.com")
+        .device_key("key1")
+        .transport(captured.transport())
+        .build()
+        .unwrap();
+
+    (client, sync, captured)
+}
+
+/// Sends `message` with each client, returning both results and the requests in order.
+async fn both(
+    message: &Message,
+) -> (
+    Result<(), Error>,
+    Result<(), Error>,
+    Vec<bark::transport::PreparedRequest>,
+) {
+    let (client, sync, captured) = clients();
+    let sent = client.send(message).await.map(drop);
+    let synced = sync.send(message).map(drop);
+
+    (sent, synced, captured.requests())
+}
+
+#[tokio::test]
+async fn both_clients_send_the_same_request() {
+    let message = Message::builder()
+        .title("t")
+        .body("b")
+        .device_keys(["KEY2", "key3"])
+        .level(Level::TimeSensitive)
+        .group("g")
+        .icon("https://example.com/icon.png")
+        .build()
+        .unwrap();
+
+    let (sent, synced, requests) = both(&message).await;
+    sent.unwrap();
+    synced.unwrap();
+
+    let [client, sync] = &requests[..] else {
+        panic!("{requests:?}");
+    };
+    assert_eq!(sync.method, client.method);
+    assert_eq!(sync.url, client.url);
+    assert_eq!(sync.headers["content-type"], client.headers["content-type"]);
+    assert_push_eq(
+        std::str::from_utf8(&sync.body).unwrap(),
+        std::str::from_utf8(&client.body).unwrap(),
+    );
+}
+
+#[tokio::test]
+async fn both_clients_reject_the_same_messages() {
+    let (client, sync, captured) = clients();
+    let oversized = Message::text("x".repeat(5000));
+
+    let sent = client.send(&oversized).await.unwrap_err();
+    let synced = sync.send(&oversized).unwrap_err();
+
+    assert!(matches!(sent, Error::PayloadTooLarge { .. }), "{sent:?}");
+    assert!(
+        matches!(synced, Error::PayloadTooLarge { .. }),
+        "{synced:?}"
+    );
+    assert!(captured.requests().is_empty());
+}
+
+#[tokio::test]
+async fn both_clients_map_responses_alike() {
+    let cases: [(StatusCode, &str); 3] = [
+        (
+            StatusCode::BAD_REQUEST,
+            r#"{"code":400,"message":"failed to get device token","timestamp":1}"#,
+        ),
+        (StatusCode::INTERNAL_SERVER_ERROR, "upstream down"),
+        (StatusCode::OK, "not json"),
+    ];
+
+    for (status, body) in cases {
+        let (client, sync, captured) = clients();
+        captured.respond_with(1, status, body);
+        captured.respond_with(2, status, body);
+
+        let sent = client.send(&Message::text("b")).await.unwrap_err();
+        let synced = sync.send(&Message::text("b")).unwrap_err();
+
+        assert_eq!(synced.to_string(), sent.to_string(), "{status}");
+    }
+}
+
+#[tokio::test]
+async fn both_clients_read_retry_after() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/push"))
+        .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "2"))
+        .mount(&server)
+        .await;
+
+    let client = bark::client::Client::builder()
+        .base_url(server.uri())
+        .device_key("key1")
+        .build()
+        .unwrap();
+    let sync = bark::sync::Client::builder()
+        .base_url(server.uri())
+        .device_key("key1")
+        .build()
+        .unwrap();
+
+    let sent = client.send(&Message::text("b")).await.unwrap_err();
+    let synced = tokio::task::spawn_blocking(move || sync.send(&Message::text("b")))
+        .await
+        .unwrap()
+        .unwrap_err();
+
+    for error in [sent, synced] {
+        assert!(
+            matches!(error, Error::RateLimited { retry_after: Some(wait) } if wait == Duration::from_secs(2)),
+            "{error:?}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn both_clients_time_out() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/push"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string(SUCCESS)
+                .set_delay(Duration::from_secs(5)),
+        )
+        .mount(&server)
+        .await;
+
+    let client = bark::client::Client::builder()
+        .base_url(server.uri())
+        .device_key("key1")
+        .build()
+        .unwrap();
+    let sync = bark::sync::Client::builder()
+        .base_url(server.uri())
+        .device_key("key1")
+        .build()
+        .unwrap();
+    let message = Message::builder()
+        .body("b")
+        .timeout(Duration::from_millis(100))
+        .build()
+        .unwrap();
+
+    let sent = client.send(&message).await.unwrap_err();
+    let synced = tokio::task::spawn_blocking(move || sync.send(&message))
+        .await
+        .unwrap()
+        .unwrap_err();
+
+    assert!(matches!(sent, Error::Timeout { .. }), "{sent:?}");
+    assert!(matches!(synced, Error::Timeout { .. }), "{synced:?}");
+}
@@ -0,0 +1,106 @@
+use std::time::Duration;
+
+use bark::{client::Client, error::Error, message::Message};
+use wiremock::{
+    Mock, MockServer, ResponseTemplate,
+    matchers::{method, path},
+};
+
+const SUCCESS: &str = r#"{"code":200,"message":"success","timestamp":1}"#;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+/// A server that takes `delay` to answer every push.
+async fn slow(delay: Duration) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/push"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string(SUCCESS)
+                .set_delay(delay),
+        )
+        .mount(&server)
+        .await;
+
+    server
+}
+
+fn client(server: &MockServer) -> Client {
+    Client::builder()
+        .base_url(server.uri())
+        .device_key("key1")
+        .build()
+        .unwrap()
+}
+
+fn timed_out(result: bark::error::Result<bark::client::BarkResponse>, cap: Duration) -> bool {
+    matches!(result, Err(Error::Timeout { elapsed, deadline: None }) if elapsed >= cap && elapsed < cap * 20)
+}
+
+#[tokio::test]
+async fn send_with_timeout_expires_as_a_timeout() {
+    let server = slow(ms(2000)).await;
+
+    let result = client(&server)
+        .send_with_timeout(&Message::text("b"), ms(50))
+        .await;
+
+    assert!(timed_out(result, ms(50)));
+}
+
+#[tokio::test]
+async fn send_with_timeout_lets_a_fast_enough_reply_through() {
+    let server = slow(ms(20)).await;
+
+    client(&server)
+        .send_with_timeout(&Message::text("b"), ms(2000))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn a_message_timeout_expires_as_a_timeout() {
+    let server = slow(ms(2000)).await;
+
+    let result = client(&server)
+        .message()
+        .body("b")
+        .timeout(ms(50))
+        .send()
+        .await;
+
+    assert!(timed_out(result, ms(50)));
+}
+
+#[tokio::test]
+async fn a_message_timeout_overrides_the_client_timeout() {
+    let server = slow(ms(200)).await;
+    let client = Client::builder()
+        .base_url(server.uri())
+        .device_key("key1")
+        .timeout(ms(50))
+        .build()
+        .unwrap();
+
+    assert!(timed_out(client.send(&Message::text("b")).await, ms(50)));
+    client
+        .message()
+        .body("b")
+        .timeout(ms(2000))
+        .send()
+        .await
+        .unwrap();
+}
+
+#[test]
+fn the_error_says_how_long_it_waited() {
+    let error = Error::Timeout {
+        elapsed: ms(50),
+        deadline: None,
+    };
+
+    assert_eq!(error.to_string(), "Request timed out after 50ms");
+}
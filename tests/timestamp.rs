@@ -0,0 +1,145 @@
+#![cfg(feature = "chrono")]
+
+use std::{sync::Arc, time::Duration};
+
+use bark::{
+    client::{Client, ClientBuilder},
+    error::Error,
+    message::Message,
+    retry::RetryPolicy,
+    test_util::{Captured, ManualClock},
+    time::SystemTime,
+    timestamp::{TimestampPlacement, TimestampZone},
+};
+use chrono::FixedOffset;
+use serde_json::Value;
+
+/// 2023-11-14 22:13:20 UTC.
+fn start() -> SystemTime {
+    SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000)
+}
+
+fn builder(clock: &Arc<ManualClock>) -> ClientBuilder {
+    Client::builder()
+        .base_url("https://bark.example.com")
+        .device_key("key1")
+        .clock(clock.clone())
+}
+
+fn sent(captured: &Captured) -> Vec<Value> {
+    captured
+        .requests()
+        .iter()
+        .map(|request| serde_json::from_slice(&request.body).unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn the_send_time_is_appended_to_the_subtitle() {
+    let clock = Arc::new(ManualClock::new(start()));
+    let (client, captured) = builder(&clock).build_capturing().unwrap();
+
+    client
+        .message()
+        .title("t")
+        .subtitle("nightly")
+        .body("b")
+        .timestamped(TimestampPlacement::Subtitle, "%Y-%m-%d %H:%M")
+        .send()
+        .await
+        .unwrap();
+    client
+        .message()
+        .body("b")
+        .timestamped(TimestampPlacement::Subtitle, "%H:%M:%S")
+        .send()
+        .await
+        .unwrap();
+
+    let sent = sent(&captured);
+    assert_eq!(sent[0]["subtitle"], "nightly · 2023-11-14 22:13");
+    assert_eq!(sent[1]["subtitle"], "22:13:20");
+}
+
+#[tokio::test]
+async fn the_send_time_can_end_the_body() {
+    let clock = Arc::new(ManualClock::new(start()));
+    let (client, captured) = builder(&clock).build_capturing().unwrap();
+    let message = Message::builder()
+        .body("disk full")
+        .timestamped(TimestampPlacement::BodySuffix, "at %H:%M")
+        .build()
+        .unwrap();
+
+    client.send(&message).await.unwrap();
+    clock.advance(Duration::from_secs(60));
+    client.send(&message).await.unwrap();
+
+    let bodies: Vec<_> = sent(&captured)
+        .iter()
+        .map(|body| body["body"].clone())
+        .collect();
+    assert_eq!(bodies, ["disk full\nat 22:13", "disk full\nat 22:14"]);
+}
+
+#[tokio::test]
+async fn a_fixed_zone_shifts_the_rendered_time() {
+    let clock = Arc::new(ManualClock::new(start()));
+    let (client, captured) = builder(&clock).build_capturing().unwrap();
+
+    client
+        .message()
+        .body("b")
+        .timestamped(TimestampPlacement::Subtitle, "%H:%M %:z")
+        .timestamp_zone(TimestampZone::Fixed(
+            FixedOffset::east_opt(8 * 3600).unwrap(),
+        ))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(sent(&captured)[0]["subtitle"], "06:13 +08:00");
+}
+
+#[tokio::test]
+async fn retries_keep_the_first_stamp() {
+    let clock = Arc::new(ManualClock::new(start()));
+    let (client, captured) = builder(&clock)
+        .retry(RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_secs(90),
+            jitter: false,
+            ..RetryPolicy::default()
+        })
+        .build_capturing()
+        .unwrap();
+    captured.fail_send(1);
+
+    client
+        .message()
+        .body("b")
+        .timestamped(TimestampPlacement::Subtitle, "%H:%M")
+        .send()
+        .await
+        .unwrap();
+
+    let subtitles: Vec<_> = sent(&captured)
+        .iter()
+        .map(|body| body["subtitle"].clone())
+        .collect();
+    assert_eq!(subtitles, ["22:13", "22:13"]);
+}
+
+#[test]
+fn an_invalid_format_is_rejected_at_build_time() {
+    let error = Message::builder()
+        .body("b")
+        .timestamped(TimestampPlacement::Subtitle, "%Q")
+        .build()
+        .unwrap_err();
+
+    assert!(
+        matches!(&error, Error::InvalidTimestampFormat(format) if format == "%Q"),
+        "{error:?}"
+    );
+}
@@ -0,0 +1,133 @@
+#![cfg(feature = "tower")]
+
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use bark::{
+    client::Client,
+    error::Error,
+    message::Message,
+    transport::{PreparedRequest, RawResponse},
+};
+use serde_json::json;
+use tower::{
+    BoxError, Service, ServiceExt,
+    util::{AndThenLayer, MapRequestLayer},
+};
+
+fn counting(
+    count: &Arc<AtomicUsize>,
+) -> MapRequestLayer<impl Fn(PreparedRequest) -> PreparedRequest + Clone + use<>> {
+    let count = count.clone();
+
+    MapRequestLayer::new(move |request: PreparedRequest| {
+        count.fetch_add(1, Ordering::SeqCst);
+        request
+    })
+}
+
+#[tokio::test]
+async fn a_counting_layer_sees_every_send() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let (client, captured) = Client::builder()
+        .base_url("https://bark.example.com")
+        .device_key("key1")
+        .layer(counting(&count))
+        .build_capturing()
+        .unwrap();
+
+    client.notify("a", "b").await.unwrap();
+    client.notify("c", "d").await.unwrap();
+
+    assert_eq!(count.load(Ordering::SeqCst), 2);
+    assert_eq!(captured.requests().len(), 2);
+    assert_eq!(client.effective_config().transport.tower_layers, 1);
+}
+
+#[tokio::test]
+async fn a_failing_layer_fails_the_send() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let (client, _) = Client::builder()
+        .base_url("https://bark.example.com")
+        .device_key("key1")
+        .layer(counting(&count))
+        .layer(AndThenLayer::new(|_: RawResponse| async {
+            Err::<RawResponse, BoxError>("quota exceeded".into())
+        }))
+        .build_capturing()
+        .unwrap();
+
+    let error = client.notify("t", "b").await.unwrap_err();
+
+    assert!(
+        matches!(&error, Error::Transport(source) if source.to_string() == "quota exceeded"),
+        "{error:?}"
+    );
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn a_crate_error_from_a_layer_comes_back_as_is() {
+    let (client, _) = Client::builder()
+        .base_url("https://bark.example.com")
+        .device_key("key1")
+        .layer(AndThenLayer::new(|_: RawResponse| async {
+            Err::<RawResponse, BoxError>(Box::new(Error::Skipped))
+        }))
+        .build_capturing()
+        .unwrap();
+
+    let error = client.notify("t", "b").await.unwrap_err();
+
+    assert!(matches!(error, Error::Skipped), "{error:?}");
+}
+
+#[tokio::test]
+async fn oneshot_sends_with_the_default_keys() {
+    let (client, captured) = Client::builder()
+        .base_url("https://bark.example.com")
+        .device_key("key1")
+        .build_capturing()
+        .unwrap();
+
+    let response = client.oneshot(Message::text("b")).await.unwrap();
+
+    assert_eq!(response.code, 200);
+    assert_eq!(
+        serde_json::to_value(&captured.messages()[0]).unwrap()["device_keys"],
+        json!(["key1"])
+    );
+}
+
+#[tokio::test]
+async fn oneshot_fails_with_the_crate_error() {
+    let (client, captured) = Client::capturing();
+
+    let error = client.oneshot(Message::text("b")).await.unwrap_err();
+
+    assert!(matches!(error, Error::MissingDeviceKey), "{error:?}");
+    assert!(captured.requests().is_empty());
+}
+
+#[tokio::test]
+async fn the_service_is_always_ready_for_another_call() {
+    let (mut client, captured) = Client::builder()
+        .base_url("https://bark.example.com")
+        .device_key("key1")
+        .build_capturing()
+        .unwrap();
+
+    for body in ["a", "b", "c"] {
+        client
+            .ready()
+            .await
+            .unwrap()
+            .call(Message::text(body))
+            .await
+            .unwrap();
+    }
+
+    assert_eq!(captured.requests().len(), 3);
+}
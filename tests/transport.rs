@@ -0,0 +1,108 @@
+use std::sync::{Arc, Mutex};
+
+use bark::{
+    BoxFuture,
+    client::Client,
+    error::{Error, Result},
+    message::Message,
+    transport::{PreparedRequest, RawResponse, Transport},
+};
+use http::{HeaderMap, Method, StatusCode};
+use serde_json::Value;
+
+/// Keeps every request and answers each with the same canned response.
+struct Canned {
+    status: StatusCode,
+    body: &'static str,
+    requests: Mutex<Vec<PreparedRequest>>,
+}
+
+impl Canned {
+    fn new(status: StatusCode, body: &'static str) -> Arc<Self> {
+        Arc::new(Canned {
+            status,
+            body,
+            requests: Mutex::default(),
+        })
+    }
+}
+
+impl Transport for Canned {
+    fn execute(&self, request: PreparedRequest) -> BoxFuture<'_, Result<RawResponse>> {
+        self.requests.lock().unwrap().push(request);
+
+        Box::pin(async {
+            Ok(RawResponse {
+                status: self.status,
+                headers: HeaderMap::new(),
+                body: self.body.as_bytes().to_vec(),
+            })
+        })
+    }
+}
+
+fn client(transport: &Arc<Canned>) -> Client {
+    Client::builder()
+        .base_url("https://bark.example.com")
+        .device_key("key1")
+        .transport(transport.clone())
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn requests_go_through_the_custom_transport() {
+    let transport = Canned::new(
+        StatusCode::OK,
+        r#"{"code":200,"message":"canned","timestamp":7}"#,
+    );
+
+    let response = client(&transport)
+        .notify("disk full", "on db-1")
+        .await
+        .unwrap();
+
+    assert_eq!(response.message, "canned");
+    assert_eq!(response.timestamp, 7);
+    let requests = transport.requests.lock().unwrap();
+    let [request] = &requests[..] else {
+        panic!("{} requests", requests.len());
+    };
+    assert_eq!(request.method, Method::POST);
+    assert_eq!(request.url.as_str(), "https://bark.example.com/push");
+    let body: Value = serde_json::from_slice(&request.body).unwrap();
+    assert_eq!(body["title"], "disk full");
+    assert_eq!(body["device_keys"], serde_json::json!(["key1"]));
+}
+
+#[tokio::test]
+async fn canned_failures_are_mapped_like_real_ones() {
+    let transport = Canned::new(
+        StatusCode::BAD_REQUEST,
+        r#"{"code":400,"message":"device token invalid","timestamp":0}"#,
+    );
+
+    let error = client(&transport)
+        .send(&Message::text("b"))
+        .await
+        .unwrap_err();
+
+    assert!(
+        matches!(&error, Error::Server { code: 400, message, .. } if message == "device token invalid"),
+        "{error:?}"
+    );
+}
+
+#[tokio::test]
+async fn one_transport_serves_every_send() {
+    let transport = Canned::new(
+        StatusCode::OK,
+        r#"{"code":200,"message":"success","timestamp":0}"#,
+    );
+    let client = client(&transport);
+
+    client.send(&Message::text("one")).await.unwrap();
+    client.send(&Message::text("two")).await.unwrap();
+
+    assert_eq!(transport.requests.lock().unwrap().len(), 2);
+}
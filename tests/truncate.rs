@@ -0,0 +1,118 @@
+use bark::{
+    message::{Message, MessageBuilder},
+    truncate::TruncateOptions,
+};
+use serde_json::Value;
+
+fn options(max_title: Option<usize>, max_body: Option<usize>) -> TruncateOptions {
+    TruncateOptions {
+        max_title,
+        max_body,
+        ..TruncateOptions::default()
+    }
+}
+
+fn field(builder: MessageBuilder, name: &str) -> String {
+    let json = serde_json::to_value(builder.build().unwrap()).unwrap();
+    match &json[name] {
+        Value::String(value) => value.clone(),
+        other => panic!("{name}: {other}"),
+    }
+}
+
+fn title(title: &str, max: usize) -> String {
+    field(
+        Message::builder()
+            .title(title)
+            .body("b")
+            .truncate(options(Some(max), None)),
+        "title",
+    )
+}
+
+fn body(body: &str, max: usize) -> String {
+    field(
+        Message::builder()
+            .body(body)
+            .truncate(options(None, Some(max))),
+        "body",
+    )
+}
+
+fn markdown(body: &str, max: usize) -> String {
+    field(
+        Message::builder()
+            .markdown(body)
+            .truncate(options(None, Some(max))),
+        "markdown",
+    )
+}
+
+#[test]
+fn multibyte_titles_are_cut_on_a_char_boundary() {
+    assert_eq!(title("héllo wörld", 6), "hé…");
+    assert_eq!(title("磁盘已满了", 10), "磁盘…");
+}
+
+#[test]
+fn combining_marks_stay_with_their_base() {
+    assert_eq!(title("e\u{301}e\u{301}e\u{301}", 7), "e\u{301}…");
+}
+
+#[test]
+fn emoji_sequences_are_not_split() {
+    assert_eq!(title("ab👨\u{200D}👩\u{200D}👧", 12), "ab…");
+    assert_eq!(title("ab👍🏽cd", 10), "ab…");
+}
+
+#[test]
+fn text_exactly_at_the_limit_is_kept() {
+    assert_eq!(title("exactly10!", 10), "exactly10!");
+    assert_eq!(body(&"x".repeat(64), 64), "x".repeat(64));
+}
+
+#[test]
+fn long_bodies_end_with_how_much_was_dropped() {
+    let cut = body(&"x".repeat(3000), 2048);
+
+    assert!(cut.len() <= 2048, "{} bytes", cut.len());
+    assert!(cut.ends_with("… (truncated 974 B)"), "{cut}");
+    assert_eq!(cut.len() - "… (truncated 974 B)".len() + 974, 3000);
+}
+
+#[test]
+fn kilobytes_are_reported_with_one_decimal() {
+    let cut = body(&"x".repeat(4000), 100);
+
+    assert!(cut.ends_with("… (truncated 3.8 KB)"), "{cut}");
+}
+
+#[test]
+fn a_short_open_fence_is_dropped() {
+    let text = format!(
+        "{}\n```rust\nlet a = 1;\nlet b = 2;\n```\n",
+        "intro ".repeat(10)
+    );
+
+    let cut = markdown(&text, 80);
+
+    assert!(!cut.contains("```"), "{cut}");
+    assert!(cut.starts_with("intro intro"), "{cut}");
+}
+
+#[test]
+fn a_long_open_fence_is_closed() {
+    let text = format!("```\n{}```\n", "line of code\n".repeat(20));
+
+    let cut = markdown(&text, 120);
+
+    assert!(cut.len() <= 120, "{} bytes", cut.len());
+    assert_eq!(cut.matches("```").count(), 2, "{cut}");
+}
+
+#[test]
+fn without_truncate_nothing_is_cut() {
+    let long = "x".repeat(5000);
+
+    assert_eq!(field(Message::builder().body(&long), "body"), long);
+}
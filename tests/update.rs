@@ -0,0 +1,144 @@
+use bark::{
+    client::{Client, ClientBuilder},
+    error::Error,
+    test_util::Captured,
+};
+use serde_json::{Value, json};
+
+fn builder() -> ClientBuilder {
+    Client::builder()
+        .base_url("https://bark.example.com")
+        .device_key("key1")
+}
+
+fn sent(captured: &Captured) -> Vec<Value> {
+    captured
+        .messages()
+        .iter()
+        .map(|message| {
+            let mut json = serde_json::to_value(message).unwrap();
+            json.as_object_mut().unwrap().remove("device_keys");
+            json
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn replacing_sends_under_the_id() {
+    let (client, captured) = builder().build_capturing().unwrap();
+
+    client
+        .replacing("backup")
+        .body("backup 40%")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(
+        sent(&captured),
+        [json!({"body": "backup 40%", "id": "backup"})]
+    );
+}
+
+#[tokio::test]
+async fn updates_start_from_scratch_by_default() {
+    let (client, captured) = builder().build_capturing().unwrap();
+
+    client
+        .update_notification("backup", |message| message.title("backup").body("40%"))
+        .await
+        .unwrap();
+    client
+        .update_notification("backup", |message| message.body("80%"))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        sent(&captured),
+        [
+            json!({"title": "backup", "body": "40%", "id": "backup"}),
+            json!({"body": "80%", "id": "backup"}),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn remembered_updates_send_deltas() {
+    let (client, captured) = builder().remember_updates(true).build_capturing().unwrap();
+
+    for body in ["40%", "80%", "done"] {
+        client
+            .update_notification("backup", |message| message.title("backup").body(body))
+            .await
+            .unwrap();
+    }
+    client
+        .update_notification("backup", |message| message.body("verified"))
+        .await
+        .unwrap();
+
+    let bodies: Vec<_> = sent(&captured)
+        .iter()
+        .map(|json| (json["title"].clone(), json["body"].clone()))
+        .collect();
+    assert_eq!(
+        bodies,
+        [
+            (json!("backup"), json!("40%")),
+            (json!("backup"), json!("80%")),
+            (json!("backup"), json!("done")),
+            (json!("backup"), json!("verified")),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn remembered_updates_are_kept_per_id() {
+    let (client, captured) = builder().remember_updates(true).build_capturing().unwrap();
+
+    client
+        .update_notification("a", |message| message.title("first").body("1"))
+        .await
+        .unwrap();
+    client
+        .update_notification("b", |message| message.body("2"))
+        .await
+        .unwrap();
+
+    assert_eq!(sent(&captured)[1], json!({"body": "2", "id": "b"}));
+}
+
+#[tokio::test]
+async fn a_delete_forgets_the_remembered_update() {
+    let (client, captured) = builder().remember_updates(true).build_capturing().unwrap();
+
+    client
+        .update_notification("a", |message| message.title("first").body("1"))
+        .await
+        .unwrap();
+    client
+        .update_notification("a", |message| message.delete(true))
+        .await
+        .unwrap();
+    client
+        .update_notification("a", |message| message.body("again"))
+        .await
+        .unwrap();
+
+    assert_eq!(sent(&captured)[2], json!({"body": "again", "id": "a"}));
+}
+
+#[tokio::test]
+async fn an_empty_id_is_rejected_before_sending() {
+    let (client, captured) = builder().build_capturing().unwrap();
+
+    let updated = client
+        .update_notification("", |message| message.body("b"))
+        .await
+        .unwrap_err();
+    let replaced = client.replacing("").body("b").send().await.unwrap_err();
+
+    assert!(matches!(updated, Error::EmptyId), "{updated:?}");
+    assert!(matches!(replaced, Error::EmptyId), "{replaced:?}");
+    assert!(captured.requests().is_empty());
+}
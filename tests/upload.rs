@@ -0,0 +1,109 @@
+use std::sync::{Arc, Mutex};
+
+use bark::{
+    BoxFuture,
+    client::Client,
+    error::{Error, Result},
+    message::Message,
+    upload::Uploader,
+};
+use url::Url;
+
+/// Records what it was given and answers with a fixed URL.
+#[derive(Default)]
+struct Recording {
+    uploads: Mutex<Vec<(Vec<u8>, String)>>,
+}
+
+impl Uploader for Recording {
+    fn upload<'a>(&'a self, bytes: &'a [u8], content_type: &'a str) -> BoxFuture<'a, Result<Url>> {
+        self.uploads
+            .lock()
+            .unwrap()
+            .push((bytes.to_vec(), content_type.to_owned()));
+
+        Box::pin(async { Ok(Url::parse("https://cdn.example.com/chart.png").unwrap()) })
+    }
+}
+
+fn attached() -> Message {
+    Message::builder()
+        .title("daily")
+        .image_bytes(b"\x89PNG".to_vec(), "image/png")
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn the_uploaded_url_lands_in_the_payload() {
+    let uploader = Arc::new(Recording::default());
+    let (client, captured) = Client::builder()
+        .base_url("https://bark.example.com")
+        .device_key("key1")
+        .uploader(uploader.clone())
+        .build_capturing()
+        .unwrap();
+
+    client.send(&attached()).await.unwrap();
+
+    let body: serde_json::Value = serde_json::from_slice(&captured.requests()[0].body).unwrap();
+    assert_eq!(body["image"], "https://cdn.example.com/chart.png");
+    assert_eq!(
+        *uploader.uploads.lock().unwrap(),
+        [(b"\x89PNG".to_vec(), "image/png".to_owned())]
+    );
+}
+
+#[tokio::test]
+async fn image_bytes_without_an_uploader_fail_before_sending() {
+    let (client, captured) = Client::builder()
+        .base_url("https://bark.example.com")
+        .device_key("key1")
+        .build_capturing()
+        .unwrap();
+
+    let error = client.send(&attached()).await.unwrap_err();
+
+    assert!(matches!(error, Error::MissingUploader), "{error:?}");
+    assert!(captured.requests().is_empty());
+}
+
+#[test]
+fn image_bytes_are_not_serialized() {
+    let json = serde_json::to_value(attached()).unwrap();
+
+    assert_eq!(json, serde_json::json!({"title": "daily"}));
+}
+
+#[cfg(feature = "put-upload")]
+#[tokio::test]
+async fn put_uploader_stores_the_bytes_and_returns_the_public_url() {
+    use bark::upload::PutUploader;
+    use wiremock::{
+        Mock, MockServer, ResponseTemplate,
+        matchers::{header, method, path_regex},
+    };
+
+    let server = MockServer::start().await;
+    Mock::given(method("PUT"))
+        .and(path_regex(r"^/bucket/[0-9a-f-]{36}\.png$"))
+        .and(header("content-type", "image/png"))
+        .respond_with(ResponseTemplate::new(201))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let uploader = PutUploader::new(format!("{}/bucket?sig=secret", server.uri()))
+        .unwrap()
+        .public_base("https://cdn.example.com/images/")
+        .unwrap();
+
+    let url = uploader.upload(b"\x89PNG", "image/png").await.unwrap();
+
+    let stored = &server.received_requests().await.unwrap()[0];
+    assert_eq!(stored.body, b"\x89PNG");
+    assert_eq!(stored.url.query(), Some("sig=secret"));
+    assert_eq!(url.host_str(), Some("cdn.example.com"));
+    assert!(url.path().starts_with("/images/"), "{url}");
+    assert!(url.path().ends_with(".png"), "{url}");
+    assert_eq!(url.query(), None);
+}
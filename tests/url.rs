@@ -0,0 +1,117 @@
+use bark::{error::Error, message::Message};
+
+fn url(message: &Message) -> String {
+    message
+        .to_url("https://bark.example.com", "key1")
+        .unwrap()
+        .url
+        .to_string()
+}
+
+#[test]
+fn title_subtitle_and_body_become_path_segments() {
+    let message = Message::builder()
+        .title("t")
+        .subtitle("s")
+        .body("b")
+        .build()
+        .unwrap();
+
+    assert_eq!(url(&message), "https://bark.example.com/key1/t/s/b");
+    assert_eq!(
+        url(&Message::text("only")),
+        "https://bark.example.com/key1/only"
+    );
+}
+
+#[test]
+fn reserved_characters_are_percent_encoded() {
+    let message = Message::titled("a/b?c", "50% #done & more");
+
+    assert_eq!(
+        url(&message),
+        "https://bark.example.com/key1/a%2Fb%3Fc/50%25%20%23done%20&%20more"
+    );
+}
+
+#[test]
+fn markdown_and_options_travel_in_the_query() {
+    let message = Message::builder()
+        .title("t")
+        .markdown("**bold**")
+        .group("g")
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        url(&message),
+        "https://bark.example.com/key1?title=t&markdown=**bold**&group=g"
+    );
+}
+
+#[test]
+fn a_base_url_subpath_is_kept() {
+    let url = Message::text("b")
+        .to_url("https://example.com/bark/", "key1")
+        .unwrap()
+        .url;
+
+    assert_eq!(url.as_str(), "https://example.com/bark/key1/b");
+}
+
+#[test]
+fn encrypted_fields_are_reported_as_omitted() {
+    let message = Message::builder()
+        .ciphertext("c2VjcmV0")
+        .iv("0123456789abcdef")
+        .build()
+        .unwrap();
+
+    let url = message.to_url("https://bark.example.com", "key1").unwrap();
+
+    assert_eq!(url.omitted, ["ciphertext", "iv"]);
+    assert_eq!(url.url.query(), None);
+}
+
+#[test]
+fn urls_past_the_limit_are_rejected() {
+    let message = Message::text("x".repeat(100));
+
+    let error = message
+        .to_url_with_max_length("https://bark.example.com", "key1", 64)
+        .unwrap_err();
+
+    assert!(
+        matches!(error, Error::UrlTooLong { length, max: 64 } if length > 64),
+        "{error:?}"
+    );
+}
+
+#[cfg(feature = "reqwest")]
+#[tokio::test]
+async fn a_server_receives_what_the_url_says() {
+    use wiremock::{
+        Mock, MockServer, ResponseTemplate,
+        matchers::{method, path, query_param},
+    };
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/key1/deploy%20done/all%20green"))
+        .and(query_param("group", "ops"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let message = Message::builder()
+        .title("deploy done")
+        .body("all green")
+        .group("ops")
+        .build()
+        .unwrap();
+
+    let url = message.to_url(&server.uri(), "key1").unwrap().url;
+    let status = reqwest::get(url).await.unwrap().status();
+
+    assert_eq!(status, 200);
+}
@@ -0,0 +1,304 @@
+use bark::{
+    client::Client,
+    error::{Error, UrlError},
+    message::{Level, Message, MessageBuilder},
+};
+
+#[test]
+fn delete_without_an_id_is_rejected() {
+    let error = Message::builder().delete(true).build().unwrap_err();
+
+    assert!(matches!(error, Error::DeleteRequiresId), "{error:?}");
+    assert_eq!(
+        error.to_string(),
+        "Delete needs an id naming the notification to remove"
+    );
+}
+
+#[test]
+fn delete_with_an_id_builds() {
+    Message::builder().id("n1").delete(true).build().unwrap();
+}
+
+#[test]
+fn an_id_without_delete_builds() {
+    Message::builder().body("b").id("n1").build().unwrap();
+}
+
+#[tokio::test]
+async fn the_client_builder_fails_before_sending() {
+    let (client, captured) = Client::capturing();
+
+    let error = client
+        .with_device_key("key1")
+        .message()
+        .delete(true)
+        .send()
+        .await
+        .unwrap_err();
+
+    assert!(matches!(error, Error::DeleteRequiresId), "{error:?}");
+    assert!(captured.requests().is_empty());
+}
+
+#[test]
+fn volume_off_the_critical_level_is_allowed_by_default() {
+    for level in [None, Some(Level::Active), Some(Level::TimeSensitive)] {
+        let mut builder = Message::builder().body("b").volume(3);
+        if let Some(level) = level {
+            builder = builder.level(level);
+        }
+
+        builder.build().unwrap();
+    }
+}
+
+#[test]
+fn strict_mode_requires_the_critical_level_for_volume() {
+    let error = Message::builder()
+        .body("b")
+        .volume(3)
+        .level(Level::Active)
+        .strict()
+        .build()
+        .unwrap_err();
+    let unleveled = Message::builder()
+        .body("b")
+        .volume(3)
+        .strict()
+        .build()
+        .unwrap_err();
+
+    assert!(
+        matches!(error, Error::VolumeRequiresCriticalLevel),
+        "{error:?}"
+    );
+    assert!(
+        matches!(unleveled, Error::VolumeRequiresCriticalLevel),
+        "{unleveled:?}"
+    );
+    assert_eq!(
+        error.to_string(),
+        "Volume only takes effect on the critical level"
+    );
+}
+
+#[test]
+fn strict_mode_accepts_a_critical_volume() {
+    Message::builder()
+        .body("b")
+        .volume(3)
+        .level(Level::Critical)
+        .strict()
+        .build()
+        .unwrap();
+}
+
+#[test]
+fn the_range_check_applies_in_both_modes() {
+    for strict in [false, true] {
+        let mut builder = Message::builder()
+            .body("b")
+            .volume(11)
+            .level(Level::Critical);
+        if strict {
+            builder = builder.strict();
+        }
+
+        let error = builder.build().unwrap_err();
+
+        assert!(
+            matches!(
+                error,
+                Error::VolumeOutOfRange {
+                    current: 11,
+                    max: 10
+                }
+            ),
+            "{error:?}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn the_client_builder_has_a_strict_mode_too() {
+    let (client, captured) = Client::capturing();
+
+    let error = client
+        .with_device_key("key1")
+        .message()
+        .body("b")
+        .volume(3)
+        .strict()
+        .send()
+        .await
+        .unwrap_err();
+
+    assert!(
+        matches!(error, Error::VolumeRequiresCriticalLevel),
+        "{error:?}"
+    );
+    assert!(captured.requests().is_empty());
+}
+
+fn sealed() -> MessageBuilder {
+    Message::builder().ciphertext("c2VjcmV0")
+}
+
+#[test]
+fn each_plaintext_field_conflicts_with_ciphertext() {
+    type Part = fn(MessageBuilder) -> MessageBuilder;
+    let parts: [(&str, Part); 16] = [
+        ("title", |builder| builder.title("t")),
+        ("subtitle", |builder| builder.subtitle("s")),
+        ("body", |builder| builder.body("b")),
+        ("body", |builder| builder.markdown("**b**")),
+        ("level", |builder| builder.level(Level::Active)),
+        ("volume", |builder| builder.volume(3)),
+        ("badge", |builder| builder.badge(1)),
+        ("call", |builder| builder.call(true)),
+        ("autoCopy", |builder| builder.auto_copy(true)),
+        ("copy", |builder| builder.copy("c")),
+        ("sound", |builder| builder.sound("bell")),
+        ("icon", |builder| builder.icon("https://example.com/i.png")),
+        ("image", |builder| {
+            builder.image("https://example.com/p.png")
+        }),
+        ("group", |builder| builder.group("g")),
+        ("isArchive", |builder| builder.is_archive(true)),
+        ("url", |builder| builder.url("https://example.com")),
+    ];
+
+    for (field, part) in parts {
+        let error = part(sealed()).build().unwrap_err();
+
+        assert!(
+            matches!(&error, Error::CiphertextConflictsWithPlaintext { fields } if fields == &[field]),
+            "{field}: {error:?}"
+        );
+    }
+}
+
+#[test]
+fn every_conflicting_field_is_listed() {
+    let error = sealed()
+        .title("t")
+        .body("b")
+        .url("https://example.com")
+        .build()
+        .unwrap_err();
+
+    assert_eq!(
+        error.to_string(),
+        "Ciphertext replaces the plaintext fields, but these are set too: title, body, url"
+    );
+}
+
+#[test]
+fn keys_iv_and_id_may_travel_with_ciphertext() {
+    sealed()
+        .device_keys(["key1", "key2"])
+        .iv("0123456789abcdef")
+        .id("n1")
+        .build()
+        .unwrap();
+}
+
+#[test]
+fn unset_flags_do_not_conflict() {
+    sealed()
+        .call(false)
+        .auto_copy(false)
+        .is_archive(false)
+        .build()
+        .unwrap();
+}
+
+#[test]
+fn icons_and_images_must_be_http() {
+    for (field, value) in [
+        ("icon", "ftp://example.com/i.png"),
+        ("icon", "example.com/i.png"),
+        ("image", "file:///tmp/p.png"),
+    ] {
+        let builder = Message::builder().body("b");
+        let builder = match field {
+            "icon" => builder.icon(value),
+            _ => builder.image(value),
+        };
+
+        let error = builder.build().unwrap_err();
+
+        assert!(
+            matches!(&error, Error::InvalidUrl { field: got, value: bad, .. } if *got == field && bad == value),
+            "{value}: {error:?}"
+        );
+    }
+}
+
+#[test]
+fn the_failure_says_why() {
+    let scheme = Message::builder()
+        .body("b")
+        .icon("ftp://example.com/i.png")
+        .build()
+        .unwrap_err();
+    let parse = Message::builder()
+        .body("b")
+        .image("not a url")
+        .build()
+        .unwrap_err();
+
+    assert!(
+        matches!(&scheme, Error::InvalidUrl { source: UrlError::UnsupportedScheme(scheme), .. } if scheme == "ftp"),
+        "{scheme:?}"
+    );
+    assert!(
+        matches!(
+            &parse,
+            Error::InvalidUrl {
+                source: UrlError::Parse(_),
+                ..
+            }
+        ),
+        "{parse:?}"
+    );
+    assert_eq!(
+        scheme.to_string(),
+        "Invalid URL for icon (ftp://example.com/i.png): Unsupported scheme (ftp)"
+    );
+}
+
+#[test]
+fn tap_urls_may_be_app_deep_links() {
+    for url in [
+        "shortcuts://run-shortcut?name=Lights",
+        "https://example.com",
+    ] {
+        Message::builder().body("b").url(url).build().unwrap();
+    }
+
+    let error = Message::builder()
+        .body("b")
+        .url("/relative/path")
+        .build()
+        .unwrap_err();
+
+    assert!(
+        matches!(error, Error::InvalidUrl { field: "url", .. }),
+        "{error:?}"
+    );
+}
+
+#[test]
+fn parsed_urls_are_accepted() {
+    let icon = url::Url::parse("https://example.com/i.png").unwrap();
+
+    Message::builder()
+        .body("b")
+        .icon(icon.clone())
+        .image(icon.clone())
+        .url(icon)
+        .build()
+        .unwrap();
+}
@@ -0,0 +1,127 @@
+#![cfg(feature = "tracing")]
+
+use std::{
+    fmt::{self, Write},
+    sync::{Arc, Mutex},
+};
+
+use bark::{
+    client::{Client, ClientBuilder},
+    message::Message,
+};
+use tracing::{
+    Event, Id, Metadata, Subscriber,
+    field::{Field, Visit},
+    span::{Attributes, Record},
+};
+
+/// Keeps every `bark::wire` event as one `name=value` line.
+#[derive(Clone, Default)]
+struct Recorder(Arc<Mutex<Vec<String>>>);
+
+struct Line<'a>(&'a mut String);
+
+impl Visit for Line<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        let _ = write!(self.0, "{}={value:?} ", field.name());
+    }
+}
+
+impl Subscriber for Recorder {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, _: &Attributes<'_>) -> Id {
+        Id::from_u64(1)
+    }
+
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        if event.metadata().target() == "bark::wire" {
+            let mut line = String::new();
+            event.record(&mut Line(&mut line));
+            self.0.lock().unwrap().push(line);
+        }
+    }
+
+    fn enter(&self, _: &Id) {}
+
+    fn exit(&self, _: &Id) {}
+}
+
+fn builder() -> ClientBuilder {
+    Client::builder()
+        .base_url("https://bark.example.com")
+        .device_key("supersecretkey")
+}
+
+async fn logged(builder: ClientBuilder, message: &Message) -> Vec<String> {
+    let recorder = Recorder::default();
+    let (client, _) = builder.build_capturing().unwrap();
+    let _guard = tracing::subscriber::set_default(recorder.clone());
+
+    client.send(message).await.unwrap();
+
+    recorder.0.lock().unwrap().clone()
+}
+
+#[tokio::test]
+async fn requests_and_responses_are_logged_with_their_bodies() {
+    let lines = logged(
+        builder().debug_wire_logging(true),
+        &Message::titled("disk full", "on db-1"),
+    )
+    .await;
+
+    let [request, response] = &lines[..] else {
+        panic!("{lines:?}");
+    };
+    assert!(request.contains("message=request"), "{request}");
+    assert!(request.contains("method=POST"), "{request}");
+    assert!(request.contains("disk full"), "{request}");
+    assert!(request.contains("on db-1"), "{request}");
+    assert!(request.contains(r#""device_keys":["supe…"]"#), "{request}");
+    assert!(!request.contains("supersecretkey"), "{request}");
+    assert!(response.contains("status=200 OK"), "{response}");
+    assert!(response.contains("success"), "{response}");
+}
+
+#[tokio::test]
+async fn device_keys_are_shortened_in_the_log() {
+    let lines = logged(
+        builder().debug_wire_logging(true),
+        &Message::builder()
+            .body("b")
+            .device_keys(["anotherlongkey"])
+            .build()
+            .unwrap(),
+    )
+    .await;
+
+    let log = lines.concat();
+    assert!(!log.contains("supersecretkey"), "{log}");
+    assert!(!log.contains("anotherlongkey"), "{log}");
+    assert!(log.contains(r#""device_keys":["anot…"]"#), "{log}");
+}
+
+#[tokio::test]
+async fn bodies_are_cut_at_the_limit() {
+    let lines = logged(
+        builder().wire_log_body_limit(16),
+        &Message::text("x".repeat(200)),
+    )
+    .await;
+
+    assert!(!lines[0].contains(&"x".repeat(32)), "{}", lines[0]);
+}
+
+#[tokio::test]
+async fn nothing_is_logged_unless_enabled() {
+    let lines = logged(builder(), &Message::text("b")).await;
+
+    assert!(lines.is_empty(), "{lines:?}");
+}